@@ -0,0 +1,78 @@
+#[macro_use]
+extern crate criterion;
+extern crate ptree;
+
+use criterion::{black_box, Criterion, Throughput};
+use ptree::item::StringItem;
+use ptree::print_config::{StyleWhen, UTF_CHARS};
+use ptree::{write_tree_with, Color, PrintConfig, Style};
+
+/// Builds a tree in which every item above `depth` 0 has `width` children
+fn make_tree(depth: u32, width: usize) -> StringItem {
+    StringItem {
+        text: format!("item at depth {}", depth),
+        children: if depth == 0 {
+            vec![]
+        } else {
+            (0..width).map(|_| make_tree(depth - 1, width)).collect()
+        },
+        ..Default::default()
+    }
+}
+
+/// Builds a chain of `depth` items below the root
+fn make_chain(depth: u32) -> StringItem {
+    let mut item = StringItem::new("leaf".to_string(), vec![]);
+    for level in (0..depth).rev() {
+        item = StringItem::new(format!("item at depth {}", level), vec![item]);
+    }
+    item
+}
+
+fn count_items(item: &StringItem) -> u64 {
+    1 + item.children.iter().map(count_items).sum::<u64>()
+}
+
+fn config(styled: StyleWhen) -> PrintConfig {
+    PrintConfig {
+        styled,
+        characters: UTF_CHARS.into(),
+        branch: Style {
+            dimmed: true,
+            ..Style::default()
+        },
+        leaf: Style {
+            foreground: Some(Color::Cyan),
+            bold: true,
+            ..Style::default()
+        },
+        ..PrintConfig::default()
+    }
+}
+
+fn bench_write_tree(c: &mut Criterion) {
+    let trees = [
+        ("balanced", make_tree(8, 4)),
+        ("wide", make_tree(1, 100_000)),
+        ("deep", make_chain(2_000)),
+    ];
+
+    for (shape, tree) in &trees {
+        let mut group = c.benchmark_group(format!("write_tree_{}", shape));
+        group.throughput(Throughput::Elements(count_items(tree)));
+        for &(name, styled) in &[("plain", StyleWhen::Never), ("styled", StyleWhen::Always)] {
+            let config = config(styled);
+            let mut out = Vec::new();
+            group.bench_function(name, |b| {
+                b.iter(|| {
+                    out.clear();
+                    write_tree_with(black_box(tree), &mut out, &config).unwrap()
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_write_tree);
+criterion_main!(benches);
@@ -21,7 +21,7 @@ impl TreeItem for PathItem {
         }
     }
 
-    fn children(&self) -> Cow<[Self::Child]> {
+    fn children(&self) -> Cow<'_, [Self::Child]> {
         let v = if let Ok(list) = fs::read_dir(&self.0) {
             list.filter_map(|item| item.ok())
                 .map(|entry| entry.path())
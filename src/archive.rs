@@ -0,0 +1,331 @@
+//!
+//! Printing of the contents of tar and zip archives
+//!
+//! An [`ArchiveItem`] lists the entries of an archive as a directory tree, reconstructed from their paths,
+//! so archives can be inspected without extracting them.
+//! Directories which are not stored in the archive themselves, but only appear in the paths of other entries,
+//! are listed as well. The entries of each directory are sorted by name.
+//!
+//! Archives are read with the [`tar`] and [`zip`] crates. [`ArchiveItem::open`] detects the format from
+//! the contents of a file, and also supports tar archives compressed with gzip.
+//!
+//! [`ArchiveItem`]: struct.ArchiveItem.html
+//! [`ArchiveItem::open`]: struct.ArchiveItem.html#method.open
+//! [`tar`]: https://docs.rs/tar/0.4/tar/
+//! [`zip`]: https://docs.rs/zip/2/zip/
+//!
+
+use item::TreeItem;
+use output::{print_tree_with, write_tree_with};
+use print_config::PrintConfig;
+use style::Style;
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Component, Path};
+
+use flate2::read::GzDecoder;
+use tar::Archive;
+use zip::ZipArchive;
+
+///
+/// Entry of an archive, or the archive itself as the root of the tree
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveItem {
+    name: String,
+    size: Option<u64>,
+    children: Vec<ArchiveItem>,
+}
+
+impl ArchiveItem {
+    /// Creates an empty directory named `name`
+    fn dir(name: String) -> Self {
+        ArchiveItem {
+            name,
+            size: None,
+            children: vec![],
+        }
+    }
+
+    ///
+    /// Reads the entries of the tar archive in `reader`
+    ///
+    /// The root of the returned tree is labeled `.`.
+    ///
+    pub fn from_tar<R: Read>(reader: R) -> io::Result<Self> {
+        let mut root = ArchiveItem::dir(".".to_string());
+        for entry in Archive::new(reader).entries()? {
+            let entry = entry?;
+            let size = if entry.header().entry_type().is_dir() {
+                None
+            } else {
+                Some(entry.size())
+            };
+            root.insert(&entry.path()?, size);
+        }
+        root.sort();
+        Ok(root)
+    }
+
+    ///
+    /// Reads the entries of the zip archive in `reader`
+    ///
+    /// Only the central directory of the archive is read, so no entries are decompressed.
+    /// The root of the returned tree is labeled `.`.
+    ///
+    pub fn from_zip<R: Read + Seek>(reader: R) -> io::Result<Self> {
+        let mut archive = ZipArchive::new(reader).map_err(zip_error)?;
+        let mut root = ArchiveItem::dir(".".to_string());
+        for i in 0..archive.len() {
+            let file = archive.by_index_raw(i).map_err(zip_error)?;
+            let size = if file.is_dir() { None } else { Some(file.size()) };
+            root.insert(Path::new(file.name()), size);
+        }
+        root.sort();
+        Ok(root)
+    }
+
+    ///
+    /// Reads the entries of the archive at `path`, which is a tar, gzip-compressed tar or zip archive
+    ///
+    /// The format is detected from the contents of the file, not from its name.
+    /// The root of the returned tree is labeled with `path`.
+    ///
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut file = BufReader::new(File::open(path)?);
+        let mut magic = [0; 4];
+        let read = read_prefix(&mut file, &mut magic)?;
+        file.seek(SeekFrom::Start(0))?;
+
+        let mut root = match &magic[..read] {
+            [0x1f, 0x8b, ..] => ArchiveItem::from_tar(GzDecoder::new(file))?,
+            [b'P', b'K', 3, 4] | [b'P', b'K', 5, 6] => ArchiveItem::from_zip(file)?,
+            _ => ArchiveItem::from_tar(file)?,
+        };
+        root.name = path.display().to_string();
+        Ok(root)
+    }
+
+    ///
+    /// Returns the name of this entry, which is the last component of its path
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    ///
+    /// Returns the uncompressed size of this entry, or `None` if it is a directory
+    ///
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// Inserts the entry at `path` into this directory, creating the directories in its path
+    fn insert(&mut self, path: &Path, size: Option<u64>) {
+        let mut names = path.components().filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        });
+        let mut name = match names.next() {
+            Some(name) => name,
+            None => return,
+        };
+
+        let mut dir = self;
+        for next in names {
+            dir = dir.child(name);
+            name = next;
+        }
+        let entry = dir.child(name);
+        if entry.size.is_none() {
+            entry.size = size;
+        }
+    }
+
+    /// Returns the child named `name`, which is added as a directory if it does not exist
+    fn child(&mut self, name: String) -> &mut ArchiveItem {
+        let index = match self.children.iter().position(|child| child.name == name) {
+            Some(index) => index,
+            None => {
+                self.children.push(ArchiveItem::dir(name));
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[index]
+    }
+
+    /// Sorts the entries of this directory and all directories below it by name
+    fn sort(&mut self) {
+        self.children.sort_by(|a, b| a.name.cmp(&b.name));
+        for child in &mut self.children {
+            child.sort();
+        }
+    }
+}
+
+impl TreeItem for ArchiveItem {
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        style.paint_to(f, format_args!("{}", self.name))
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        Cow::from(&self.children[..])
+    }
+}
+
+/// Reads into `buf` until it is full or the end of `reader` is reached, returning the number of bytes read
+fn read_prefix<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..])? {
+            0 => break,
+            n => read += n,
+        }
+    }
+    Ok(read)
+}
+
+/// Converts an error of the `zip` crate into an I/O error
+fn zip_error(error: zip::result::ZipError) -> io::Error {
+    match error {
+        zip::result::ZipError::Io(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error),
+    }
+}
+
+/// Print the contents of the archive at `path` to standard output using default formatting
+pub fn print_archive<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    print_archive_with(path, &PrintConfig::for_terminal())
+}
+
+/// Print the contents of the archive at `path` to standard output using custom formatting
+pub fn print_archive_with<P: AsRef<Path>>(path: P, print_config: &PrintConfig) -> io::Result<()> {
+    print_tree_with(&ArchiveItem::open(path)?, print_config)
+}
+
+/// Write the contents of the archive at `path` to writer `f` using custom formatting
+pub fn write_archive_with<P, W>(path: P, f: W, print_config: &PrintConfig) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    W: io::Write,
+{
+    write_tree_with(&ArchiveItem::open(path)?, f, print_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use print_config::UTF_CHARS;
+    use std::io::{Cursor, Write};
+    use std::str::from_utf8;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use tar::{Builder, Header};
+    use tempfile;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn render(item: &ArchiveItem) -> String {
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        let mut out = Vec::new();
+        write_tree_with(item, &mut out, &config).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn tar_bytes() -> Vec<u8> {
+        let mut builder = Builder::new(Vec::new());
+        for &(path, contents) in &[
+            ("./src/main.rs", "fn main() {}\n"),
+            ("README.md", "# Readme\n"),
+            ("src/bin/cli.rs", ""),
+        ] {
+            let mut header = Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, contents.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn tar_entries() {
+        let item = ArchiveItem::from_tar(&tar_bytes()[..]).unwrap();
+        assert_eq!(
+            render(&item),
+            "\
+             .\n\
+             ├─ README.md\n\
+             └─ src\n   \
+                ├─ bin\n   \
+                │  └─ cli.rs\n   \
+                └─ main.rs\n"
+        );
+        let src = &item.children()[1];
+        assert_eq!(src.size(), None);
+        assert_eq!(src.children()[1].name(), "main.rs");
+        assert_eq!(src.children()[1].size(), Some(13));
+    }
+
+    #[test]
+    fn zip_entries() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+        writer.add_directory("docs/", options).unwrap();
+        writer.start_file("lib/a/b.txt", options).unwrap();
+        writer.write_all(b"hello").unwrap();
+        writer.start_file("Cargo.toml", options).unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let item = ArchiveItem::from_zip(Cursor::new(bytes)).unwrap();
+        assert_eq!(
+            render(&item),
+            "\
+             .\n\
+             ├─ Cargo.toml\n\
+             ├─ docs\n\
+             └─ lib\n   \
+                └─ a\n      \
+                   └─ b.txt\n"
+        );
+        assert_eq!(item.children()[0].size(), Some(0));
+        assert_eq!(item.children()[1].size(), None);
+    }
+
+    #[test]
+    fn detected_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let tar_path = dir.path().join("archive.tar");
+        std::fs::write(&tar_path, tar_bytes()).unwrap();
+
+        let gz_path = dir.path().join("archive");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&tar_bytes()).unwrap();
+        std::fs::write(&gz_path, encoder.finish().unwrap()).unwrap();
+
+        let plain = ArchiveItem::open(&tar_path).unwrap();
+        let compressed = ArchiveItem::open(&gz_path).unwrap();
+        assert_eq!(plain.name(), tar_path.display().to_string());
+        assert_eq!(plain.children, compressed.children);
+
+        let mut out = Vec::new();
+        write_archive_with(&gz_path, &mut out, &PrintConfig::default()).unwrap();
+        assert!(from_utf8(&out).unwrap().contains("main.rs"));
+
+        let text_path = dir.path().join("text");
+        std::fs::write(&text_path, "not an archive").unwrap();
+        assert!(ArchiveItem::open(&text_path).is_err());
+    }
+}
@@ -17,10 +17,7 @@ impl TreeBuilder {
     ///
     pub fn new(text: String) -> TreeBuilder {
         TreeBuilder {
-            item: StringItem {
-                text,
-                children: Vec::new(),
-            },
+            item: StringItem::new(text, Vec::new()),
             level: 0,
         }
     }
@@ -40,10 +37,7 @@ impl TreeBuilder {
         TreeBuilder::append_child_level(
             &mut self.item,
             self.level,
-            StringItem {
-                text,
-                children: Vec::new(),
-            },
+            StringItem::new(text, Vec::new()),
         );
         self.level += 1;
         self
@@ -64,10 +58,24 @@ impl TreeBuilder {
         self.begin_child(text).end_child()
     }
 
+    ///
+    /// Set the metadata entry `key` of the current item to `value`
+    ///
+    /// The current item is the one added by the last call to [`begin_child`] which was not ended yet,
+    /// or the top level item. Metadata is printed as configured by [`PrintConfig::metadata`].
+    ///
+    /// [`begin_child`]: #method.begin_child
+    /// [`PrintConfig::metadata`]: ../print_config/struct.PrintConfig.html#structfield.metadata
+    pub fn add_metadata(&mut self, key: String, value: String) -> &mut Self {
+        let current = (0..self.level).fold(&mut self.item, |item, _| item.children.last_mut().unwrap());
+        current.metadata.insert(key, value);
+        self
+    }
+
     ///
     /// Finish building the tree and return the top level item
     ///
-    pub fn build(&mut self) -> StringItem {
+    pub fn build(&self) -> StringItem {
         self.item.clone()
     }
 }
@@ -132,4 +140,21 @@ mod tests {
 
         assert_eq!(item.children.len(), 0);
     }
+
+    #[test]
+    fn metadata() {
+        let tree = TreeBuilder::new("test".to_string())
+            .add_metadata("kind".to_string(), "root".to_string())
+            .begin_child("child".to_string())
+            .add_metadata("size".to_string(), "1".to_string())
+            .add_metadata("size".to_string(), "2".to_string())
+            .end_child()
+            .add_empty_child("leaf".to_string())
+            .build();
+
+        assert_eq!(tree.metadata.len(), 1);
+        assert_eq!(tree.metadata["kind"], "root");
+        assert_eq!(tree.children[0].metadata["size"], "2");
+        assert!(tree.children[1].metadata.is_empty());
+    }
 }
@@ -0,0 +1,352 @@
+//!
+//! Printing of cargo dependency trees
+//!
+//! A [`DependencyItem`] is built from the resolved dependency graph in the output of `cargo metadata`,
+//! as read by the [`cargo_metadata`] crate, and prints a view similar to `cargo tree`.
+//! Each package is labeled with its name and version, and optionally with its enabled features,
+//! such as `serde v1.0.200 [derive, std]`.
+//!
+//! A package with dependencies is only expanded the first time it appears,
+//! and later occurrences are marked with `(*)`, like in `cargo tree`.
+//!
+//! [`DependencyItem`]: struct.DependencyItem.html
+//! [`cargo_metadata`]: https://docs.rs/cargo_metadata/0.23/cargo_metadata/
+//!
+
+use item::TreeItem;
+use output::{print_tree_with, write_tree_with};
+use print_config::PrintConfig;
+use style::Style;
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
+
+use cargo_metadata::{DependencyKind, Metadata, MetadataCommand, Node, NodeDep, Package, PackageId};
+
+///
+/// Structure controlling how dependency trees are built
+///
+#[derive(Clone, Debug)]
+pub struct CargoConfig {
+    /// Whether the enabled features of each package are printed after its version
+    ///
+    /// The default value is `true`.
+    pub show_features: bool,
+    /// Whether packages are only expanded the first time they appear
+    ///
+    /// The default value is `true`. Dependency cycles, which are possible through dev-dependencies,
+    /// are never expanded.
+    pub dedup: bool,
+    /// Whether build-dependencies are listed
+    ///
+    /// The default value is `true`.
+    pub build_dependencies: bool,
+    /// Whether dev-dependencies are listed
+    ///
+    /// `cargo metadata` only resolves the dev-dependencies of workspace members.
+    /// The default value is `false`.
+    pub dev_dependencies: bool,
+}
+
+impl Default for CargoConfig {
+    fn default() -> CargoConfig {
+        CargoConfig {
+            show_features: true,
+            dedup: true,
+            build_dependencies: true,
+            dev_dependencies: false,
+        }
+    }
+}
+
+///
+/// Package in a dependency tree
+///
+/// The root is the root package of the metadata, or the workspace directory
+/// listing all members of a virtual workspace.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DependencyItem {
+    label: String,
+    package: Option<PackageId>,
+    children: Vec<DependencyItem>,
+}
+
+impl DependencyItem {
+    ///
+    /// Builds the dependency tree of the root package or workspace described by `metadata`
+    ///
+    /// Returns `None` if `metadata` has no dependency graph, i.e. if it was read with `--no-deps`.
+    ///
+    pub fn from_metadata(metadata: &Metadata, config: &CargoConfig) -> Option<Self> {
+        let resolve = metadata.resolve.as_ref()?;
+        if let Some(ref root) = resolve.root {
+            return DependencyItem::from_package(metadata, root, config);
+        }
+
+        let mut builder = TreeBuilder::new(metadata, config)?;
+        let mut members: Vec<_> = metadata.workspace_members.iter().collect();
+        members.sort_by_key(|&id| builder.sort_key(id));
+        Some(DependencyItem {
+            label: metadata.workspace_root.to_string(),
+            package: None,
+            children: members.into_iter().map(|id| builder.build(id)).collect(),
+        })
+    }
+
+    ///
+    /// Builds the dependency tree of the package `id` in `metadata`
+    ///
+    /// Returns `None` if `metadata` has no dependency graph or does not contain the package.
+    ///
+    pub fn from_package(metadata: &Metadata, id: &PackageId, config: &CargoConfig) -> Option<Self> {
+        let mut builder = TreeBuilder::new(metadata, config)?;
+        if !builder.nodes.contains_key(id) {
+            return None;
+        }
+        Some(builder.build(id))
+    }
+
+    ///
+    /// Returns the label of this item, such as `serde v1.0.200 [derive, std]`
+    ///
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    ///
+    /// Returns the id of the package of this item, or `None` for the root of a virtual workspace
+    ///
+    pub fn package_id(&self) -> Option<&PackageId> {
+        self.package.as_ref()
+    }
+}
+
+impl TreeItem for DependencyItem {
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        style.paint_to(f, format_args!("{}", self.label))
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        Cow::from(&self.children[..])
+    }
+}
+
+/// Depth-first construction of a dependency tree, in the order in which it is printed
+struct TreeBuilder<'a> {
+    config: &'a CargoConfig,
+    packages: HashMap<&'a PackageId, &'a Package>,
+    nodes: HashMap<&'a PackageId, &'a Node>,
+    /// Packages which were already expanded
+    expanded: HashSet<&'a PackageId>,
+    /// Packages on the path from the root to the package being built
+    ancestors: Vec<&'a PackageId>,
+}
+
+impl<'a> TreeBuilder<'a> {
+    fn new(metadata: &'a Metadata, config: &'a CargoConfig) -> Option<Self> {
+        let resolve = metadata.resolve.as_ref()?;
+        Some(TreeBuilder {
+            config,
+            packages: metadata.packages.iter().map(|package| (&package.id, package)).collect(),
+            nodes: resolve.nodes.iter().map(|node| (&node.id, node)).collect(),
+            expanded: HashSet::new(),
+            ancestors: vec![],
+        })
+    }
+
+    /// Returns whether the dependency `dep` is listed according to its kinds
+    fn is_listed(&self, dep: &NodeDep) -> bool {
+        dep.dep_kinds.iter().any(|info| match info.kind {
+            DependencyKind::Normal => true,
+            DependencyKind::Build => self.config.build_dependencies,
+            DependencyKind::Development => self.config.dev_dependencies,
+            _ => false,
+        })
+    }
+
+    /// Returns the name and version of package `id`, by which dependencies are sorted
+    fn sort_key(&self, id: &PackageId) -> (String, String) {
+        match self.packages.get(id) {
+            Some(package) => (package.name.to_string(), package.version.to_string()),
+            None => (id.repr.clone(), String::new()),
+        }
+    }
+
+    fn build(&mut self, id: &'a PackageId) -> DependencyItem {
+        let mut label = match self.packages.get(id) {
+            Some(package) => format!("{} v{}", package.name.as_str(), package.version),
+            None => id.repr.clone(),
+        };
+
+        let node = self.nodes.get(id).cloned();
+        if let Some(node) = node {
+            if self.config.show_features && !node.features.is_empty() {
+                let features: Vec<&str> = node.features.iter().map(|feature| feature.as_str()).collect();
+                label.push_str(&format!(" [{}]", features.join(", ")));
+            }
+        }
+
+        let mut deps: Vec<&'a PackageId> = node
+            .map(|node| {
+                node.deps
+                    .iter()
+                    .filter(|dep| self.is_listed(dep))
+                    .map(|dep| &dep.pkg)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let repeated = self.ancestors.contains(&id) || (self.config.dedup && self.expanded.contains(id));
+        if repeated && !deps.is_empty() {
+            label.push_str(" (*)");
+            deps.clear();
+        }
+        deps.sort_by_key(|&dep| self.sort_key(dep));
+        deps.dedup();
+
+        self.expanded.insert(id);
+        self.ancestors.push(id);
+        let children = deps.into_iter().map(|dep| self.build(dep)).collect();
+        self.ancestors.pop();
+
+        DependencyItem {
+            label,
+            package: Some(id.clone()),
+            children,
+        }
+    }
+}
+
+/// Runs `cargo metadata` for the manifest at `manifest_path`
+fn read_metadata(manifest_path: &Path) -> io::Result<Metadata> {
+    MetadataCommand::new()
+        .manifest_path(manifest_path)
+        .exec()
+        .map_err(io::Error::other)
+}
+
+/// Builds the dependency tree of `metadata`, failing if it has no dependency graph
+fn dependency_tree(metadata: &Metadata, config: &CargoConfig) -> io::Result<DependencyItem> {
+    DependencyItem::from_metadata(metadata, config)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "metadata contains no dependency graph"))
+}
+
+/// Print the dependency tree of the package or workspace at `manifest_path` to standard output using default formatting
+pub fn print_dependencies<P: AsRef<Path>>(manifest_path: P) -> io::Result<()> {
+    print_dependencies_with(manifest_path, &PrintConfig::for_terminal(), &CargoConfig::default())
+}
+
+/// Print the dependency tree of the package or workspace at `manifest_path` to standard output using custom formatting
+pub fn print_dependencies_with<P: AsRef<Path>>(
+    manifest_path: P,
+    print_config: &PrintConfig,
+    cargo_config: &CargoConfig,
+) -> io::Result<()> {
+    let metadata = read_metadata(manifest_path.as_ref())?;
+    print_tree_with(&dependency_tree(&metadata, cargo_config)?, print_config)
+}
+
+/// Write the dependency tree described by `metadata` to writer `f` using custom formatting
+pub fn write_dependencies_with<W: io::Write>(
+    metadata: &Metadata,
+    f: W,
+    print_config: &PrintConfig,
+    cargo_config: &CargoConfig,
+) -> io::Result<()> {
+    write_tree_with(&dependency_tree(metadata, cargo_config)?, f, print_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use print_config::UTF_CHARS;
+    use std::fs;
+    use std::str::from_utf8;
+
+    use tempfile;
+
+    fn write_package(dir: &Path, name: &str, dependencies: &str) {
+        fs::create_dir_all(dir.join(name).join("src")).unwrap();
+        let manifest = format!(
+            "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n{}",
+            name, dependencies
+        );
+        fs::write(dir.join(name).join("Cargo.toml"), manifest).unwrap();
+        fs::write(dir.join(name).join("src/lib.rs"), "").unwrap();
+    }
+
+    #[test]
+    fn dependency_tree_output() {
+        let dir = tempfile::tempdir().unwrap();
+        write_package(
+            dir.path(),
+            "app",
+            "[dependencies]\n\
+             core = { path = \"../core\", features = [\"extra\"] }\n\
+             util = { path = \"../util\" }\n\
+             [build-dependencies]\n\
+             gen = { path = \"../gen\" }\n\
+             [dev-dependencies]\n\
+             check = { path = \"../check\" }\n",
+        );
+        write_package(
+            dir.path(),
+            "core",
+            "[dependencies]\nutil = { path = \"../util\" }\n\n[features]\ndefault = []\nextra = []\n",
+        );
+        write_package(dir.path(), "util", "[dependencies]\nleaf = { path = \"../leaf\" }\n");
+        write_package(dir.path(), "leaf", "");
+        write_package(dir.path(), "gen", "");
+        write_package(dir.path(), "check", "");
+
+        let metadata = MetadataCommand::new()
+            .manifest_path(dir.path().join("app/Cargo.toml"))
+            .other_options(vec!["--offline".to_string()])
+            .exec()
+            .unwrap();
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_dependencies_with(&metadata, &mut out, &config, &CargoConfig::default()).unwrap();
+        assert_eq!(
+            from_utf8(&out).unwrap(),
+            "\
+             app v0.1.0\n\
+             ├─ core v0.1.0 [default, extra]\n\
+             │  └─ util v0.1.0\n\
+             │     └─ leaf v0.1.0\n\
+             ├─ gen v0.1.0\n\
+             └─ util v0.1.0 (*)\n"
+        );
+
+        let cargo_config = CargoConfig {
+            show_features: false,
+            dedup: false,
+            build_dependencies: false,
+            dev_dependencies: true,
+        };
+        let mut out = Vec::new();
+        write_dependencies_with(&metadata, &mut out, &config, &cargo_config).unwrap();
+        assert_eq!(
+            from_utf8(&out).unwrap(),
+            "\
+             app v0.1.0\n\
+             ├─ check v0.1.0\n\
+             ├─ core v0.1.0\n\
+             │  └─ util v0.1.0\n\
+             │     └─ leaf v0.1.0\n\
+             └─ util v0.1.0\n   \
+                └─ leaf v0.1.0\n"
+        );
+    }
+}
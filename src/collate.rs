@@ -0,0 +1,198 @@
+//!
+//! Orderings of item names for sorting
+//!
+//! By default, names are sorted by their bytes, so `file10` comes before `file2` and `Zebra` before `apple`.
+//! A [`Collation`] selects a different ordering for the places in which this crate sorts items,
+//! such as [`FsConfig::collation`], and can be used with [`StringItem::sort_by`].
+//!
+//! [`Collation::Natural`] compares runs of digits by their numeric value, so `file2` comes before `file10`.
+//! With the `"icu"` feature, [`Collation::locale`] orders names like a dictionary of the given language,
+//! so `Äpfel` sorts next to `Apfel` instead of after `Zebra`.
+//!
+//! ```
+//! # use ptree::TreeBuilder;
+//! # use ptree::collate::Collation;
+//! let mut tree = TreeBuilder::new("root".to_string())
+//!     .add_empty_child("file10".to_string())
+//!     .add_empty_child("file2".to_string())
+//!     .add_empty_child("file1".to_string())
+//!     .build();
+//!
+//! let collation = Collation::Natural;
+//! tree.children.sort_by(|a, b| collation.compare(&a.text, &b.text));
+//!
+//! let names: Vec<_> = tree.children.iter().map(|child| child.text.as_str()).collect();
+//! assert_eq!(names, ["file1", "file2", "file10"]);
+//! ```
+//!
+//! [`Collation`]: enum.Collation.html
+//! [`Collation::Natural`]: enum.Collation.html#variant.Natural
+//! [`Collation::locale`]: enum.Collation.html#method.locale
+//! [`FsConfig::collation`]: ../fs/struct.FsConfig.html#structfield.collation
+//! [`StringItem::sort_by`]: ../item/struct.StringItem.html#method.sort_by
+//!
+
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[cfg(feature = "icu")]
+use std::io;
+#[cfg(feature = "icu")]
+use std::rc::Rc;
+
+#[cfg(feature = "icu")]
+use icu_collator::{Collator, CollatorOptions, Numeric};
+#[cfg(feature = "icu")]
+use icu_locid::Locale;
+
+///
+/// Ordering used to sort names
+///
+#[derive(Clone, Debug, Default)]
+pub enum Collation {
+    /// Order by bytes, i.e. by Unicode code points
+    #[default]
+    Bytes,
+    /// Order by code points, except that runs of digits are compared by their numeric value
+    ///
+    /// See [`natural_cmp`](fn.natural_cmp.html).
+    Natural,
+    /// Order by the rules of a locale, created by [`Collation::locale`](#method.locale)
+    #[cfg(feature = "icu")]
+    Locale(Rc<Collator>),
+}
+
+impl Collation {
+    ///
+    /// Returns the ordering of the locale `tag`, such as `de` or `sv-SE`
+    ///
+    /// If `numeric` is set, runs of digits are compared by their numeric value, like with [`Collation::Natural`].
+    /// Returns an error of kind `InvalidInput` if the tag is invalid or the locale is not supported.
+    ///
+    /// [`Collation::Natural`]: #variant.Natural
+    ///
+    #[cfg(feature = "icu")]
+    pub fn locale(tag: &str, numeric: bool) -> io::Result<Collation> {
+        let invalid = |e: &dyn std::fmt::Display| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("locale {:?}: {}", tag, e))
+        };
+        let locale: Locale = tag.parse().map_err(|e| invalid(&e))?;
+        let mut options = CollatorOptions::new();
+        if numeric {
+            options.numeric = Some(Numeric::On);
+        }
+        let collator = Collator::try_new(&(&locale).into(), options).map_err(|e| invalid(&e))?;
+        Ok(Collation::Locale(Rc::new(collator)))
+    }
+
+    ///
+    /// Compares the names `a` and `b`
+    ///
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match *self {
+            Collation::Bytes => a.cmp(b),
+            Collation::Natural => natural_cmp(a, b),
+            #[cfg(feature = "icu")]
+            Collation::Locale(ref collator) => collator.compare(a, b),
+        }
+    }
+}
+
+/// Consumes a run of ASCII digits, and returns it without leading zeros along with the number of zeros
+fn digit_run(chars: &mut Peekable<Chars>) -> (String, usize) {
+    let mut zeros = 0;
+    while chars.peek() == Some(&'0') {
+        chars.next();
+        zeros += 1;
+    }
+    let mut digits = String::new();
+    while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+        digits.push(c);
+        chars.next();
+    }
+    (digits, zeros)
+}
+
+///
+/// Compares `a` and `b` by code points, except that runs of ASCII digits are compared by their numeric value
+///
+/// This sorts `file2` before `file10`. Numbers which only differ in leading zeros, such as `07` and `7`,
+/// are ordered by their number of zeros, so the ordering is consistent with equality.
+///
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    let mut zeros = Ordering::Equal;
+    loop {
+        match (a.peek(), b.peek()) {
+            (None, None) => return zeros,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x, x_zeros) = digit_run(&mut a);
+                let (y, y_zeros) = digit_run(&mut b);
+                let ordering = x.len().cmp(&y.len()).then_with(|| x.cmp(&y));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                zeros = zeros.then(x_zeros.cmp(&y_zeros));
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.cmp(y);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(collation: &Collation, names: &[&str]) -> Vec<String> {
+        let mut names: Vec<_> = names.iter().map(|name| name.to_string()).collect();
+        names.sort_by(|a, b| collation.compare(a, b));
+        names
+    }
+
+    #[test]
+    fn natural_order() {
+        let names = ["file10", "file2", "file1", "file02", "a", "file", "file2b", "file2a"];
+        assert_eq!(
+            sorted(&Collation::Bytes, &names),
+            ["a", "file", "file02", "file1", "file10", "file2", "file2a", "file2b"]
+        );
+        assert_eq!(
+            sorted(&Collation::Natural, &names),
+            ["a", "file", "file1", "file2", "file02", "file2a", "file2b", "file10"]
+        );
+        assert_eq!(natural_cmp("v1.10.0", "v1.9.3"), Ordering::Greater);
+        assert_eq!(natural_cmp("007", "007"), Ordering::Equal);
+        assert_eq!(
+            natural_cmp("99999999999999999999999", "100000000000000000000000"),
+            Ordering::Less
+        );
+    }
+
+    #[cfg(feature = "icu")]
+    #[test]
+    fn locale_order() {
+        let names = ["Zebra", "Äpfel", "apple", "file10", "file2"];
+        assert_eq!(
+            sorted(&Collation::locale("de", false).unwrap(), &names),
+            ["Äpfel", "apple", "file10", "file2", "Zebra"]
+        );
+        assert_eq!(
+            sorted(&Collation::locale("de", true).unwrap(), &names),
+            ["Äpfel", "apple", "file2", "file10", "Zebra"]
+        );
+        assert_eq!(
+            Collation::locale("not a locale", false).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+}
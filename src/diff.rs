@@ -0,0 +1,594 @@
+//!
+//! Comparison of trees
+//!
+//! [`diff`] compares two trees by the text of their items, and returns the [`Edit`]s turning the old tree
+//! into the new one, together with an annotated tree showing the changes.
+//!
+//! Children of corresponding items are matched by their text. Items which are only in one of the trees are
+//! moved if an item with the same text was removed elsewhere in the tree, renamed if an unmatched item
+//! is at the same position in the old tree, and inserted or deleted otherwise.
+//! Matched children whose order changed are moved as well, keeping the longest sequence of children in order.
+//!
+//! ```
+//! # use ptree::builder::TreeBuilder;
+//! # use ptree::diff::{diff, Edit};
+//! let old = TreeBuilder::new("deps".to_string())
+//!     .add_empty_child("serde".to_string())
+//!     .add_empty_child("rand".to_string())
+//!     .build();
+//! let new = TreeBuilder::new("deps".to_string())
+//!     .add_empty_child("serde".to_string())
+//!     .add_empty_child("tint".to_string())
+//!     .build();
+//!
+//! let diff = diff(&old, &new).unwrap();
+//! assert_eq!(
+//!     diff.edits(),
+//!     &[Edit::Rename {
+//!         path: vec![1],
+//!         from: "rand".to_string(),
+//!         to: "tint".to_string(),
+//!     }]
+//! );
+//! ```
+//!
+//! [`diff`]: fn.diff.html
+//! [`Edit`]: enum.Edit.html
+//!
+
+use item::{StringItem, TreeItem};
+use output::item_text;
+use style::{Color, Style};
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+///
+/// Operation turning the old tree into the new one
+///
+/// Items are identified by their paths, the indices of the item and its ancestors among their siblings,
+/// starting below the root, which has an empty path.
+/// Deleted items and the sources of moves are identified by their paths in the old tree, all others by their
+/// paths in the new tree. Inserted and deleted items include all items below them.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Edit {
+    /// The item at `path` was inserted
+    Insert {
+        /// Path of the item in the new tree
+        path: Vec<usize>,
+        /// Text of the item
+        label: String,
+    },
+    /// The item at `path` was deleted
+    Delete {
+        /// Path of the item in the old tree
+        path: Vec<usize>,
+        /// Text of the item
+        label: String,
+    },
+    /// The text of the item at `path` changed
+    Rename {
+        /// Path of the item in the new tree
+        path: Vec<usize>,
+        /// Text of the item in the old tree
+        from: String,
+        /// Text of the item in the new tree
+        to: String,
+    },
+    /// The item was moved to another position or parent
+    Move {
+        /// Path of the item in the old tree
+        from: Vec<usize>,
+        /// Path of the item in the new tree
+        to: Vec<usize>,
+        /// Text of the item
+        label: String,
+    },
+}
+
+///
+/// Kind of change of an item in a [`DiffItem`]
+///
+/// [`DiffItem`]: struct.DiffItem.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// The item is at the same position in both trees, although items below it may have changed
+    Unchanged,
+    /// The item only exists in the new tree
+    Inserted,
+    /// The item only exists in the old tree
+    Deleted,
+    /// The text of the item changed
+    Renamed,
+    /// The item is at another position in the new tree
+    Moved,
+}
+
+impl Change {
+    ///
+    /// Returns the marker printed before items with this change, such as `+ ` for inserted items
+    ///
+    pub fn marker(self) -> &'static str {
+        match self {
+            Change::Unchanged => "",
+            Change::Inserted => "+ ",
+            Change::Deleted => "- ",
+            Change::Renamed => "~ ",
+            Change::Moved => "> ",
+        }
+    }
+
+    ///
+    /// Returns the style of items with this change
+    ///
+    /// Inserted items are green, deleted items are red, renamed items are yellow and moved items are cyan.
+    ///
+    pub fn style(self) -> Style {
+        let color = match self {
+            Change::Unchanged => return Style::default(),
+            Change::Inserted => Color::Green,
+            Change::Deleted => Color::Red,
+            Change::Renamed => Color::Yellow,
+            Change::Moved => Color::Cyan,
+        };
+        Style {
+            foreground: Some(color),
+            ..Style::default()
+        }
+    }
+}
+
+///
+/// Item of the annotated tree of a [`TreeDiff`]
+///
+/// The tree has the shape of the new tree, with deleted items added after the other children of their parent.
+/// Each item is marked and styled according to its [`Change`], and renamed items are printed as `old → new`.
+///
+/// [`TreeDiff`]: struct.TreeDiff.html
+/// [`Change`]: enum.Change.html
+#[derive(Clone, Debug)]
+pub struct DiffItem {
+    label: String,
+    change: Change,
+    children: Vec<DiffItem>,
+}
+
+impl DiffItem {
+    ///
+    /// Returns the change of this item
+    ///
+    pub fn change(&self) -> Change {
+        self.change
+    }
+
+    /// Creates the item of a subtree which only exists in one of the trees
+    fn one_sided(item: &StringItem, change: Change) -> Self {
+        DiffItem {
+            label: item.text.clone(),
+            change,
+            children: item.children.iter().map(|c| DiffItem::one_sided(c, change)).collect(),
+        }
+    }
+}
+
+impl TreeItem for DiffItem {
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        let style = style.merge(&self.change.style());
+        style.paint_to(f, format_args!("{}{}", self.change.marker(), self.label))
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        Cow::from(&self.children[..])
+    }
+}
+
+///
+/// Differences between two trees, returned by [`diff`]
+///
+/// [`diff`]: fn.diff.html
+#[derive(Clone, Debug)]
+pub struct TreeDiff {
+    edits: Vec<Edit>,
+    tree: DiffItem,
+}
+
+impl TreeDiff {
+    ///
+    /// Returns the edits turning the old tree into the new one
+    ///
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    ///
+    /// Returns the annotated tree, which can be printed like any other tree
+    ///
+    pub fn tree(&self) -> &DiffItem {
+        &self.tree
+    }
+
+    ///
+    /// Returns whether the trees are equal
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+}
+
+/// Copies the tree `item` into a tree of the plain text of its items
+fn labels<T: TreeItem>(item: &T) -> io::Result<StringItem> {
+    Ok(StringItem::new(
+        item_text(item)?,
+        item.children().iter().map(labels).collect::<io::Result<_>>()?,
+    ))
+}
+
+/// Returns the item at `path` below `root`
+fn item_at<'a>(root: &'a StringItem, path: &[usize]) -> &'a StringItem {
+    path.iter().fold(root, |item, &i| &item.children[i])
+}
+
+/// Returns `path` extended by `index`
+fn child_path(path: &[usize], index: usize) -> Vec<usize> {
+    let mut path = path.to_vec();
+    path.push(index);
+    path
+}
+
+/// Children of two corresponding items, matched by their text
+struct Matching {
+    /// Indices of matching old and new children, in the order of the new children
+    pairs: Vec<(usize, usize)>,
+    old_unmatched: Vec<usize>,
+    new_unmatched: Vec<usize>,
+}
+
+impl Matching {
+    fn new(old: &StringItem, new: &StringItem) -> Self {
+        let mut used = vec![false; old.children.len()];
+        let mut pairs = vec![];
+        let mut new_unmatched = vec![];
+        for (j, n) in new.children.iter().enumerate() {
+            match (0..used.len()).find(|&i| !used[i] && old.children[i].text == n.text) {
+                Some(i) => {
+                    used[i] = true;
+                    pairs.push((i, j));
+                }
+                None => new_unmatched.push(j),
+            }
+        }
+        Matching {
+            pairs,
+            old_unmatched: (0..used.len()).filter(|&i| !used[i]).collect(),
+            new_unmatched,
+        }
+    }
+}
+
+/// Returns which elements of `seq` belong to a longest increasing subsequence
+fn longest_increasing(seq: &[usize]) -> Vec<bool> {
+    // Indices of the smallest last elements of increasing subsequences of each length
+    let mut tails: Vec<usize> = vec![];
+    let mut previous = vec![None; seq.len()];
+    for (k, &x) in seq.iter().enumerate() {
+        let len = tails.partition_point(|&t| seq[t] < x);
+        if len > 0 {
+            previous[k] = Some(tails[len - 1]);
+        }
+        if len == tails.len() {
+            tails.push(k);
+        } else {
+            tails[len] = k;
+        }
+    }
+
+    let mut keep = vec![false; seq.len()];
+    let mut k = tails.last().cloned();
+    while let Some(i) = k {
+        keep[i] = true;
+        k = previous[i];
+    }
+    keep
+}
+
+/// Collects the paths of the unmatched children below items matched by their text or position
+fn collect_unmatched<'a>(
+    old: &'a StringItem,
+    new: &'a StringItem,
+    paths: (&[usize], &[usize]),
+    deleted: &mut Vec<(Vec<usize>, &'a str)>,
+    inserted: &mut Vec<(Vec<usize>, &'a str)>,
+) {
+    let matching = Matching::new(old, new);
+    for &i in &matching.old_unmatched {
+        deleted.push((child_path(paths.0, i), &old.children[i].text));
+    }
+    for &j in &matching.new_unmatched {
+        inserted.push((child_path(paths.1, j), &new.children[j].text));
+    }
+    // Unmatched children at the same position may be renamed, so moves out of them are found as well
+    let renamed = matching
+        .new_unmatched
+        .iter()
+        .filter(|j| matching.old_unmatched.contains(j))
+        .map(|&j| (j, j));
+    for (i, j) in matching.pairs.iter().cloned().chain(renamed) {
+        let child_paths = (&child_path(paths.0, i)[..], &child_path(paths.1, j)[..]);
+        collect_unmatched(&old.children[i], &new.children[j], child_paths, deleted, inserted);
+    }
+}
+
+/// Comparison of two trees, after moves between parents were found
+struct Differ<'a> {
+    old_root: &'a StringItem,
+    /// Old paths of moved items, by their new paths
+    moved_to: HashMap<Vec<usize>, Vec<usize>>,
+    /// Old paths of moved items
+    moved_from: HashSet<Vec<usize>>,
+    edits: Vec<Edit>,
+}
+
+impl<'a> Differ<'a> {
+    fn new(old: &'a StringItem, new: &'a StringItem) -> Self {
+        let (mut deleted, mut inserted) = (vec![], vec![]);
+        collect_unmatched(old, new, (&[], &[]), &mut deleted, &mut inserted);
+
+        let mut moved_to = HashMap::new();
+        for (to, label) in inserted {
+            if let Some(k) = deleted.iter().position(|&(_, l)| l == label) {
+                moved_to.insert(to, deleted.remove(k).0);
+            }
+        }
+        Differ {
+            old_root: old,
+            moved_from: moved_to.values().cloned().collect(),
+            moved_to,
+            edits: vec![],
+        }
+    }
+
+    /// Compares corresponding items `old` and `new`, which are at `paths` in the old and new trees
+    fn diff_item(
+        &mut self,
+        old: &StringItem,
+        new: &StringItem,
+        paths: (&[usize], &[usize]),
+        change: Change,
+    ) -> DiffItem {
+        let label = if change == Change::Renamed {
+            format!("{} \u{2192} {}", old.text, new.text)
+        } else {
+            new.text.clone()
+        };
+        DiffItem {
+            label,
+            change,
+            children: self.diff_children(old, new, paths),
+        }
+    }
+
+    fn diff_children(&mut self, old: &StringItem, new: &StringItem, paths: (&[usize], &[usize])) -> Vec<DiffItem> {
+        let (old_path, new_path) = paths;
+        let matching = Matching::new(old, new);
+        let mut children: Vec<(usize, DiffItem)> = vec![];
+
+        let old_order: Vec<usize> = matching.pairs.iter().map(|&(i, _)| i).collect();
+        let in_order = longest_increasing(&old_order);
+        for (&(i, j), in_order) in matching.pairs.iter().zip(in_order) {
+            let (from, to) = (child_path(old_path, i), child_path(new_path, j));
+            let change = if in_order {
+                Change::Unchanged
+            } else {
+                self.edits.push(Edit::Move {
+                    from: from.clone(),
+                    to: to.clone(),
+                    label: new.children[j].text.clone(),
+                });
+                Change::Moved
+            };
+            let item = self.diff_item(&old.children[i], &new.children[j], (&from, &to), change);
+            children.push((j, item));
+        }
+
+        let old_left: Vec<usize> = matching
+            .old_unmatched
+            .iter()
+            .cloned()
+            .filter(|&i| !self.moved_from.contains(&child_path(old_path, i)))
+            .collect();
+        let mut deleted = old_left.clone();
+        for &j in &matching.new_unmatched {
+            let to = child_path(new_path, j);
+            let item = match self.moved_to.get(&to).cloned() {
+                Some(from) => {
+                    self.edits.push(Edit::Move {
+                        from: from.clone(),
+                        to: to.clone(),
+                        label: new.children[j].text.clone(),
+                    });
+                    let old_root = self.old_root;
+                    self.diff_item(item_at(old_root, &from), &new.children[j], (&from, &to), Change::Moved)
+                }
+                None if old_left.contains(&j) => {
+                    deleted.retain(|&i| i != j);
+                    self.edits.push(Edit::Rename {
+                        path: to.clone(),
+                        from: old.children[j].text.clone(),
+                        to: new.children[j].text.clone(),
+                    });
+                    let from = child_path(old_path, j);
+                    self.diff_item(&old.children[j], &new.children[j], (&from, &to), Change::Renamed)
+                }
+                None => {
+                    self.edits.push(Edit::Insert {
+                        path: to,
+                        label: new.children[j].text.clone(),
+                    });
+                    DiffItem::one_sided(&new.children[j], Change::Inserted)
+                }
+            };
+            children.push((j, item));
+        }
+        children.sort_by_key(|&(j, _)| j);
+
+        for i in deleted {
+            self.edits.push(Edit::Delete {
+                path: child_path(old_path, i),
+                label: old.children[i].text.clone(),
+            });
+            children.push((
+                new.children.len(),
+                DiffItem::one_sided(&old.children[i], Change::Deleted),
+            ));
+        }
+        children.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+///
+/// Compares the trees `old` and `new` by the text of their items
+///
+/// The text of each item is written without any style. See the [module documentation] for how items are matched.
+///
+/// ### Errors
+///
+/// Returns an error if writing the text of an item fails.
+///
+/// [module documentation]: index.html
+pub fn diff<T: TreeItem>(old: &T, new: &T) -> io::Result<TreeDiff> {
+    let (old, new) = (labels(old)?, labels(new)?);
+    let mut differ = Differ::new(&old, &new);
+
+    let change = if old.text == new.text {
+        Change::Unchanged
+    } else {
+        differ.edits.push(Edit::Rename {
+            path: vec![],
+            from: old.text.clone(),
+            to: new.text.clone(),
+        });
+        Change::Renamed
+    };
+    let tree = differ.diff_item(&old, &new, (&[], &[]), change);
+    Ok(TreeDiff {
+        edits: differ.edits,
+        tree,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use builder::TreeBuilder;
+    use output::write_tree_with;
+    use print_config::{PrintConfig, StyleWhen, UTF_CHARS};
+    use std::str::from_utf8;
+
+    #[test]
+    fn edits_and_annotated_tree() {
+        let old = TreeBuilder::new("project".to_string())
+            .begin_child("src".to_string())
+            .add_empty_child("main.rs".to_string())
+            .add_empty_child("lib.rs".to_string())
+            .add_empty_child("build.rs".to_string())
+            .end_child()
+            .begin_child("docs".to_string())
+            .add_empty_child("guide.md".to_string())
+            .end_child()
+            .add_empty_child("README".to_string())
+            .build();
+        let new = TreeBuilder::new("project".to_string())
+            .begin_child("src".to_string())
+            .add_empty_child("lib.rs".to_string())
+            .add_empty_child("main.rs".to_string())
+            .add_empty_child("guide.md".to_string())
+            .end_child()
+            .add_empty_child("doc".to_string())
+            .add_empty_child("README.md".to_string())
+            .add_empty_child("LICENSE".to_string())
+            .build();
+
+        let diff = diff(&old, &new).unwrap();
+        assert_eq!(
+            diff.edits(),
+            &[
+                Edit::Move {
+                    from: vec![0, 1],
+                    to: vec![0, 0],
+                    label: "lib.rs".to_string(),
+                },
+                Edit::Move {
+                    from: vec![1, 0],
+                    to: vec![0, 2],
+                    label: "guide.md".to_string(),
+                },
+                Edit::Delete {
+                    path: vec![0, 2],
+                    label: "build.rs".to_string(),
+                },
+                Edit::Rename {
+                    path: vec![1],
+                    from: "docs".to_string(),
+                    to: "doc".to_string(),
+                },
+                Edit::Rename {
+                    path: vec![2],
+                    from: "README".to_string(),
+                    to: "README.md".to_string(),
+                },
+                Edit::Insert {
+                    path: vec![3],
+                    label: "LICENSE".to_string(),
+                },
+            ]
+        );
+
+        let config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Never,
+            ..PrintConfig::default()
+        };
+        let mut out = Vec::new();
+        write_tree_with(diff.tree(), &mut out, &config).unwrap();
+        assert_eq!(
+            from_utf8(&out).unwrap(),
+            "\
+             project\n\
+             ├─ src\n\
+             │  ├─ > lib.rs\n\
+             │  ├─ main.rs\n\
+             │  ├─ > guide.md\n\
+             │  └─ - build.rs\n\
+             ├─ ~ docs → doc\n\
+             ├─ ~ README → README.md\n\
+             └─ + LICENSE\n"
+        );
+    }
+
+    #[test]
+    fn equal_trees() {
+        let tree = TreeBuilder::new("a".to_string())
+            .add_empty_child("b".to_string())
+            .build();
+        assert!(diff(&tree, &tree).unwrap().is_empty());
+
+        let renamed = TreeBuilder::new("c".to_string())
+            .add_empty_child("b".to_string())
+            .build();
+        let diff = diff(&tree, &renamed).unwrap();
+        assert_eq!(diff.tree().change(), Change::Renamed);
+        assert_eq!(diff.edits().len(), 1);
+    }
+
+    #[test]
+    fn longest_increasing_subsequence() {
+        assert!(longest_increasing(&[]).is_empty());
+        assert_eq!(longest_increasing(&[0, 1, 2]), vec![true, true, true]);
+        assert_eq!(longest_increasing(&[2, 0, 1, 3]), vec![false, true, true, true]);
+    }
+}
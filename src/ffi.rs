@@ -0,0 +1,354 @@
+//!
+//! C interface for building and rendering trees
+//!
+//! The functions in this module can be called from C, C++, or other languages with a C foreign function interface,
+//! such as Python through `ctypes`, when the crate is built as a dynamic or static library:
+//!
+//! ```sh
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! Trees are built like with a [`TreeBuilder`], and rendered into a caller-provided buffer.
+//! The declarations for C are:
+//!
+//! ```c
+//! #include <stddef.h>
+//!
+//! typedef struct ptree_builder ptree_builder;
+//! typedef struct ptree_config ptree_config;
+//!
+//! ptree_builder *ptree_builder_new(const char *text);
+//! int ptree_builder_begin_child(ptree_builder *builder, const char *text);
+//! int ptree_builder_add_empty_child(ptree_builder *builder, const char *text);
+//! int ptree_builder_end_child(ptree_builder *builder);
+//! void ptree_builder_free(ptree_builder *builder);
+//!
+//! ptree_config *ptree_config_new(void);
+//! int ptree_config_set(ptree_config *config, const char *key, const char *value);
+//! void ptree_config_free(ptree_config *config);
+//!
+//! ptrdiff_t ptree_render(const ptree_builder *builder, const ptree_config *config, char *buffer, size_t len);
+//! ```
+//!
+//! Strings are NUL-terminated and UTF-8 encoded. Functions returning `int` return 0 on success and -1 on failure,
+//! such as for invalid UTF-8 or unknown configuration keys.
+//! Objects are owned by the caller, who has to release them with the matching `free` function.
+//!
+//! [`TreeBuilder`]: ../builder/struct.TreeBuilder.html
+//!
+
+use builder::TreeBuilder;
+use output::write_tree_with;
+use print_config::{PrintConfig, StyleWhen};
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+use std::slice;
+
+///
+/// Tree under construction, created by [`ptree_builder_new`]
+///
+/// [`ptree_builder_new`]: fn.ptree_builder_new.html
+///
+pub struct PtreeBuilder {
+    builder: TreeBuilder,
+    /// Number of children begun and not yet ended
+    open: u32,
+}
+
+///
+/// Formatting configuration, created by [`ptree_config_new`]
+///
+/// [`ptree_config_new`]: fn.ptree_config_new.html
+///
+pub struct PtreeConfig {
+    config: PrintConfig,
+}
+
+/// Returns the configuration used if none is given, which does not style output
+fn default_config() -> PrintConfig {
+    PrintConfig {
+        styled: StyleWhen::Never,
+        ..PrintConfig::default()
+    }
+}
+
+/// Converts a NUL-terminated string from C, returning `None` if it is null or not UTF-8
+unsafe fn to_string(text: *const c_char) -> Option<String> {
+    if text.is_null() {
+        return None;
+    }
+    CStr::from_ptr(text).to_str().ok().map(String::from)
+}
+
+/// Converts the result of an operation to a C status code
+fn status(ok: bool) -> c_int {
+    if ok {
+        0
+    } else {
+        -1
+    }
+}
+
+///
+/// Starts building a tree whose root has the text `text`
+///
+/// Returns null if `text` is null or not valid UTF-8.
+///
+/// # Safety
+///
+/// `text` must be null or point to a NUL-terminated string.
+///
+#[no_mangle]
+pub unsafe extern "C" fn ptree_builder_new(text: *const c_char) -> *mut PtreeBuilder {
+    match to_string(text) {
+        Some(text) => Box::into_raw(Box::new(PtreeBuilder {
+            builder: TreeBuilder::new(text),
+            open: 0,
+        })),
+        None => ptr::null_mut(),
+    }
+}
+
+///
+/// Adds a child with the text `text` to the current item, and makes it the current item
+///
+/// # Safety
+///
+/// `builder` must be null or returned by `ptree_builder_new` and not yet freed,
+/// and `text` must be null or point to a NUL-terminated string.
+///
+#[no_mangle]
+pub unsafe extern "C" fn ptree_builder_begin_child(builder: *mut PtreeBuilder, text: *const c_char) -> c_int {
+    let (builder, text) = match (builder.as_mut(), to_string(text)) {
+        (Some(builder), Some(text)) => (builder, text),
+        _ => return -1,
+    };
+    builder.builder.begin_child(text);
+    builder.open += 1;
+    0
+}
+
+///
+/// Adds a child without children with the text `text` to the current item
+///
+/// # Safety
+///
+/// `builder` must be null or returned by `ptree_builder_new` and not yet freed,
+/// and `text` must be null or point to a NUL-terminated string.
+///
+#[no_mangle]
+pub unsafe extern "C" fn ptree_builder_add_empty_child(builder: *mut PtreeBuilder, text: *const c_char) -> c_int {
+    let (builder, text) = match (builder.as_mut(), to_string(text)) {
+        (Some(builder), Some(text)) => (builder, text),
+        _ => return -1,
+    };
+    builder.builder.add_empty_child(text);
+    0
+}
+
+///
+/// Makes the parent of the current item the current item
+///
+/// Fails if the current item is the root.
+///
+/// # Safety
+///
+/// `builder` must be null or returned by `ptree_builder_new` and not yet freed.
+///
+#[no_mangle]
+pub unsafe extern "C" fn ptree_builder_end_child(builder: *mut PtreeBuilder) -> c_int {
+    match builder.as_mut() {
+        Some(builder) if builder.open > 0 => {
+            builder.builder.end_child();
+            builder.open -= 1;
+            0
+        }
+        _ => -1,
+    }
+}
+
+///
+/// Frees a tree created by `ptree_builder_new`
+///
+/// # Safety
+///
+/// `builder` must be null or returned by `ptree_builder_new` and not yet freed.
+///
+#[no_mangle]
+pub unsafe extern "C" fn ptree_builder_free(builder: *mut PtreeBuilder) {
+    if !builder.is_null() {
+        drop(Box::from_raw(builder));
+    }
+}
+
+///
+/// Creates the default configuration, which does not style output
+///
+#[no_mangle]
+pub extern "C" fn ptree_config_new() -> *mut PtreeConfig {
+    Box::into_raw(Box::new(PtreeConfig {
+        config: default_config(),
+    }))
+}
+
+///
+/// Sets the configuration parameter `key` to `value`
+///
+/// Only the following keys are supported, and setting any other key fails:
+/// `depth`, `max_children`, `indent` and `padding` take numbers,
+/// `characters` takes the name of a character set such as `utf` or `ascii`,
+/// `branch`, `leaf`, `separator` and `omitted` take comma-separated styles such as `bold,red`,
+/// and `styled` takes `always` or `never`.
+///
+/// # Safety
+///
+/// `config` must be null or returned by `ptree_config_new` and not yet freed,
+/// and `key` and `value` must be null or point to NUL-terminated strings.
+///
+#[no_mangle]
+pub unsafe extern "C" fn ptree_config_set(
+    config: *mut PtreeConfig,
+    key: *const c_char,
+    value: *const c_char,
+) -> c_int {
+    let (config, key, value) = match (config.as_mut(), to_string(key), to_string(value)) {
+        (Some(config), Some(key), Some(value)) => (&mut config.config, key, value),
+        _ => return -1,
+    };
+
+    let ok = match &key[..] {
+        "depth" => value.parse().map(|depth| config.depth = depth).is_ok(),
+        "max_children" => value.parse().map(|max| config.max_children = Some(max)).is_ok(),
+        "indent" => value.parse().map(|indent| config.indent = indent).is_ok(),
+        "padding" => value.parse().map(|padding| config.padding = padding).is_ok(),
+        "characters" => value.parse().map(|characters| config.characters = characters).is_ok(),
+        "branch" => value.parse().map(|style| config.branch = style).is_ok(),
+        "leaf" => value.parse().map(|style| config.leaf = style).is_ok(),
+        "separator" => value.parse().map(|style| config.separator = style).is_ok(),
+        "omitted" => value.parse().map(|style| config.omitted = style).is_ok(),
+        "styled" => match &value[..] {
+            "always" => {
+                config.styled = StyleWhen::Always;
+                true
+            }
+            "never" => {
+                config.styled = StyleWhen::Never;
+                true
+            }
+            _ => false,
+        },
+        _ => false,
+    };
+    status(ok)
+}
+
+///
+/// Frees a configuration created by `ptree_config_new`
+///
+/// # Safety
+///
+/// `config` must be null or returned by `ptree_config_new` and not yet freed.
+///
+#[no_mangle]
+pub unsafe extern "C" fn ptree_config_free(config: *mut PtreeConfig) {
+    if !config.is_null() {
+        drop(Box::from_raw(config));
+    }
+}
+
+///
+/// Renders the tree built by `builder` into `buffer`, which has room for `len` bytes
+///
+/// The output is truncated to fit into the buffer, and terminated with a NUL byte if `len` is not 0.
+/// Like `snprintf`, this returns the length of the complete output without the NUL byte,
+/// so a buffer of the right size can be allocated by first calling this with a `len` of 0.
+/// The length is returned as an `isize`, which is `ptrdiff_t` in C and as wide as a pointer on all platforms.
+/// Returns -1 if `builder` is null or rendering fails.
+///
+/// If `config` is null, the default configuration is used.
+///
+/// # Safety
+///
+/// `builder` must be null or returned by `ptree_builder_new` and not yet freed,
+/// `config` must be null or returned by `ptree_config_new` and not yet freed,
+/// and `buffer` must point to at least `len` writable bytes unless `len` is 0.
+///
+#[no_mangle]
+pub unsafe extern "C" fn ptree_render(
+    builder: *const PtreeBuilder,
+    config: *const PtreeConfig,
+    buffer: *mut c_char,
+    len: usize,
+) -> isize {
+    let builder = match builder.as_ref() {
+        Some(builder) => builder,
+        None => return -1,
+    };
+    let default_config = default_config();
+    let config = config.as_ref().map_or(&default_config, |config| &config.config);
+
+    let tree = builder.builder.build();
+    let mut out = Vec::new();
+    if write_tree_with(&tree, &mut out, config).is_err() {
+        return -1;
+    }
+
+    if len > 0 && !buffer.is_null() {
+        let n = out.len().min(len - 1);
+        let buffer = slice::from_raw_parts_mut(buffer as *mut u8, len);
+        buffer[..n].copy_from_slice(&out[..n]);
+        buffer[n] = 0;
+    }
+    // A `Vec` never holds more than `isize::MAX` bytes
+    out.len() as isize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ffi::CString;
+
+    fn c(text: &str) -> CString {
+        CString::new(text).unwrap()
+    }
+
+    #[test]
+    fn build_and_render() {
+        unsafe {
+            let builder = ptree_builder_new(c("root").as_ptr());
+            assert_eq!(ptree_builder_begin_child(builder, c("branch").as_ptr()), 0);
+            assert_eq!(ptree_builder_add_empty_child(builder, c("leaf").as_ptr()), 0);
+            assert_eq!(ptree_builder_end_child(builder), 0);
+            assert_eq!(ptree_builder_end_child(builder), -1);
+            assert_eq!(ptree_builder_add_empty_child(builder, ptr::null()), -1);
+
+            let config = ptree_config_new();
+            assert_eq!(ptree_config_set(config, c("characters").as_ptr(), c("utf").as_ptr()), 0);
+            assert_eq!(
+                ptree_config_set(config, c("branch").as_ptr(), c("bold,red").as_ptr()),
+                0
+            );
+            assert_eq!(ptree_config_set(config, c("indent").as_ptr(), c("4").as_ptr()), 0);
+            assert_eq!(ptree_config_set(config, c("indent").as_ptr(), c("four").as_ptr()), -1);
+            assert_eq!(ptree_config_set(config, c("unknown").as_ptr(), c("1").as_ptr()), -1);
+
+            let expected = "root\n└── branch\n    └── leaf\n";
+            let len = ptree_render(builder, config, ptr::null_mut(), 0);
+            assert_eq!(len as usize, expected.len());
+
+            let mut buffer = vec![1 as c_char; len as usize + 1];
+            assert_eq!(ptree_render(builder, config, buffer.as_mut_ptr(), buffer.len()), len);
+            assert_eq!(CStr::from_ptr(buffer.as_ptr()).to_str().unwrap(), expected);
+
+            let mut short = [1 as c_char; 5];
+            assert_eq!(ptree_render(builder, config, short.as_mut_ptr(), short.len()), len);
+            assert_eq!(CStr::from_ptr(short.as_ptr()).to_str().unwrap(), "root");
+
+            ptree_config_free(config);
+            ptree_builder_free(builder);
+        }
+    }
+}
@@ -0,0 +1,1204 @@
+//!
+//! Printing of directory trees
+//!
+//! An [`FsItem`] lists the entries of a directory as its children, sorted by name
+//! in the ordering selected by [`FsConfig::collation`].
+//! Directories are expanded recursively, while symbolic links are listed with their targets,
+//! such as `current -> v1.2`, and are only followed if [`FsConfig::follow_links`] is set.
+//! Directories which are reached again below themselves, such as through a link to a parent directory,
+//! are marked and not expanded a second time, so printing can't loop forever.
+//! Entries which cannot be read are listed without children.
+//!
+//! Metadata of each entry, such as its size or permissions, can be printed in columns before its name,
+//! similar to `tree -pugsh`. The columns are selected in the [`FsConfig`].
+//!
+//! Traversal can be bounded by depth, by the number of entries listed per directory,
+//! and by glob patterns for the names of included and excluded entries.
+//!
+//! Names are styled by file type and extension, and can be prefixed with emoji or Nerd Font icons,
+//! similar to `lsd --tree`.
+//!
+//! The [`Column::TotalSize`] column shows the size of each directory including everything below it, like `du`.
+//! With the `"parallel"` feature, these sizes are computed on multiple threads.
+//!
+//! With the `"ignore"` feature, entries matched by `.gitignore` files can be skipped,
+//! which is useful for printing project directories.
+//!
+//! [`FsItem`]: struct.FsItem.html
+//! [`FsConfig`]: struct.FsConfig.html
+//! [`Column::TotalSize`]: enum.Column.html#variant.TotalSize
+//! [`FsConfig::follow_links`]: struct.FsConfig.html#structfield.follow_links
+//! [`FsConfig::collation`]: struct.FsConfig.html#structfield.collation
+//!
+
+use collate::Collation;
+use item::TreeItem;
+use output::{print_tree_with, write_tree_with};
+use print_config::PrintConfig;
+use style::{Color, Style};
+
+use std::borrow::Cow;
+use std::cell::{Ref, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+#[cfg(unix)]
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+#[cfg(feature = "ignore")]
+use ignore::WalkBuilder;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+///
+/// Metadata column printed before the name of each entry
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Column {
+    /// File type and permissions, such as `drwxr-xr-x`
+    Permissions,
+    /// Name of the owning user, or its id if it has no name
+    Owner,
+    /// Name of the owning group, or its id if it has no name
+    Group,
+    /// Size in bytes, or with a unit suffix if [`FsConfig::human_sizes`] is set
+    ///
+    /// [`FsConfig::human_sizes`]: struct.FsConfig.html#structfield.human_sizes
+    Size,
+    /// Size of the entry including all entries below it, like `du`, formatted like [`Column::Size`]
+    ///
+    /// Symbolic links are not followed, and all entries are counted, including hidden and excluded ones.
+    ///
+    /// [`Column::Size`]: enum.Column.html#variant.Size
+    TotalSize,
+    /// Time of the last modification, in UTC, such as `2024-01-31 14:05`
+    Modified,
+}
+
+///
+/// Set of icons printed before the names of entries
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Icons {
+    /// No icons are printed
+    #[default]
+    None,
+    /// Emoji, such as `📁` for directories, which most terminals can show
+    Emoji,
+    /// Icons of the [Nerd Fonts](https://www.nerdfonts.com/), which require such a font in the terminal
+    NerdFont,
+}
+
+///
+/// Structure controlling how directory trees are printed
+///
+#[derive(Clone, Debug)]
+pub struct FsConfig {
+    /// Metadata columns printed before the name of each entry, in this order
+    ///
+    /// The columns are printed in brackets, such as `[drwxr-xr-x 4.0K]  src`.
+    /// The default value is empty, i.e. only names are printed.
+    pub columns: Vec<Column>,
+    /// Styles of the metadata columns
+    ///
+    /// Columns without a style are printed with the style of the item.
+    /// By default, all columns are dimmed, so the names stand out.
+    pub column_styles: BTreeMap<Column, Style>,
+    /// Whether sizes are printed with a unit suffix, such as `4.0K` or `12M`, instead of in bytes
+    pub human_sizes: bool,
+    /// Whether sizes with a unit suffix use binary unit names, such as `4.0 KiB` or `12 MiB`
+    pub binary_units: bool,
+    /// Whether hidden entries, whose names start with a dot, are listed
+    ///
+    /// The default value is `true`.
+    pub show_hidden: bool,
+    /// Whether entries matched by `.gitignore`, `.ignore` and the other ignore files of `git` are skipped
+    ///
+    /// Ignore files are read from the printed directories and their ancestors,
+    /// also outside of git repositories.
+    /// The default value is `false`.
+    ///
+    /// This option is only available with the `"ignore"` feature.
+    #[cfg(feature = "ignore")]
+    pub git_ignore: bool,
+    /// Whether symbolic links to directories are expanded like directories
+    ///
+    /// Links which lead back to one of their ancestors are never expanded.
+    /// The root is always followed if it is a link.
+    /// The default value is `false`.
+    pub follow_links: bool,
+    /// Whether the targets of symbolic links are printed after their names, such as `current -> v1.2`
+    ///
+    /// The default value is `true`.
+    pub show_link_targets: bool,
+    /// Maximum depth of listed entries, where the entries of the root directory have depth 1
+    ///
+    /// Deeper directories are not read at all.
+    /// The default value is `None`, i.e. all entries are listed.
+    pub max_depth: Option<usize>,
+    /// Maximum number of entries listed per directory
+    ///
+    /// Directories with more entries are labeled with their number of entries, such as `node_modules  [1204 entries]`,
+    /// and only the first entries by name are listed.
+    /// The default value is `None`, i.e. all entries are listed.
+    pub max_entries_per_dir: Option<usize>,
+    /// Glob patterns, such as `*.rs`, of which the names of listed files must match one
+    ///
+    /// Directories are always listed, so matching files in subdirectories are found.
+    /// Patterns support `*` for any number of characters and `?` for a single character.
+    /// The default value is empty, i.e. all files are listed.
+    pub include: Vec<String>,
+    /// Glob patterns, such as `node_modules` or `*.o`, whose matching entries are not listed
+    ///
+    /// Excluded directories are not read at all.
+    /// The default value is empty.
+    pub exclude: Vec<String>,
+    /// Icons printed before the names of entries
+    ///
+    /// The default value is [`Icons::None`].
+    ///
+    /// [`Icons::None`]: enum.Icons.html#variant.None
+    pub icons: Icons,
+    /// Style of the names of directories, merged with the style of the item
+    ///
+    /// By default, directories are bold and blue.
+    pub dir_style: Style,
+    /// Styles of the names of files by their extension without the dot, such as `rs`
+    ///
+    /// Extensions are matched case-insensitively, so the keys should be in lowercase.
+    /// By default, a few common extensions are colored, such as `rs` in orange.
+    pub extension_styles: BTreeMap<String, Style>,
+    /// Ordering of the names of entries within each directory
+    ///
+    /// The default value is [`Collation::Bytes`], which sorts `file10` before `file2` and `Zebra` before `apple`.
+    ///
+    /// [`Collation::Bytes`]: ../collate/enum.Collation.html#variant.Bytes
+    pub collation: Collation,
+}
+
+impl Default for FsConfig {
+    fn default() -> FsConfig {
+        let dimmed = Style {
+            dimmed: true,
+            ..Style::default()
+        };
+        let colored = |color| Style {
+            foreground: Some(color),
+            ..Style::default()
+        };
+        let extensions = [
+            ("rs", colored(Color::Fixed(208))),
+            ("toml", colored(Color::Yellow)),
+            ("json", colored(Color::Yellow)),
+            ("yaml", colored(Color::Yellow)),
+            ("yml", colored(Color::Yellow)),
+            ("md", colored(Color::White)),
+            ("sh", colored(Color::Green)),
+        ];
+        let columns = [
+            Column::Permissions,
+            Column::Owner,
+            Column::Group,
+            Column::Size,
+            Column::TotalSize,
+            Column::Modified,
+        ];
+
+        FsConfig {
+            columns: vec![],
+            column_styles: columns.iter().map(|&c| (c, dimmed.clone())).collect(),
+            human_sizes: false,
+            binary_units: false,
+            show_hidden: true,
+            #[cfg(feature = "ignore")]
+            git_ignore: false,
+            follow_links: false,
+            show_link_targets: true,
+            max_depth: None,
+            max_entries_per_dir: None,
+            include: vec![],
+            exclude: vec![],
+            icons: Icons::None,
+            dir_style: Style {
+                bold: true,
+                ..colored(Color::Blue)
+            },
+            extension_styles: extensions
+                .iter()
+                .map(|(e, style)| (e.to_string(), style.clone()))
+                .collect(),
+            collation: Collation::default(),
+        }
+    }
+}
+
+/// State shared by all items of one printed directory tree
+#[derive(Debug)]
+struct FsState {
+    config: FsConfig,
+    /// Names of users and groups by id, read from `/etc/passwd` and `/etc/group` when first needed
+    users: RefCell<Option<HashMap<u32, String>>>,
+    groups: RefCell<Option<HashMap<u32, String>>>,
+    /// Total sizes of directories by path, computed when first needed
+    totals: Mutex<HashMap<PathBuf, u64>>,
+}
+
+/// Identity of a directory, independent of the path it was reached by
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
+
+/// Directories enclosing an entry, from the nearest one up to the root
+#[derive(Debug)]
+struct Ancestors {
+    id: DirId,
+    parent: Option<Rc<Ancestors>>,
+}
+
+///
+/// Item for printing a directory tree
+///
+/// The root is labeled with the path it was created with, and the other entries with their file names.
+///
+#[derive(Clone, Debug)]
+pub struct FsItem {
+    path: PathBuf,
+    /// Metadata of the entry, without following symbolic links
+    metadata: Option<Metadata>,
+    root: bool,
+    /// Number of directories between the root and this entry, plus one
+    depth: usize,
+    ancestors: Option<Rc<Ancestors>>,
+    state: Rc<FsState>,
+    /// Listed entries of this directory, read when they are first needed
+    ///
+    /// Both the entry count and the children are taken from them, so the directory is only read once.
+    entries: RefCell<Option<Vec<FsItem>>>,
+}
+
+impl FsItem {
+    ///
+    /// Creates the root item for printing the directory tree at `path`
+    ///
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        FsItem::with_config(path, FsConfig::default())
+    }
+
+    ///
+    /// Creates the root item for printing the directory tree at `path` with the options in `config`
+    ///
+    pub fn with_config<P: Into<PathBuf>>(path: P, config: FsConfig) -> Self {
+        let path = path.into();
+        FsItem {
+            metadata: fs::symlink_metadata(&path).ok(),
+            path,
+            root: true,
+            depth: 0,
+            ancestors: None,
+            state: Rc::new(FsState {
+                config,
+                users: RefCell::new(None),
+                groups: RefCell::new(None),
+                totals: Mutex::new(HashMap::new()),
+            }),
+            entries: RefCell::new(None),
+        }
+    }
+
+    ///
+    /// Returns the path of this entry
+    ///
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    ///
+    /// Returns the metadata of this entry, if it could be read
+    ///
+    /// Symbolic links are not followed, so this is the metadata of the link itself.
+    ///
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.as_ref()
+    }
+
+    /// Returns the metadata of the directory listed as children of this entry, if it is one
+    fn dir_metadata(&self) -> Option<Metadata> {
+        if self.state.config.max_depth.is_some_and(|max| self.depth >= max) {
+            return None;
+        }
+        let metadata = self.metadata.as_ref()?;
+        if metadata.is_dir() {
+            Some(metadata.clone())
+        } else if metadata.file_type().is_symlink() && (self.root || self.state.config.follow_links) {
+            fs::metadata(&self.path).ok().filter(Metadata::is_dir)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether the directory `id` encloses this entry
+    fn is_ancestor(&self, id: &DirId) -> bool {
+        let mut ancestor = self.ancestors.as_ref();
+        while let Some(current) = ancestor {
+            if current.id == *id {
+                return true;
+            }
+            ancestor = current.parent.as_ref();
+        }
+        false
+    }
+
+    /// Returns whether this entry is a directory enclosing itself, which is not expanded again
+    fn is_cycle(&self) -> bool {
+        self.dir_metadata()
+            .and_then(|metadata| dir_id(&self.path, &metadata))
+            .is_some_and(|id| self.is_ancestor(&id))
+    }
+
+    /// Returns the text of `column` for this entry
+    fn column_text(&self, column: Column) -> String {
+        let metadata = match self.metadata {
+            Some(ref metadata) => metadata,
+            None => return "?".to_string(),
+        };
+        match column {
+            Column::Permissions => permissions_text(metadata),
+            Column::Owner => self.owner_text(metadata, true),
+            Column::Group => self.owner_text(metadata, false),
+            Column::Size => self.size_text(metadata.len()),
+            Column::TotalSize => self.size_text(total_size(&self.path, &self.state.totals)),
+            Column::Modified => match metadata.modified().ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()) {
+                Some(duration) => format_time(duration.as_secs()),
+                None => format!("{:16}", "?"),
+            },
+        }
+    }
+
+    /// Returns the lowercase extension of this entry, if it is a file with one
+    fn extension(&self) -> Option<String> {
+        if self.metadata.as_ref().is_some_and(Metadata::is_dir) {
+            return None;
+        }
+        self.path.extension().map(|e| e.to_string_lossy().to_lowercase())
+    }
+
+    /// Returns the style of the name of this entry, if it has one
+    fn name_style(&self) -> Option<&Style> {
+        let config = &self.state.config;
+        if self.metadata.as_ref().is_some_and(Metadata::is_dir) {
+            Some(&config.dir_style)
+        } else {
+            self.extension().and_then(|e| config.extension_styles.get(&e))
+        }
+    }
+
+    /// Returns the icon printed before the name of this entry
+    fn icon(&self) -> Option<&'static str> {
+        let icons = self.state.config.icons;
+        if icons == Icons::None {
+            return None;
+        }
+        let file_type = self.metadata.as_ref().map(Metadata::file_type);
+        let kind = if file_type.is_some_and(|t| t.is_dir()) {
+            "dir"
+        } else if file_type.is_some_and(|t| t.is_symlink()) {
+            "link"
+        } else {
+            match self.extension() {
+                Some(ref e) => match e.as_str() {
+                    "rs" => "rust",
+                    "md" | "txt" | "rst" => "text",
+                    "toml" | "json" | "yaml" | "yml" | "ini" | "conf" => "config",
+                    "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" => "image",
+                    "tar" | "gz" | "zip" | "xz" | "zst" | "7z" => "archive",
+                    "sh" | "bash" | "zsh" => "script",
+                    _ => "file",
+                },
+                None => "file",
+            }
+        };
+        Some(match (icons, kind) {
+            (Icons::Emoji, "dir") => "📁",
+            (Icons::Emoji, "link") => "🔗",
+            (Icons::Emoji, "rust") => "🦀",
+            (Icons::Emoji, "text") => "📝",
+            (Icons::Emoji, "config") => "⚙️",
+            (Icons::Emoji, "image") => "🖼️",
+            (Icons::Emoji, "archive") => "📦",
+            (Icons::Emoji, "script") => "📜",
+            (Icons::Emoji, _) => "📄",
+            (_, "dir") => "\u{f115}",
+            (_, "link") => "\u{f0c1}",
+            (_, "rust") => "\u{e7a8}",
+            (_, "text") => "\u{f15c}",
+            (_, "config") => "\u{e615}",
+            (_, "image") => "\u{f1c5}",
+            (_, "archive") => "\u{f410}",
+            (_, "script") => "\u{f489}",
+            (_, _) => "\u{f15b}",
+        })
+    }
+
+    /// Formats `size` for the size columns
+    fn size_text(&self, size: u64) -> String {
+        let config = &self.state.config;
+        match (config.human_sizes, config.binary_units) {
+            (true, true) => format!("{:>8}", binary_size(size)),
+            (true, false) => format!("{:>5}", human_size(size)),
+            (false, _) => format!("{:>11}", size),
+        }
+    }
+
+    /// Returns whether the entry at `path` passes the include and exclude patterns
+    fn is_listed(&self, path: &Path, metadata: Option<&Metadata>) -> bool {
+        let config = &self.state.config;
+        let name = match path.file_name() {
+            Some(name) => name.to_string_lossy(),
+            None => return true,
+        };
+        if config.exclude.iter().any(|pattern| glob_matches(pattern, &name)) {
+            return false;
+        }
+        config.include.is_empty()
+            || metadata.is_some_and(Metadata::is_dir)
+            || config.include.iter().any(|pattern| glob_matches(pattern, &name))
+    }
+
+    /// Returns all listed entries of this directory, sorted by name, reading them if they were not read yet
+    fn entries(&self) -> Ref<'_, [FsItem]> {
+        if self.entries.borrow().is_none() {
+            let entries = self.read_entries();
+            *self.entries.borrow_mut() = Some(entries);
+        }
+        Ref::map(self.entries.borrow(), |entries| entries.as_deref().unwrap_or(&[]))
+    }
+
+    /// Reads all listed entries of this directory, sorted by name
+    fn read_entries(&self) -> Vec<FsItem> {
+        let metadata = match self.dir_metadata() {
+            Some(metadata) => metadata,
+            None => return vec![],
+        };
+        let ancestors = match dir_id(&self.path, &metadata) {
+            Some(ref id) if self.is_ancestor(id) => return vec![],
+            Some(id) => Some(Rc::new(Ancestors {
+                id,
+                parent: self.ancestors.clone(),
+            })),
+            None => self.ancestors.clone(),
+        };
+
+        let mut entries: Vec<_> = self
+            .entry_paths()
+            .into_iter()
+            .map(|path| FsItem {
+                metadata: fs::symlink_metadata(&path).ok(),
+                path,
+                root: false,
+                depth: self.depth + 1,
+                ancestors: ancestors.clone(),
+                state: self.state.clone(),
+                entries: RefCell::new(None),
+            })
+            .filter(|entry| self.is_listed(&entry.path, entry.metadata.as_ref()))
+            .collect();
+        let collation = &self.state.config.collation;
+        entries.sort_by(|a, b| {
+            let (a, b) = (a.path.file_name(), b.path.file_name());
+            match (a.and_then(|a| a.to_str()), b.and_then(|b| b.to_str())) {
+                (Some(a), Some(b)) => collation.compare(a, b),
+                _ => a.cmp(&b),
+            }
+        });
+        entries
+    }
+
+    /// Returns the paths of the entries of this directory
+    fn entry_paths(&self) -> Vec<PathBuf> {
+        let config = &self.state.config;
+
+        #[cfg(feature = "ignore")]
+        {
+            if config.git_ignore {
+                return WalkBuilder::new(&self.path)
+                    .max_depth(Some(1))
+                    .hidden(!config.show_hidden)
+                    .require_git(false)
+                    .build()
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.depth() > 0)
+                    .map(|entry| entry.into_path())
+                    .collect();
+            }
+        }
+
+        match fs::read_dir(&self.path) {
+            Ok(entries) => entries
+                .filter_map(Result::ok)
+                .filter(|entry| config.show_hidden || !entry.file_name().to_string_lossy().starts_with('.'))
+                .map(|entry| entry.path())
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    #[cfg(unix)]
+    fn owner_text(&self, metadata: &Metadata, user: bool) -> String {
+        let (id, names, file) = if user {
+            (metadata.uid(), &self.state.users, "/etc/passwd")
+        } else {
+            (metadata.gid(), &self.state.groups, "/etc/group")
+        };
+        let mut names = names.borrow_mut();
+        let names = names.get_or_insert_with(|| read_names(file));
+        match names.get(&id) {
+            Some(name) => format!("{:8}", name),
+            None => format!("{:8}", id),
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn owner_text(&self, _metadata: &Metadata, _user: bool) -> String {
+        format!("{:8}", "?")
+    }
+}
+
+impl TreeItem for FsItem {
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        let config = &self.state.config;
+        if !self.root && !config.columns.is_empty() {
+            style.paint_to(f, format_args!("["))?;
+            for (i, &column) in config.columns.iter().enumerate() {
+                if i > 0 {
+                    style.paint_to(f, format_args!(" "))?;
+                }
+                let column_style = match config.column_styles.get(&column) {
+                    Some(column_style) => style.merge(column_style),
+                    None => style.clone(),
+                };
+                column_style.paint_to(f, format_args!("{}", self.column_text(column)))?;
+            }
+            style.paint_to(f, format_args!("]  "))?;
+        }
+
+        let name = match self.path.file_name() {
+            Some(name) if !self.root => name.to_string_lossy(),
+            _ => self.path.to_string_lossy(),
+        };
+        let name_style = match self.name_style() {
+            Some(name_style) => style.merge(name_style),
+            None => style.clone(),
+        };
+        match self.icon() {
+            Some(icon) => name_style.paint_to(f, format_args!("{} {}", icon, name))?,
+            None => name_style.paint_to(f, format_args!("{}", name))?,
+        }
+
+        let is_link = self.metadata.as_ref().is_some_and(|m| m.file_type().is_symlink());
+        if is_link && config.show_link_targets {
+            if let Ok(target) = fs::read_link(&self.path) {
+                style.paint_to(f, format_args!(" -> {}", target.display()))?;
+            }
+        }
+        if self.is_cycle() {
+            style.paint_to(f, format_args!("  [recursive, not followed]"))?;
+        } else if let Some(max) = config.max_entries_per_dir {
+            let len = self.entries().len();
+            if len > max {
+                style.paint_to(f, format_args!("  [{} entries]", len))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        let entries = self.entries();
+        let shown = match self.state.config.max_entries_per_dir {
+            Some(max) => max.min(entries.len()),
+            None => entries.len(),
+        };
+        Cow::from(entries[..shown].to_vec())
+    }
+}
+
+/// Returns whether `name` matches the glob `pattern`, where `*` matches any characters and `?` a single one
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // Matches greedily, backtracking to the last `*` on a mismatch
+    let (mut p, mut n) = (0, 0);
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Returns the identity of the directory at `path`, from its device and inode numbers
+#[cfg(unix)]
+fn dir_id(_path: &Path, metadata: &Metadata) -> Option<DirId> {
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// Returns the identity of the directory at `path`, from its canonical path
+#[cfg(not(unix))]
+fn dir_id(path: &Path, _metadata: &Metadata) -> Option<DirId> {
+    fs::canonicalize(path).ok()
+}
+
+/// Formats the file type and permissions of an entry, like `ls -l`
+#[cfg(unix)]
+fn permissions_text(metadata: &Metadata) -> String {
+    let file_type = metadata.file_type();
+    let kind = if file_type.is_dir() {
+        'd'
+    } else if file_type.is_symlink() {
+        'l'
+    } else if file_type.is_file() {
+        '-'
+    } else {
+        '?'
+    };
+
+    let mode = metadata.permissions().mode();
+    let mut text = kind.to_string();
+    for shift in &[6, 3, 0] {
+        let bits = mode >> shift;
+        text.push(if bits & 4 != 0 { 'r' } else { '-' });
+        text.push(if bits & 2 != 0 { 'w' } else { '-' });
+        text.push(if bits & 1 != 0 { 'x' } else { '-' });
+    }
+    text
+}
+
+/// Formats the file type and permissions of an entry, which only distinguish read-only entries
+#[cfg(not(unix))]
+fn permissions_text(metadata: &Metadata) -> String {
+    let kind = if metadata.is_dir() { 'd' } else { '-' };
+    let write = if metadata.permissions().readonly() { '-' } else { 'w' };
+    format!("{}r{}-------", kind, write)
+}
+
+/// Reads the names of users or groups by id from a file in the format of `/etc/passwd`
+#[cfg(unix)]
+fn read_names(file: &str) -> HashMap<u32, String> {
+    let contents = fs::read_to_string(file).unwrap_or_default();
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse().ok()?;
+            Some((id, name.to_string()))
+        })
+        .collect()
+}
+
+/// Returns the total size of the entry at `path` and all entries below it, caching the totals of directories
+fn total_size(path: &Path, totals: &Mutex<HashMap<PathBuf, u64>>) -> u64 {
+    if let Some(&total) = totals.lock().unwrap().get(path) {
+        return total;
+    }
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return 0,
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    let entries: Vec<PathBuf> = match fs::read_dir(path) {
+        Ok(entries) => entries.filter_map(Result::ok).map(|entry| entry.path()).collect(),
+        Err(_) => vec![],
+    };
+    #[cfg(feature = "parallel")]
+    let entries_size: u64 = entries.par_iter().map(|entry| total_size(entry, totals)).sum();
+    #[cfg(not(feature = "parallel"))]
+    let entries_size: u64 = entries.iter().map(|entry| total_size(entry, totals)).sum();
+
+    let total = metadata.len() + entries_size;
+    totals.lock().unwrap().insert(path.to_path_buf(), total);
+    total
+}
+
+/// Scales a size of at least 1024 bytes to a value below 1024 and the index of its unit, starting at kibibytes
+fn scaled_size(size: u64) -> (f64, usize) {
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < 5 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    (value, unit)
+}
+
+/// Formats a size with a unit suffix, like `tree -h`
+pub(crate) fn human_size(size: u64) -> String {
+    const UNITS: &[&str] = &["K", "M", "G", "T", "P", "E"];
+
+    if size < 1024 {
+        return size.to_string();
+    }
+    let (value, unit) = scaled_size(size);
+    if value < 10.0 {
+        format!("{:.1}{}", value, UNITS[unit])
+    } else {
+        format!("{:.0}{}", value, UNITS[unit])
+    }
+}
+
+/// Formats a size with a binary unit name, such as `4.0 KiB`
+fn binary_size(size: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+    let (value, unit) = scaled_size(size);
+    if value < 10.0 {
+        format!("{:.1} {}", value, UNITS[unit])
+    } else {
+        format!("{:.0} {}", value, UNITS[unit])
+    }
+}
+
+/// Formats a number of seconds since the Unix epoch as a UTC date and time, such as `2024-01-31 14:05`
+fn format_time(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let (hour, minute) = (secs % 86_400 / 3600, secs % 3600 / 60);
+
+    // Converts days since the epoch into a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, hour, minute)
+}
+
+/// Print the directory tree at `path` to standard output using default formatting
+pub fn print_dir<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    print_dir_with(path, &PrintConfig::for_terminal(), &FsConfig::default())
+}
+
+/// Print the directory tree at `path` to standard output using custom formatting
+pub fn print_dir_with<P: AsRef<Path>>(path: P, print_config: &PrintConfig, fs_config: &FsConfig) -> io::Result<()> {
+    let item = FsItem::with_config(path.as_ref(), fs_config.clone());
+    print_tree_with(&item, print_config)
+}
+
+///
+/// Write the directory tree at `path` to writer `f` using custom formatting
+///
+/// ```
+/// # extern crate ptree;
+/// # extern crate tempfile;
+/// # use std::fs;
+/// # use ptree::fs::{write_dir_with, Column, FsConfig};
+/// # use ptree::print_config::{PrintConfig, StyleWhen, UTF_CHARS};
+/// # fn main() {
+/// let dir = tempfile::tempdir().unwrap();
+/// fs::create_dir(dir.path().join("src")).unwrap();
+/// fs::write(dir.path().join("src/lib.rs"), "// empty\n").unwrap();
+///
+/// let print_config = PrintConfig {
+///     characters: UTF_CHARS.into(),
+///     styled: StyleWhen::Never,
+///     ..PrintConfig::default()
+/// };
+/// let fs_config = FsConfig {
+///     columns: vec![Column::Size],
+///     ..FsConfig::default()
+/// };
+///
+/// let mut out = Vec::new();
+/// write_dir_with(dir.path().join("src"), &mut out, &print_config, &fs_config).unwrap();
+/// let expected = format!("{}\n└─ [          9]  lib.rs\n", dir.path().join("src").display());
+/// assert_eq!(String::from_utf8(out).unwrap(), expected);
+/// # }
+/// ```
+pub fn write_dir_with<P, W>(path: P, f: W, print_config: &PrintConfig, fs_config: &FsConfig) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    W: io::Write,
+{
+    let item = FsItem::with_config(path.as_ref(), fs_config.clone());
+    write_tree_with(&item, f, print_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use print_config::{StyleWhen, UTF_CHARS};
+    use std::fs::File;
+    use std::str::from_utf8;
+    use std::time::Duration;
+
+    use tempfile;
+
+    #[test]
+    fn formatted_sizes_and_times() {
+        assert_eq!(human_size(0), "0");
+        assert_eq!(human_size(1023), "1023");
+        assert_eq!(human_size(4096), "4.0K");
+        assert_eq!(human_size(15 << 20), "15M");
+        assert_eq!(binary_size(512), "512 B");
+        assert_eq!(binary_size(1536), "1.5 KiB");
+        assert_eq!(binary_size(300 << 30), "300 GiB");
+        assert_eq!(format_time(0), "1970-01-01 00:00");
+        assert_eq!(format_time(951_827_696), "2000-02-29 12:34");
+        assert_eq!(format_time(1_706_709_900), "2024-01-31 14:05");
+    }
+
+    #[test]
+    fn dir_columns_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("b.txt"), vec![0; 5000]).unwrap();
+        fs::write(root.join("sub/a.txt"), "hello").unwrap();
+
+        let modified = UNIX_EPOCH + Duration::from_secs(1_706_709_900);
+        for file in &["b.txt", "sub/a.txt"] {
+            File::options()
+                .write(true)
+                .open(root.join(file))
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        let fs_config = FsConfig {
+            columns: vec![Column::Size, Column::Modified],
+            human_sizes: true,
+            ..FsConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_dir_with(&root, &mut out, &config, &fs_config).unwrap();
+        let out = from_utf8(&out).unwrap();
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], root.display().to_string());
+        assert_eq!(lines[1], "├─ [ 4.9K 2024-01-31 14:05]  b.txt");
+        assert!(lines[2].starts_with("└─ [") && lines[2].ends_with("]  sub"));
+        assert_eq!(lines[3], "   └─ [    5 2024-01-31 14:05]  a.txt");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn dir_permissions_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("script.sh");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o754)).unwrap();
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o750)).unwrap();
+
+        let item = FsItem::new(dir.path());
+        let children = item.children();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].column_text(Column::Permissions), "-rwxr-xr--");
+        assert_eq!(item.column_text(Column::Permissions), "drwxr-x---");
+        assert!(!children[0].column_text(Column::Owner).trim_end().is_empty());
+    }
+
+    fn child_names(item: &FsItem) -> Vec<String> {
+        item.children()
+            .iter()
+            .map(|c| c.path().file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn hidden_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".hidden"), "").unwrap();
+        fs::write(dir.path().join("shown"), "").unwrap();
+
+        assert_eq!(child_names(&FsItem::new(dir.path())), vec![".hidden", "shown"]);
+
+        let config = FsConfig {
+            show_hidden: false,
+            ..FsConfig::default()
+        };
+        assert_eq!(child_names(&FsItem::with_config(dir.path(), config)), vec!["shown"]);
+    }
+
+    #[test]
+    fn collated_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in &["file10", "file2", "file1"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        assert_eq!(child_names(&FsItem::new(dir.path())), vec!["file1", "file10", "file2"]);
+
+        let config = FsConfig {
+            collation: Collation::Natural,
+            ..FsConfig::default()
+        };
+        assert_eq!(
+            child_names(&FsItem::with_config(dir.path(), config)),
+            vec!["file1", "file2", "file10"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ignore")]
+    fn git_ignored_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "/target\n*.log\n").unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(dir.path().join("src/build.log"), "").unwrap();
+
+        let config = FsConfig {
+            git_ignore: true,
+            ..FsConfig::default()
+        };
+        let item = FsItem::with_config(dir.path(), config.clone());
+        assert_eq!(child_names(&item), vec![".gitignore", "src"]);
+        assert_eq!(child_names(&item.children()[1]), vec!["main.rs"]);
+
+        let config = FsConfig {
+            show_hidden: false,
+            ..config
+        };
+        assert_eq!(child_names(&FsItem::with_config(dir.path(), config)), vec!["src"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn symlinks_and_cycles() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        fs::create_dir_all(root.join("data")).unwrap();
+        fs::write(root.join("data/file.txt"), "").unwrap();
+        symlink("data", root.join("view")).unwrap();
+        symlink("..", root.join("data/up")).unwrap();
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_dir_with(&root, &mut out, &config, &FsConfig::default()).unwrap();
+        let expected = format!(
+            "{}\n\
+             ├─ data\n\
+             │  ├─ file.txt\n\
+             │  └─ up -> ..\n\
+             └─ view -> data\n",
+            root.display()
+        );
+        assert_eq!(from_utf8(&out).unwrap(), expected);
+
+        let fs_config = FsConfig {
+            follow_links: true,
+            show_link_targets: false,
+            ..FsConfig::default()
+        };
+        let mut out = Vec::new();
+        write_dir_with(&root, &mut out, &config, &fs_config).unwrap();
+        let expected = format!(
+            "{}\n\
+             ├─ data\n\
+             │  ├─ file.txt\n\
+             │  └─ up  [recursive, not followed]\n\
+             └─ view\n   \
+                ├─ file.txt\n   \
+                └─ up  [recursive, not followed]\n",
+            root.display()
+        );
+        assert_eq!(from_utf8(&out).unwrap(), expected);
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_matches("*.rs", "main.rs"));
+        assert!(glob_matches("*.rs", ".rs"));
+        assert!(!glob_matches("*.rs", "main.rsx"));
+        assert!(glob_matches("a?c*", "abcdef"));
+        assert!(!glob_matches("a?c", "ac"));
+        assert!(glob_matches("*a*b*", "xxaxxbxx"));
+        assert!(glob_matches("node_modules", "node_modules"));
+        assert!(!glob_matches("", "a"));
+    }
+
+    #[test]
+    fn traversal_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        fs::create_dir_all(root.join("node_modules/left-pad")).unwrap();
+        fs::create_dir_all(root.join("src/deep/deeper")).unwrap();
+        for file in &["a.rs", "b.rs", "c.rs", "notes.txt"] {
+            fs::write(root.join("src").join(file), "").unwrap();
+        }
+        fs::write(root.join("src/deep/deeper/d.rs"), "").unwrap();
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        let fs_config = FsConfig {
+            max_depth: Some(3),
+            max_entries_per_dir: Some(3),
+            include: vec!["*.rs".to_string()],
+            exclude: vec!["node_*".to_string()],
+            ..FsConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_dir_with(&root, &mut out, &config, &fs_config).unwrap();
+        let expected = format!(
+            "{}\n\
+             └─ src  [4 entries]\n   \
+                ├─ a.rs\n   \
+                ├─ b.rs\n   \
+                └─ c.rs\n",
+            root.display()
+        );
+        assert_eq!(from_utf8(&out).unwrap(), expected);
+
+        let fs_config = FsConfig {
+            max_entries_per_dir: None,
+            ..fs_config
+        };
+        let item = FsItem::with_config(&root, fs_config);
+        let src = &item.children()[0];
+        assert_eq!(child_names(src), vec!["a.rs", "b.rs", "c.rs", "deep"]);
+        assert_eq!(child_names(&src.children()[3]), vec!["deeper"]);
+        assert!(src.children()[3].children()[0].children().is_empty());
+    }
+
+    #[test]
+    fn entries_read_once() {
+        let dir = tempfile::tempdir().unwrap();
+        for file in &["a", "b", "c"] {
+            fs::write(dir.path().join(file), "").unwrap();
+        }
+        let fs_config = FsConfig {
+            max_entries_per_dir: Some(2),
+            ..FsConfig::default()
+        };
+        let item = FsItem::with_config(dir.path(), fs_config);
+
+        let mut text = Vec::new();
+        item.write_self(&mut text, &Style::default()).unwrap();
+        assert!(from_utf8(&text).unwrap().ends_with("  [3 entries]"));
+
+        // The children are taken from the entries counted above, not from a second listing
+        fs::write(dir.path().join("0"), "").unwrap();
+        assert_eq!(child_names(&item), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn total_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::write(root.join("a/one"), vec![0; 5000]).unwrap();
+        fs::write(root.join("a/b/two"), vec![0; 3000]).unwrap();
+
+        let dir_len = |path: &str| fs::symlink_metadata(root.join(path)).unwrap().len();
+        let config = FsConfig {
+            columns: vec![Column::TotalSize],
+            ..FsConfig::default()
+        };
+        let item = FsItem::with_config(&root, config);
+        let a = &item.children()[0];
+        let total = dir_len("a") + dir_len("a/b") + 8000;
+        assert_eq!(a.column_text(Column::TotalSize), format!("{:>11}", total));
+        let children = a.children();
+        assert_eq!(
+            children[0].column_text(Column::TotalSize),
+            format!("{:>11}", dir_len("a/b") + 3000)
+        );
+        assert_eq!(children[1].column_text(Column::TotalSize), format!("{:>11}", 5000));
+    }
+
+    #[test]
+    fn icons_and_styles() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.RS"), "").unwrap();
+        fs::write(root.join("LICENSE"), "").unwrap();
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            separator: Style::default(),
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Always,
+            ..PrintConfig::default()
+        };
+        let fs_config = FsConfig {
+            icons: Icons::Emoji,
+            ..FsConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_dir_with(&root, &mut out, &config, &fs_config).unwrap();
+        let dir_style = &fs_config.dir_style;
+        let rs_style = &fs_config.extension_styles["rs"];
+        let expected = format!(
+            "{}\n\
+             ├─ {}\n\
+             └─ {}\n   \
+                └─ {}\n",
+            dir_style.paint(format!("📁 {}", root.display())),
+            Style::default().paint("📄 LICENSE"),
+            dir_style.paint("📁 src"),
+            rs_style.paint("🦀 main.RS")
+        );
+        assert_eq!(from_utf8(&out).unwrap(), expected);
+    }
+}
@@ -0,0 +1,379 @@
+//!
+//! Printing of the directory trees of commits and of the branch and tag hierarchy of git repositories
+//!
+//! A [`GitItem`] is either the directory tree of a commit, similar to `git ls-tree -r`,
+//! or the hierarchy of the references in a repository, such as `heads/feature/parser` and `tags/v1.0`,
+//! with the components of their names as directories.
+//! Repositories are read with the [`git2`] crate, so no `git` executable is needed.
+//!
+//! [`GitItem`]: struct.GitItem.html
+//! [`git2`]: https://docs.rs/git2/0.20/git2/
+//!
+
+use item::TreeItem;
+use output::{print_tree_with, write_tree_with};
+use print_config::PrintConfig;
+use style::Style;
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use git2::{ErrorCode, ObjectType, Oid, Repository, Tree};
+
+///
+/// Entry of a commit's directory tree, or component of a reference name
+///
+#[derive(Clone)]
+pub struct GitItem {
+    name: String,
+    id: Option<Oid>,
+    children: Vec<GitItem>,
+    /// Repository to read the entries of this tree from, or `None` if it is not a tree
+    repo: Option<Rc<Repository>>,
+    /// Entries of this tree, read when they are first needed
+    entries: RefCell<Option<Vec<GitItem>>>,
+}
+
+impl GitItem {
+    /// Creates an item named `name` without children
+    fn new(name: String, id: Option<Oid>) -> Self {
+        GitItem {
+            name,
+            id,
+            children: vec![],
+            repo: None,
+            entries: RefCell::new(None),
+        }
+    }
+
+    ///
+    /// Reads the directory tree of the commit, tag or tree that `rev` resolves to in `repo`
+    ///
+    /// `rev` can be any revision understood by `git rev-parse`, such as `HEAD`, `main~2` or a commit id.
+    /// The root of the returned tree is labeled with `rev`.
+    ///
+    pub fn from_rev(repo: Rc<Repository>, rev: &str) -> io::Result<Self> {
+        let id = repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_tree())
+            .map(|tree| tree.id())
+            .map_err(git_error)?;
+        Ok(GitItem::tree(repo, rev.to_string(), id))
+    }
+
+    ///
+    /// Creates an item for the git tree object `tree` of `repo`
+    ///
+    /// The root of the returned tree is labeled `name`.
+    /// The entries of a tree are only read from `repo` when its children are first requested,
+    /// so printing with a limited depth does not read the trees below that depth.
+    /// Entries keep the order in which git stores them, which is sorted by name.
+    /// Submodules are listed without children.
+    ///
+    pub fn from_tree(repo: Rc<Repository>, tree: &Tree, name: String) -> Self {
+        GitItem::tree(repo, name, tree.id())
+    }
+
+    /// Creates an item for the tree `id` of `repo`, whose entries are read when first needed
+    fn tree(repo: Rc<Repository>, name: String, id: Oid) -> Self {
+        GitItem {
+            repo: Some(repo),
+            ..GitItem::new(name, Some(id))
+        }
+    }
+
+    ///
+    /// Reads the references of `repo`, such as branches, remote-tracking branches and tags
+    ///
+    /// The root of the returned tree is labeled `refs`, and each reference is split into
+    /// directories at its slashes, so `refs/heads/feature/parser` is listed as `parser` below
+    /// `heads` and `feature`. The entries of each directory are sorted by name.
+    ///
+    pub fn from_refs(repo: &Repository) -> io::Result<Self> {
+        let mut root = GitItem::new("refs".to_string(), None);
+        for reference in repo.references().map_err(git_error)? {
+            let reference = reference.map_err(git_error)?;
+            let name = String::from_utf8_lossy(reference.name_bytes()).into_owned();
+            let name = name.strip_prefix("refs/").unwrap_or(&name);
+            root.insert(name, reference.target());
+        }
+        root.sort();
+        Ok(root)
+    }
+
+    ///
+    /// Returns the name of this item, which is the last component of its path or reference name
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    ///
+    /// Returns the id of the object of this item
+    ///
+    /// For references, this is the id of the object they point to.
+    /// Returns `None` for symbolic references and the directories of reference names.
+    ///
+    pub fn id(&self) -> Option<Oid> {
+        self.id
+    }
+
+    /// Inserts the reference `name` into this directory, creating the directories in its name
+    fn insert(&mut self, name: &str, id: Option<Oid>) {
+        let mut dir = self;
+        let mut components = name.split('/').filter(|component| !component.is_empty()).peekable();
+        while let Some(component) = components.next() {
+            let index = match dir.children.iter().position(|child| child.name == component) {
+                Some(index) => index,
+                None => {
+                    dir.children.push(GitItem::new(component.to_string(), None));
+                    dir.children.len() - 1
+                }
+            };
+            dir = &mut dir.children[index];
+            if components.peek().is_none() {
+                dir.id = id;
+            }
+        }
+    }
+
+    /// Returns the entries of this tree, reading them from the repository on first use
+    fn entries(&self, repo: &Rc<Repository>) -> Vec<GitItem> {
+        if self.entries.borrow().is_none() {
+            let entries = self.read_entries(repo);
+            *self.entries.borrow_mut() = Some(entries);
+        }
+        self.entries.borrow().clone().unwrap_or_default()
+    }
+
+    /// Reads the entries of this tree, or none if it cannot be read
+    fn read_entries(&self, repo: &Rc<Repository>) -> Vec<GitItem> {
+        let tree = match self.id.map(|id| repo.find_tree(id)) {
+            Some(Ok(tree)) => tree,
+            _ => return vec![],
+        };
+        tree.iter()
+            .map(|entry| {
+                let name = String::from_utf8_lossy(entry.name_bytes()).into_owned();
+                if entry.kind() == Some(ObjectType::Tree) {
+                    GitItem::tree(repo.clone(), name, entry.id())
+                } else {
+                    GitItem::new(name, Some(entry.id()))
+                }
+            })
+            .collect()
+    }
+
+    /// Sorts the entries of this directory and all directories below it by name
+    fn sort(&mut self) {
+        self.children.sort_by(|a, b| a.name.cmp(&b.name));
+        for child in &mut self.children {
+            child.sort();
+        }
+    }
+}
+
+impl TreeItem for GitItem {
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        style.paint_to(f, format_args!("{}", self.name))
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        match self.repo {
+            Some(ref repo) => Cow::from(self.entries(repo)),
+            None => Cow::from(&self.children[..]),
+        }
+    }
+}
+
+impl fmt::Debug for GitItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GitItem")
+            .field("name", &self.name)
+            .field("id", &self.id)
+            .field("children", &self.children())
+            .finish()
+    }
+}
+
+/// Converts an error of the `git2` crate into an I/O error
+fn git_error(error: git2::Error) -> io::Error {
+    let kind = match error.code() {
+        ErrorCode::NotFound => io::ErrorKind::NotFound,
+        ErrorCode::Ambiguous | ErrorCode::InvalidSpec => io::ErrorKind::InvalidInput,
+        _ => io::ErrorKind::Other,
+    };
+    io::Error::new(kind, error)
+}
+
+/// Opens the repository containing `path`, searching its parent directories like `git` does
+fn open_repository(path: &Path) -> io::Result<Repository> {
+    Repository::discover(path).map_err(git_error)
+}
+
+/// Print the directory tree of revision `rev` of the repository at `repo_path` to standard output using default formatting
+pub fn print_commit_tree<P: AsRef<Path>>(repo_path: P, rev: &str) -> io::Result<()> {
+    print_commit_tree_with(repo_path, rev, &PrintConfig::for_terminal())
+}
+
+/// Print the directory tree of revision `rev` of the repository at `repo_path` to standard output using custom formatting
+pub fn print_commit_tree_with<P: AsRef<Path>>(repo_path: P, rev: &str, print_config: &PrintConfig) -> io::Result<()> {
+    let repo = open_repository(repo_path.as_ref())?;
+    print_tree_with(&GitItem::from_rev(Rc::new(repo), rev)?, print_config)
+}
+
+/// Write the directory tree of revision `rev` of `repo` to writer `f` using custom formatting
+pub fn write_commit_tree_with<W: io::Write>(
+    repo: Rc<Repository>,
+    rev: &str,
+    f: W,
+    print_config: &PrintConfig,
+) -> io::Result<()> {
+    write_tree_with(&GitItem::from_rev(repo, rev)?, f, print_config)
+}
+
+/// Print the references of the repository at `repo_path` to standard output using default formatting
+pub fn print_refs<P: AsRef<Path>>(repo_path: P) -> io::Result<()> {
+    print_refs_with(repo_path, &PrintConfig::for_terminal())
+}
+
+/// Print the references of the repository at `repo_path` to standard output using custom formatting
+pub fn print_refs_with<P: AsRef<Path>>(repo_path: P, print_config: &PrintConfig) -> io::Result<()> {
+    let repo = open_repository(repo_path.as_ref())?;
+    print_tree_with(&GitItem::from_refs(&repo)?, print_config)
+}
+
+/// Write the references of `repo` to writer `f` using custom formatting
+pub fn write_refs_with<W: io::Write>(repo: &Repository, f: W, print_config: &PrintConfig) -> io::Result<()> {
+    write_tree_with(&GitItem::from_refs(repo)?, f, print_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use print_config::UTF_CHARS;
+    use std::str::from_utf8;
+
+    use git2::Signature;
+    use tempfile;
+
+    fn config() -> PrintConfig {
+        PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        }
+    }
+
+    /// Creates a repository with a single commit on `main`, returning it with the id of the commit
+    fn repository(dir: &Path) -> (Repository, Oid) {
+        let repo = Repository::init(dir).unwrap();
+        let readme = repo.blob(b"# Readme\n").unwrap();
+        let main = repo.blob(b"fn main() {}\n").unwrap();
+
+        let root = {
+            let mut bin = repo.treebuilder(None).unwrap();
+            bin.insert("cli.rs", main, 0o100644).unwrap();
+            let bin = bin.write().unwrap();
+            let mut src = repo.treebuilder(None).unwrap();
+            src.insert("main.rs", main, 0o100644).unwrap();
+            src.insert("bin", bin, 0o040000).unwrap();
+            let src = src.write().unwrap();
+            let mut root = repo.treebuilder(None).unwrap();
+            root.insert("src", src, 0o040000).unwrap();
+            root.insert("README.md", readme, 0o100644).unwrap();
+            root.write().unwrap()
+        };
+
+        let commit = {
+            let tree = repo.find_tree(root).unwrap();
+            let signature = Signature::now("Test", "test@example.com").unwrap();
+            repo.commit(Some("refs/heads/main"), &signature, &signature, "Initial commit", &tree, &[])
+                .unwrap()
+        };
+        (repo, commit)
+    }
+
+    #[test]
+    fn commit_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let (repo, commit) = repository(dir.path());
+        let repo = Rc::new(repo);
+
+        let mut out = Vec::new();
+        write_commit_tree_with(repo.clone(), "main", &mut out, &config()).unwrap();
+        assert_eq!(
+            from_utf8(&out).unwrap(),
+            "\
+             main\n\
+             ├─ README.md\n\
+             └─ src\n   \
+                ├─ bin\n   \
+                │  └─ cli.rs\n   \
+                └─ main.rs\n"
+        );
+
+        let item = GitItem::from_rev(repo.clone(), &commit.to_string()).unwrap();
+        let src = &item.children()[1];
+        assert_eq!(src.children()[0].children()[0].id(), src.children()[1].id());
+
+        let error = GitItem::from_rev(repo, "missing").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn trees_read_when_needed() {
+        let dir = tempfile::tempdir().unwrap();
+        let (repo, _) = repository(dir.path());
+        let item = GitItem::from_rev(Rc::new(repo), "main").unwrap();
+        assert!(item.entries.borrow().is_none());
+
+        let children = item.children();
+        assert!(item.entries.borrow().is_some());
+        assert_eq!(children[1].name(), "src");
+        assert!(children[1].entries.borrow().is_none());
+
+        let mut out = Vec::new();
+        let config = PrintConfig { depth: 1, ..config() };
+        write_tree_with(&item, &mut out, &config).unwrap();
+        assert_eq!(from_utf8(&out).unwrap(), "main\n├─ README.md\n└─ src\n");
+    }
+
+    #[test]
+    fn reference_hierarchy() {
+        let dir = tempfile::tempdir().unwrap();
+        let (repo, commit) = repository(dir.path());
+        let commit = repo.find_commit(commit).unwrap();
+        repo.branch("feature/parser", &commit, false).unwrap();
+        repo.branch("feature/lexer", &commit, false).unwrap();
+        repo.tag_lightweight("v1.0", commit.as_object(), false).unwrap();
+
+        let mut out = Vec::new();
+        write_refs_with(&repo, &mut out, &config()).unwrap();
+        assert_eq!(
+            from_utf8(&out).unwrap(),
+            "\
+             refs\n\
+             ├─ heads\n\
+             │  ├─ feature\n\
+             │  │  ├─ lexer\n\
+             │  │  └─ parser\n\
+             │  └─ main\n\
+             └─ tags\n   \
+                └─ v1.0\n"
+        );
+
+        let item = GitItem::from_refs(&repo).unwrap();
+        let heads = &item.children()[0];
+        assert_eq!(heads.id(), None);
+        assert_eq!(heads.children()[1].id(), Some(commit.id()));
+    }
+}
@@ -1,17 +1,30 @@
-use item::TreeItem;
-use output::{print_tree, write_tree_with};
+use item::{StringItem, TreeItem};
+use output::{print_tree, print_tree_with, write_tree_with};
 use print_config::PrintConfig;
 use style::Style;
 
 use std::io;
 use std::borrow::Cow;
-use std::fmt::Display;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{self, Display};
+use std::hash::Hash;
+use std::rc::Rc;
 
 use petgraph::prelude::*;
 use petgraph::EdgeType;
 use petgraph::graph::IndexType;
+use petgraph::graphmap::{GraphMap, NodeTrait};
+use petgraph::algo::{condensation, dijkstra, Measure};
+use petgraph::data::DataMap;
+use petgraph::visit::{EdgeRef, IntoEdges, IntoEdgesDirected, IntoNeighborsDirected, IntoNodeIdentifiers, Reversed};
+use petgraph::visit::{NodeIndexable, Visitable};
 
-impl<'a, N, E, Ty, Ix> TreeItem for (&'a Graph<N, E, Ty, Ix>, NodeIndex<Ix>)
+#[cfg(feature = "daggy")]
+use daggy::Dag;
+
+impl<N, E, Ty, Ix> TreeItem for (&Graph<N, E, Ty, Ix>, NodeIndex<Ix>)
 where
     Ty: EdgeType,
     Ix: IndexType,
@@ -22,85 +35,1716 @@ where
 
     fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
         if let Some(w) = self.0.node_weight(self.1) {
-            write!(f, "{}", style.paint(w))
+            style.paint_to(f, format_args!("{}", w))
         } else {
             Ok(())
         }
     }
 
-    fn children(&self) -> Cow<[Self::Child]> {
+    fn children(&self) -> Cow<'_, [Self::Child]> {
         let v: Vec<_> = self.0.neighbors(self.1).map(|i| (self.0, i)).collect();
         Cow::from(v)
     }
 }
 
-///
-/// Print `graph`, starting at node `start`, to standard output using default formatting
-///
-pub fn print_graph<N, E, Ty, Ix>(graph: &Graph<N, E, Ty, Ix>, start: NodeIndex<Ix>) -> io::Result<()>
+impl<N, E, Ty, Ix> TreeItem for (&StableGraph<N, E, Ty, Ix>, NodeIndex<Ix>)
 where
     Ty: EdgeType,
     Ix: IndexType,
     N: Clone + Display,
     E: Clone,
 {
-    print_tree(&(graph, start))
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        if let Some(w) = self.0.node_weight(self.1) {
+            style.paint_to(f, format_args!("{}", w))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        let v: Vec<_> = self.0.neighbors(self.1).map(|i| (self.0, i)).collect();
+        Cow::from(v)
+    }
+}
+
+impl<N, E, Ty> TreeItem for (&GraphMap<N, E, Ty>, N)
+where
+    Ty: EdgeType,
+    N: NodeTrait + Display,
+{
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        style.paint_to(f, format_args!("{}", self.1))
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        let v: Vec<_> = self.0.neighbors(self.1).map(|n| (self.0, n)).collect();
+        Cow::from(v)
+    }
+}
+
+///
+/// Options for printing graphs
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphConfig {
+    /// Print the subtree of each node only once
+    ///
+    /// A node reachable by several paths has its children printed at its first occurrence only.
+    /// Later occurrences are marked with `(*)`, like in the output of `cargo tree`.
+    /// This keeps the output of graphs with many shared nodes small, and makes cyclic graphs printable.
+    ///
+    /// The default value is `false`.
+    pub deduplicate: bool,
+    /// Format of the edge labels printed in front of nodes by [`write_graph_with_edges`]
+    ///
+    /// The first `{}` is replaced by the weight of the edge leading to the node.
+    /// The default value is `"[{}] "`.
+    ///
+    /// [`write_graph_with_edges`]: fn.write_graph_with_edges.html
+    pub edge_format: String,
+    /// Style of the edge labels
+    ///
+    /// The default value is `None`, meaning that [`write_graph_with_edges`] uses the [`PrintConfig::edge`] style.
+    /// Items created with [`GraphItem::with_edges`] print the edge labels in the same style as the nodes instead,
+    /// as they are not aware of the `PrintConfig`.
+    ///
+    /// [`write_graph_with_edges`]: fn.write_graph_with_edges.html
+    /// [`PrintConfig::edge`]: ../print_config/struct.PrintConfig.html#structfield.edge
+    /// [`GraphItem::with_edges`]: struct.GraphItem.html#method.with_edges
+    pub edge_style: Option<Style>,
+    /// Maximum number of nodes to print
+    ///
+    /// Graphs with many paths to the same nodes can produce huge trees.
+    /// Once this many nodes have been listed, no further children are listed,
+    /// and a single marker line indicates that the output was truncated.
+    /// Nodes count towards the limit as soon as their parent lists them,
+    /// so the limit also bounds the time spent on printing.
+    ///
+    /// The default value is `None`, i.e. there is no limit.
+    pub max_nodes: Option<usize>,
+}
+
+impl GraphConfig {
+    /// Returns a copy of this configuration which styles edge labels with `config.edge` unless `edge_style` is set
+    fn with_edge_style(&self, config: &PrintConfig) -> GraphConfig {
+        GraphConfig {
+            edge_style: Some(match self.edge_style {
+                Some(ref style) => style.clone(),
+                None => config.edge.downsample(config.color_depth.resolve()),
+            }),
+            ..self.clone()
+        }
+    }
+}
+
+impl Default for GraphConfig {
+    fn default() -> GraphConfig {
+        GraphConfig {
+            deduplicate: false,
+            edge_format: "[{}] ".to_string(),
+            edge_style: None,
+            max_nodes: None,
+        }
+    }
+}
+
+///
+/// Tree item for a node of a graph, printed according to a [`GraphConfig`]
+///
+/// The graph can be any reference implementing the `petgraph` traits [`IntoEdges`] and [`DataMap`],
+/// such as `&Graph` and `&StableGraph`.
+///
+/// All items created from the same root share their state,
+/// so deduplication works across the whole printed tree.
+/// A new root item has to be created for every time the graph is printed.
+///
+/// [`GraphConfig`]: struct.GraphConfig.html
+/// [`IntoEdges`]: https://docs.rs/petgraph/0.6/petgraph/visit/trait.IntoEdges.html
+/// [`DataMap`]: https://docs.rs/petgraph/0.6/petgraph/data/trait.DataMap.html
+pub struct GraphItem<'a, G: IntoEdges> {
+    graph: G,
+    node: G::NodeId,
+    /// The edge leading to this node, unless it is the root
+    edge: Option<G::EdgeRef>,
+    /// Whether this item is the marker for truncated output instead of a node
+    marker: bool,
+    state: Rc<GraphState<'a, G>>,
+}
+
+/// Function writing the text of a node
+type NodeFormatter<'a, Id> = Rc<dyn Fn(Id, &mut dyn io::Write, &Style) -> io::Result<()> + 'a>;
+
+/// Function formatting an edge weight
+type EdgeFormatter<E> = fn(&E, &mut fmt::Formatter) -> fmt::Result;
+
+/// Function selecting the edges to follow
+type EdgeFilter<'a, E> = Rc<dyn Fn(&E) -> bool + 'a>;
+
+/// Function selecting the style of a node
+type NodeStyler<'a, Id> = Rc<dyn Fn(Id) -> Option<Style> + 'a>;
+
+/// State shared by all items of one printed graph
+struct GraphState<'a, G: IntoEdges> {
+    config: GraphConfig,
+    node_formatter: NodeFormatter<'a, G::NodeId>,
+    /// Formats the edge labels, if they are printed
+    edge_formatter: Option<EdgeFormatter<G::EdgeWeight>>,
+    edge_filter: Option<EdgeFilter<'a, G::EdgeWeight>>,
+    node_styler: Option<NodeStyler<'a, G::NodeId>>,
+    /// Nodes whose children were already listed
+    expanded: RefCell<HashSet<G::NodeId>>,
+    /// Number of nodes listed so far, including the root
+    listed: Cell<usize>,
+    /// Whether the output was truncated because of `max_nodes`
+    truncated: Cell<bool>,
+}
+
+impl<'a, G: IntoEdges> GraphState<'a, G> {
+    /// Copies the settings of this state, without the progress of printing
+    fn settings(&self) -> Self {
+        GraphState {
+            config: self.config.clone(),
+            node_formatter: self.node_formatter.clone(),
+            edge_formatter: self.edge_formatter,
+            edge_filter: self.edge_filter.clone(),
+            node_styler: self.node_styler.clone(),
+            expanded: RefCell::new(HashSet::new()),
+            listed: Cell::new(1),
+            truncated: Cell::new(false),
+        }
+    }
+}
+
+/// Display adapter for an edge weight
+struct EdgeLabel<'e, E: 'e>(&'e E, EdgeFormatter<E>);
+
+impl<'e, E> Display for EdgeLabel<'e, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (self.1)(self.0, f)
+    }
+}
+
+/// Writes a node weight using its `Display` implementation
+fn write_display<N: Display>(weight: &N, f: &mut dyn io::Write, style: &Style) -> io::Result<()> {
+    style.paint_to(f, format_args!("{}", weight))
+}
+
+/// Wraps a formatter of node weights into a formatter of node ids
+fn weight_formatter<'a, G, F>(graph: G, formatter: F) -> NodeFormatter<'a, G::NodeId>
+where
+    G: DataMap + 'a,
+    F: Fn(&G::NodeWeight, &mut dyn io::Write, &Style) -> io::Result<()> + 'a,
+{
+    Rc::new(move |node, f, style| match graph.node_weight(node) {
+        Some(w) => formatter(w, f, style),
+        None => Ok(()),
+    })
+}
+
+impl<'a, G> GraphItem<'a, G>
+where
+    G: IntoEdges + DataMap + 'a,
+{
+    ///
+    /// Creates the root item for printing `graph` starting at node `start`
+    ///
+    pub fn new(graph: G, start: G::NodeId, config: &GraphConfig) -> Self
+    where
+        G::NodeWeight: Display,
+    {
+        Self::from_parts(graph, start, config, weight_formatter(graph, write_display), None)
+    }
+
+    ///
+    /// Creates the root item for printing `graph` starting at node `start`, with custom node formatting
+    ///
+    /// `formatter` is called with the weight of each node, the writer and the style.
+    /// Like [`TreeItem::write_self`], it should write the node's text using the style.
+    ///
+    /// [`TreeItem::write_self`]: ../item/trait.TreeItem.html#tymethod.write_self
+    pub fn with_formatter<F>(graph: G, start: G::NodeId, config: &GraphConfig, formatter: F) -> Self
+    where
+        F: Fn(&G::NodeWeight, &mut dyn io::Write, &Style) -> io::Result<()> + 'a,
+    {
+        Self::from_parts(graph, start, config, weight_formatter(graph, formatter), None)
+    }
+
+    ///
+    /// Creates the root item for printing `graph` starting at node `start`, with edge labels
+    ///
+    /// Every node except the root is preceded by the weight of the edge leading to it,
+    /// formatted according to [`GraphConfig::edge_format`].
+    ///
+    /// [`GraphConfig::edge_format`]: struct.GraphConfig.html#structfield.edge_format
+    pub fn with_edges(graph: G, start: G::NodeId, config: &GraphConfig) -> Self
+    where
+        G::NodeWeight: Display,
+        G::EdgeWeight: Display,
+    {
+        Self::from_parts(
+            graph,
+            start,
+            config,
+            weight_formatter(graph, write_display),
+            Some(<G::EdgeWeight as Display>::fmt),
+        )
+    }
+
+    ///
+    /// Styles each node according to its weight
+    ///
+    /// `style` is called with the weight of each node, and the returned style is applied on top of the
+    /// branch or leaf style of the node.
+    /// Edge labels and repetition markers keep the regular style.
+    ///
+    pub fn style_nodes<F>(mut self, style: F) -> Self
+    where
+        F: Fn(&G::NodeWeight) -> Style + 'a,
+    {
+        let graph = self.graph;
+        let mut state = self.state.settings();
+        state.node_styler = Some(Rc::new(move |node| graph.node_weight(node).map(&style)));
+        self.state = Rc::new(state);
+        self
+    }
+}
+
+impl<'a, G: IntoEdges> GraphItem<'a, G> {
+    fn from_parts(
+        graph: G,
+        start: G::NodeId,
+        config: &GraphConfig,
+        node_formatter: NodeFormatter<'a, G::NodeId>,
+        edge_formatter: Option<EdgeFormatter<G::EdgeWeight>>,
+    ) -> Self {
+        GraphItem {
+            graph,
+            node: start,
+            edge: None,
+            marker: false,
+            state: Rc::new(GraphState {
+                config: config.clone(),
+                node_formatter,
+                edge_formatter,
+                edge_filter: None,
+                node_styler: None,
+                expanded: RefCell::new(HashSet::new()),
+                listed: Cell::new(1),
+                truncated: Cell::new(false),
+            }),
+        }
+    }
+
+    ///
+    /// Only follows the edges for which `filter` returns `true`
+    ///
+    /// `filter` is called with the weight of each edge.
+    /// Nodes reachable only through rejected edges are not printed.
+    ///
+    pub fn filter_edges<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&G::EdgeWeight) -> bool + 'a,
+    {
+        let mut state = self.state.settings();
+        state.edge_filter = Some(Rc::new(filter));
+        self.state = Rc::new(state);
+        self
+    }
+
+    /// Returns the edges leading to the children of this node
+    fn edges(&self) -> impl Iterator<Item = G::EdgeRef> + '_ {
+        let filter = self.state.edge_filter.as_ref();
+        self.graph
+            .edges(self.node)
+            .filter(move |edge| filter.is_none_or(|filter| filter(edge.weight())))
+    }
+
+    /// Writes the label of the edge leading to this node, if edge labels are printed
+    fn write_edge<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        let (formatter, edge) = match (self.state.edge_formatter, self.edge) {
+            (Some(formatter), Some(edge)) => (formatter, edge),
+            _ => return Ok(()),
+        };
+
+        let config = &self.state.config;
+        let (before, after) = match config.edge_format.find("{}") {
+            Some(i) => (&config.edge_format[..i], &config.edge_format[i + 2..]),
+            None => (&config.edge_format[..], ""),
+        };
+        let style = config.edge_style.as_ref().unwrap_or(style);
+        style.paint_to(f, format_args!("{}{}{}", before, EdgeLabel(edge.weight(), formatter), after))
+    }
+}
+
+impl<'a, G> GraphItem<'a, G>
+where
+    G: IntoEdges,
+    G::NodeId: Hash + Eq,
+{
+    /// Whether the children of this node were already listed by an earlier occurrence
+    fn is_repeated(&self) -> bool {
+        self.state.config.deduplicate
+            && self.state.expanded.borrow().contains(&self.node)
+            && self.edges().next().is_some()
+            && !self.state.truncated.get()
+    }
+}
+
+impl<'a, G: IntoEdges> Clone for GraphItem<'a, G> {
+    fn clone(&self) -> Self {
+        GraphItem {
+            graph: self.graph,
+            node: self.node,
+            edge: self.edge,
+            marker: self.marker,
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<'a, G> TreeItem for GraphItem<'a, G>
+where
+    G: IntoEdges,
+    G::NodeId: Hash + Eq,
+{
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        if self.marker {
+            let max_nodes = self.state.config.max_nodes.unwrap_or_default();
+            return style.paint_to(f, format_args!("(truncated after {} nodes)", max_nodes));
+        }
+
+        self.write_edge(f, style)?;
+        match self.state.node_styler.as_ref().and_then(|styler| styler(self.node)) {
+            Some(node_style) => (self.state.node_formatter)(self.node, f, &style.merge(&node_style))?,
+            None => (self.state.node_formatter)(self.node, f, style)?,
+        }
+        if self.is_repeated() {
+            style.paint_to(f, format_args!(" (*)"))?;
+        }
+        Ok(())
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        let state = &self.state;
+        if self.marker || state.truncated.get() {
+            return Cow::from(vec![]);
+        }
+        if state.config.deduplicate && !state.expanded.borrow_mut().insert(self.node) {
+            return Cow::from(vec![]);
+        }
+
+        let remaining = match state.config.max_nodes {
+            Some(max) => max.saturating_sub(state.listed.get()),
+            None => usize::MAX,
+        };
+        let mut v: Vec<_> = self
+            .edges()
+            .take(remaining)
+            .map(|edge| GraphItem {
+                graph: self.graph,
+                node: edge.target(),
+                edge: Some(edge),
+                marker: false,
+                state: state.clone(),
+            })
+            .collect();
+        state.listed.set(state.listed.get() + v.len());
+
+        if v.len() == remaining && self.edges().nth(remaining).is_some() {
+            state.truncated.set(true);
+            v.push(GraphItem {
+                marker: true,
+                edge: None,
+                ..self.clone()
+            });
+        }
+        Cow::from(v)
+    }
+
+    fn has_children(&self) -> bool {
+        // Retrieving the children would mark the node as expanded and count them as listed
+        let state = &self.state;
+        let expanded = state.config.deduplicate && state.expanded.borrow().contains(&self.node);
+        !(self.marker || state.truncated.get() || expanded) && self.edges().next().is_some()
+    }
+}
+
+///
+/// Print `graph`, starting at node `start`, to standard output using default formatting
+///
+/// `graph` can be a reference to a `Graph` or a `StableGraph`.
+///
+pub fn print_graph<G>(graph: G, start: G::NodeId) -> io::Result<()>
+where
+    G: IntoEdges + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+{
+    print_tree(&GraphItem::new(graph, start, &GraphConfig::default()))
 }
 
 ///
 /// Write `graph`, starting at node `start`, to writer `f` using custom formatting
 ///
-pub fn write_graph_with<N, E, Ty, Ix, W: io::Write>(
-    graph: &Graph<N, E, Ty, Ix>,
-    start: NodeIndex<Ix>,
+/// `graph` can be a reference to a `Graph` or a `StableGraph`.
+///
+pub fn write_graph_with<G, W: io::Write>(graph: G, start: G::NodeId, f: W, config: &PrintConfig) -> io::Result<()>
+where
+    G: IntoEdges + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+{
+    write_tree_with(&GraphItem::new(graph, start, &GraphConfig::default()), f, config)
+}
+
+///
+/// Print `graph`, starting at node `start`, to standard output using default formatting and the given graph options
+///
+pub fn print_graph_with_config<G>(graph: G, start: G::NodeId, graph_config: &GraphConfig) -> io::Result<()>
+where
+    G: IntoEdges + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+{
+    print_tree(&GraphItem::new(graph, start, graph_config))
+}
+
+///
+/// Write `graph`, starting at node `start`, to writer `f` using custom formatting and graph options
+///
+pub fn write_graph_with_config<G, W: io::Write>(
+    graph: G,
+    start: G::NodeId,
     f: W,
     config: &PrintConfig,
+    graph_config: &GraphConfig,
 ) -> io::Result<()>
 where
-    Ty: EdgeType,
-    Ix: IndexType,
-    N: Clone + Display,
-    E: Clone,
+    G: IntoEdges + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
 {
-    write_tree_with(&(graph, start), f, config)
+    write_tree_with(&GraphItem::new(graph, start, graph_config), f, config)
 }
 
-#[cfg(test)]
-mod tests {
-    use std::io::Cursor;
-    use std::str::from_utf8;
-    use super::*;
+///
+/// Print `graph`, starting at node `start`, to standard output using a custom node formatter
+///
+/// See [`write_graph_with_formatter`] for details.
+///
+/// [`write_graph_with_formatter`]: fn.write_graph_with_formatter.html
+pub fn print_graph_with_formatter<G, F>(graph: G, start: G::NodeId, formatter: F) -> io::Result<()>
+where
+    G: IntoEdges + DataMap,
+    G::NodeId: Hash + Eq,
+    F: Fn(&G::NodeWeight, &mut dyn io::Write, &Style) -> io::Result<()>,
+{
+    let item = GraphItem::with_formatter(graph, start, &GraphConfig::default(), formatter);
+    print_tree(&item)
+}
 
-    #[test]
-    fn small_graph_output() {
-        let mut deps = Graph::<&str, &str>::new();
-        let pg = deps.add_node("petgraph");
-        let fb = deps.add_node("fixedbitset");
-        let qc = deps.add_node("quickcheck");
-        let rand = deps.add_node("rand");
-        let libc = deps.add_node("libc");
-        deps.extend_with_edges(&[(pg, fb), (pg, qc), (qc, rand), (rand, libc), (qc, libc)]);
+///
+/// Write `graph`, starting at node `start`, to writer `f` using a custom node formatter
+///
+/// `formatter` is called with the weight of each node, the writer and the style,
+/// so node weights do not have to implement `Display`.
+///
+/// ```
+/// # extern crate petgraph;
+/// # extern crate ptree;
+/// # use petgraph::Graph;
+/// # use ptree::PrintConfig;
+/// # use ptree::graph::write_graph_with_formatter;
+/// # fn main() {
+/// struct Crate {
+///     name: &'static str,
+///     version: &'static str,
+/// }
+///
+/// let mut deps = Graph::<Crate, ()>::new();
+/// let pg = deps.add_node(Crate { name: "petgraph", version: "0.6.5" });
+/// let fb = deps.add_node(Crate { name: "fixedbitset", version: "0.4.2" });
+/// deps.add_edge(pg, fb, ());
+///
+/// let mut out = Vec::new();
+/// write_graph_with_formatter(&deps, pg, &mut out, &PrintConfig::default(), |c, f, style| {
+///     style.paint_to(f, format_args!("{} v{}", c.name, c.version))
+/// })
+/// .unwrap();
+/// assert!(String::from_utf8(out).unwrap().ends_with("fixedbitset v0.4.2\n"));
+/// # }
+/// ```
+pub fn write_graph_with_formatter<G, W, F>(
+    graph: G,
+    start: G::NodeId,
+    f: W,
+    config: &PrintConfig,
+    formatter: F,
+) -> io::Result<()>
+where
+    G: IntoEdges + DataMap,
+    G::NodeId: Hash + Eq,
+    W: io::Write,
+    F: Fn(&G::NodeWeight, &mut dyn io::Write, &Style) -> io::Result<()>,
+{
+    let item = GraphItem::with_formatter(graph, start, &GraphConfig::default(), formatter);
+    write_tree_with(&item, f, config)
+}
 
-        let config = PrintConfig {
-            indent: 4,
-            leaf: Style::default(),
-            branch: Style::default(),
-            ..PrintConfig::default()
-        };
+///
+/// Print `graph`, starting at node `start`, to standard output with edge labels
+///
+/// See [`write_graph_with_edges`] for details.
+///
+/// [`write_graph_with_edges`]: fn.write_graph_with_edges.html
+pub fn print_graph_with_edges<G>(graph: G, start: G::NodeId, graph_config: &GraphConfig) -> io::Result<()>
+where
+    G: IntoEdges + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+    G::EdgeWeight: Display,
+{
+    let config = PrintConfig::for_terminal();
+    let item = GraphItem::with_edges(graph, start, &graph_config.with_edge_style(&config));
+    print_tree_with(&item, &config)
+}
 
-        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+///
+/// Write `graph`, starting at node `start`, to writer `f` with edge labels
+///
+/// Every node except the root is preceded by the weight of the edge leading to it.
+/// The labels are formatted with [`GraphConfig::edge_format`] and styled with [`GraphConfig::edge_style`],
+/// or with [`PrintConfig::edge`] if it is not set.
+///
+/// ```
+/// # extern crate petgraph;
+/// # extern crate ptree;
+/// # use petgraph::Graph;
+/// # use ptree::PrintConfig;
+/// # use ptree::graph::{write_graph_with_edges, GraphConfig};
+/// # fn main() {
+/// let mut deps = Graph::<&str, &str>::new();
+/// let pg = deps.add_node("petgraph");
+/// let qc = deps.add_node("quickcheck");
+/// deps.add_edge(pg, qc, "dev");
+///
+/// let mut out = Vec::new();
+/// write_graph_with_edges(&deps, pg, &mut out, &PrintConfig::default(), &GraphConfig::default()).unwrap();
+/// assert!(String::from_utf8(out).unwrap().ends_with("[dev] quickcheck\n"));
+/// # }
+/// ```
+///
+/// [`GraphConfig::edge_format`]: struct.GraphConfig.html#structfield.edge_format
+/// [`GraphConfig::edge_style`]: struct.GraphConfig.html#structfield.edge_style
+/// [`PrintConfig::edge`]: ../print_config/struct.PrintConfig.html#structfield.edge
+pub fn write_graph_with_edges<G, W: io::Write>(
+    graph: G,
+    start: G::NodeId,
+    f: W,
+    config: &PrintConfig,
+    graph_config: &GraphConfig,
+) -> io::Result<()>
+where
+    G: IntoEdges + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+    G::EdgeWeight: Display,
+{
+    let item = GraphItem::with_edges(graph, start, &graph_config.with_edge_style(config));
+    write_tree_with(&item, f, config)
+}
 
-        write_graph_with(&deps, pg, &mut cursor, &config).unwrap();
+///
+/// Print the ancestors of node `target` in `graph` to standard output using default formatting
+///
+/// See [`write_graph_reverse_with`] for details.
+///
+/// [`write_graph_reverse_with`]: fn.write_graph_reverse_with.html
+pub fn print_graph_reverse<G>(graph: G, target: G::NodeId) -> io::Result<()>
+where
+    G: IntoEdgesDirected + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+{
+    print_graph(Reversed(graph), target)
+}
 
-        let data = cursor.into_inner();
-        let expected = "\
-                        petgraph\n\
-                        ├── quickcheck\n\
-                        │   ├── libc\n\
-                        │   └── rand\n\
-                        │       └── libc\n\
-                        └── fixedbitset\n\
-                        ";
-        assert_eq!(from_utf8(&data).unwrap(), expected);
+///
+/// Write the ancestors of node `target` in `graph` to writer `f` using custom formatting
+///
+/// The tree is built by following incoming edges instead of outgoing ones,
+/// so the children of each node are the nodes with an edge pointing to it.
+/// For a dependency graph, this shows everything which depends on `target`.
+///
+/// Any of the other functions in this module can be used the same way,
+/// by passing the graph wrapped in [`Reversed`].
+///
+/// [`Reversed`]: https://docs.rs/petgraph/0.6/petgraph/visit/struct.Reversed.html
+pub fn write_graph_reverse_with<G, W: io::Write>(
+    graph: G,
+    target: G::NodeId,
+    f: W,
+    config: &PrintConfig,
+) -> io::Result<()>
+where
+    G: IntoEdgesDirected + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+{
+    write_graph_with(Reversed(graph), target, f, config)
+}
+
+///
+/// Returns the nodes of `graph` without incoming edges
+///
+fn roots<G>(graph: G) -> impl Iterator<Item = G::NodeId>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected,
+{
+    graph
+        .node_identifiers()
+        .filter(move |&n| graph.neighbors_directed(n, Direction::Incoming).next().is_none())
+}
+
+///
+/// Print every tree of `graph` to standard output using default formatting
+///
+/// See [`write_graph_forest_with`] for details.
+///
+/// [`write_graph_forest_with`]: fn.write_graph_forest_with.html
+pub fn print_graph_forest<G>(graph: G) -> io::Result<()>
+where
+    G: IntoEdges + IntoNodeIdentifiers + IntoNeighborsDirected + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+{
+    for root in roots(graph) {
+        print_graph(graph, root)?;
+    }
+    Ok(())
+}
+
+///
+/// Write every tree of `graph` to writer `f` using custom formatting
+///
+/// Every node without incoming edges is printed as the root of a separate tree, in the order of the node indices.
+/// Nodes which are only part of cycles are not reachable from any root, and are not printed.
+/// In undirected graphs, only isolated nodes are roots.
+///
+pub fn write_graph_forest_with<G, W: io::Write>(graph: G, mut f: W, config: &PrintConfig) -> io::Result<()>
+where
+    G: IntoEdges + IntoNodeIdentifiers + IntoNeighborsDirected + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+{
+    for root in roots(graph) {
+        write_graph_with(graph, root, &mut f, config)?;
+    }
+    Ok(())
+}
+
+///
+/// Print `graph`, starting at node `start`, to standard output following only selected edges
+///
+/// See [`write_graph_with_filter`] for details.
+///
+/// [`write_graph_with_filter`]: fn.write_graph_with_filter.html
+pub fn print_graph_with_filter<G, F>(graph: G, start: G::NodeId, filter: F) -> io::Result<()>
+where
+    G: IntoEdges + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+    F: Fn(&G::EdgeWeight) -> bool,
+{
+    print_tree(&GraphItem::new(graph, start, &GraphConfig::default()).filter_edges(filter))
+}
+
+///
+/// Write `graph`, starting at node `start`, to writer `f` following only selected edges
+///
+/// `filter` is called with the weight of each edge, and only edges for which it returns `true` are followed.
+///
+/// ```
+/// # extern crate petgraph;
+/// # extern crate ptree;
+/// # use petgraph::Graph;
+/// # use ptree::PrintConfig;
+/// # use ptree::graph::write_graph_with_filter;
+/// # fn main() {
+/// let mut deps = Graph::<&str, &str>::new();
+/// let pg = deps.add_node("petgraph");
+/// let fb = deps.add_node("fixedbitset");
+/// let qc = deps.add_node("quickcheck");
+/// deps.extend_with_edges(&[(pg, fb, "normal"), (pg, qc, "dev")]);
+///
+/// let mut out = Vec::new();
+/// write_graph_with_filter(&deps, pg, &mut out, &PrintConfig::default(), |&kind| kind != "dev").unwrap();
+/// assert!(!String::from_utf8(out).unwrap().contains("quickcheck"));
+/// # }
+/// ```
+pub fn write_graph_with_filter<G, W, F>(
+    graph: G,
+    start: G::NodeId,
+    f: W,
+    config: &PrintConfig,
+    filter: F,
+) -> io::Result<()>
+where
+    G: IntoEdges + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+    W: io::Write,
+    F: Fn(&G::EdgeWeight) -> bool,
+{
+    let item = GraphItem::new(graph, start, &GraphConfig::default()).filter_edges(filter);
+    write_tree_with(&item, f, config)
+}
+
+///
+/// Print `graph`, starting at node `start`, to standard output with node styles depending on their weights
+///
+/// `style` is called with the weight of each node, see [`GraphItem::style_nodes`].
+///
+/// [`GraphItem::style_nodes`]: struct.GraphItem.html#method.style_nodes
+pub fn print_graph_with_style<G, F>(graph: G, start: G::NodeId, style: F) -> io::Result<()>
+where
+    G: IntoEdges + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+    F: Fn(&G::NodeWeight) -> Style,
+{
+    print_tree(&GraphItem::new(graph, start, &GraphConfig::default()).style_nodes(style))
+}
+
+///
+/// Write `graph`, starting at node `start`, to writer `f` with node styles depending on their weights
+///
+/// `style` is called with the weight of each node, see [`GraphItem::style_nodes`].
+///
+/// ```
+/// # extern crate petgraph;
+/// # extern crate ptree;
+/// # use petgraph::Graph;
+/// # use ptree::{Color, PrintConfig, Style};
+/// # use ptree::graph::write_graph_with_style;
+/// # fn main() {
+/// let mut deps = Graph::<&str, ()>::new();
+/// let app = deps.add_node("app");
+/// let core = deps.add_node("app-core");
+/// let serde = deps.add_node("serde");
+/// deps.extend_with_edges(&[(app, core), (core, serde)]);
+///
+/// // Workspace crates in green, external crates dimmed
+/// let workspace = ["app", "app-core"];
+/// write_graph_with_style(&deps, app, std::io::stdout(), &PrintConfig::default(), |name| {
+///     if workspace.contains(name) {
+///         Style { foreground: Some(Color::Green), ..Style::default() }
+///     } else {
+///         Style { dimmed: true, ..Style::default() }
+///     }
+/// })
+/// .unwrap();
+/// # }
+/// ```
+pub fn write_graph_with_style<G, W, F>(
+    graph: G,
+    start: G::NodeId,
+    f: W,
+    config: &PrintConfig,
+    style: F,
+) -> io::Result<()>
+where
+    G: IntoEdges + DataMap,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+    W: io::Write,
+    F: Fn(&G::NodeWeight) -> Style,
+{
+    let item = GraphItem::new(graph, start, &GraphConfig::default()).style_nodes(style);
+    write_tree_with(&item, f, config)
+}
+
+///
+/// Builds the tree described by a map of predecessors, rooted at `source`
+///
+/// `predecessors` maps each node to its parent in the tree, as computed by path finding algorithms.
+/// Nodes are labeled with their weights, followed by their cost in parentheses if `costs` contains it.
+/// The children of each node are sorted by their index.
+///
+/// Nodes which are not connected to `source` through `predecessors` are left out.
+///
+/// ```
+/// # extern crate petgraph;
+/// # extern crate ptree;
+/// # use std::collections::HashMap;
+/// # use petgraph::Graph;
+/// # use petgraph::algo::bellman_ford;
+/// # use petgraph::visit::NodeIndexable;
+/// # use ptree::graph::path_tree;
+/// # fn main() {
+/// let mut graph = Graph::<&str, f32>::new();
+/// let a = graph.add_node("a");
+/// let b = graph.add_node("b");
+/// let c = graph.add_node("c");
+/// graph.extend_with_edges(&[(a, b, 1.0), (b, c, 1.0), (a, c, 3.0)]);
+///
+/// let paths = bellman_ford(&graph, a).unwrap();
+/// let predecessors: HashMap<_, _> = paths
+///     .predecessors
+///     .iter()
+///     .enumerate()
+///     .filter_map(|(i, p)| p.map(|p| (graph.from_index(i), p)))
+///     .collect();
+/// let costs: HashMap<_, _> = graph.node_indices().zip(paths.distances).collect();
+///
+/// let tree = path_tree(&graph, a, &predecessors, Some(&costs));
+/// assert_eq!(tree.children[0].children[0].text, "c (2)");
+/// # }
+/// ```
+pub fn path_tree<G, K>(
+    graph: G,
+    source: G::NodeId,
+    predecessors: &HashMap<G::NodeId, G::NodeId>,
+    costs: Option<&HashMap<G::NodeId, K>>,
+) -> StringItem
+where
+    G: DataMap + NodeIndexable,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+    K: Display,
+{
+    let mut children: HashMap<G::NodeId, Vec<G::NodeId>> = HashMap::new();
+    for (&node, &parent) in predecessors {
+        if node != source {
+            children.entry(parent).or_default().push(node);
+        }
+    }
+    for nodes in children.values_mut() {
+        nodes.sort_by_key(|&n| graph.to_index(n));
+    }
+
+    let label = |node: G::NodeId| {
+        let weight = graph.node_weight(node).map(|w| w.to_string()).unwrap_or_default();
+        match costs.and_then(|costs| costs.get(&node)) {
+            Some(cost) => format!("{} ({})", weight, cost),
+            None => weight,
+        }
+    };
+
+    fn build<N: Copy + Hash + Eq>(
+        node: N,
+        children: &HashMap<N, Vec<N>>,
+        visited: &mut HashSet<N>,
+        label: &dyn Fn(N) -> String,
+    ) -> StringItem {
+        let mut item = StringItem::new(label(node), Vec::new());
+        for &child in children.get(&node).into_iter().flatten() {
+            // The predecessors of a tree have no cycles, but a map passed in by the user could
+            if visited.insert(child) {
+                item.children.push(build(child, children, visited, label));
+            }
+        }
+        item
+    }
+
+    let mut visited = HashSet::new();
+    visited.insert(source);
+    build(source, &children, &mut visited, &label)
+}
+
+///
+/// Builds the breadth-first search tree of `graph` from `source`
+///
+/// Each node reachable from `source` appears once, as a child of the node through which it was first discovered.
+/// The depth of each node in the tree is its distance from `source` in edges.
+///
+pub fn bfs_tree<G>(graph: G, source: G::NodeId) -> StringItem
+where
+    G: IntoEdges + DataMap + NodeIndexable,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+{
+    let mut predecessors = HashMap::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(source);
+    while let Some(node) = queue.pop_front() {
+        for edge in graph.edges(node) {
+            let next = edge.target();
+            if next != source && !predecessors.contains_key(&next) {
+                predecessors.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    path_tree::<G, usize>(graph, source, &predecessors, None)
+}
+
+///
+/// Builds the shortest path tree of `graph` from `source`, annotated with the path costs
+///
+/// The costs are computed with [`petgraph::algo::dijkstra`], using `edge_cost` for the cost of each edge.
+/// Each node reachable from `source` appears once, as a child of its predecessor on a shortest path.
+///
+/// [`petgraph::algo::dijkstra`]: https://docs.rs/petgraph/0.6/petgraph/algo/dijkstra/fn.dijkstra.html
+pub fn dijkstra_tree<G, F, K>(graph: G, source: G::NodeId, mut edge_cost: F) -> StringItem
+where
+    G: IntoEdges + Visitable + DataMap + NodeIndexable,
+    G::NodeId: Hash + Eq,
+    G::NodeWeight: Display,
+    F: FnMut(G::EdgeRef) -> K,
+    K: Measure + Copy + Display,
+{
+    let costs = dijkstra(graph, source, None, &mut edge_cost);
+
+    let mut order: Vec<_> = costs.keys().cloned().collect();
+    order.sort_by(|a, b| {
+        costs[a]
+            .partial_cmp(&costs[b])
+            .unwrap_or(Ordering::Equal)
+            .then(graph.to_index(*a).cmp(&graph.to_index(*b)))
+    });
+
+    // Predecessors are only taken from nodes already in the tree, so zero-cost cycles cannot form loops
+    let mut predecessors = HashMap::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &node in &order {
+            if node != source && !predecessors.contains_key(&node) {
+                continue;
+            }
+            for edge in graph.edges(node) {
+                let next = edge.target();
+                if next == source || predecessors.contains_key(&next) {
+                    continue;
+                }
+                if costs.get(&next).is_some_and(|&c| costs[&node] + edge_cost(edge) == c) {
+                    predecessors.insert(next, node);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    path_tree(graph, source, &predecessors, Some(&costs))
+}
+
+/// Condenses the strongly connected components of `graph`, and passes the item of the component of `start` to `f`
+fn with_condensed_item<N, E, Ty, Ix, F>(
+    graph: &Graph<N, E, Ty, Ix>,
+    start: NodeIndex<Ix>,
+    graph_config: &GraphConfig,
+    f: F,
+) -> io::Result<()>
+where
+    Ty: EdgeType,
+    Ix: IndexType,
+    N: Display,
+    F: FnOnce(&GraphItem<&Graph<Vec<NodeIndex<Ix>>, (), Ty, Ix>>) -> io::Result<()>,
+{
+    let components = condensation(graph.map(|i, _| i, |_, _| ()), true);
+
+    // Several edges between the same components would print the same subtree repeatedly
+    let mut condensed = components.filter_map(|_, members| Some(members.clone()), |_, _| None);
+    for edge in components.edge_references() {
+        condensed.update_edge(edge.source(), edge.target(), ());
+    }
+    for members in condensed.node_weights_mut() {
+        members.sort();
+    }
+
+    let root = condensed
+        .node_indices()
+        .find(|&i| condensed[i].contains(&start))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "start node is not in the graph"))?;
+    let item = GraphItem::with_formatter(&condensed, root, graph_config, |members, f, style| {
+        if let [member] = members[..] {
+            return style.paint_to(f, format_args!("{}", graph[member]));
+        }
+        style.paint_to(f, format_args!("{{"))?;
+        for (i, &member) in members.iter().enumerate() {
+            let separator = if i == 0 { "" } else { ", " };
+            style.paint_to(f, format_args!("{}{}", separator, graph[member]))?;
+        }
+        style.paint_to(f, format_args!("}}"))
+    });
+    f(&item)
+}
+
+///
+/// Print the strongly connected components of `graph`, starting at the one containing node `start`,
+/// to standard output
+///
+/// See [`write_graph_condensed_with`] for details.
+///
+/// [`write_graph_condensed_with`]: fn.write_graph_condensed_with.html
+pub fn print_graph_condensed<N, E, Ty, Ix>(
+    graph: &Graph<N, E, Ty, Ix>,
+    start: NodeIndex<Ix>,
+    graph_config: &GraphConfig,
+) -> io::Result<()>
+where
+    Ty: EdgeType,
+    Ix: IndexType,
+    N: Display,
+{
+    with_condensed_item(graph, start, graph_config, |item| print_tree(item))
+}
+
+///
+/// Write the strongly connected components of `graph`, starting at the one containing node `start`,
+/// to writer `f` using custom formatting
+///
+/// Each strongly connected component is printed as a single node listing its members in braces,
+/// such as `{a, b, c}`, while components with a single member are printed like regular nodes.
+/// As the components form an acyclic graph, the output is finite even for cyclic graphs,
+/// which makes this useful for dependency graphs with cycles.
+///
+/// Edges between the same pair of components are merged into one.
+/// An error of kind [`NotFound`] is returned if `start` is not a node of `graph`.
+///
+/// ```
+/// # extern crate petgraph;
+/// # extern crate ptree;
+/// # use petgraph::Graph;
+/// # use ptree::print_config::{PrintConfig, StyleWhen, UTF_CHARS};
+/// # use ptree::graph::{write_graph_condensed_with, GraphConfig};
+/// # fn main() {
+/// let mut modules = Graph::<&str, ()>::new();
+/// let main = modules.add_node("main");
+/// let parser = modules.add_node("parser");
+/// let lexer = modules.add_node("lexer");
+/// let util = modules.add_node("util");
+/// modules.extend_with_edges(&[(main, parser), (parser, lexer), (lexer, parser), (lexer, util)]);
+///
+/// let config = PrintConfig {
+///     characters: UTF_CHARS.into(),
+///     styled: StyleWhen::Never,
+///     ..PrintConfig::default()
+/// };
+/// let mut out = Vec::new();
+/// write_graph_condensed_with(&modules, main, &mut out, &config, &GraphConfig::default()).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "main\n└─ {parser, lexer}\n   └─ util\n");
+/// # }
+/// ```
+///
+/// [`NotFound`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.NotFound
+pub fn write_graph_condensed_with<N, E, Ty, Ix, W: io::Write>(
+    graph: &Graph<N, E, Ty, Ix>,
+    start: NodeIndex<Ix>,
+    f: W,
+    config: &PrintConfig,
+    graph_config: &GraphConfig,
+) -> io::Result<()>
+where
+    Ty: EdgeType,
+    Ix: IndexType,
+    N: Display,
+{
+    with_condensed_item(graph, start, graph_config, |item| write_tree_with(item, f, config))
+}
+
+#[cfg(feature = "daggy")]
+impl<N, E, Ix> TreeItem for (&Dag<N, E, Ix>, NodeIndex<Ix>)
+where
+    Ix: IndexType,
+    N: Clone + Display,
+    E: Clone,
+{
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        (self.0.graph(), self.1).write_self(f, style)
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        let v: Vec<_> = self.0.graph().neighbors(self.1).map(|i| (self.0, i)).collect();
+        Cow::from(v)
+    }
+}
+
+///
+/// Print `dag`, starting at node `start`, to standard output using default formatting and the given graph options
+///
+/// As a `Dag` cannot contain cycles, the tree is always finite, even without [`GraphConfig::deduplicate`].
+///
+/// [`GraphConfig::deduplicate`]: struct.GraphConfig.html#structfield.deduplicate
+#[cfg(feature = "daggy")]
+pub fn print_dag<N, E, Ix>(dag: &Dag<N, E, Ix>, start: NodeIndex<Ix>, graph_config: &GraphConfig) -> io::Result<()>
+where
+    Ix: IndexType,
+    N: Display,
+{
+    print_graph_with_config(dag.graph(), start, graph_config)
+}
+
+///
+/// Write `dag`, starting at node `start`, to writer `f` using custom formatting and graph options
+///
+/// As a `Dag` cannot contain cycles, the tree is always finite, even without [`GraphConfig::deduplicate`].
+///
+/// [`GraphConfig::deduplicate`]: struct.GraphConfig.html#structfield.deduplicate
+#[cfg(feature = "daggy")]
+pub fn write_dag_with<N, E, Ix, W: io::Write>(
+    dag: &Dag<N, E, Ix>,
+    start: NodeIndex<Ix>,
+    f: W,
+    config: &PrintConfig,
+    graph_config: &GraphConfig,
+) -> io::Result<()>
+where
+    Ix: IndexType,
+    N: Display,
+{
+    write_graph_with_config(dag.graph(), start, f, config, graph_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::str::from_utf8;
+    use super::*;
+    use print_config::UTF_CHARS;
+
+    #[test]
+    fn small_graph_output() {
+        let mut deps = Graph::<&str, &str>::new();
+        let pg = deps.add_node("petgraph");
+        let fb = deps.add_node("fixedbitset");
+        let qc = deps.add_node("quickcheck");
+        let rand = deps.add_node("rand");
+        let libc = deps.add_node("libc");
+        deps.extend_with_edges(&[(pg, fb), (pg, qc), (qc, rand), (rand, libc), (qc, libc)]);
+
+        let config = PrintConfig {
+            indent: 4,
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+
+        write_graph_with(&deps, pg, &mut cursor, &config).unwrap();
+
+        let data = cursor.into_inner();
+        let expected = "\
+                        petgraph\n\
+                        ├── quickcheck\n\
+                        │   ├── libc\n\
+                        │   └── rand\n\
+                        │       └── libc\n\
+                        └── fixedbitset\n\
+                        ";
+        assert_eq!(from_utf8(&data).unwrap(), expected);
+    }
+    #[test]
+    fn deduplicated_graph_output() {
+        let mut deps = Graph::<&str, &str>::new();
+        let pg = deps.add_node("petgraph");
+        let fb = deps.add_node("fixedbitset");
+        let qc = deps.add_node("quickcheck");
+        let rand = deps.add_node("rand");
+        let libc = deps.add_node("libc");
+        let cfg = deps.add_node("cfg-if");
+        deps.extend_with_edges(&[(pg, fb), (pg, qc), (qc, rand), (rand, libc), (qc, libc), (libc, cfg)]);
+        // A cycle, which is only printable with deduplication
+        deps.add_edge(cfg, qc, "");
+
+        let config = PrintConfig {
+            indent: 4,
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        let graph_config = GraphConfig {
+            deduplicate: true,
+            ..GraphConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+
+        write_graph_with_config(&deps, pg, &mut cursor, &config, &graph_config).unwrap();
+
+        let data = cursor.into_inner();
+        let expected = "\
+                        petgraph\n\
+                        ├── quickcheck\n\
+                        │   ├── libc\n\
+                        │   │   └── cfg-if\n\
+                        │   │       └── quickcheck (*)\n\
+                        │   └── rand\n\
+                        │       └── libc (*)\n\
+                        └── fixedbitset\n\
+                        ";
+        assert_eq!(from_utf8(&data).unwrap(), expected);
+    }
+    #[test]
+    fn mirrored_deduplicated_graph_output() {
+        let mut graph = Graph::<&str, ()>::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let d = graph.add_node("d");
+        graph.extend_with_edges([(a, b), (b, c), (a, c), (c, d)]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            right_to_left: true,
+            ..PrintConfig::default()
+        };
+        let graph_config = GraphConfig {
+            deduplicate: true,
+            ..GraphConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_graph_with_config(&graph, a, &mut cursor, &config, &graph_config).unwrap();
+        // The graph is walked once, so only the second occurrence of c is marked as repeated
+        let expected = ["          a", "       c ─┤", "    d ─┘  │", "       b ─┘", "c (*) ─┘   ", ""].join("\n");
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn deduplicated_graph_fold_markers() {
+        let mut graph = Graph::<&str, ()>::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let d = graph.add_node("d");
+        graph.extend_with_edges([(a, b), (b, c), (a, c), (c, d)]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            fold_markers: true,
+            ..PrintConfig::default()
+        };
+        let graph_config = GraphConfig {
+            deduplicate: true,
+            ..GraphConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_graph_with_config(&graph, a, &mut cursor, &config, &graph_config).unwrap();
+        let expected = "\
+                        [-] a\n\
+                        ├─ [-] c\n\
+                        │  └─ d\n\
+                        └─ [-] b\n   \
+                        └─ c (*)\n\
+                        ";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn graph_with_edges_output() {
+        let mut deps = Graph::<&str, &str>::new();
+        let pg = deps.add_node("petgraph");
+        let fb = deps.add_node("fixedbitset");
+        let qc = deps.add_node("quickcheck");
+        let rand = deps.add_node("rand");
+        deps.extend_with_edges(&[(pg, fb, "normal"), (pg, qc, "dev"), (qc, rand, "normal")]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        let graph_config = GraphConfig {
+            edge_format: "<{}> ".to_string(),
+            ..GraphConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+
+        write_graph_with_edges(&deps, pg, &mut cursor, &config, &graph_config).unwrap();
+
+        let data = cursor.into_inner();
+        let expected = "\
+                        petgraph\n\
+                        ├─ <dev> quickcheck\n\
+                        │  └─ <normal> rand\n\
+                        └─ <normal> fixedbitset\n\
+                        ";
+        assert_eq!(from_utf8(&data).unwrap(), expected);
+    }
+
+    #[test]
+    fn graph_edge_style() {
+        use print_config::StyleWhen;
+        use style::{Color, ColorDepth};
+
+        let mut deps = Graph::<&str, &str>::new();
+        let pg = deps.add_node("petgraph");
+        let qc = deps.add_node("quickcheck");
+        deps.add_edge(pg, qc, "dev");
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            separator: Style::default(),
+            edge: Style {
+                foreground: Some(Color::Blue),
+                ..Style::default()
+            },
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Always,
+            color_depth: ColorDepth::TrueColor,
+            ..PrintConfig::default()
+        };
+        let mut graph_config = GraphConfig::default();
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_graph_with_edges(&deps, pg, &mut cursor, &config, &graph_config).unwrap();
+        let expected = format!("petgraph\n└─ {}quickcheck\n", config.edge.paint("[dev] "));
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        // The style from the graph configuration takes precedence
+        graph_config.edge_style = Some(Style {
+            italic: true,
+            ..Style::default()
+        });
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_graph_with_edges(&deps, pg, &mut cursor, &config, &graph_config).unwrap();
+        let edge_style = graph_config.edge_style.unwrap();
+        let expected = format!("petgraph\n└─ {}quickcheck\n", edge_style.paint("[dev] "));
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn graph_with_formatter_output() {
+        let mut deps = Graph::<(&str, u32), ()>::new();
+        let pg = deps.add_node(("petgraph", 6));
+        let fb = deps.add_node(("fixedbitset", 4));
+        deps.add_edge(pg, fb, ());
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+
+        write_graph_with_formatter(&deps, pg, &mut cursor, &config, |&(name, minor), f, style| {
+            style.paint_to(f, format_args!("{} 0.{}", name, minor))
+        })
+        .unwrap();
+
+        let data = cursor.into_inner();
+        assert_eq!(from_utf8(&data).unwrap(), "petgraph 0.6\n└─ fixedbitset 0.4\n");
+    }
+    #[test]
+    fn stable_graph_output() {
+        let mut deps = StableGraph::<&str, &str>::new();
+        let pg = deps.add_node("petgraph");
+        let removed = deps.add_node("removed");
+        let fb = deps.add_node("fixedbitset");
+        let qc = deps.add_node("quickcheck");
+        deps.extend_with_edges(&[(pg, removed), (pg, fb), (pg, qc), (removed, qc)]);
+        deps.remove_node(removed);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_graph_with(&deps, pg, &mut cursor, &config).unwrap();
+        let expected = "petgraph\n├─ quickcheck\n└─ fixedbitset\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&(&deps, pg), &mut cursor, &config).unwrap();
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+    #[test]
+    fn graph_map_output() {
+        let mut deps = DiGraphMap::<&str, ()>::new();
+        deps.add_edge("petgraph", "fixedbitset", ());
+        deps.add_edge("petgraph", "quickcheck", ());
+        deps.add_edge("quickcheck", "rand", ());
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&(&deps, "petgraph"), &mut cursor, &config).unwrap();
+
+        let data = cursor.into_inner();
+        let expected = "\
+                        petgraph\n\
+                        ├─ fixedbitset\n\
+                        └─ quickcheck\n   \
+                        └─ rand\n\
+                        ";
+        assert_eq!(from_utf8(&data).unwrap(), expected);
+    }
+    #[test]
+    #[cfg(feature = "daggy")]
+    fn dag_output() {
+        let mut dag = Dag::<&str, ()>::new();
+        let pg = dag.add_node("petgraph");
+        let (_, qc) = dag.add_child(pg, (), "quickcheck");
+        let (_, fb) = dag.add_child(pg, (), "fixedbitset");
+        dag.add_edge(qc, fb, ()).unwrap();
+        assert!(dag.add_edge(fb, pg, ()).is_err());
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        let expected = "petgraph\n├─ fixedbitset\n└─ quickcheck\n   └─ fixedbitset\n";
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_dag_with(&dag, pg, &mut cursor, &config, &GraphConfig::default()).unwrap();
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&(&dag, pg), &mut cursor, &config).unwrap();
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+    #[test]
+    fn reverse_graph_output() {
+        let mut deps = Graph::<&str, &str>::new();
+        let pg = deps.add_node("petgraph");
+        let qc = deps.add_node("quickcheck");
+        let rand = deps.add_node("rand");
+        let libc = deps.add_node("libc");
+        deps.extend_with_edges(&[(pg, qc), (qc, rand), (rand, libc), (qc, libc)]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_graph_reverse_with(&deps, libc, &mut cursor, &config).unwrap();
+
+        let data = cursor.into_inner();
+        let expected = "\
+                        libc\n\
+                        ├─ quickcheck\n\
+                        │  └─ petgraph\n\
+                        └─ rand\n   \
+                        └─ quickcheck\n      \
+                        └─ petgraph\n\
+                        ";
+        assert_eq!(from_utf8(&data).unwrap(), expected);
+    }
+    #[test]
+    fn condensed_graph_output() {
+        let mut graph = Graph::<&str, ()>::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let d = graph.add_node("d");
+        let e = graph.add_node("e");
+        graph.extend_with_edges([(a, b), (b, c), (c, a), (a, d), (b, d), (d, e), (c, e)]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        let graph_config = GraphConfig {
+            deduplicate: true,
+            ..GraphConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_graph_condensed_with(&graph, b, &mut cursor, &config, &graph_config).unwrap();
+        let expected = "{a, b, c}\n├─ e\n└─ d\n   └─ e\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let missing = NodeIndex::new(10);
+        let err = write_graph_condensed_with(&graph, missing, io::sink(), &config, &graph_config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn graph_forest_output() {
+        let mut deps = Graph::<&str, &str>::new();
+        let pg = deps.add_node("petgraph");
+        let fb = deps.add_node("fixedbitset");
+        let ptree = deps.add_node("ptree");
+        let tint = deps.add_node("tint");
+        deps.extend_with_edges(&[(pg, fb), (ptree, pg), (ptree, tint)]);
+        deps.add_node("serde");
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_graph_forest_with(&deps, &mut cursor, &config).unwrap();
+
+        let data = cursor.into_inner();
+        let expected = "\
+                        ptree\n\
+                        ├─ tint\n\
+                        └─ petgraph\n   \
+                        └─ fixedbitset\n\
+                        serde\n\
+                        ";
+        assert_eq!(from_utf8(&data).unwrap(), expected);
+    }
+    #[test]
+    fn filtered_graph_output() {
+        let mut deps = Graph::<&str, &str>::new();
+        let pg = deps.add_node("petgraph");
+        let fb = deps.add_node("fixedbitset");
+        let qc = deps.add_node("quickcheck");
+        let rand = deps.add_node("rand");
+        deps.extend_with_edges(&[(pg, fb, "normal"), (pg, qc, "dev"), (qc, rand, "normal")]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        let graph_config = GraphConfig {
+            deduplicate: true,
+            ..GraphConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_graph_with_filter(&deps, pg, &mut cursor, &config, |&kind| kind != "dev").unwrap();
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), "petgraph\n└─ fixedbitset\n");
+
+        // A node whose children are all filtered out is not marked as repeated
+        deps.add_edge(fb, qc, "normal");
+        let item = GraphItem::new(&deps, pg, &graph_config).filter_edges(|&kind| kind != "normal");
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), "petgraph\n└─ quickcheck\n");
+    }
+
+    #[test]
+    fn styled_graph_output() {
+        use print_config::StyleWhen;
+        use style::Color;
+
+        let mut deps = Graph::<&str, ()>::new();
+        let app = deps.add_node("app");
+        let serde = deps.add_node("serde");
+        deps.add_edge(app, serde, ());
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            separator: Style::default(),
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Always,
+            ..PrintConfig::default()
+        };
+        let external = Style {
+            foreground: Some(Color::Red),
+            ..Style::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_graph_with_style(&deps, app, &mut cursor, &config, |&name| {
+            if name == "app" {
+                Style::default()
+            } else {
+                external.clone()
+            }
+        })
+        .unwrap();
+        let expected = format!("app\n└─ {}\n", external.paint("serde"));
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn truncated_graph_output() {
+        // Every layer doubles the number of paths to the bottom node
+        let mut graph = Graph::<usize, ()>::new();
+        let nodes: Vec<_> = (0..20).map(|i| graph.add_node(i)).collect();
+        for pair in nodes.windows(2) {
+            graph.add_edge(pair[0], pair[1], ());
+            graph.add_edge(pair[0], pair[1], ());
+        }
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        let graph_config = GraphConfig {
+            max_nodes: Some(4),
+            ..GraphConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_graph_with_config(&graph, nodes[0], &mut cursor, &config, &graph_config).unwrap();
+
+        let data = cursor.into_inner();
+        let expected = "\
+                        0\n\
+                        ├─ 1\n\
+                        │  ├─ 2\n\
+                        │  └─ (truncated after 4 nodes)\n\
+                        └─ 1\n\
+                        ";
+        assert_eq!(from_utf8(&data).unwrap(), expected);
+    }
+    #[test]
+    fn path_trees() {
+        let mut graph = Graph::<&str, u32>::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let d = graph.add_node("d");
+        graph.extend_with_edges([(a, b, 1), (a, c, 5), (b, c, 1), (c, d, 1), (d, a, 1)]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&dijkstra_tree(&graph, a, |e| *e.weight()), &mut cursor, &config).unwrap();
+        let expected = "a (0)\n└─ b (1)\n   └─ c (2)\n      └─ d (3)\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&bfs_tree(&graph, a), &mut cursor, &config).unwrap();
+        let expected = "a\n├─ b\n└─ c\n   └─ d\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
     }
 }
@@ -0,0 +1,152 @@
+//!
+//! Rendering of trees as HTML
+//!
+//! The functions in this module produce the same lines as the terminal output,
+//! as a `<pre class="ptree">` element in which styled text is wrapped in `<span>` elements with inline CSS.
+//! Hyperlinks of styles become `<a>` elements.
+//! The HTML can be inserted into a web page as it is, for example by a playground compiled to WebAssembly.
+//!
+//! For building the elements of a page directly, [`tree_lines`] returns the lines with their styled segments.
+//!
+//! ```
+//! # use ptree::builder::TreeBuilder;
+//! # use ptree::html::tree_to_html;
+//! # use ptree::print_config::{PrintConfig, UTF_CHARS};
+//! # use ptree::style::Style;
+//! let tree = TreeBuilder::new("<root>".to_string())
+//!     .add_empty_child("child".to_string())
+//!     .build();
+//! let config = PrintConfig {
+//!     characters: UTF_CHARS.into(),
+//!     branch: Style::default(),
+//!     separator: Style::default(),
+//!     leaf: "bold".parse().unwrap(),
+//!     ..PrintConfig::default()
+//! };
+//!
+//! let html = tree_to_html(&tree, &config).unwrap();
+//! assert_eq!(
+//!     html,
+//!     "<pre class=\"ptree\">\
+//!      <span style=\"font-weight:bold\">&lt;root&gt;</span>\n\
+//!      └─ <span style=\"font-weight:bold\">child</span>\n\
+//!      </pre>\n"
+//! );
+//! ```
+//!
+//! [`tree_lines`]: ../output/fn.tree_lines.html
+//!
+
+use item::TreeItem;
+use output::tree_lines;
+use print_config::PrintConfig;
+
+use std::io;
+
+/// Writes `text` with the characters that are special in HTML replaced by entities
+fn write_escaped<W: io::Write>(f: &mut W, text: &str) -> io::Result<()> {
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        let entity = match c {
+            '&' => "&amp;",
+            '<' => "&lt;",
+            '>' => "&gt;",
+            '"' => "&quot;",
+            '\'' => "&#39;",
+            _ => continue,
+        };
+        f.write_all(&text.as_bytes()[start..i])?;
+        f.write_all(entity.as_bytes())?;
+        start = i + 1;
+    }
+    f.write_all(&text.as_bytes()[start..])
+}
+
+/// Write the tree `item` as HTML to writer `f` using custom formatting
+///
+/// Text is styled unless [`PrintConfig::styled`] is set to [`StyleWhen::Never`].
+///
+/// [`PrintConfig::styled`]: ../print_config/struct.PrintConfig.html#structfield.styled
+/// [`StyleWhen::Never`]: ../print_config/enum.StyleWhen.html#variant.Never
+pub fn write_tree_html<T: TreeItem, W: io::Write>(item: &T, mut f: W, config: &PrintConfig) -> io::Result<()> {
+    f.write_all(b"<pre class=\"ptree\">")?;
+    for line in tree_lines(item, config)? {
+        for (style, text) in line.segments() {
+            if let Some(ref target) = style.hyperlink {
+                f.write_all(b"<a href=\"")?;
+                write_escaped(&mut f, target)?;
+                f.write_all(b"\">")?;
+            }
+
+            let css = style.to_css();
+            if css.is_empty() {
+                write_escaped(&mut f, text)?;
+            } else {
+                f.write_all(b"<span style=\"")?;
+                write_escaped(&mut f, &css)?;
+                f.write_all(b"\">")?;
+                write_escaped(&mut f, text)?;
+                f.write_all(b"</span>")?;
+            }
+
+            if style.hyperlink.is_some() {
+                f.write_all(b"</a>")?;
+            }
+        }
+        f.write_all(b"\n")?;
+    }
+    f.write_all(b"</pre>\n")
+}
+
+/// Render the tree `item` as a string of HTML using custom formatting
+pub fn tree_to_html<T: TreeItem>(item: &T, config: &PrintConfig) -> io::Result<String> {
+    let mut out = Vec::new();
+    write_tree_html(item, &mut out, config)?;
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use builder::TreeBuilder;
+    use print_config::{StyleWhen, UTF_CHARS};
+    use style::{Color, Style};
+
+    #[test]
+    fn html_output() {
+        let tree = TreeBuilder::new("deps".to_string())
+            .begin_child("a & b".to_string())
+            .add_empty_child("c".to_string())
+            .end_child()
+            .build();
+        let mut config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            branch: Style {
+                foreground: Some(Color::Fixed(8)),
+                ..Style::default()
+            },
+            separator: Style::default(),
+            leaf: Style {
+                hyperlink: Some("https://example.com/?a=1&b=2".to_string()),
+                ..Style::default()
+            },
+            ..PrintConfig::default()
+        };
+
+        assert_eq!(
+            tree_to_html(&tree, &config).unwrap(),
+            "<pre class=\"ptree\">\
+             <a href=\"https://example.com/?a=1&amp;b=2\">deps</a>\n\
+             <span style=\"color:#7f7f7f\">└─</span> <a href=\"https://example.com/?a=1&amp;b=2\">a &amp; b</a>\n\
+             <span style=\"color:#7f7f7f\">   └─</span> <a href=\"https://example.com/?a=1&amp;b=2\">c</a>\n\
+             </pre>\n"
+        );
+
+        config.styled = StyleWhen::Never;
+        assert_eq!(
+            tree_to_html(&tree, &config).unwrap(),
+            "<pre class=\"ptree\">deps\n└─ a &amp; b\n   └─ c\n</pre>\n"
+        );
+    }
+}
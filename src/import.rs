@@ -0,0 +1,803 @@
+//!
+//! Conversion of graph files into trees
+//!
+//! Graphs exported by other tools in the [DOT] or [GraphML] format can be read into a [`StringItem`],
+//! which can then be printed like any other tree.
+//!
+//! Every node is listed with its label, or with its id if it has no label.
+//! In directed graphs, the children of each node are the targets of its outgoing edges.
+//! A node reachable through several paths is only expanded once, and later occurrences are marked with `(*)`.
+//! In undirected graphs, every node appears once, in the order of a depth-first search from the root.
+//!
+//! Only the structure of the graphs is imported, attributes other than node labels are ignored.
+//!
+//! Hierarchies exported from spreadsheets and databases as CSV, with one row per node
+//! naming the id of its parent, are read into a forest of [`StringItem`]s by [`from_csv`].
+//!
+//! [DOT]: https://graphviz.org/doc/info/lang.html
+//! [GraphML]: http://graphml.graphdrawing.org/
+//! [`StringItem`]: ../item/struct.StringItem.html
+//! [`from_csv`]: fn.from_csv.html
+//!
+
+use item::StringItem;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use csv::ReaderBuilder;
+use roxmltree::{Document, Node};
+
+///
+/// Error returned when importing a graph fails
+///
+#[derive(Debug)]
+pub enum ImportError {
+    /// The file could not be read
+    Io(io::Error),
+    /// The input is not a valid graph description
+    Syntax(String),
+    /// The requested root node does not exist
+    UnknownNode(String),
+    /// The graph has no nodes
+    Empty,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Io(e) => write!(f, "{}", e),
+            ImportError::Syntax(msg) => write!(f, "invalid graph: {}", msg),
+            ImportError::UnknownNode(id) => write!(f, "unknown node '{}'", id),
+            ImportError::Empty => write!(f, "the graph has no nodes"),
+        }
+    }
+}
+
+impl Error for ImportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImportError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImportError {
+    fn from(e: io::Error) -> Self {
+        ImportError::Io(e)
+    }
+}
+
+///
+/// Reads the graph file at `path` into a tree rooted at the node with id `root`
+///
+/// Files with the extensions `graphml` or `xml` are read as GraphML, all others as DOT.
+/// If `root` is `None`, the first node without incoming edges is used, see [`from_dot`].
+///
+/// [`from_dot`]: fn.from_dot.html
+pub fn from_file<P: AsRef<Path>>(path: P, root: Option<&str>) -> Result<StringItem, ImportError> {
+    let path = path.as_ref();
+    let input = fs::read_to_string(path)?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("graphml") || ext.eq_ignore_ascii_case("xml") => {
+            from_graphml(&input, root)
+        }
+        _ => from_dot(&input, root),
+    }
+}
+
+///
+/// Reads a graph in the DOT format into a tree rooted at the node with id `root`
+///
+/// If `root` is `None`, the first node without incoming edges is used.
+/// In undirected graphs, or if every node has incoming edges, the first node of the graph is used.
+///
+/// ```
+/// # use ptree::import::from_dot;
+/// let dot = r#"
+///     digraph deps {
+///         app [label="my-app"];
+///         app -> { core cli };
+///         cli -> core;
+///     }
+/// "#;
+///
+/// let tree = from_dot(dot, None).unwrap();
+/// assert_eq!(tree.text, "my-app");
+/// assert_eq!(tree.children[1].children[0].text, "core");
+/// ```
+pub fn from_dot(input: &str, root: Option<&str>) -> Result<StringItem, ImportError> {
+    let mut parser = DotParser {
+        tokens: tokenize(input)?,
+        pos: 0,
+        graph: ImportGraph::default(),
+    };
+    parser.parse()?;
+    parser.graph.into_tree(root)
+}
+
+///
+/// Reads a graph in the GraphML format into a tree rooted at the node with id `root`
+///
+/// Node labels are taken from the data keys named `label` or `name`.
+/// If `root` is `None`, the first node without incoming edges is used, see [`from_dot`].
+/// Only the first graph of the document is read, including the nodes of nested graphs.
+///
+/// [`from_dot`]: fn.from_dot.html
+pub fn from_graphml(input: &str, root: Option<&str>) -> Result<StringItem, ImportError> {
+    let doc = Document::parse(input).map_err(|e| ImportError::Syntax(e.to_string()))?;
+
+    let label_keys: Vec<_> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("key"))
+        .filter(|n| matches!(n.attribute("for"), None | Some("node") | Some("all")))
+        .filter(|n| matches!(n.attribute("attr.name"), Some("label") | Some("name")))
+        .filter_map(|n| n.attribute("id"))
+        .collect();
+
+    let element = doc
+        .descendants()
+        .find(|n| n.has_tag_name("graph"))
+        .ok_or_else(|| ImportError::Syntax("missing <graph> element".to_string()))?;
+    let mut graph = ImportGraph {
+        directed: element.attribute("edgedefault") != Some("undirected"),
+        ..ImportGraph::default()
+    };
+    for node in element.descendants().filter(|n| n.has_tag_name("node")) {
+        let index = graph.node(required(node, "id")?);
+        let label = node
+            .children()
+            .filter(|n| n.has_tag_name("data"))
+            .find(|n| n.attribute("key").is_some_and(|k| label_keys.contains(&k)))
+            .and_then(|n| n.text());
+        if let Some(label) = label {
+            graph.labels[index] = Some(label.trim().to_string());
+        }
+    }
+    for edge in element.descendants().filter(|n| n.has_tag_name("edge")) {
+        let source = graph.node(required(edge, "source")?);
+        let target = graph.node(required(edge, "target")?);
+        graph.add_edge(source, target);
+    }
+
+    graph.into_tree(root)
+}
+
+///
+/// Names of the columns read by [`from_csv`]
+///
+/// [`from_csv`]: fn.from_csv.html
+#[derive(Clone, Debug)]
+pub struct CsvColumns {
+    /// Column with the unique id of each node
+    ///
+    /// The default value is `id`.
+    pub id: String,
+    /// Column with the id of the parent of each node, which is empty for root nodes
+    ///
+    /// The default value is `parent_id`.
+    pub parent: String,
+    /// Column with the label of each node
+    ///
+    /// Nodes are labeled with their id if this column is empty or missing.
+    /// The default value is `label`.
+    pub label: String,
+}
+
+impl Default for CsvColumns {
+    fn default() -> CsvColumns {
+        CsvColumns {
+            id: "id".to_string(),
+            parent: "parent_id".to_string(),
+            label: "label".to_string(),
+        }
+    }
+}
+
+///
+/// Reads CSV rows with the id, parent id and label of each node into a forest of trees
+///
+/// The first row contains the names of the columns, which are selected by `columns`,
+/// and other columns are ignored. Rows with an empty parent id are the roots of the returned trees.
+/// Roots and the children of each node are listed in the order of their rows,
+/// and parents do not need to appear before their children.
+///
+/// ```
+/// # use ptree::import::{from_csv, CsvColumns};
+/// let csv = "\
+///     id,parent_id,label
+///     2,1,Engineering
+///     1,,Company
+///     3,2,Platform
+///     4,1,Sales
+/// ";
+///
+/// let forest = from_csv(csv.as_bytes(), &CsvColumns::default()).unwrap();
+/// assert_eq!(forest.len(), 1);
+/// assert_eq!(forest[0].text, "Company");
+/// assert_eq!(forest[0].children[0].children[0].text, "Platform");
+/// ```
+pub fn from_csv<R: io::Read>(reader: R, columns: &CsvColumns) -> Result<Vec<StringItem>, ImportError> {
+    let mut reader = ReaderBuilder::new().trim(csv::Trim::All).from_reader(reader);
+    let headers = reader.headers().map_err(csv_error)?.clone();
+    let column = |name: &str| headers.iter().position(|header| header == name);
+    let missing = |name: &str| ImportError::Syntax(format!("missing column '{}'", name));
+    let id_column = column(&columns.id).ok_or_else(|| missing(&columns.id))?;
+    let parent_column = column(&columns.parent).ok_or_else(|| missing(&columns.parent))?;
+    let label_column = column(&columns.label);
+
+    let mut ids = Vec::new();
+    let mut labels = Vec::new();
+    let mut parents = Vec::new();
+    let mut index = HashMap::new();
+    for record in reader.records() {
+        let record = record.map_err(csv_error)?;
+        let id = record.get(id_column).unwrap_or_default();
+        if id.is_empty() {
+            return Err(ImportError::Syntax("row without id".to_string()));
+        }
+        if index.insert(id.to_string(), ids.len()).is_some() {
+            return Err(ImportError::Syntax(format!("duplicate node '{}'", id)));
+        }
+        let label = label_column.and_then(|column| record.get(column)).unwrap_or_default();
+        ids.push(id.to_string());
+        labels.push(if label.is_empty() { id } else { label }.to_string());
+        parents.push(record.get(parent_column).unwrap_or_default().to_string());
+    }
+
+    let mut roots = Vec::new();
+    let mut children = vec![Vec::new(); ids.len()];
+    for (node, parent) in parents.iter().enumerate() {
+        if parent.is_empty() {
+            roots.push(node);
+        } else {
+            let &parent = index.get(parent).ok_or_else(|| ImportError::UnknownNode(parent.clone()))?;
+            children[parent].push(node);
+        }
+    }
+
+    let mut reached = vec![false; ids.len()];
+    let forest = roots
+        .into_iter()
+        .map(|root| build_csv_node(root, &mut labels, &children, &mut reached))
+        .collect();
+    // Nodes which are not below any root are part of a cycle
+    if let Some(node) = reached.iter().position(|&r| !r) {
+        return Err(ImportError::Syntax(format!("cycle through node '{}'", ids[node])));
+    }
+    Ok(forest)
+}
+
+/// Builds the subtree of the CSV node `node`, recording the visited nodes in `reached`
+fn build_csv_node(node: usize, labels: &mut [String], children: &[Vec<usize>], reached: &mut [bool]) -> StringItem {
+    reached[node] = true;
+    StringItem {
+        text: std::mem::take(&mut labels[node]),
+        children: children[node]
+            .iter()
+            .map(|&child| build_csv_node(child, labels, children, reached))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Converts an error of the `csv` crate into an import error
+fn csv_error(error: csv::Error) -> ImportError {
+    let message = error.to_string();
+    match error.into_kind() {
+        csv::ErrorKind::Io(e) => ImportError::Io(e),
+        _ => ImportError::Syntax(message),
+    }
+}
+
+/// Returns the attribute `name` of the GraphML element `node`, which is required by the format
+fn required<'a>(node: Node<'a, '_>, name: &str) -> Result<&'a str, ImportError> {
+    node.attribute(name)
+        .ok_or_else(|| ImportError::Syntax(format!("<{}> without '{}' attribute", node.tag_name().name(), name)))
+}
+
+/// Nodes and edges of an imported graph
+#[derive(Default)]
+struct ImportGraph {
+    ids: Vec<String>,
+    labels: Vec<Option<String>>,
+    index: HashMap<String, usize>,
+    edges: Vec<Vec<usize>>,
+    has_incoming: Vec<bool>,
+    directed: bool,
+}
+
+impl ImportGraph {
+    /// Returns the index of the node with id `id`, adding it if it does not exist yet
+    fn node(&mut self, id: &str) -> usize {
+        if let Some(&index) = self.index.get(id) {
+            return index;
+        }
+
+        let index = self.ids.len();
+        self.ids.push(id.to_string());
+        self.labels.push(None);
+        self.edges.push(Vec::new());
+        self.has_incoming.push(false);
+        self.index.insert(id.to_string(), index);
+        index
+    }
+
+    fn add_edge(&mut self, source: usize, target: usize) {
+        self.edges[source].push(target);
+        self.has_incoming[target] = true;
+        if !self.directed && source != target {
+            self.edges[target].push(source);
+        }
+    }
+
+    fn into_tree(self, root: Option<&str>) -> Result<StringItem, ImportError> {
+        let root = match root {
+            Some(id) => *self
+                .index
+                .get(id)
+                .ok_or_else(|| ImportError::UnknownNode(id.to_string()))?,
+            None if self.ids.is_empty() => return Err(ImportError::Empty),
+            None if self.directed => self.has_incoming.iter().position(|&i| !i).unwrap_or(0),
+            None => 0,
+        };
+
+        let mut visited = vec![false; self.ids.len()];
+        visited[root] = true;
+        Ok(self.build(root, &mut visited))
+    }
+
+    /// Builds the subtree of `node`, whose visit was already recorded in `visited`
+    fn build(&self, node: usize, visited: &mut [bool]) -> StringItem {
+        let mut item = StringItem::new(self.label(node), Vec::new());
+
+        for &child in &self.edges[node] {
+            if !visited[child] {
+                visited[child] = true;
+                item.children.push(self.build(child, visited));
+            } else if self.directed {
+                let text = if self.edges[child].is_empty() {
+                    self.label(child)
+                } else {
+                    format!("{} (*)", self.label(child))
+                };
+                item.children.push(StringItem::new(text, Vec::new()));
+            }
+        }
+        item
+    }
+
+    fn label(&self, node: usize) -> String {
+        self.labels[node].clone().unwrap_or_else(|| self.ids[node].clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// An unquoted identifier or numeral
+    Word(String),
+    /// A quoted or HTML string
+    Str(String),
+    Punct(char),
+    /// `->` or `--`
+    EdgeOp,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ImportError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut line_start = true;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => {
+                line_start = true;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            // Lines starting with '#' are preprocessor output
+            '#' if line_start => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if last == '*' => break,
+                        Some(c) => last = c,
+                        None => return Err(ImportError::Syntax("unterminated comment".to_string())),
+                    }
+                }
+            }
+            '-' if chars.peek() == Some(&'>') || chars.peek() == Some(&'-') => {
+                chars.next();
+                tokens.push(Token::EdgeOp);
+            }
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('"') => s.push('"'),
+                            // An escaped newline continues the string on the next line
+                            Some('\n') => {}
+                            Some(c) => {
+                                s.push('\\');
+                                s.push(c);
+                            }
+                            None => break,
+                        },
+                        Some(c) => s.push(c),
+                        None => return Err(ImportError::Syntax("unterminated string".to_string())),
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            '<' => {
+                let mut s = String::new();
+                let mut depth = 1;
+                loop {
+                    match chars.next() {
+                        Some('<') => depth += 1,
+                        Some('>') if depth == 1 => break,
+                        Some('>') => depth -= 1,
+                        Some(c) => s.push(c),
+                        None => return Err(ImportError::Syntax("unterminated HTML string".to_string())),
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            '{' | '}' | '[' | ']' | ';' | ',' | '=' | ':' | '+' => tokens.push(Token::Punct(c)),
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' || !c.is_ascii() => {
+                let mut s = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' || c == '.' || !c.is_ascii() {
+                        s.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Word(s));
+            }
+            c => return Err(ImportError::Syntax(format!("unexpected character '{}'", c))),
+        }
+        line_start = false;
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser for the statements of a DOT graph
+struct DotParser {
+    tokens: Vec<Token>,
+    pos: usize,
+    graph: ImportGraph,
+}
+
+impl DotParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(w)) => w.eq_ignore_ascii_case(keyword),
+            _ => false,
+        }
+    }
+
+    fn eat(&mut self, punct: char) -> bool {
+        if self.peek() == Some(&Token::Punct(punct)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), ImportError> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", punct)))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ImportError {
+        match self.peek() {
+            Some(token) => ImportError::Syntax(format!("expected {}, found {:?}", expected, token)),
+            None => ImportError::Syntax(format!("expected {}, found end of input", expected)),
+        }
+    }
+
+    /// Parses an identifier, joining strings concatenated with `+`
+    fn id(&mut self) -> Result<String, ImportError> {
+        let mut id = match self.peek() {
+            Some(Token::Word(w)) => w.clone(),
+            Some(Token::Str(s)) => s.clone(),
+            _ => return Err(self.unexpected("an identifier")),
+        };
+        self.pos += 1;
+
+        while self.peek() == Some(&Token::Punct('+')) {
+            self.pos += 1;
+            match self.peek() {
+                Some(Token::Str(s)) => id.push_str(s),
+                _ => return Err(self.unexpected("a string")),
+            }
+            self.pos += 1;
+        }
+        Ok(id)
+    }
+
+    fn parse(&mut self) -> Result<(), ImportError> {
+        if self.peek_keyword("strict") {
+            self.pos += 1;
+        }
+        if self.peek_keyword("digraph") {
+            self.graph.directed = true;
+        } else if !self.peek_keyword("graph") {
+            return Err(self.unexpected("'graph' or 'digraph'"));
+        }
+        self.pos += 1;
+
+        if !self.eat('{') {
+            self.id()?;
+            self.expect('{')?;
+        }
+        self.statements()?;
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.unexpected("end of input")),
+        }
+    }
+
+    /// Parses statements up to the closing brace, and returns the nodes they mention
+    fn statements(&mut self) -> Result<Vec<usize>, ImportError> {
+        let mut nodes = Vec::new();
+        while !self.eat('}') {
+            if self.peek().is_none() {
+                return Err(self.unexpected("'}'"));
+            }
+            self.statement(&mut nodes)?;
+            if !self.eat(';') {
+                self.eat(',');
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn statement(&mut self, nodes: &mut Vec<usize>) -> Result<(), ImportError> {
+        // Default attributes for the graph, nodes or edges
+        let defaults = ["graph", "node", "edge"].iter().any(|k| self.peek_keyword(k));
+        if defaults && self.tokens.get(self.pos + 1) == Some(&Token::Punct('[')) {
+            self.pos += 1;
+            self.attributes()?;
+            return Ok(());
+        }
+        // Graph attribute
+        if self.tokens.get(self.pos + 1) == Some(&Token::Punct('=')) {
+            self.id()?;
+            self.pos += 1;
+            self.id()?;
+            return Ok(());
+        }
+
+        let first = self.operand()?;
+        nodes.extend(&first);
+        if self.peek() != Some(&Token::EdgeOp) {
+            if let [node] = first[..] {
+                let attributes = self.attributes()?;
+                if let Some((_, label)) = attributes.into_iter().find(|(k, _)| k == "label") {
+                    let id = &self.graph.ids[node];
+                    self.graph.labels[node] = Some(label.replace("\\N", id));
+                }
+            }
+            return Ok(());
+        }
+
+        let mut previous = first;
+        while self.peek() == Some(&Token::EdgeOp) {
+            self.pos += 1;
+            let next = self.operand()?;
+            nodes.extend(&next);
+            for &source in &previous {
+                for &target in &next {
+                    self.graph.add_edge(source, target);
+                }
+            }
+            previous = next;
+        }
+        self.attributes()?;
+        Ok(())
+    }
+
+    /// Parses a node or a subgraph, and returns the nodes it contains
+    fn operand(&mut self) -> Result<Vec<usize>, ImportError> {
+        if self.peek_keyword("subgraph") {
+            self.pos += 1;
+            if !self.eat('{') {
+                self.id()?;
+                self.expect('{')?;
+            }
+            return self.statements();
+        }
+        if self.eat('{') {
+            return self.statements();
+        }
+
+        let id = self.id()?;
+        // Ports are ignored
+        while self.eat(':') {
+            self.id()?;
+        }
+        Ok(vec![self.graph.node(&id)])
+    }
+
+    /// Parses optional attribute lists
+    fn attributes(&mut self) -> Result<Vec<(String, String)>, ImportError> {
+        let mut attributes = Vec::new();
+        while self.eat('[') {
+            while !self.eat(']') {
+                let key = self.id()?;
+                let value = if self.eat('=') { self.id()? } else { String::new() };
+                attributes.push((key, value));
+                if !self.eat(',') {
+                    self.eat(';');
+                }
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use output::write_tree_with;
+    use print_config::{PrintConfig, UTF_CHARS};
+    use style::Style;
+
+    fn render(tree: &StringItem) -> String {
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        let mut out = Vec::new();
+        write_tree_with(tree, &mut out, &config).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn dot_import() {
+        let dot = "\
+            /* generated */\n\
+            strict digraph \"deps\" {\n\
+                node [shape=box];\n\
+                rankdir = LR\n\
+                app [label=\"my app\"]\n\
+                app -> core -> \"serde\":n;\n\
+                app -> { cli; core } [style=dashed]\n\
+                subgraph cluster_0 { cli -> core }\n\
+            }\n";
+
+        let expected = "\
+                        my app\n\
+                        ├─ core\n\
+                        │  └─ serde\n\
+                        ├─ cli\n\
+                        │  └─ core (*)\n\
+                        └─ core (*)\n";
+        assert_eq!(render(&from_dot(dot, None).unwrap()), expected);
+        assert_eq!(
+            render(&from_dot(dot, Some("cli")).unwrap()),
+            "cli\n└─ core\n   └─ serde\n"
+        );
+
+        let undirected = "graph { a -- b -- c -- a; c -- d }";
+        assert_eq!(
+            render(&from_dot(undirected, Some("c")).unwrap()),
+            "c\n├─ b\n│  └─ a\n└─ d\n"
+        );
+    }
+
+    #[test]
+    fn dot_errors() {
+        assert!(matches!(
+            from_dot("digraph { a -> b", None),
+            Err(ImportError::Syntax(_))
+        ));
+        assert!(matches!(
+            from_dot("digraph { a -> }", None),
+            Err(ImportError::Syntax(_))
+        ));
+        assert!(matches!(from_dot("digraph {}", None), Err(ImportError::Empty)));
+        assert!(matches!(
+            from_dot("digraph { a }", Some("b")),
+            Err(ImportError::UnknownNode(_))
+        ));
+    }
+
+    #[test]
+    fn graphml_import() {
+        let graphml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+              <key id="d0" for="node" attr.name="label" attr.type="string"/>
+              <graph id="G" edgedefault="directed">
+                <node id="n0"><data key="d0">root</data></node>
+                <node id="n1"><data key="d0">left</data></node>
+                <node id="n2"/>
+                <edge source="n0" target="n1"/>
+                <edge source="n0" target="n2"/>
+                <edge source="n1" target="n2"/>
+              </graph>
+            </graphml>"#;
+
+        assert_eq!(
+            render(&from_graphml(graphml, None).unwrap()),
+            "root\n├─ left\n│  └─ n2\n└─ n2\n"
+        );
+        assert!(matches!(from_graphml("<graphml>", None), Err(ImportError::Syntax(_))));
+    }
+
+    #[test]
+    fn csv_import() {
+        let csv = "\
+            label,parent,key,size\n\
+            src,root,s,\n\
+            README.md,root,r,10\n\
+            main.rs,s,m,20\n\
+            ptree,,root,\n\
+            other,,o,\n\
+            ,o,empty,\n";
+        let columns = CsvColumns {
+            id: "key".to_string(),
+            parent: "parent".to_string(),
+            ..CsvColumns::default()
+        };
+
+        let forest = from_csv(csv.as_bytes(), &columns).unwrap();
+        assert_eq!(forest.len(), 2);
+        assert_eq!(render(&forest[0]), "ptree\n├─ src\n│  └─ main.rs\n└─ README.md\n");
+        assert_eq!(render(&forest[1]), "other\n└─ empty\n");
+    }
+
+    #[test]
+    fn csv_errors() {
+        let columns = CsvColumns::default();
+        assert!(matches!(
+            from_csv("id,label\n1,a\n".as_bytes(), &columns),
+            Err(ImportError::Syntax(ref msg)) if msg == "missing column 'parent_id'"
+        ));
+        assert!(matches!(
+            from_csv("id,parent_id\n1,\n1,\n".as_bytes(), &columns),
+            Err(ImportError::Syntax(_))
+        ));
+        assert!(matches!(
+            from_csv("id,parent_id\n1,\n2,3\n".as_bytes(), &columns),
+            Err(ImportError::UnknownNode(ref id)) if id == "3"
+        ));
+        assert!(matches!(
+            from_csv("id,parent_id\n1,\n2,3\n3,2\n".as_bytes(), &columns),
+            Err(ImportError::Syntax(ref msg)) if msg == "cycle through node '2'"
+        ));
+    }
+}
@@ -1,7 +1,13 @@
+use output::item_text;
 use style::Style;
 
 use std::io;
 use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 ///
 /// Main trait for exposing a tree structure to `ptree`
@@ -26,18 +32,180 @@ pub trait TreeItem: Clone {
     /// only parts of the output, apply its own formatting in combination with the provided
     /// config, or ignore formatting altogether.
     ///
+    /// [`style.paint_to`] writes the formatted text directly to `f`, avoiding an allocation for every item.
+    ///
     /// [io::Result]: https://doc.rust-lang.org/std/io/type.Result.html
     /// [write_fn]: https://doc.rust-lang.org/std/io/trait.Write.html#tymethod.write
     /// [write_macro]: https://doc.rust-lang.org/std/macro.write.html
     /// [`style.paint`]: ../style/struct.Style.html#typemethod.paint
+    /// [`style.paint_to`]: ../style/struct.Style.html#method.paint_to
     fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()>;
 
+    ///
+    /// Retrieve the name of this item's class, if it has one
+    ///
+    /// The style of the class in [`PrintConfig::classes`] is merged into the style passed to [`write_self`],
+    /// which allows styling items by their kind, such as numbers and strings in a value tree.
+    /// The default implementation returns `None`.
+    ///
+    /// [`PrintConfig::classes`]: ../print_config/struct.PrintConfig.html#structfield.classes
+    /// [`write_self`]: #tymethod.write_self
+    fn class(&self) -> Option<&str> {
+        None
+    }
+
+    ///
+    /// Retrieve the glyph replacing the branch character directly in front of this item, if it has one
+    ///
+    /// This marks items by their state, such as `▶` for items which can be expanded
+    /// or `✔` and `✘` for the results of checks.
+    /// The glyph replaces the last branch character before the padding and is written in the branch style,
+    /// so it should be as wide as that character. The indentation of the item's children is not changed.
+    /// The root item has no branch characters, so its glyph is not printed.
+    /// The default implementation returns `None`.
+    fn branch_char(&self) -> Option<&str> {
+        None
+    }
+
+    ///
+    /// Retrieve the key-value metadata of this item, if it has any
+    ///
+    /// Metadata is printed as configured by [`PrintConfig::metadata`], either after the text of the item
+    /// or as lines above its children, and is hidden by default.
+    /// The default implementation returns `None`.
+    ///
+    /// [`PrintConfig::metadata`]: ../print_config/struct.PrintConfig.html#structfield.metadata
+    fn metadata(&self) -> Option<&BTreeMap<String, String>> {
+        None
+    }
+
     ///
     /// Retrieve a list of this item's children
     ///
     /// If the items contains no children (it is a leaf item), this method returns an empty list.
     ///
-    fn children(&self) -> Cow<[Self::Child]>;
+    fn children(&self) -> Cow<'_, [Self::Child]>;
+
+    ///
+    /// Return whether this item has any children
+    ///
+    /// This is used for markers which are printed before the children of an item are retrieved,
+    /// such as those of [`PrintConfig::fold_markers`], so it must not change the state of the item.
+    /// The default implementation retrieves the children, so items whose children change their state,
+    /// or are expensive to retrieve, should implement it.
+    ///
+    /// [`PrintConfig::fold_markers`]: ../print_config/struct.PrintConfig.html#structfield.fold_markers
+    fn has_children(&self) -> bool {
+        !self.children().is_empty()
+    }
+}
+
+///
+/// Variant of [`TreeItem`] for trees whose children are retrieved by operations that can fail
+///
+/// This suits trees backed by the filesystem, a database or the network,
+/// which would otherwise have to hide errors by returning no children.
+/// Such trees are printed with [`try_print_tree`] and [`try_write_tree`],
+/// which stop at the first error and return it along with the position of the failing item.
+///
+/// ```
+/// # use ptree::item::TryTreeItem;
+/// # use ptree::output::{try_write_tree_with, TryWriteError};
+/// # use ptree::print_config::{PrintConfig, StyleWhen, UTF_CHARS};
+/// # use ptree::Style;
+/// # use std::borrow::Cow;
+/// # use std::io;
+/// #[derive(Clone)]
+/// struct Countdown(u32);
+///
+/// impl TryTreeItem for Countdown {
+///     type Child = Self;
+///     type Error = String;
+///
+///     fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+///         style.paint_to(f, format_args!("{}", self.0))
+///     }
+///
+///     fn try_children(&self) -> Result<Cow<'_, [Self]>, String> {
+///         match self.0 {
+///             0 => Err("liftoff failed".to_string()),
+///             n => Ok(Cow::from(vec![Countdown(n - 1)])),
+///         }
+///     }
+/// }
+///
+/// let config = PrintConfig {
+///     characters: UTF_CHARS.into(),
+///     styled: StyleWhen::Never,
+///     ..PrintConfig::default()
+/// };
+/// let mut out = Vec::new();
+/// match try_write_tree_with(&Countdown(2), &mut out, &config) {
+///     Err(TryWriteError::Children { path, error }) => {
+///         assert_eq!(path, vec![0, 0]);
+///         assert_eq!(error, "liftoff failed");
+///     }
+///     _ => panic!("the error is returned"),
+/// }
+/// ```
+///
+/// [`TreeItem`]: trait.TreeItem.html
+/// [`try_print_tree`]: ../output/fn.try_print_tree.html
+/// [`try_write_tree`]: ../output/fn.try_write_tree.html
+///
+pub trait TryTreeItem: Clone {
+    ///
+    /// The type of this item's child items, which fail with the same error type
+    ///
+    type Child: TryTreeItem<Error = Self::Error>;
+
+    ///
+    /// The type of error returned when the children cannot be retrieved
+    ///
+    type Error;
+
+    ///
+    /// Write the item's own contents (without children) to `f`
+    ///
+    /// This works like [`TreeItem::write_self`].
+    ///
+    /// [`TreeItem::write_self`]: trait.TreeItem.html#tymethod.write_self
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()>;
+
+    ///
+    /// Retrieve the name of this item's class, if it has one
+    ///
+    /// This works like [`TreeItem::class`].
+    ///
+    /// [`TreeItem::class`]: trait.TreeItem.html#method.class
+    fn class(&self) -> Option<&str> {
+        None
+    }
+
+    ///
+    /// Retrieve the glyph replacing the branch character directly in front of this item, if it has one
+    ///
+    /// This works like [`TreeItem::branch_char`].
+    ///
+    /// [`TreeItem::branch_char`]: trait.TreeItem.html#method.branch_char
+    fn branch_char(&self) -> Option<&str> {
+        None
+    }
+
+    ///
+    /// Retrieve the key-value metadata of this item, if it has any
+    ///
+    /// This works like [`TreeItem::metadata`].
+    ///
+    /// [`TreeItem::metadata`]: trait.TreeItem.html#method.metadata
+    fn metadata(&self) -> Option<&BTreeMap<String, String>> {
+        None
+    }
+
+    ///
+    /// Retrieve a list of this item's children, or the error which prevented retrieving them
+    ///
+    fn try_children(&self) -> Result<Cow<'_, [Self::Child]>, Self::Error>;
 }
 
 ///
@@ -49,7 +217,7 @@ pub trait TreeItem: Clone {
 /// [`TreeItem`]: ../item/trait.TreeItem.html
 /// [`String`]: https://doc.rust-lang.org/std/string/struct.String.html
 /// [`TreeBuilder`]: ../builder/struct.TreeBuilder.html
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct StringItem {
     /// The item's own text, to be returned by [`write_self`]
     ///
@@ -57,28 +225,386 @@ pub struct StringItem {
     pub text: String,
     /// The list of item's children
     pub children: Vec<StringItem>,
+    /// Key-value metadata of the item, to be returned by [`metadata`]
+    ///
+    /// [`metadata`]: trait.TreeItem.html#method.metadata
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl StringItem {
+    ///
+    /// Creates an item with text `text`, the children `children` and no metadata
+    ///
+    pub fn new(text: String, children: Vec<StringItem>) -> StringItem {
+        StringItem {
+            text,
+            children,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    ///
+    /// Copies the tree `item` into a tree of `StringItem`s, down to `depth` levels below it
+    ///
+    /// The text of each item is written with the default style and stripped of escape sequences.
+    /// Its class is not kept, but its metadata is.
+    /// Items `depth` levels below `item` are copied without their children,
+    /// so printing the copy with [`PrintConfig::depth`] set to `depth` gives the same tree as printing `item`.
+    ///
+    /// [`PrintConfig::depth`]: ../print_config/struct.PrintConfig.html#structfield.depth
+    pub fn from_item<T: TreeItem>(item: &T, depth: u32) -> io::Result<StringItem> {
+        // The text is copied first, as items such as deduplicated graph nodes change it once their children are listed
+        let mut copy = StringItem::leaf(item)?;
+        copy.children = item
+            .children()
+            .iter()
+            .map(|child| match depth {
+                0 => StringItem::leaf(child),
+                _ => StringItem::from_item(child, depth - 1),
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(copy)
+    }
+
+    ///
+    /// Copies the tree `item` into a tree of `StringItem`s like [`from_item`], visiting children on multiple threads
+    ///
+    /// This is useful for trees whose `children()` are expensive to compute, such as those reading the file system.
+    /// The copy is then printed quickly, since printing itself has to visit items one by one.
+    ///
+    /// This function is only available with the `"parallel"` feature.
+    ///
+    /// [`from_item`]: #method.from_item
+    #[cfg(feature = "parallel")]
+    pub fn from_item_parallel<T: TreeItem<Child = T> + Sync>(item: &T, depth: u32) -> io::Result<StringItem> {
+        let mut copy = StringItem::leaf(item)?;
+        copy.children = item
+            .children()
+            .par_iter()
+            .map(|child| match depth {
+                0 => StringItem::leaf(child),
+                _ => StringItem::from_item_parallel(child, depth - 1),
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(copy)
+    }
+
+    ///
+    /// Sets the metadata entry `key` of this item to `value`, replacing any previous value
+    ///
+    /// ```
+    /// # use ptree::item::StringItem;
+    /// let item = StringItem::new("serde".to_string(), vec![])
+    ///     .with_metadata("version".to_string(), "1.0".to_string());
+    ///
+    /// assert_eq!(item.metadata["version"], "1.0");
+    /// ```
+    pub fn with_metadata(mut self, key: String, value: String) -> Self {
+        self.metadata.insert(key, value);
+        self
+    }
+
+    /// Copies `item` without its children
+    pub(crate) fn leaf<T: TreeItem>(item: &T) -> io::Result<StringItem> {
+        Ok(StringItem {
+            text: item_text(item)?,
+            metadata: item.metadata().cloned().unwrap_or_default(),
+            ..Default::default()
+        })
+    }
+
+    ///
+    /// Replaces the text of this item and all items below it with the result of `f`
+    ///
+    /// Like the other transformations, this takes and returns the tree, so transformations can be chained
+    /// before printing. Trees of other items can be transformed after copying them with [`from_item`]:
+    ///
+    /// ```
+    /// # use ptree::builder::TreeBuilder;
+    /// let tree = TreeBuilder::new("deps".to_string())
+    ///     .add_empty_child("serde".to_string())
+    ///     .add_empty_child("rand".to_string())
+    ///     .add_empty_child("tint".to_string())
+    ///     .build()
+    ///     .prune(|item| item.text.starts_with('t'))
+    ///     .sort()
+    ///     .map_labels(|text| text.to_uppercase());
+    ///
+    /// let texts: Vec<&str> = tree.children.iter().map(|child| &child.text[..]).collect();
+    /// assert_eq!(texts, vec!["RAND", "SERDE"]);
+    /// ```
+    ///
+    /// [`from_item`]: #method.from_item
+    pub fn map_labels<F: FnMut(&str) -> String>(mut self, mut f: F) -> Self {
+        self.map_labels_with(&mut f);
+        self
+    }
+
+    fn map_labels_with<F: FnMut(&str) -> String>(&mut self, f: &mut F) {
+        self.text = f(&self.text);
+        for child in &mut self.children {
+            child.map_labels_with(f);
+        }
+    }
+
+    ///
+    /// Removes the items below this one for which `predicate` returns `true`, together with their children
+    ///
+    /// Items are visited from the top, so the children of removed items are not visited.
+    ///
+    pub fn prune<P: FnMut(&StringItem) -> bool>(mut self, mut predicate: P) -> Self {
+        self.prune_with(&mut predicate);
+        self
+    }
+
+    fn prune_with<P: FnMut(&StringItem) -> bool>(&mut self, predicate: &mut P) {
+        self.children.retain(|child| !predicate(child));
+        for child in &mut self.children {
+            child.prune_with(predicate);
+        }
+    }
+
+    ///
+    /// Removes the items more than `depth` levels below this one
+    ///
+    /// With a `depth` of 0, only this item is kept.
+    ///
+    pub fn take_depth(mut self, depth: u32) -> Self {
+        if depth == 0 {
+            self.children.clear();
+        } else {
+            self.children = self
+                .children
+                .into_iter()
+                .map(|child| child.take_depth(depth - 1))
+                .collect();
+        }
+        self
+    }
+
+    ///
+    /// Sorts the children of this item and all items below it by their text
+    ///
+    pub fn sort(self) -> Self {
+        self.sort_by(|a, b| a.text.cmp(&b.text))
+    }
+
+    ///
+    /// Sorts the children of this item and all items below it with the comparison function `compare`
+    ///
+    /// The sort is stable, so children which compare equal keep their order.
+    ///
+    pub fn sort_by<F: FnMut(&StringItem, &StringItem) -> Ordering>(mut self, mut compare: F) -> Self {
+        self.sort_with(&mut compare);
+        self
+    }
+
+    fn sort_with<F: FnMut(&StringItem, &StringItem) -> Ordering>(&mut self, compare: &mut F) {
+        for child in &mut self.children {
+            child.sort_with(compare);
+        }
+        self.children.sort_by(|a, b| compare(a, b));
+    }
+
+    ///
+    /// Adds `subtree` as the last child of the item at `path`
+    ///
+    /// The path contains the indices of the item and its ancestors among their siblings,
+    /// starting below this item, which has an empty path.
+    ///
+    /// ### Panics
+    ///
+    /// Panics if there is no item at `path`.
+    ///
+    pub fn graft_at(mut self, path: &[usize], subtree: StringItem) -> Self {
+        let parent = path.iter().fold(&mut self, |item, &i| &mut item.children[i]);
+        parent.children.push(subtree);
+        self
+    }
+}
+
+///
+/// Returns the index path of the first item below `item` for which `predicate` returns `true`
+///
+/// Items are visited depth-first, in the order in which they are printed, starting with `item` itself.
+/// The path contains the indices of the item and its ancestors among their siblings, so `item` has an empty path.
+/// It can be used as the [`PrintConfig::highlight_path`]:
+///
+/// ```
+/// # use ptree::builder::TreeBuilder;
+/// # use ptree::item::path_to;
+/// let tree = TreeBuilder::new("src".to_string())
+///     .begin_child("output".to_string())
+///     .add_empty_child("mod.rs".to_string())
+///     .end_child()
+///     .begin_child("item".to_string())
+///     .add_empty_child("mod.rs".to_string())
+///     .add_empty_child("source.rs".to_string())
+///     .end_child()
+///     .build();
+///
+/// assert_eq!(path_to(&tree, |item| item.text == "source.rs"), Some(vec![1, 1]));
+/// assert_eq!(path_to(&tree, |item| item.text == "lib.rs"), None);
+/// ```
+///
+/// [`PrintConfig::highlight_path`]: ../print_config/struct.PrintConfig.html#structfield.highlight_path
+pub fn path_to<T: TreeItem<Child = T>, P: FnMut(&T) -> bool>(item: &T, mut predicate: P) -> Option<Vec<usize>> {
+    let mut path = vec![];
+    if find_path(item, &mut predicate, &mut path) {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+fn find_path<T: TreeItem<Child = T>, P: FnMut(&T) -> bool>(item: &T, predicate: &mut P, path: &mut Vec<usize>) -> bool {
+    if predicate(item) {
+        return true;
+    }
+    for (i, child) in item.children().iter().enumerate() {
+        path.push(i);
+        if find_path(child, predicate, path) {
+            return true;
+        }
+        path.pop();
+    }
+    false
 }
 
 impl TreeItem for StringItem {
     type Child = Self;
 
     fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
-        write!(f, "{}", style.paint(&self.text))
+        style.paint_to(f, format_args!("{}", self.text))
+    }
+
+    fn metadata(&self) -> Option<&BTreeMap<String, String>> {
+        Some(&self.metadata)
     }
 
-    fn children(&self) -> Cow<[Self::Child]> {
+    fn children(&self) -> Cow<'_, [Self::Child]> {
         Cow::from(&self.children[..])
     }
 }
 
+///
+/// A tree or graph which can be printed starting at any of its nodes
+///
+/// Implementing `TreeSource` is an alternative to implementing [`TreeItem`] when nodes are identified by ids,
+/// such as indices into a graph structure.
+/// Only the label and the children of a node have to be provided,
+/// and a [`SourceItem`] adapts the node with a given id into a [`TreeItem`].
+///
+/// ```
+/// # use std::borrow::Cow;
+/// # use ptree::item::{SourceItem, TreeSource};
+/// # use ptree::output::print_tree;
+/// struct Tasks {
+///     names: Vec<&'static str>,
+///     subtasks: Vec<Vec<usize>>,
+/// }
+///
+/// impl TreeSource for Tasks {
+///     type Id = usize;
+///
+///     fn label(&self, id: &usize) -> Cow<'_, str> {
+///         Cow::from(self.names[*id])
+///     }
+///
+///     fn children(&self, id: &usize) -> Cow<'_, [usize]> {
+///         Cow::from(&self.subtasks[*id][..])
+///     }
+/// }
+///
+/// let tasks = Tasks {
+///     names: vec!["release", "build", "test"],
+///     subtasks: vec![vec![1, 2], vec![], vec![1]],
+/// };
+/// print_tree(&SourceItem::new(&tasks, 0)).unwrap();
+/// ```
+///
+/// [`TreeItem`]: trait.TreeItem.html
+/// [`SourceItem`]: struct.SourceItem.html
+pub trait TreeSource {
+    ///
+    /// The type identifying a node
+    ///
+    type Id: Clone;
+
+    ///
+    /// Retrieve the text of the node `id`
+    ///
+    fn label(&self, id: &Self::Id) -> Cow<'_, str>;
+
+    ///
+    /// Retrieve the ids of the children of the node `id`
+    ///
+    fn children(&self, id: &Self::Id) -> Cow<'_, [Self::Id]>;
+}
+
+///
+/// Adapter implementing [`TreeItem`] for a node of a [`TreeSource`]
+///
+/// [`TreeItem`]: trait.TreeItem.html
+/// [`TreeSource`]: trait.TreeSource.html
+#[derive(Debug)]
+pub struct SourceItem<'a, S: TreeSource + 'a> {
+    source: &'a S,
+    id: S::Id,
+}
+
+impl<'a, S: TreeSource> SourceItem<'a, S> {
+    ///
+    /// Creates the item for the node `id` of `source`
+    ///
+    pub fn new(source: &'a S, id: S::Id) -> Self {
+        SourceItem { source, id }
+    }
+
+    ///
+    /// Returns the id of the node represented by this item
+    ///
+    pub fn id(&self) -> &S::Id {
+        &self.id
+    }
+}
+
+impl<'a, S: TreeSource> Clone for SourceItem<'a, S> {
+    fn clone(&self) -> Self {
+        SourceItem {
+            source: self.source,
+            id: self.id.clone(),
+        }
+    }
+}
+
+impl<'a, S: TreeSource> TreeItem for SourceItem<'a, S> {
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        style.paint_to(f, format_args!("{}", self.source.label(&self.id)))
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        let v: Vec<_> = self
+            .source
+            .children(&self.id)
+            .iter()
+            .map(|id| SourceItem::new(self.source, id.clone()))
+            .collect();
+        Cow::from(v)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
     use std::str::from_utf8;
     use super::*;
 
+    use builder::TreeBuilder;
     use output::write_tree_with;
-    use print_config::PrintConfig;
+    use print_config::{PrintConfig, UTF_CHARS};
 
     #[test]
     fn small_item_output() {
@@ -88,32 +614,25 @@ mod tests {
                 StringItem {
                     text: "quickcheck".to_string(),
                     children: vec![
-                        StringItem {
-                            text: "libc".to_string(),
-                            children: vec![],
-                        },
+                        StringItem::new("libc".to_string(), vec![]),
                         StringItem {
                             text: "rand".to_string(),
-                            children: vec![
-                                StringItem {
-                                    text: "libc".to_string(),
-                                    children: vec![],
-                                },
-                            ],
+                            children: vec![StringItem::new("libc".to_string(), vec![])],
+                            ..Default::default()
                         },
                     ],
+                    ..Default::default()
                 },
-                StringItem {
-                    text: "fixedbitset".to_string(),
-                    children: vec![],
-                },
+                StringItem::new("fixedbitset".to_string(), vec![]),
             ],
+            ..Default::default()
         };
 
         let config = PrintConfig {
             indent: 4,
             leaf: Style::default(),
             branch: Style::default(),
+            characters: UTF_CHARS.into(),
             ..PrintConfig::default()
         };
 
@@ -132,4 +651,128 @@ mod tests {
                         ";
         assert_eq!(from_utf8(&data).unwrap(), expected);
     }
+
+    #[test]
+    fn source_item_output() {
+        use std::collections::BTreeMap;
+
+        struct Deps(BTreeMap<&'static str, Vec<&'static str>>);
+
+        impl TreeSource for Deps {
+            type Id = &'static str;
+
+            fn label(&self, id: &Self::Id) -> Cow<'_, str> {
+                Cow::from(*id)
+            }
+
+            fn children(&self, id: &Self::Id) -> Cow<'_, [Self::Id]> {
+                self.0.get(id).map_or(Cow::from(vec![]), |c| Cow::from(&c[..]))
+            }
+        }
+
+        let mut deps = BTreeMap::new();
+        deps.insert("petgraph", vec!["quickcheck", "fixedbitset"]);
+        deps.insert("quickcheck", vec!["rand"]);
+        let deps = Deps(deps);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&SourceItem::new(&deps, "petgraph"), &mut cursor, &config).unwrap();
+
+        let expected = "petgraph\n├─ quickcheck\n│  └─ rand\n└─ fixedbitset\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn string_item_from_item() {
+        fn item(text: &str, children: Vec<StringItem>) -> StringItem {
+            StringItem::new(text.to_string(), children)
+        }
+
+        let deps = item(
+            "petgraph",
+            vec![
+                item("quickcheck", vec![item("rand", vec![item("libc", vec![])])]),
+                item("fixedbitset", vec![]),
+            ],
+        );
+        let config = PrintConfig {
+            depth: 1,
+            depth_marker: true,
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        let output = |item: &StringItem| {
+            let mut out = Vec::new();
+            write_tree_with(item, &mut out, &config).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let copy = StringItem::from_item(&deps, 1).unwrap();
+        assert!(copy.children[0].children[0].children.is_empty());
+        assert_eq!(output(&copy), output(&deps));
+        assert_eq!(output(&copy), "petgraph\n├─ quickcheck\n│  └─ …\n└─ fixedbitset\n");
+
+        #[cfg(feature = "parallel")]
+        {
+            let copy = StringItem::from_item_parallel(&deps, 1).unwrap();
+            assert_eq!(output(&copy), output(&deps));
+        }
+    }
+
+    #[test]
+    fn string_item_transformations() {
+        let tree = TreeBuilder::new("root".to_string())
+            .begin_child("b".to_string())
+            .add_empty_child("b2".to_string())
+            .add_empty_child("b1".to_string())
+            .end_child()
+            .begin_child("a".to_string())
+            .begin_child("a1".to_string())
+            .add_empty_child("a1x".to_string())
+            .end_child()
+            .end_child()
+            .add_empty_child("tmp".to_string())
+            .build();
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let b3 = StringItem::new("b3".to_string(), vec![]);
+        let tree = tree
+            .prune(|item| item.text == "tmp")
+            .take_depth(2)
+            .sort()
+            .graft_at(&[1], b3)
+            .map_labels(|text| format!("<{}>", text));
+
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(
+            from_utf8(&out).unwrap(),
+            "\
+             <root>\n\
+             ├─ <a>\n\
+             │  └─ <a1>\n\
+             └─ <b>\n   \
+                ├─ <b1>\n   \
+                ├─ <b2>\n   \
+                └─ <b3>\n"
+        );
+
+        let tree = tree.sort_by(|a, b| b.text.cmp(&a.text));
+        assert_eq!(tree.children[0].text, "<b>");
+        assert_eq!(tree.children[0].children[0].text, "<b3>");
+    }
 }
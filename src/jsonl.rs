@@ -0,0 +1,167 @@
+//!
+//! Output of trees as JSON Lines, for consumption by other programs
+//!
+//! Every item is written as a JSON object on its own line, in the order in which the items are printed.
+//! The object contains the `depth` of the item, with 0 for the root, its `text` without escape sequences,
+//! and whether it is the `last` child of its parent. The `class` of the item is included if it has one.
+//!
+//! Children which are not printed because of [`PrintConfig::max_children`], or because of [`PrintConfig::depth`]
+//! if [`PrintConfig::depth_marker`] is set, are replaced by an object with the number of `omitted` items
+//! instead of the text.
+//!
+//! ```
+//! # use ptree::builder::TreeBuilder;
+//! # use ptree::jsonl::write_tree_jsonl;
+//! # use ptree::PrintConfig;
+//! let tree = TreeBuilder::new("root".to_string())
+//!     .begin_child("a \"quoted\" name".to_string())
+//!     .add_empty_child("b".to_string())
+//!     .end_child()
+//!     .add_empty_child("c".to_string())
+//!     .build();
+//! let config = PrintConfig {
+//!     depth: 1,
+//!     depth_marker: true,
+//!     ..PrintConfig::default()
+//! };
+//!
+//! let mut out = Vec::new();
+//! write_tree_jsonl(&tree, &mut out, &config).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(out).unwrap(),
+//!     "{\"depth\":0,\"text\":\"root\",\"last\":true}\n\
+//!      {\"depth\":1,\"text\":\"a \\\"quoted\\\" name\",\"last\":false}\n\
+//!      {\"depth\":2,\"omitted\":1,\"last\":true}\n\
+//!      {\"depth\":1,\"text\":\"c\",\"last\":true}\n"
+//! );
+//! ```
+//!
+//! [`PrintConfig::depth`]: ../print_config/struct.PrintConfig.html#structfield.depth
+//! [`PrintConfig::depth_marker`]: ../print_config/struct.PrintConfig.html#structfield.depth_marker
+//! [`PrintConfig::max_children`]: ../print_config/struct.PrintConfig.html#structfield.max_children
+//!
+
+use item::TreeItem;
+use output::item_text;
+use print_config::PrintConfig;
+
+use std::io::{self, Write};
+
+/// Writes `text` as a JSON string, with quotes and escapes
+fn write_json_string<W: io::Write>(f: &mut W, text: &str) -> io::Result<()> {
+    f.write_all(b"\"")?;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        let escaped = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            c if (c as u32) < 0x20 => "",
+            _ => continue,
+        };
+        f.write_all(&text.as_bytes()[start..i])?;
+        if escaped.is_empty() {
+            write!(f, "\\u{:04x}", c as u32)?;
+        } else {
+            f.write_all(escaped.as_bytes())?;
+        }
+        start = i + c.len_utf8();
+    }
+    f.write_all(&text.as_bytes()[start..])?;
+    f.write_all(b"\"")
+}
+
+/// Writes the object of `item` at `depth`, followed by the objects of its children
+fn write_item<T: TreeItem, W: io::Write>(
+    item: &T,
+    f: &mut W,
+    config: &PrintConfig,
+    depth: u32,
+    last: bool,
+) -> io::Result<()> {
+    write!(f, "{{\"depth\":{},\"text\":", depth)?;
+    write_json_string(f, &item_text(item)?)?;
+    if let Some(class) = item.class() {
+        f.write_all(b",\"class\":")?;
+        write_json_string(f, class)?;
+    }
+    writeln!(f, ",\"last\":{}}}", last)?;
+
+    if depth >= config.depth && !config.depth_marker {
+        return Ok(());
+    }
+    let children = item.children();
+    let shown = if depth < config.depth {
+        config
+            .max_children
+            .map_or(children.len(), |max| max.min(children.len()))
+    } else {
+        0
+    };
+    let omitted = children.len() - shown;
+
+    for (i, child) in children[..shown].iter().enumerate() {
+        write_item(child, f, config, depth + 1, omitted == 0 && i + 1 == shown)?;
+    }
+    if omitted > 0 {
+        writeln!(f, "{{\"depth\":{},\"omitted\":{},\"last\":true}}", depth + 1, omitted)?;
+    }
+    Ok(())
+}
+
+/// Write the tree `item` to writer `f` as JSON Lines, with one object for each item
+///
+/// Only the `depth`, `depth_marker` and `max_children` fields of `config` are used, as the output is never styled.
+pub fn write_tree_jsonl<T: TreeItem, W: io::Write>(item: &T, f: W, config: &PrintConfig) -> io::Result<()> {
+    let mut out = io::BufWriter::new(f);
+    write_item(item, &mut out, config, 0, true)?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use item::StringItem;
+
+    fn json(text: &str) -> String {
+        let mut out = Vec::new();
+        write_json_string(&mut out, text).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn string_escapes() {
+        assert_eq!(json("plain"), "\"plain\"");
+        assert_eq!(json("a\\b\t\"c\"\n"), "\"a\\\\b\\t\\\"c\\\"\\n\"");
+        assert_eq!(json("\u{1}ž"), "\"\\u0001ž\"");
+    }
+
+    #[test]
+    fn omitted_children() {
+        let tree = StringItem {
+            text: "\x1b[1mroot\x1b[0m".to_string(),
+            children: vec!["a", "b", "c"]
+                .into_iter()
+                .map(|text| StringItem::new(text.to_string(), vec![]))
+                .collect(),
+            ..Default::default()
+        };
+        let config = PrintConfig {
+            max_children: Some(2),
+            ..PrintConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_tree_jsonl(&tree, &mut out, &config).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"depth\":0,\"text\":\"root\",\"last\":true}\n\
+             {\"depth\":1,\"text\":\"a\",\"last\":false}\n\
+             {\"depth\":1,\"text\":\"b\",\"last\":false}\n\
+             {\"depth\":1,\"omitted\":1,\"last\":true}\n"
+        );
+    }
+}
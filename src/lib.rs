@@ -61,6 +61,8 @@
 //! ```
 //!
 //! See [`PrintConfig`] for the list of all configuration options.
+//! The default characters and color depth adapt to the capabilities of the terminal,
+//! as detected by the functions in the [`term`] module.
 //!
 //! ## Advanced usage
 //!
@@ -81,7 +83,7 @@
 //!     fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
 //!         write!(f, "{}", style.paint("My custom tree"))
 //!     }
-//!     fn children(&self) -> Cow<[Self::Child]> {
+//!     fn children(&self) -> Cow<'_, [Self::Child]> {
 //!         Cow::from(vec![])
 //!     }
 //! }
@@ -173,34 +175,99 @@
 //! # }
 //! ```
 //!
+//! ### Styling backends
+//!
+//! By default, text is styled with ANSI escape codes produced by `ansi_term` (the `"ansi"` feature).
+//! As `ansi_term` is no longer maintained, the `"nu-ansi-term"` feature selects its maintained fork instead.
+//! Applications which already use `crossterm` can enable the `"crossterm"` feature,
+//! so that `ptree` uses the same library for styling and terminal detection.
+//!
+//! The backend is selected at compile time.
+//! If more than one of these features is enabled, `"crossterm"` takes precedence over `"nu-ansi-term"`,
+//! which in turn takes precedence over `"ansi"`.
+//!
+//! On Windows 10 and later, [`print_tree`] and [`print_tree_with`] enable the processing of
+//! ANSI escape codes in the console before printing.
+//! Older Windows consoles do not process ANSI escape codes at all.
+//! With the `"termcolor"` feature enabled, [`print_tree`] and [`print_tree_with`] apply styles
+//! through the console API instead, and [`write_tree_with_color`] can be used to write a styled
+//! tree to any `termcolor::WriteColor` implementation.
+//!
 //! [`config_dir`]: https://docs.rs/directories/1.0.1/directories/struct.BaseDirs.html#method.config_dir
 //! [`TreeItem`]: item/trait.TreeItem.html
 //! [`print_tree`]: output/fn.print_tree.html
 //! [`print_tree_with`]: output/fn.print_tree_with.html
 //! [`write_tree`]: output/fn.write_tree.html
 //! [`write_tree_with`]: output/fn.write_tree_with.html
+//! [`write_tree_with_color`]: output/fn.write_tree_with_color.html
 //! [`PrintConfig::styled`]: print_config/struct.PrintConfig.html#structfield.styled
 //! [`Always`]: print_config/struct.PrintConfig.html#structfield.styled
 //! [`PrintConfig`]: print_config/struct.PrintConfig.html
+//! [`term`]: term/index.html
 
 #[cfg(feature = "petgraph")]
 extern crate petgraph;
+#[cfg(feature = "daggy")]
+extern crate daggy;
 
 #[cfg(feature = "ansi")]
 extern crate ansi_term;
-#[cfg(feature = "ansi")]
+#[cfg(feature = "nu-ansi-term")]
+extern crate nu_ansi_term;
+#[cfg(all(
+    any(feature = "ansi", feature = "nu-ansi-term", feature = "termcolor"),
+    not(target_arch = "wasm32")
+))]
 extern crate atty;
-#[cfg(feature = "ansi")]
-extern crate tint;
+#[cfg(feature = "termcolor")]
+extern crate termcolor;
+#[cfg(feature = "crossterm")]
+extern crate crossterm;
 
 #[cfg(feature = "value")]
 extern crate serde_value;
+#[cfg(feature = "json")]
+extern crate serde_json;
 
 #[cfg(feature = "conf")]
 extern crate config;
-#[cfg(feature = "conf")]
+#[cfg(all(feature = "conf", not(target_arch = "wasm32")))]
 extern crate directories;
+#[cfg(feature = "import")]
+extern crate roxmltree;
+#[cfg(feature = "import")]
+extern crate csv;
+#[cfg(feature = "ignore")]
+extern crate ignore;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "archive")]
+extern crate flate2;
+#[cfg(feature = "archive")]
+extern crate tar;
+#[cfg(feature = "archive")]
+extern crate zip;
+#[cfg(all(feature = "proc", not(target_os = "linux")))]
+extern crate sysinfo;
+#[cfg(feature = "cargo")]
+extern crate cargo_metadata;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "tracing")]
+extern crate tracing_subscriber;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "icu")]
+extern crate icu_collator;
+#[cfg(feature = "icu")]
+extern crate icu_locid;
+#[cfg(feature = "git")]
+extern crate git2;
 extern crate serde;
+extern crate tint;
+extern crate unicode_width;
+#[cfg(all(windows, any(feature = "ansi", feature = "nu-ansi-term")))]
+extern crate winapi;
 
 ///
 /// Contains the `TreeItem` trait
@@ -222,38 +289,163 @@ pub mod print_config;
 ///
 pub mod style;
 
+///
+/// Semantic style presets, such as `ERROR` and `WARNING`
+///
+pub mod styles;
+
 ///
 /// Functions for printing trees to standard output or to custom writers
 ///
 pub mod output;
 
+///
+/// Detection of terminal color and unicode support
+///
+pub mod term;
+
+///
+/// Printing of directory trees, with optional metadata columns
+///
+pub mod fs;
+
+///
+/// Rendering of trees as HTML, for displaying them on web pages
+///
+pub mod html;
+
+///
+/// Output of trees as JSON Lines, with one object for each item
+///
+pub mod jsonl;
+
+///
+/// Comparison of trees, with edit scripts and annotated trees of the differences
+///
+pub mod diff;
+
+///
+/// Searching trees for the index paths of matching items
+///
+pub mod search;
+
+///
+/// Natural and locale-aware orderings of names, for sorting items
+///
+pub mod collate;
+
+///
+/// Rendering of trees with a fixed configuration and comparison with expected text, for snapshot tests
+///
+#[macro_use]
+pub mod testing;
+
+#[cfg(feature = "archive")]
+///
+/// Printing of the contents of tar and zip archives
+///
+/// This module is enabled by the `"archive"` feature.
+///
+pub mod archive;
+
+#[cfg(feature = "proc")]
+///
+/// Printing of process trees, like `pstree`
+///
+/// This module is enabled by the `"proc"` feature.
+///
+pub mod process;
+
+#[cfg(feature = "cargo")]
+///
+/// Printing of cargo dependency trees, like `cargo tree`
+///
+/// This module is enabled by the `"cargo"` feature.
+///
+pub mod cargo;
+
+#[cfg(feature = "git")]
+///
+/// Printing of the directory trees of commits and of the branch and tag hierarchy of git repositories
+///
+/// This module is enabled by the `"git"` feature.
+///
+pub mod git;
+
+#[cfg(feature = "tracing")]
+///
+/// Rendering of `tracing` spans as trees, with a layer for `tracing-subscriber`
+///
+/// This module is enabled by the `"tracing"` feature.
+///
+pub mod trace;
+
+#[cfg(feature = "tui")]
+///
+/// Interactive viewer for trees in the terminal, with navigation, expansion and search
+///
+/// This module is enabled by the `"tui"` feature.
+///
+pub mod tui;
+
+#[cfg(feature = "ffi")]
+///
+/// C interface for building trees and rendering them into buffers
+///
+/// This module is enabled by the `"ffi"` feature.
+///
+pub mod ffi;
+
 #[cfg(feature = "petgraph")]
 ///
-/// Implementation of `TreeItem` for [`petgraph::Graph`]
+/// Implementation of `TreeItem` for [`petgraph::Graph`], [`petgraph::StableGraph`] and [`petgraph::GraphMap`]
 ///
 /// This module is enabled by the `"petgraph"` feature.
+/// The `"daggy"` feature adds support for [`daggy::Dag`].
 ///
-/// [`petgraph::Graph`]: https://docs.rs/petgraph/0.4.13/petgraph/graph/struct.Graph.html
+/// [`daggy::Dag`]: https://docs.rs/daggy/0.8/daggy/struct.Dag.html
+/// [`petgraph::Graph`]: https://docs.rs/petgraph/0.6/petgraph/graph/struct.Graph.html
+/// [`petgraph::StableGraph`]: https://docs.rs/petgraph/0.6/petgraph/stable_graph/struct.StableGraph.html
+/// [`petgraph::GraphMap`]: https://docs.rs/petgraph/0.6/petgraph/graphmap/struct.GraphMap.html
 pub mod graph;
 
-#[cfg(feature = "value")]
+#[cfg(any(feature = "value", feature = "json"))]
 ///
 /// Implementation of `TreeItem` for [`serde_value::Value`], allowing easy printing of
 /// deserialized structures from a variety of formats.
 ///
-/// This module is enabled by the `"serde"` feature.
+/// This module is enabled by the `"value"` feature.
+/// The `"json"` feature enables it with an implementation for [`serde_json::Value`] instead,
+/// for applications which only print JSON and do not need `serde_value`.
 ///
 /// [`TreeItem`]: item/trait.TreeItem.html
+/// [`serde_value::Value`]: https://docs.rs/serde-value/0.7/serde_value/enum.Value.html
+/// [`serde_json::Value`]: https://docs.rs/serde_json/1/serde_json/enum.Value.html
 pub mod value;
 
+#[cfg(feature = "import")]
+///
+/// Conversion of DOT, GraphML and CSV files into `StringItem` trees
+///
+/// This module is enabled by the `"import"` feature.
+///
+pub mod import;
+
 pub use builder::TreeBuilder;
 pub use item::TreeItem;
-pub use output::{print_tree, print_tree_with, write_tree, write_tree_with};
+pub use output::{print_tree, print_tree_with, write_tree, write_tree_chunked, write_tree_with};
+#[cfg(feature = "termcolor")]
+pub use output::write_tree_with_color;
 pub use print_config::{IndentChars, PrintConfig};
-pub use style::{Color, Style};
+pub use search::{find_by_label, search};
+pub use style::{Color, ColorDepth, Style, StyledText};
+#[cfg(any(feature = "value", feature = "json"))]
+pub use value::{print_serializable, write_serializable_with};
 
 #[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 #[cfg(test)]
 extern crate serde_any;
+#[cfg(test)]
+extern crate tempfile;
@@ -0,0 +1,258 @@
+//!
+//! Command line tool printing data files and directories as trees
+//!
+//! Files are read with `serde_any`, which detects the format from the file extension,
+//! such as `.json`, `.toml` or `.yaml`. Directories are printed like with `tree`.
+//!
+//! This binary is built with the `"cli"` feature.
+//!
+
+extern crate ptree;
+extern crate serde_any;
+extern crate serde_value;
+extern crate structopt;
+
+use structopt::StructOpt;
+
+use ptree::fs::{print_dir_with, write_dir_with, Column, FsConfig, Icons};
+use ptree::print_config::StyleWhen;
+use ptree::style::Style;
+use ptree::value::{print_value_tree_with, write_value_tree_with, PathQuery, ValueConfig};
+use ptree::{IndentChars, PrintConfig};
+
+use std::fs::File;
+use std::path::PathBuf;
+use std::process;
+
+#[derive(Clone, Debug, StructOpt)]
+#[structopt(name = "ptree", about = "Print data files and directories as trees")]
+struct Opt {
+    /// Data file in a format such as JSON, TOML or YAML, or a directory
+    #[structopt(name = "PATH", parse(from_os_str))]
+    path: PathBuf,
+
+    /// Maximum depth of printed items
+    #[structopt(short = "d", long = "depth")]
+    depth: Option<u32>,
+
+    /// Maximum number of printed children of each item
+    #[structopt(short = "m", long = "max-children")]
+    max_children: Option<usize>,
+
+    /// Writes the tree to a file instead of standard output
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    output: Option<PathBuf>,
+
+    /// Style of leaves, such as "bold green"
+    #[structopt(short = "l", long = "leaf-style")]
+    leaf_style: Option<Style>,
+
+    /// Style of branches, such as "dimmed"
+    #[structopt(short = "b", long = "branch-style")]
+    branch_style: Option<Style>,
+
+    /// Characters of branches: utf, utf-bold, utf-double, utf-dashed, ascii or ascii-plus
+    #[structopt(short = "c", long = "character-set", parse(try_from_str = chars_from_str))]
+    character_set: Option<IndentChars>,
+
+    /// Width of each level of indentation
+    #[structopt(short = "i", long = "indent")]
+    indent: Option<usize>,
+
+    /// Spaces between branches and labels
+    #[structopt(long = "padding")]
+    padding: Option<usize>,
+
+    /// When output is styled: always, never or auto
+    #[structopt(long = "color", parse(try_from_str = style_when_from_str))]
+    color: Option<StyleWhen>,
+
+    /// Prints the type of each value, such as "(number)"
+    #[structopt(short = "t", long = "type-hints")]
+    type_hints: bool,
+
+    /// Maximum number of characters of printed values
+    #[structopt(long = "max-value-length")]
+    max_value_length: Option<usize>,
+
+    /// Prints only the values matching a path, such as "servers.*.port"
+    #[structopt(short = "q", long = "query")]
+    query: Option<String>,
+
+    /// Skips hidden files in directories
+    #[structopt(long = "no-hidden")]
+    no_hidden: bool,
+
+    /// Prints the permissions of files
+    #[structopt(short = "p", long = "permissions")]
+    permissions: bool,
+
+    /// Prints the owners and groups of files
+    #[structopt(short = "u", long = "owner")]
+    owner: bool,
+
+    /// Prints the sizes of files
+    #[structopt(short = "s", long = "size")]
+    size: bool,
+
+    /// Prints the total sizes of directories
+    #[structopt(long = "du")]
+    total_size: bool,
+
+    /// Prints the times of last modification of files
+    #[structopt(short = "D", long = "modified")]
+    modified: bool,
+
+    /// Prints sizes with units, such as "4.0K"
+    #[structopt(long = "human")]
+    human: bool,
+
+    /// Expands symbolic links to directories
+    #[structopt(short = "L", long = "follow-links")]
+    follow_links: bool,
+
+    /// Maximum number of listed entries of each directory
+    #[structopt(long = "max-entries")]
+    max_entries: Option<usize>,
+
+    /// Lists only files matching a glob pattern, such as "*.rs"
+    #[structopt(short = "P", long = "include")]
+    include: Vec<String>,
+
+    /// Skips entries matching a glob pattern, such as "target"
+    #[structopt(short = "I", long = "exclude")]
+    exclude: Vec<String>,
+
+    /// Icons before file names: none, emoji or nerd
+    #[structopt(long = "icons", parse(try_from_str = icons_from_str))]
+    icons: Option<Icons>,
+
+    /// Skips files ignored by git
+    #[cfg(feature = "ignore")]
+    #[structopt(long = "gitignore")]
+    git_ignore: bool,
+}
+
+fn chars_from_str(s: &str) -> Result<IndentChars, String> {
+    s.parse().map_err(|_| format!("unknown character set '{}'", s))
+}
+
+fn style_when_from_str(s: &str) -> Result<StyleWhen, String> {
+    match &s.to_lowercase()[..] {
+        "always" => Ok(StyleWhen::Always),
+        "never" => Ok(StyleWhen::Never),
+        "auto" | "tty" => Ok(StyleWhen::Tty),
+        _ => Err(format!("expected always, never or auto, found '{}'", s)),
+    }
+}
+
+fn icons_from_str(s: &str) -> Result<Icons, String> {
+    match &s.to_lowercase()[..] {
+        "none" => Ok(Icons::None),
+        "emoji" => Ok(Icons::Emoji),
+        "nerd" | "nerd-font" => Ok(Icons::NerdFont),
+        _ => Err(format!("expected none, emoji or nerd, found '{}'", s)),
+    }
+}
+
+impl Opt {
+    fn print_config(&self) -> PrintConfig {
+        let mut config = if self.output.is_some() {
+            PrintConfig::default()
+        } else {
+            PrintConfig::for_terminal()
+        };
+        if let Some(depth) = self.depth {
+            config.depth = depth;
+        }
+        if let Some(max_children) = self.max_children {
+            config.max_children = Some(max_children);
+        }
+        if let Some(ref branch) = self.branch_style {
+            config.branch = branch.clone();
+        }
+        if let Some(ref leaf) = self.leaf_style {
+            config.leaf = leaf.clone();
+        }
+        if let Some(ref characters) = self.character_set {
+            config.characters = characters.clone();
+        }
+        if let Some(indent) = self.indent {
+            config.indent = indent;
+        }
+        if let Some(padding) = self.padding {
+            config.padding = padding;
+        }
+        if let Some(color) = self.color {
+            config.styled = color;
+        }
+        config
+    }
+
+    fn value_config(&self) -> ValueConfig {
+        ValueConfig {
+            root_label: self.path.display().to_string(),
+            type_hints: self.type_hints,
+            max_value_length: self.max_value_length,
+            query: self.query.as_ref().map(|query| PathQuery::new(query)),
+            ..ValueConfig::default()
+        }
+    }
+
+    fn fs_config(&self) -> FsConfig {
+        let columns = [
+            (self.permissions, Column::Permissions),
+            (self.owner, Column::Owner),
+            (self.owner, Column::Group),
+            (self.size, Column::Size),
+            (self.total_size, Column::TotalSize),
+            (self.modified, Column::Modified),
+        ];
+        FsConfig {
+            columns: columns.iter().filter(|c| c.0).map(|c| c.1).collect(),
+            human_sizes: self.human,
+            show_hidden: !self.no_hidden,
+            follow_links: self.follow_links,
+            max_entries_per_dir: self.max_entries,
+            include: self.include.clone(),
+            exclude: self.exclude.clone(),
+            icons: self.icons.unwrap_or_default(),
+            #[cfg(feature = "ignore")]
+            git_ignore: self.git_ignore,
+            ..FsConfig::default()
+        }
+    }
+}
+
+fn run(opt: &Opt) -> Result<(), String> {
+    let print_config = opt.print_config();
+    let value: Option<serde_value::Value> = if opt.path.is_dir() {
+        None
+    } else {
+        let value =
+            serde_any::from_file(&opt.path).map_err(|e| format!("cannot read {}: {}", opt.path.display(), e))?;
+        Some(value)
+    };
+
+    // Standard output is written with the print functions, which style output only on terminals
+    let result = match (&opt.output, value) {
+        (Some(output), value) => {
+            let file = File::create(output).map_err(|e| format!("cannot create {}: {}", output.display(), e))?;
+            match value {
+                Some(value) => write_value_tree_with(&value, file, &print_config, &opt.value_config()),
+                None => write_dir_with(&opt.path, file, &print_config, &opt.fs_config()),
+            }
+        }
+        (None, Some(value)) => print_value_tree_with(&value, &print_config, &opt.value_config()),
+        (None, None) => print_dir_with(&opt.path, &print_config, &opt.fs_config()),
+    };
+    result.map_err(|e| e.to_string())
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    if let Err(message) = run(&opt) {
+        eprintln!("ptree: {}", message);
+        process::exit(1);
+    }
+}
@@ -1,152 +1,2075 @@
 use item::*;
 use print_config::*;
 use style::*;
+use term;
 
-use std::io;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+use std::mem;
+use std::ops::Range;
+use std::rc::Rc;
+use std::str;
 
-struct Indent {
+use unicode_width::UnicodeWidthStr;
+
+#[cfg(feature = "tokio")]
+use std::future::Future;
+#[cfg(feature = "tokio")]
+use std::pin::Pin;
+#[cfg(feature = "tokio")]
+use std::task::{Context, Poll};
+
+#[cfg(feature = "termcolor")]
+use termcolor::{ColorChoice, StandardStream, WriteColor};
+#[cfg(feature = "tokio")]
+use tokio::io::AsyncWrite;
+
+///
+/// Branch characters added to the prefix of an item, depending on whether it is the last child of its parent
+///
+pub(crate) struct Indent {
     pub regular_prefix: String,
     pub child_prefix: String,
     pub last_regular_prefix: String,
     pub last_child_prefix: String,
+    /// Padding between the branch characters and the item text, included at the end of the regular prefixes
+    pub item_pad: String,
+}
+
+impl Indent {
+    pub fn from_config(config: &PrintConfig) -> Indent {
+        Self::from_characters_and_padding(config.indent, config.padding, &config.characters)
+    }
+
+    #[allow(dead_code)]
+    pub fn from_characters(indent_size: usize, characters: &IndentChars) -> Indent {
+        Self::from_characters_and_padding(indent_size, 1, characters)
+    }
+
+    ///
+    /// Builds the prefixes from `characters`, each `indent_size` columns wide, ending in `padding` empty characters
+    ///
+    /// Widths are measured in terminal columns, so characters wider than one column, such as emoji,
+    /// are repeated fewer times. Columns which cannot be filled with a whole character are filled with spaces.
+    /// If the widest character does not fit in `indent_size` columns along with the padding,
+    /// all prefixes are widened to fit it, so the columns of children stay aligned.
+    ///
+    pub fn from_characters_and_padding(indent_size: usize, padding: usize, characters: &IndentChars) -> Indent {
+        let item_pad = characters.empty.repeat(padding);
+        let leads = [
+            &characters.down_and_right,
+            &characters.down,
+            &characters.turn_right,
+            &characters.empty,
+        ];
+        let widest = leads.iter().map(|lead| text_width(lead)).max().unwrap_or(0);
+        let size = indent_size.max(widest + text_width(&item_pad));
+
+        // The lead character, followed by as many fill characters as fit before the padding
+        let prefix = |lead: &str, fill: &str| {
+            let columns = size - text_width(lead) - text_width(&item_pad);
+            let (count, rest) = match text_width(fill) {
+                0 => (0, columns),
+                width => (columns / width, columns % width),
+            };
+            format!("{}{}{:rest$}{}", lead, fill.repeat(count), "", item_pad, rest = rest)
+        };
+
+        Indent {
+            regular_prefix: prefix(&characters.down_and_right, &characters.right),
+            child_prefix: prefix(&characters.down, &characters.empty),
+            last_regular_prefix: prefix(&characters.turn_right, &characters.right),
+            last_child_prefix: prefix(&characters.empty, &characters.empty),
+            item_pad,
+        }
+    }
+}
+
+///
+/// Output destination which knows how to apply a `Style` to written text
+///
+trait StyledWrite: io::Write {
+    /// Write `args` using `style`
+    fn write_styled(&mut self, style: &Style, args: fmt::Arguments) -> io::Result<()>;
+
+    /// Write the item's own contents using `style`, with the replacements of `text`
+    fn write_item<T: TreeItem>(&mut self, item: &T, style: &Style, text: TextFilter) -> io::Result<()>;
+}
+
+///
+/// Replacements applied to the text of an item while it is written
+///
+#[derive(Clone, Copy)]
+struct TextFilter<'a> {
+    /// Replacement for tab characters, or `None` if they are written unchanged
+    tab: Option<&'a str>,
+    /// Text written after the item in the same style, such as its inline metadata
+    suffix: &'a str,
+    newline: NewlineMode,
+    /// Indentation of the continuation lines if newlines are split,
+    /// which consists of the prefix of the item and the part added for its children
+    continuation: (&'a str, &'a str),
+}
+
+impl TextFilter<'_> {
+    /// Writes the text of `item` to `f` using `style`, with the replacements applied
+    fn write_item<T: TreeItem, W: io::Write>(self, item: &T, f: W, style: &Style) -> io::Result<()> {
+        let mut writer = FilterWriter {
+            inner: f,
+            filter: self,
+            truncated: false,
+            state: EscapeState::Text,
+        };
+        item.write_self(&mut writer, style)?;
+        if !self.suffix.is_empty() {
+            style.paint_to(&mut writer, format_args!("{}", self.suffix))?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Writer that replaces tabs and newlines in the text written to it, according to a [`TextFilter`]
+///
+/// Both are single bytes in UTF-8, so characters split between several writes are passed through unchanged.
+///
+struct FilterWriter<'a, W> {
+    inner: W,
+    filter: TextFilter<'a>,
+    /// Whether the text was truncated at a newline
+    truncated: bool,
+    /// State of the escape sequences written after the text was truncated
+    state: EscapeState,
+}
+
+impl<W: io::Write> io::Write for FilterWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut start = 0;
+        for (i, &b) in buf.iter().enumerate() {
+            if self.truncated {
+                // Escape sequences are still written, so styles of the item are reset
+                if self.state != EscapeState::Text || b == 0x1b {
+                    self.inner.write_all(&[b])?;
+                }
+                self.state = self.state.next(b);
+                continue;
+            }
+
+            let replacement: &[u8] = match (b, self.filter.newline) {
+                (b'\t', _) => match self.filter.tab {
+                    Some(tab) => tab.as_bytes(),
+                    None => continue,
+                },
+                (b'\n', NewlineMode::Escape) => b"\\n",
+                (b'\r', NewlineMode::Escape) => b"\\r",
+                (b'\n', NewlineMode::Split) => b"\n",
+                (b'\n', NewlineMode::Truncate) => {
+                    self.inner.write_all(&buf[start..i])?;
+                    self.truncated = true;
+                    continue;
+                }
+                (b'\r', _) => b"",
+                _ => continue,
+            };
+            self.inner.write_all(&buf[start..i])?;
+            self.inner.write_all(replacement)?;
+            if b == b'\n' && self.filter.newline == NewlineMode::Split {
+                let (prefix, child_prefix) = self.filter.continuation;
+                self.inner.write_all(prefix.as_bytes())?;
+                self.inner.write_all(child_prefix.as_bytes())?;
+            }
+            start = i + 1;
+        }
+
+        if !self.truncated {
+            self.inner.write_all(&buf[start..])?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+///
+/// Writer that styles text by embedding ANSI escape codes, as produced by [`Style::paint_to`]
+///
+struct AnsiWriter<W>(W);
+
+impl<W: io::Write> io::Write for AnsiWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<W: io::Write> StyledWrite for AnsiWriter<W> {
+    fn write_styled(&mut self, style: &Style, args: fmt::Arguments) -> io::Result<()> {
+        style.paint_to(&mut self.0, args)
+    }
+
+    fn write_item<T: TreeItem>(&mut self, item: &T, style: &Style, text: TextFilter) -> io::Result<()> {
+        text.write_item(item, &mut self.0, style)
+    }
+}
+
+///
+/// Writer that styles text through the `termcolor::WriteColor` interface
+///
+/// Items are written with the color already set on the writer,
+/// and receive an empty style so they do not emit escape codes of their own.
+///
+#[cfg(feature = "termcolor")]
+struct ColorWriter<W>(W);
+
+#[cfg(feature = "termcolor")]
+impl<W: WriteColor> io::Write for ColorWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(feature = "termcolor")]
+impl<W: WriteColor> StyledWrite for ColorWriter<W> {
+    fn write_styled(&mut self, style: &Style, args: fmt::Arguments) -> io::Result<()> {
+        style.write_to(&mut self.0, args)
+    }
+
+    fn write_item<T: TreeItem>(&mut self, item: &T, style: &Style, text: TextFilter) -> io::Result<()> {
+        if *style == Style::default() {
+            return text.write_item(item, &mut self.0, style);
+        }
+
+        self.0.set_color(&style.to_color_spec())?;
+        text.write_item(item, &mut self.0, &Style::default())?;
+        self.0.reset()
+    }
+}
+
+///
+/// Writer that collects styled lines instead of writing bytes
+///
+/// Items receive an empty style, and any escape codes of their own are removed.
+///
+#[derive(Default)]
+struct LineWriter {
+    lines: Vec<StyledText>,
+    line: StyledText,
+}
+
+impl LineWriter {
+    fn push(&mut self, style: &Style, text: &str) {
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.lines.push(mem::take(&mut self.line));
+            }
+            if !part.is_empty() {
+                self.line.push(style.clone(), part);
+            }
+        }
+    }
+}
+
+impl io::Write for LineWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.push(&Style::default(), &String::from_utf8_lossy(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl StyledWrite for LineWriter {
+    fn write_styled(&mut self, style: &Style, args: fmt::Arguments) -> io::Result<()> {
+        self.push(style, &args.to_string());
+        Ok(())
+    }
+
+    fn write_item<T: TreeItem>(&mut self, item: &T, style: &Style, filter: TextFilter) -> io::Result<()> {
+        let mut text = Vec::new();
+        filter.write_item(item, StripAnsi::new(&mut text), &Style::default())?;
+        self.push(style, &String::from_utf8_lossy(&text));
+        Ok(())
+    }
+}
+
+///
+/// Writer that measures the lines of a tree instead of keeping them
+///
+/// Escape sequences are skipped, such as those in custom branch characters.
+///
+struct MeasureWriter {
+    measurement: Measurement,
+    line: StripAnsi<WidthCounter>,
+}
+
+impl Default for MeasureWriter {
+    fn default() -> Self {
+        MeasureWriter {
+            measurement: Measurement::default(),
+            line: StripAnsi::new(WidthCounter::default()),
+        }
+    }
+}
+
+impl io::Write for MeasureWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for (i, part) in buf.split(|&b| b == b'\n').enumerate() {
+            if i > 0 {
+                let width = mem::take(&mut self.line.inner).width;
+                self.measurement.lines += 1;
+                self.measurement.max_line_width = self.measurement.max_line_width.max(width);
+            }
+            self.line.write_all(part)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl StyledWrite for MeasureWriter {
+    fn write_styled(&mut self, _style: &Style, args: fmt::Arguments) -> io::Result<()> {
+        self.write_fmt(args)
+    }
+
+    fn write_item<T: TreeItem>(&mut self, item: &T, style: &Style, text: TextFilter) -> io::Result<()> {
+        text.write_item(item, self, style)
+    }
+}
+
+///
+/// Writer that collects whole lines and passes them to a callback in chunks of at most `size` bytes
+///
+struct ChunkWriter<F> {
+    buffer: Vec<u8>,
+    size: usize,
+    on_chunk: F,
+}
+
+impl<F: FnMut(&str) -> io::Result<()>> ChunkWriter<F> {
+    /// Passes the first `len` bytes of the buffer to the callback
+    fn emit(&mut self, len: usize) -> io::Result<()> {
+        if len > 0 {
+            (self.on_chunk)(&String::from_utf8_lossy(&self.buffer[..len]))?;
+            self.buffer.drain(..len);
+        }
+        Ok(())
+    }
+}
+
+impl<F: FnMut(&str) -> io::Result<()>> io::Write for ChunkWriter<F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        while self.buffer.len() >= self.size {
+            // Lines are kept whole, unless a single line is longer than a chunk
+            let len = match self.buffer[..self.size].iter().rposition(|&b| b == b'\n') {
+                Some(end) => end + 1,
+                None => {
+                    // Characters are not split, but their bytes are counted towards the next chunk
+                    let mut end = self.size;
+                    while end > 0 && self.buffer.get(end).is_some_and(|&b| b & 0xc0 == 0x80) {
+                        end -= 1;
+                    }
+                    if end == 0 {
+                        self.size
+                    } else {
+                        end
+                    }
+                }
+            };
+            self.emit(len)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let len = self.buffer.len();
+        self.emit(len)
+    }
+}
+
+///
+/// Writer that removes ANSI escape sequences from the text written to it
+///
+/// Used for items when output is not styled,
+/// so that items which style parts of their text themselves do not emit escape codes either.
+///
+struct StripAnsi<W> {
+    inner: W,
+    state: EscapeState,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EscapeState {
+    Text,
+    Escape,
+    Csi,
+    Osc,
+    OscEscape,
+}
+
+impl EscapeState {
+    /// Returns the state after the byte `b`
+    fn next(self, b: u8) -> EscapeState {
+        match (self, b) {
+            (EscapeState::Text, 0x1b) => EscapeState::Escape,
+            (EscapeState::Text, _) => EscapeState::Text,
+            (EscapeState::Escape, b'[') => EscapeState::Csi,
+            (EscapeState::Escape, b']') => EscapeState::Osc,
+            (EscapeState::Csi, 0x40..=0x7e) => EscapeState::Text,
+            (EscapeState::Csi, _) => EscapeState::Csi,
+            (EscapeState::Osc, 0x07) => EscapeState::Text,
+            (EscapeState::Osc, 0x1b) => EscapeState::OscEscape,
+            (EscapeState::OscEscape, b'\\') => EscapeState::Text,
+            (EscapeState::Osc, _) | (EscapeState::OscEscape, _) => EscapeState::Osc,
+            (EscapeState::Escape, _) => EscapeState::Text,
+        }
+    }
+}
+
+impl<W: io::Write> StripAnsi<W> {
+    fn new(inner: W) -> StripAnsi<W> {
+        StripAnsi {
+            inner,
+            state: EscapeState::Text,
+        }
+    }
+}
+
+impl<W: io::Write> io::Write for StripAnsi<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut start = 0;
+
+        for (i, &b) in buf.iter().enumerate() {
+            if self.state == EscapeState::Text {
+                if b != 0x1b {
+                    continue;
+                }
+                self.inner.write_all(&buf[start..i])?;
+            }
+            self.state = self.state.next(b);
+
+            if self.state == EscapeState::Text {
+                start = i + 1;
+            }
+        }
+
+        if self.state == EscapeState::Text {
+            self.inner.write_all(&buf[start..])?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+///
+/// Writer that measures the display width of the text written to it
+///
+/// Text is expected to be UTF-8 encoded, with no escape sequences.
+/// Characters may be split between several writes.
+///
+#[derive(Default)]
+struct WidthCounter {
+    width: usize,
+    pending: Vec<u8>,
+}
+
+impl WidthCounter {
+    /// Adds the width of complete characters in `buf`, and returns the trailing incomplete character, if any
+    fn count<'b>(&mut self, mut buf: &'b [u8]) -> &'b [u8] {
+        loop {
+            match str::from_utf8(buf) {
+                Ok(s) => {
+                    self.width += s.width();
+                    return &[];
+                }
+                Err(e) => {
+                    let (valid, rest) = buf.split_at(e.valid_up_to());
+                    self.width += str::from_utf8(valid).map_or(0, UnicodeWidthStr::width);
+                    match e.error_len() {
+                        // Invalid sequences are displayed as a replacement character
+                        Some(len) => {
+                            self.width += 1;
+                            buf = &rest[len..];
+                        }
+                        None => return rest,
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl io::Write for WidthCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            let rest = self.count(buf);
+            self.pending.extend_from_slice(rest);
+        } else {
+            let mut joined = mem::take(&mut self.pending);
+            joined.extend_from_slice(buf);
+            self.pending = self.count(&joined).to_vec();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+///
+/// Returns the text of `item` itself, without escape sequences
+///
+pub(crate) fn item_text<T: TreeItem>(item: &T) -> io::Result<String> {
+    let mut text = Vec::new();
+    item.write_self(&mut StripAnsi::new(&mut text), &Style::default())?;
+    Ok(String::from_utf8_lossy(&text).into_owned())
+}
+
+/// Returns the length in bytes of the first character of `prefix`, which is the line going down
+fn lead_len(prefix: &str) -> usize {
+    prefix.chars().next().map_or(0, char::len_utf8)
+}
+
+///
+/// Returns the display width of the last line of the item's own text with the replacements of `text`,
+/// ignoring any escape sequences and the indentation of continuation lines
+///
+fn item_width<T: TreeItem>(item: &T, text: TextFilter) -> io::Result<usize> {
+    let mut buf = Vec::new();
+    let text = TextFilter {
+        continuation: ("", ""),
+        ..text
+    };
+    text.write_item(item, StripAnsi::new(&mut buf), &Style::default())?;
+
+    let mut counter = WidthCounter::default();
+    counter.write_all(buf.rsplit(|&b| b == b'\n').next().unwrap_or_default())?;
+    Ok(counter.width)
+}
+
+///
+/// Returns the display width of `text` in terminal columns, skipping ANSI escape sequences
+///
+/// Text which was styled before it is printed, such as the output of [`TreeItem::write_self`],
+/// takes fewer columns than its length suggests.
+///
+/// ```
+/// # use ptree::output::text_width;
+/// assert_eq!(text_width("\x1b[1;32mok\x1b[0m 漢字"), 7);
+/// assert_eq!(text_width("\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\"), 4);
+/// ```
+///
+/// [`TreeItem::write_self`]: ../item/trait.TreeItem.html#tymethod.write_self
+pub fn text_width(text: &str) -> usize {
+    if !text.contains('\x1b') {
+        return text.width();
+    }
+    let mut counter = StripAnsi::new(WidthCounter::default());
+    // Neither writer can fail
+    let _ = counter.write_all(text.as_bytes());
+    counter.inner.width
+}
+
+///
+/// Part of the regular prefix of an item written in the highlight style
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Mark {
+    /// The item is not next to the highlighted path
+    #[default]
+    None,
+    /// The item is a sibling before the highlighted path, so only the line going down past it is highlighted
+    Lead,
+    /// The item is on the highlighted path, so all of its branch characters are highlighted
+    Branch,
+}
+
+///
+/// Highlighted path through the tree, with the position of the printed item relative to it
+///
+struct Trail {
+    path: Vec<usize>,
+    style: Style,
+    /// Level of the deepest item on the path among the ancestors of the printed item
+    matched: Cell<u32>,
+    /// Byte ranges of the prefix which are written in the highlight style
+    marks: RefCell<Vec<Range<usize>>>,
+    /// Mark of the next printed item, set by its parent
+    next: Cell<Mark>,
+}
+
+///
+/// State shared by all items while printing a single tree
+///
+struct TreePrinter<'a> {
+    config: &'a PrintConfig,
+    characters: Indent,
+    /// Replacement for tab characters in item text, or `None` if they are written unchanged
+    tab: Option<String>,
+    branch_style: Style,
+    leaf_style: Style,
+    separator_style: Style,
+    omitted_style: Style,
+    /// Ellipsis used in the markers for omitted children
+    ellipsis: &'static str,
+    styled: bool,
+    /// Line width and style used to extend the leaf background, if enabled
+    fill: Option<(usize, Style)>,
+    /// Whether the padding after the branch characters is written separately, in the separator style
+    separate_pad: bool,
+    /// Styles of item classes merged into the leaf and branch styles, which are empty if the output is not styled
+    ///
+    /// They are merged once here instead of for every printed item.
+    classes: BTreeMap<String, (Style, Style)>,
+    /// Classes of the printed items, collected for the legend if it is enabled
+    used_classes: Option<RefCell<BTreeSet<String>>>,
+    /// Width to which lines are aligned on the right, if the tree is mirrored
+    mirror_width: Option<Cell<usize>>,
+    /// Path of the highlighted item, if one is set and the output is styled
+    trail: Option<Trail>,
+}
+
+impl<'a> TreePrinter<'a> {
+    fn new(config: &'a PrintConfig, styled: bool) -> TreePrinter<'a> {
+        let (branch_style, leaf_style, separator_style, omitted_style) = if styled {
+            let depth = config.color_depth.resolve();
+            (
+                config.branch.downsample(depth),
+                config.leaf.downsample(depth),
+                config.separator.downsample(depth),
+                config.omitted.downsample(depth),
+            )
+        } else {
+            (Style::default(), Style::default(), Style::default(), Style::default())
+        };
+        let classes = if styled {
+            let depth = config.color_depth.resolve();
+            config
+                .classes
+                .iter()
+                .map(|(class, style)| {
+                    let style = style.downsample(depth);
+                    (class.clone(), (leaf_style.merge(&style), branch_style.merge(&style)))
+                })
+                .collect()
+        } else {
+            BTreeMap::new()
+        };
+
+        let fill = match (config.fill_background, config.line_width, &leaf_style.background) {
+            (true, Some(width), Some(background)) => Some((
+                width,
+                Style {
+                    background: Some(background.clone()),
+                    ..Style::default()
+                },
+            )),
+            _ => None,
+        };
+
+        TreePrinter {
+            config,
+            characters: Indent::from_config(config),
+            tab: match config.tabs {
+                TabMode::Expand => Some(" ".repeat(config.tab_width)),
+                TabMode::Escape => Some("\\t".to_string()),
+                TabMode::Keep => None,
+            },
+            separate_pad: separator_style != branch_style,
+            branch_style,
+            leaf_style,
+            separator_style,
+            omitted_style,
+            ellipsis: if config.characters.down.is_ascii() {
+                "..."
+            } else {
+                "\u{2026}"
+            },
+            used_classes: if styled && config.legend {
+                Some(RefCell::new(BTreeSet::new()))
+            } else {
+                None
+            },
+            mirror_width: if config.right_to_left { Some(Cell::new(0)) } else { None },
+            trail: match config.highlight_path {
+                Some(ref path) if styled => Some(Trail {
+                    path: path.clone(),
+                    style: config.highlight.downsample(config.color_depth.resolve()),
+                    matched: Cell::new(0),
+                    marks: RefCell::new(vec![]),
+                    next: Cell::new(Mark::None),
+                }),
+                _ => None,
+            },
+            styled,
+            fill,
+            classes,
+        }
+    }
+
+    fn print_tree<T: TreeItem, W: StyledWrite>(&self, item: &T, f: &mut W) -> io::Result<()> {
+        self.print_subtree(&[], item, f)
+    }
+
+    ///
+    /// Copies the part of the tree `item` which is printed
+    ///
+    /// The children of items at the maximum depth are only copied if the markers printed for these items need them.
+    ///
+    fn copy_shown<T: TreeItem>(&self, item: &T) -> io::Result<StringItem> {
+        match self.config.depth {
+            depth if self.config.depth_marker || self.config.fold_markers => StringItem::from_item(item, depth),
+            0 => StringItem::leaf(item),
+            depth => StringItem::from_item(item, depth - 1),
+        }
+    }
+
+    ///
+    /// Prints the classes of the items printed so far in their styles, if the legend is enabled
+    ///
+    fn print_legend<W: StyledWrite>(&self, f: &mut W) -> io::Result<()> {
+        let used = match self.used_classes {
+            Some(ref used) => used.replace(BTreeSet::new()),
+            None => return Ok(()),
+        };
+        if used.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f)?;
+        f.write_styled(&Style::default(), format_args!("Legend:"))?;
+        for class in &used {
+            let (style, _) = &self.classes[class];
+            f.write_styled(&Style::default(), format_args!(" "))?;
+            f.write_styled(style, format_args!("{}", class))?;
+        }
+        writeln!(f)
+    }
+
+    ///
+    /// Prints the tree with escape codes through a buffer, as every line consists of several small writes
+    ///
+    fn print_tree_buffered<T: TreeItem, W: io::Write>(&self, item: &T, f: W) -> io::Result<()> {
+        let mut out = io::BufWriter::new(f);
+        self.print_tree(item, &mut AnsiWriter(&mut out))?;
+        out.flush()
+    }
+
+    ///
+    /// Prints `item` and its children
+    ///
+    /// `prefix` contains the indentation inherited from the item's ancestors.
+    /// It is shared by the whole tree, with each level appending to it before printing its children
+    /// and truncating it afterwards, so printing an item does not allocate.
+    /// `regular_prefix` and `child_prefix` are the parts added for the item itself and for its children.
+    ///
+    fn print_item<T: TreeItem, W: StyledWrite>(
+        &self,
+        item: &T,
+        f: &mut W,
+        prefix: &mut String,
+        regular_prefix: &str,
+        child_prefix: &str,
+        level: u32,
+    ) -> io::Result<()> {
+        let metadata = self.metadata_children(item);
+        let expanded = level < self.config.depth;
+        // Items such as deduplicated graph nodes change once their children are retrieved,
+        // so the markers printed before the children only check whether there are any
+        let marker = match (self.config.fold_markers, expanded) {
+            (false, _) => "",
+            _ if metadata.is_empty() && !item.has_children() => "",
+            (true, true) => "[-]",
+            (true, false) => "[+]",
+        };
+        self.print_line(item, f, prefix, regular_prefix, child_prefix, marker)?;
+        let mark = self.trail.as_ref().map_or(Mark::None, |trail| trail.next.replace(Mark::None));
+
+        let children = if expanded {
+            item.children()
+        } else if self.config.depth_marker && (!metadata.is_empty() || item.has_children()) {
+            // Beyond the maximum depth, only the marker is printed
+            Cow::from(&[][..])
+        } else {
+            return Ok(());
+        };
+        if expanded && children.is_empty() && metadata.is_empty() {
+            return Ok(());
+        }
+
+        let len = prefix.len();
+        prefix.push_str(child_prefix);
+        self.enter_trail(mark, level, len, child_prefix);
+        let chars = &self.characters;
+
+        if expanded {
+            let shown = match self.config.max_children {
+                Some(max) if max < children.len() => max,
+                _ => children.len(),
+            };
+            let omitted = children.len() - shown;
+
+            for (i, entry) in metadata.iter().enumerate() {
+                let (regular_prefix, child_prefix) = if children.is_empty() && i + 1 == metadata.len() {
+                    (&chars.last_regular_prefix, &chars.last_child_prefix)
+                } else {
+                    (&chars.regular_prefix, &chars.child_prefix)
+                };
+                self.mark_child(level, None);
+                self.print_item(entry, f, prefix, regular_prefix, child_prefix, level + 1)?;
+            }
+            for (i, c) in children[..shown].iter().enumerate() {
+                let (regular_prefix, child_prefix) = if omitted == 0 && i + 1 == shown {
+                    (&chars.last_regular_prefix, &chars.last_child_prefix)
+                } else {
+                    (&chars.regular_prefix, &chars.child_prefix)
+                };
+                self.mark_child(level, Some(i));
+                self.print_item(c, f, prefix, regular_prefix, child_prefix, level + 1)?;
+            }
+            if omitted > 0 {
+                self.print_omitted(f, prefix, Some(omitted))?;
+            }
+        } else {
+            self.print_omitted(f, prefix, None)?;
+        }
+
+        self.leave_trail(mark, level);
+        prefix.truncate(len);
+        Ok(())
+    }
+
+    ///
+    /// Returns the metadata entries of `item` as leaf items, if they are printed as children
+    ///
+    fn metadata_children<T: TreeItem>(&self, item: &T) -> Vec<StringItem> {
+        match (self.config.metadata, item.metadata()) {
+            (MetadataMode::Children, Some(metadata)) => metadata
+                .iter()
+                .map(|(key, value)| StringItem::new(format!("{}={}", key, value), vec![]))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    ///
+    /// Returns the metadata entries of `item` as text following it, if they are printed inline
+    ///
+    fn inline_metadata<T: TreeItem>(&self, item: &T) -> String {
+        let mut text = String::new();
+        if let (MetadataMode::Inline, Some(metadata)) = (self.config.metadata, item.metadata()) {
+            for (key, value) in metadata {
+                text.push_str(&format!(" {}={}", key, value));
+            }
+        }
+        text
+    }
+
+    ///
+    /// Sets the mark of the child at `index` of an item at `level`, which is printed next
+    ///
+    /// The `index` of metadata lines is `None`, as they are printed before the children.
+    ///
+    fn mark_child(&self, level: u32, index: Option<usize>) {
+        if let Some(ref trail) = self.trail {
+            let mark = match trail.path.get(level as usize) {
+                // The parent is on the path if all its ancestors are
+                Some(&target) if trail.matched.get() == level => {
+                    match index.map_or(Ordering::Less, |index| index.cmp(&target)) {
+                        Ordering::Less => Mark::Lead,
+                        Ordering::Equal => Mark::Branch,
+                        Ordering::Greater => Mark::None,
+                    }
+                }
+                _ => Mark::None,
+            };
+            trail.next.set(mark);
+        }
+    }
+
+    ///
+    /// Updates the trail before printing the children of an item at `level` with `mark`
+    ///
+    /// `child_prefix` was appended to the prefix at byte `start`.
+    ///
+    fn enter_trail(&self, mark: Mark, level: u32, start: usize, child_prefix: &str) {
+        if let Some(ref trail) = self.trail {
+            match mark {
+                Mark::None => {}
+                Mark::Lead => trail.marks.borrow_mut().push(start..start + lead_len(child_prefix)),
+                Mark::Branch => trail.matched.set(level),
+            }
+        }
+    }
+
+    ///
+    /// Restores the trail after printing the children of an item at `level` with `mark`
+    ///
+    fn leave_trail(&self, mark: Mark, level: u32) {
+        if let Some(ref trail) = self.trail {
+            match mark {
+                Mark::None => {}
+                Mark::Lead => {
+                    trail.marks.borrow_mut().pop();
+                }
+                Mark::Branch => trail.matched.set(level - 1),
+            }
+        }
+    }
+
+    ///
+    /// Prints the line of `item` itself, without its children
+    ///
+    /// The fold `marker` is written between the branch characters and the item, unless it is empty.
+    ///
+    fn print_line<T: TreeItem, W: StyledWrite>(
+        &self,
+        item: &T,
+        f: &mut W,
+        prefix: &str,
+        regular_prefix: &str,
+        child_prefix: &str,
+        marker: &str,
+    ) -> io::Result<()> {
+        let metadata = self.inline_metadata(item);
+        let text = TextFilter {
+            suffix: &metadata,
+            ..self.text_filter(prefix, child_prefix)
+        };
+        let regular_prefix = &self.branch_prefix(item, regular_prefix)[..];
+        let mark = self.trail.as_ref().map_or(Mark::None, |trail| trail.next.get());
+        let marker_width = if marker.is_empty() { 0 } else { text_width(marker) + 1 };
+        if self.mirror_width.is_some() {
+            let used = text_width(prefix) + text_width(regular_prefix) + marker_width + item_width(item, text)?;
+            self.write_alignment(f, used)?;
+            self.write_item(item, f, true, text)?;
+            if !marker.is_empty() {
+                f.write_styled(&self.branch_style, format_args!(" {}", marker))?;
+            }
+            self.write_prefix(f, prefix, regular_prefix, mark)?;
+            return writeln!(f);
+        }
+
+        self.write_prefix(f, prefix, regular_prefix, mark)?;
+        if !marker.is_empty() {
+            f.write_styled(&self.branch_style, format_args!("{} ", marker))?;
+        }
+        self.write_item(item, f, true, text)?;
+        if let Some((width, ref style)) = self.fill {
+            let used = text_width(prefix) + text_width(regular_prefix) + marker_width + item_width(item, text)?;
+            if width > used {
+                f.write_styled(style, format_args!("{:1$}", "", width - used))?;
+            }
+        }
+        writeln!(f)
+    }
+
+    ///
+    /// Prints the marker for `omitted` children, or for all children if the maximum depth was reached
+    ///
+    fn print_omitted<W: StyledWrite>(&self, f: &mut W, prefix: &str, omitted: Option<usize>) -> io::Result<()> {
+        let regular_prefix = &self.characters.last_regular_prefix;
+        let marker = match omitted {
+            Some(omitted) => Cow::from(format!("{} and {} more", self.ellipsis, omitted)),
+            None => Cow::from(self.ellipsis),
+        };
+        if self.mirror_width.is_some() {
+            self.write_alignment(f, text_width(prefix) + text_width(regular_prefix) + text_width(&marker))?;
+            f.write_styled(&self.omitted_style, format_args!("{}", marker))?;
+            self.write_prefix(f, prefix, regular_prefix, Mark::None)?;
+        } else {
+            self.write_prefix(f, prefix, regular_prefix, Mark::None)?;
+            f.write_styled(&self.omitted_style, format_args!("{}", marker))?;
+        }
+        writeln!(f)
+    }
+
+    ///
+    /// Prints `ancestors` on a single line, followed by `item` and its children below the last ancestor
+    ///
+    /// A mirrored tree is aligned to its widest line, so it is measured before it is printed.
+    /// As items such as deduplicated graph nodes change once their children are listed,
+    /// the tree is only walked once, to copy it, and the copy is measured and printed.
+    /// The copy is measured with a printer which does not mirror it, as mirroring does not change the width of lines.
+    ///
+    fn print_subtree<T: TreeItem, W: StyledWrite>(&self, ancestors: &[T], item: &T, f: &mut W) -> io::Result<()> {
+        let width = match self.mirror_width {
+            Some(ref width) => width,
+            None => return self.print_subtree_lines(ancestors, item, f),
+        };
+        let ancestors = ancestors.iter().map(StringItem::leaf).collect::<io::Result<Vec<_>>>()?;
+        let item = self.copy_shown(item)?;
+
+        let printer = TreePrinter {
+            mirror_width: None,
+            ..TreePrinter::new(self.config, false)
+        };
+        let mut measure = MeasureWriter::default();
+        printer.print_subtree_lines(&ancestors, &item, &mut measure)?;
+        width.set(measure.measurement.max_line_width);
+
+        self.print_subtree_lines(&ancestors, &item, f)
+    }
+
+    ///
+    /// Prints the lines of [`print_subtree`], whose width has to be known already if the tree is mirrored
+    ///
+    /// [`print_subtree`]: #method.print_subtree
+    fn print_subtree_lines<T: TreeItem, W: StyledWrite>(
+        &self,
+        ancestors: &[T],
+        item: &T,
+        f: &mut W,
+    ) -> io::Result<()> {
+        if ancestors.is_empty() {
+            self.print_item(item, f, &mut String::new(), "", "", 0)?;
+            return self.print_legend(f);
+        }
+
+        let mirrored = self.mirror_width.is_some();
+        let arrow = match (self.config.characters.down.is_ascii(), mirrored) {
+            (true, false) => " -> ",
+            (true, true) => " <- ",
+            (false, false) => " \u{2192} ",
+            (false, true) => " \u{2190} ",
+        };
+        let mut ordered: Vec<&T> = ancestors.iter().collect();
+        if mirrored {
+            let mut used = text_width(arrow) * (ancestors.len() - 1);
+            for ancestor in ancestors {
+                used += item_width(ancestor, self.text_filter("", ""))?;
+            }
+            self.write_alignment(f, used)?;
+            // The ancestors of a mirrored tree are listed from right to left
+            ordered.reverse();
+        }
+        for (i, ancestor) in ordered.into_iter().enumerate() {
+            if i > 0 {
+                f.write_styled(&self.separator_style, format_args!("{}", arrow))?;
+            }
+            self.write_item(ancestor, f, false, self.text_filter("", ""))?;
+        }
+        writeln!(f)?;
+
+        let (regular, child) = (&self.characters.last_regular_prefix, &self.characters.last_child_prefix);
+        self.print_item(item, f, &mut String::new(), regular, child, 0)
+    }
+
+    ///
+    /// Returns `regular_prefix` with its last branch character replaced by the [`TreeItem::branch_char`] of `item`
+    ///
+    /// [`TreeItem::branch_char`]: ../item/trait.TreeItem.html#method.branch_char
+    fn branch_prefix<'b, T: TreeItem>(&self, item: &T, regular_prefix: &'b str) -> Cow<'b, str> {
+        match item.branch_char() {
+            Some(glyph) if !regular_prefix.is_empty() => {
+                let pad = &self.characters.item_pad;
+                let branch = regular_prefix.strip_suffix(&pad[..]).unwrap_or(regular_prefix);
+                let last = branch.char_indices().last().map_or(0, |(i, _)| i);
+                Cow::from(format!("{}{}{}", &branch[..last], glyph, &regular_prefix[branch.len()..]))
+            }
+            _ => Cow::from(regular_prefix),
+        }
+    }
+
+    ///
+    /// Returns the replacements for the text of an item, whose children are indented by `prefix` and `child_prefix`
+    ///
+    fn text_filter<'b>(&'b self, prefix: &'b str, child_prefix: &'b str) -> TextFilter<'b> {
+        TextFilter {
+            tab: self.tab.as_deref(),
+            suffix: "",
+            newline: match self.config.newline_mode {
+                // Continuation lines would be indented on the wrong side
+                NewlineMode::Split if self.config.right_to_left => NewlineMode::Escape,
+                mode => mode,
+            },
+            continuation: (prefix, child_prefix),
+        }
+    }
+
+    ///
+    /// Writes the item's own text in the leaf style, or in the branch style if `leaf` is `false`
+    ///
+    /// Escape codes of the item are removed if the output is not styled.
+    ///
+    fn write_item<T: TreeItem, W: StyledWrite>(
+        &self,
+        item: &T,
+        f: &mut W,
+        leaf: bool,
+        text: TextFilter,
+    ) -> io::Result<()> {
+        let style = if leaf { &self.leaf_style } else { &self.branch_style };
+        if self.styled {
+            if let (Some(class), Some(used)) = (item.class(), &self.used_classes) {
+                if self.classes.contains_key(class) && !used.borrow().contains(class) {
+                    used.borrow_mut().insert(class.to_string());
+                }
+            }
+            match item.class().and_then(|class| self.classes.get(class)) {
+                Some((leaf_style, _)) if leaf => f.write_item(item, leaf_style, text),
+                Some((_, branch_style)) => f.write_item(item, branch_style, text),
+                None => f.write_item(item, style, text),
+            }
+        } else {
+            text.write_item(item, StripAnsi::new(&mut *f), style)
+        }
+    }
+
+    ///
+    /// Writes the indentation in front of an item or marker, or its mirror image after it if the tree is mirrored
+    ///
+    fn write_prefix<W: StyledWrite>(&self, f: &mut W, prefix: &str, regular_prefix: &str, mark: Mark) -> io::Result<()> {
+        // The padding at the end of the regular prefix is written separately if it has its own style
+        let pad = &self.characters.item_pad;
+        if prefix.is_empty() && regular_prefix.is_empty() {
+            // The root item has no prefix
+            Ok(())
+        } else if let Some(ref trail) = self.trail {
+            self.write_highlighted_prefix(f, trail, prefix, regular_prefix, mark)
+        } else if self.mirror_width.is_some() {
+            let mirror = |s: &str| s.chars().rev().map(mirror_glyph).collect::<String>();
+            if !self.separate_pad || !regular_prefix.ends_with(&pad[..]) {
+                let branch = mirror(&format!("{}{}", prefix, regular_prefix));
+                f.write_styled(&self.branch_style, format_args!("{}", branch))
+            } else {
+                let branch = mirror(&format!("{}{}", prefix, &regular_prefix[..regular_prefix.len() - pad.len()]));
+                f.write_styled(&self.separator_style, format_args!("{}", mirror(pad)))?;
+                f.write_styled(&self.branch_style, format_args!("{}", branch))
+            }
+        } else if !self.separate_pad || !regular_prefix.ends_with(&pad[..]) {
+            f.write_styled(&self.branch_style, format_args!("{}{}", prefix, regular_prefix))
+        } else {
+            let branch = &regular_prefix[..regular_prefix.len() - pad.len()];
+            f.write_styled(&self.branch_style, format_args!("{}{}", prefix, branch))?;
+            f.write_styled(&self.separator_style, format_args!("{}", pad))
+        }
+    }
+
+    ///
+    /// Writes the indentation like [`write_prefix`], with the parts leading to the highlighted item in its style
+    ///
+    /// [`write_prefix`]: #method.write_prefix
+    fn write_highlighted_prefix<W: StyledWrite>(
+        &self,
+        f: &mut W,
+        trail: &Trail,
+        prefix: &str,
+        regular_prefix: &str,
+        mark: Mark,
+    ) -> io::Result<()> {
+        let mut spans = vec![];
+        let mut start = 0;
+        for range in trail.marks.borrow().iter() {
+            spans.push((&prefix[start..range.start], &self.branch_style));
+            spans.push((&prefix[range.clone()], &trail.style));
+            start = range.end;
+        }
+        spans.push((&prefix[start..], &self.branch_style));
+
+        let branch = regular_prefix
+            .strip_suffix(&self.characters.item_pad[..])
+            .unwrap_or(regular_prefix);
+        let highlighted = match mark {
+            Mark::None => 0,
+            Mark::Lead => lead_len(branch),
+            Mark::Branch => branch.len(),
+        };
+        let pad_style = if self.separate_pad { &self.separator_style } else { &self.branch_style };
+        spans.push((&branch[..highlighted], &trail.style));
+        spans.push((&branch[highlighted..], &self.branch_style));
+        spans.push((&regular_prefix[branch.len()..], pad_style));
+
+        if self.mirror_width.is_some() {
+            for &(text, style) in spans.iter().rev().filter(|(text, _)| !text.is_empty()) {
+                let mirrored = text.chars().rev().map(mirror_glyph).collect::<String>();
+                f.write_styled(style, format_args!("{}", mirrored))?;
+            }
+        } else {
+            for &(text, style) in spans.iter().filter(|(text, _)| !text.is_empty()) {
+                f.write_styled(style, format_args!("{}", text))?;
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Writes the spaces in front of a mirrored line of `used` columns, which align it on the right
+    ///
+    /// If the leaf background is extended, the spaces fill the line width in its style instead.
+    ///
+    fn write_alignment<W: StyledWrite>(&self, f: &mut W, used: usize) -> io::Result<()> {
+        let width = self.mirror_width.as_ref().map_or(0, Cell::get);
+        let (width, style) = match self.fill {
+            Some((line_width, ref style)) => (width.max(line_width), style.clone()),
+            None => (width, Style::default()),
+        };
+        if width > used {
+            f.write_styled(&style, format_args!("{:1$}", "", width - used))?;
+        }
+        Ok(())
+    }
+}
+
+/// Print the tree `item` to standard output using default formatting
+pub fn print_tree<T: TreeItem>(item: &T) -> io::Result<()> {
+    print_tree_with(item, &PrintConfig::for_terminal())
+}
+
+/// Print the tree `item` to standard output using custom formatting
+///
+/// With the `"termcolor"` feature, styles are applied through the console API,
+/// so they work on legacy Windows consoles as well.
+pub fn print_tree_with<T: TreeItem>(item: &T, config: &PrintConfig) -> io::Result<()> {
+    let styled = config.should_style_output(OutputKind::Stdout);
+
+    // Escape codes only work on Windows consoles with virtual terminal processing enabled
+    #[cfg(not(feature = "termcolor"))]
+    let styled = styled && term::enable_virtual_terminal();
+
+    let printer = TreePrinter::new(config, styled);
+
+    #[cfg(feature = "termcolor")]
+    {
+        let choice = if styled {
+            ColorChoice::Always
+        } else {
+            ColorChoice::Never
+        };
+        let out = StandardStream::stdout(choice);
+        let mut handle = out.lock();
+        printer.print_tree(item, &mut ColorWriter(&mut handle))
+    }
+
+    #[cfg(not(feature = "termcolor"))]
+    {
+        let out = io::stdout();
+        printer.print_tree_buffered(item, out.lock())
+    }
+}
+
+/// Write the tree `item` to writer `f` using default formatting
+pub fn write_tree<T: TreeItem, W: io::Write>(item: &T, mut f: W) -> io::Result<()> {
+    write_tree_with(item, &mut f, &PrintConfig::from_env())
+}
+
+/// Write the tree `item` to writer `f` using custom formatting
+///
+/// The output is buffered, so `f` does not need to be wrapped in a `BufWriter`.
+pub fn write_tree_with<T: TreeItem, W: io::Write>(item: &T, f: W, config: &PrintConfig) -> io::Result<()> {
+    let printer = TreePrinter::new(config, config.should_style_output(OutputKind::Unknown));
+    printer.print_tree_buffered(item, f)
+}
+
+///
+/// Error returned when printing a tree of [`TryTreeItem`]s fails
+///
+/// [`TryTreeItem`]: ../item/trait.TryTreeItem.html
+///
+#[derive(Debug)]
+pub enum TryWriteError<E> {
+    /// The children of an item could not be retrieved
+    Children {
+        /// Index of each ancestor below the root among its siblings, followed by the index of the item
+        ///
+        /// The path of the root is empty.
+        path: Vec<usize>,
+        /// Error returned by the item
+        error: E,
+    },
+    /// The tree could not be written
+    Io(io::Error),
+}
+
+impl<E: fmt::Display> fmt::Display for TryWriteError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TryWriteError::Children { path, error } => {
+                write!(f, "cannot retrieve the children of the item at {:?}: {}", path, error)
+            }
+            TryWriteError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for TryWriteError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TryWriteError::Children { error, .. } => Some(error),
+            TryWriteError::Io(e) => Some(e),
+        }
+    }
+}
+
+impl<E> From<io::Error> for TryWriteError<E> {
+    fn from(e: io::Error) -> Self {
+        TryWriteError::Io(e)
+    }
+}
+
+///
+/// Adapter printing a [`TryTreeItem`] with the printer for [`TreeItem`]s
+///
+/// The first error is stored in `failure`, shared by all items of the tree.
+/// The failing item then has no children, and writing any further item fails, which stops printing.
+///
+/// [`TryTreeItem`]: ../item/trait.TryTreeItem.html
+/// [`TreeItem`]: ../item/trait.TreeItem.html
+///
+struct Fallible<T: TryTreeItem> {
+    item: T,
+    path: Vec<usize>,
+    failure: Failure<T::Error>,
+}
+
+/// The path of the first item whose children could not be retrieved, and the error
+type Failure<E> = Rc<RefCell<Option<(Vec<usize>, E)>>>;
+
+// Derived `Clone` would require the error type to be `Clone` as well
+impl<T: TryTreeItem> Clone for Fallible<T> {
+    fn clone(&self) -> Self {
+        Fallible {
+            item: self.item.clone(),
+            path: self.path.clone(),
+            failure: self.failure.clone(),
+        }
+    }
+}
+
+impl<T: TryTreeItem> TreeItem for Fallible<T> {
+    type Child = Fallible<T::Child>;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        if self.failure.borrow().is_some() {
+            return Err(io::Error::other("the children of an item could not be retrieved"));
+        }
+        self.item.write_self(f, style)
+    }
+
+    fn class(&self) -> Option<&str> {
+        self.item.class()
+    }
+
+    fn branch_char(&self) -> Option<&str> {
+        self.item.branch_char()
+    }
+
+    fn metadata(&self) -> Option<&BTreeMap<String, String>> {
+        self.item.metadata()
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        if self.failure.borrow().is_some() {
+            return Cow::from(vec![]);
+        }
+        match self.item.try_children() {
+            Ok(children) => Cow::from(
+                children
+                    .iter()
+                    .enumerate()
+                    .map(|(i, child)| {
+                        let mut path = self.path.clone();
+                        path.push(i);
+                        Fallible {
+                            item: child.clone(),
+                            path,
+                            failure: self.failure.clone(),
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Err(error) => {
+                *self.failure.borrow_mut() = Some((self.path.clone(), error));
+                Cow::from(vec![])
+            }
+        }
+    }
+
+    fn has_children(&self) -> bool {
+        // A failure is only recorded once the children are retrieved, so the line of the item is printed first
+        self.failure.borrow().is_none() && self.item.try_children().is_ok_and(|children| !children.is_empty())
+    }
+}
+
+/// Prints the tree `item` with `print`, returning the first error of its items
+fn try_print<T, P>(item: &T, print: P) -> Result<(), TryWriteError<T::Error>>
+where
+    T: TryTreeItem,
+    P: FnOnce(&Fallible<T>) -> io::Result<()>,
+{
+    let root = Fallible {
+        item: item.clone(),
+        path: vec![],
+        failure: Rc::new(RefCell::new(None)),
+    };
+    let result = print(&root);
+    let failure = root.failure.borrow_mut().take();
+    match failure {
+        Some((path, error)) => Err(TryWriteError::Children { path, error }),
+        None => result.map_err(TryWriteError::Io),
+    }
+}
+
+/// Print the tree `item`, whose children may fail to be retrieved, to standard output using default formatting
+pub fn try_print_tree<T: TryTreeItem>(item: &T) -> Result<(), TryWriteError<T::Error>> {
+    try_print_tree_with(item, &PrintConfig::for_terminal())
+}
+
+/// Print the tree `item`, whose children may fail to be retrieved, to standard output using custom formatting
+///
+/// Printing stops at the first item whose children cannot be retrieved,
+/// and the error is returned with the path of the item.
+/// The lines printed until then are kept.
+pub fn try_print_tree_with<T: TryTreeItem>(item: &T, config: &PrintConfig) -> Result<(), TryWriteError<T::Error>> {
+    try_print(item, |root| print_tree_with(root, config))
+}
+
+/// Write the tree `item`, whose children may fail to be retrieved, to writer `f` using default formatting
+pub fn try_write_tree<T: TryTreeItem, W: io::Write>(item: &T, f: W) -> Result<(), TryWriteError<T::Error>> {
+    try_write_tree_with(item, f, &PrintConfig::from_env())
+}
+
+/// Write the tree `item`, whose children may fail to be retrieved, to writer `f` using custom formatting
+///
+/// Writing stops at the first item whose children cannot be retrieved,
+/// and the error is returned with the path of the item.
+/// The lines written until then are kept.
+pub fn try_write_tree_with<T: TryTreeItem, W: io::Write>(
+    item: &T,
+    f: W,
+    config: &PrintConfig,
+) -> Result<(), TryWriteError<T::Error>> {
+    try_print(item, |root| write_tree_with(root, f, config))
+}
+
+/// Write the tree `item` to asynchronous writer `f` using default formatting
+///
+/// This function is only available with the `"tokio"` feature.
+#[cfg(feature = "tokio")]
+pub fn write_tree_async<T: TreeItem, W: AsyncWrite + Unpin>(item: &T, f: W) -> WriteTreeAsync<W> {
+    write_tree_with_async(item, f, &PrintConfig::from_env())
+}
+
+/// Write the tree `item` to asynchronous writer `f` using custom formatting
+///
+/// The tree is formatted right away, as children are produced synchronously by `TreeItem`,
+/// and the returned future writes and flushes the formatted text without blocking.
+/// This lets async servers and tools send trees over sockets or to async files.
+///
+/// This function is only available with the `"tokio"` feature.
+#[cfg(feature = "tokio")]
+pub fn write_tree_with_async<T: TreeItem, W: AsyncWrite + Unpin>(
+    item: &T,
+    f: W,
+    config: &PrintConfig,
+) -> WriteTreeAsync<W> {
+    let mut buffer = Vec::new();
+    let result = write_tree_with(item, &mut buffer, config);
+    WriteTreeAsync {
+        f,
+        buffer,
+        written: 0,
+        error: result.err(),
+    }
+}
+
+///
+/// Future returned by [`write_tree_async`] and [`write_tree_with_async`]
+///
+/// This type is only available with the `"tokio"` feature.
+///
+/// [`write_tree_async`]: fn.write_tree_async.html
+/// [`write_tree_with_async`]: fn.write_tree_with_async.html
+///
+#[cfg(feature = "tokio")]
+#[must_use = "futures do nothing unless polled"]
+pub struct WriteTreeAsync<W> {
+    f: W,
+    /// The formatted tree
+    buffer: Vec<u8>,
+    /// Number of bytes of `buffer` written so far
+    written: usize,
+    /// Error which occurred while formatting the tree
+    error: Option<io::Error>,
+}
+
+#[cfg(feature = "tokio")]
+impl<W: AsyncWrite + Unpin> Future for WriteTreeAsync<W> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if let Some(error) = this.error.take() {
+            return Poll::Ready(Err(error));
+        }
+        while this.written < this.buffer.len() {
+            match Pin::new(&mut this.f).poll_write(cx, &this.buffer[this.written..]) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                Poll::Ready(Ok(n)) => this.written += n,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Pin::new(&mut this.f).poll_flush(cx)
+    }
+}
+
+///
+/// Finds the first item of the tree `item` for which `matches` returns `true`, in depth-first order
+///
+/// Returns the ancestors of the found item, followed by the item itself.
+///
+fn find_path<T, P>(item: &T, matches: &mut P, path: &mut Vec<T>) -> bool
+where
+    T: TreeItem<Child = T>,
+    P: FnMut(&T) -> bool,
+{
+    path.push(item.clone());
+    if matches(item) {
+        return true;
+    }
+    for child in item.children().iter() {
+        if find_path(child, matches, path) {
+            return true;
+        }
+    }
+    path.pop();
+    false
+}
+
+/// Write the subtree of the first item matching `matches` to writer `f`, below the chain of its ancestors
+///
+/// The tree `item` is searched in depth-first order, calling `matches` for every visited item.
+/// The ancestors of the found item are written on a single line, such as `root → a → b`,
+/// followed by the found item and all of its children.
+/// [`PrintConfig::depth`] is counted from the found item.
+///
+/// An error of kind [`NotFound`] is returned if no item matches.
+///
+/// ```
+/// # use ptree::TreeBuilder;
+/// # use ptree::output::write_subtree_with;
+/// # use ptree::print_config::{PrintConfig, StyleWhen, UTF_CHARS};
+/// let tree = TreeBuilder::new("root".to_string())
+///     .begin_child("a".to_string())
+///     .begin_child("b".to_string())
+///     .add_empty_child("c".to_string())
+///     .end_child()
+///     .end_child()
+///     .add_empty_child("d".to_string())
+///     .build();
+///
+/// let config = PrintConfig {
+///     characters: UTF_CHARS.into(),
+///     styled: StyleWhen::Never,
+///     ..PrintConfig::default()
+/// };
+/// let mut out = Vec::new();
+/// write_subtree_with(&tree, |item| item.text == "b", &mut out, &config).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "root \u{2192} a\n└─ b\n   └─ c\n");
+/// ```
+///
+/// [`PrintConfig::depth`]: ../print_config/struct.PrintConfig.html#structfield.depth
+/// [`NotFound`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.NotFound
+pub fn write_subtree_with<T, P, W>(item: &T, mut matches: P, f: W, config: &PrintConfig) -> io::Result<()>
+where
+    T: TreeItem<Child = T>,
+    P: FnMut(&T) -> bool,
+    W: io::Write,
+{
+    let mut path = Vec::new();
+    if !find_path(item, &mut matches, &mut path) {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "no matching item in the tree"));
+    }
+
+    let found = path.pop().expect("the path contains the found item");
+    let printer = TreePrinter::new(config, config.should_style_output(OutputKind::Unknown));
+    printer.print_subtree(&path, &found, &mut AnsiWriter(f))
+}
+
+///
+/// Dimensions of a rendered tree, as computed by [`measure_tree`]
+///
+/// [`measure_tree`]: fn.measure_tree.html
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Measurement {
+    /// Number of lines
+    pub lines: usize,
+    /// Display width of the longest line in terminal columns, without escape sequences
+    pub max_line_width: usize,
+}
+
+///
+/// Computes the dimensions of the tree `item` as it would be printed using custom formatting, without writing it
+///
+/// This lets callers decide up front whether to print the tree inline, through a pager, or with a lower depth.
+/// Lines are counted as they are printed, before any wrapping by the terminal.
+///
+/// ```
+/// # use ptree::TreeBuilder;
+/// # use ptree::output::{measure_tree, Measurement};
+/// # use ptree::print_config::{PrintConfig, UTF_CHARS};
+/// let tree = TreeBuilder::new("root".to_string())
+///     .begin_child("a".to_string())
+///     .add_empty_child("leaf".to_string())
+///     .end_child()
+///     .build();
+/// let config = PrintConfig {
+///     characters: UTF_CHARS.into(),
+///     ..PrintConfig::default()
+/// };
+///
+/// let measurement = measure_tree(&tree, &config).unwrap();
+/// assert_eq!(measurement, Measurement { lines: 3, max_line_width: 10 });
+/// ```
+pub fn measure_tree<T: TreeItem>(item: &T, config: &PrintConfig) -> io::Result<Measurement> {
+    let printer = TreePrinter::new(config, false);
+    let mut writer = MeasureWriter::default();
+    printer.print_tree(item, &mut writer)?;
+    Ok(writer.measurement)
+}
+
+///
+/// Write the tree `item` in chunks of whole lines to the callback `on_chunk`, using custom formatting
+///
+/// Rendering is interleaved with the calls of `on_chunk`, which receive at most `chunk_size` bytes each,
+/// so a GUI event loop can process its events and a server can send its responses between chunks,
+/// instead of waiting for the whole tree to be formatted.
+/// Lines longer than `chunk_size` are split into multiple chunks.
+/// If `on_chunk` returns an error, rendering stops and the error is returned, so rendering can be cancelled.
+///
+/// ```
+/// # use ptree::{write_tree_chunked, PrintConfig, TreeBuilder};
+/// # use ptree::print_config::{StyleWhen, UTF_CHARS};
+/// let mut builder = TreeBuilder::new("root".to_string());
+/// for i in 0..1000 {
+///     builder.add_empty_child(format!("item {}", i));
+/// }
+/// let tree = builder.build();
+/// let config = PrintConfig {
+///     characters: UTF_CHARS.into(),
+///     styled: StyleWhen::Never,
+///     ..PrintConfig::default()
+/// };
+///
+/// let mut chunks = Vec::new();
+/// write_tree_chunked(&tree, &config, 4096, |chunk| {
+///     chunks.push(chunk.to_string());
+///     Ok(())
+/// })
+/// .unwrap();
+/// assert!(chunks.len() > 1);
+/// assert!(chunks.iter().all(|chunk| chunk.len() <= 4096 && chunk.ends_with('\n')));
+/// ```
+pub fn write_tree_chunked<T, F>(item: &T, config: &PrintConfig, chunk_size: usize, on_chunk: F) -> io::Result<()>
+where
+    T: TreeItem,
+    F: FnMut(&str) -> io::Result<()>,
+{
+    let printer = TreePrinter::new(config, config.should_style_output(OutputKind::Unknown));
+    let mut writer = ChunkWriter {
+        buffer: Vec::with_capacity(chunk_size),
+        size: chunk_size.max(1),
+        on_chunk,
+    };
+    printer.print_tree(item, &mut AnsiWriter(&mut writer))?;
+    writer.flush()
+}
+
+///
+/// Formats the tree `item` into lines of styled text, using custom formatting
+///
+/// Instead of escape codes, the styles are kept with the segments of each line,
+/// so the tree can be displayed by other means than a terminal, such as the elements of a web page.
+/// Lines are styled unless [`PrintConfig::styled`] is set to [`StyleWhen::Never`].
+///
+/// [`PrintConfig::styled`]: ../print_config/struct.PrintConfig.html#structfield.styled
+/// [`StyleWhen::Never`]: ../print_config/enum.StyleWhen.html#variant.Never
+pub fn tree_lines<T: TreeItem>(item: &T, config: &PrintConfig) -> io::Result<Vec<StyledText>> {
+    let printer = TreePrinter::new(config, config.styled != StyleWhen::Never);
+    let mut writer = LineWriter::default();
+    printer.print_tree(item, &mut writer)?;
+    Ok(writer.lines)
+}
+
+///
+/// Writer for a tree whose children are produced one at a time
+///
+/// The root is written when the writer is created, and each child with its subtree as soon as the next one is pushed,
+/// since the last child is printed with different branch characters.
+/// This allows printing trees with a huge number of children, such as the records of a large document,
+/// without holding all of them in memory.
+///
+/// ```
+/// # use ptree::item::StringItem;
+/// # use ptree::output::StreamWriter;
+/// # use ptree::print_config::{PrintConfig, StyleWhen, UTF_CHARS};
+/// let config = PrintConfig {
+///     characters: UTF_CHARS.into(),
+///     styled: StyleWhen::Never,
+///     ..PrintConfig::default()
+/// };
+/// let root = StringItem::new("numbers".to_string(), vec![]);
+///
+/// let mut out = Vec::new();
+/// {
+///     let mut writer = StreamWriter::new(&root, &mut out, &config).unwrap();
+///     for i in 0..3 {
+///         writer.push(StringItem::new(i.to_string(), vec![])).unwrap();
+///     }
+///     writer.finish().unwrap();
+/// }
+/// assert_eq!(String::from_utf8(out).unwrap(), "numbers\n├─ 0\n├─ 1\n└─ 2\n");
+/// ```
+pub struct StreamWriter<'a, T, W> {
+    printer: TreePrinter<'a>,
+    f: AnsiWriter<W>,
+    /// The last pushed child, which is printed once it is known whether it is the last one
+    pending: Option<T>,
+    /// Number of children pushed so far
+    pushed: usize,
+}
+
+impl<'a, T: TreeItem, W: io::Write> StreamWriter<'a, T, W> {
+    ///
+    /// Creates a writer for a tree with the root `root`, writing the root to `f`
+    ///
+    /// The children of `root` itself are not printed.
+    ///
+    pub fn new<R: TreeItem>(root: &R, f: W, config: &'a PrintConfig) -> io::Result<Self> {
+        let printer = TreePrinter::new(config, config.should_style_output(OutputKind::Unknown));
+        let mut f = AnsiWriter(f);
+        printer.print_line(root, &mut f, "", "", "", "")?;
+        Ok(StreamWriter {
+            printer,
+            f,
+            pending: None,
+            pushed: 0,
+        })
+    }
+
+    ///
+    /// Adds the next child of the root, printing the previous one
+    ///
+    pub fn push(&mut self, child: T) -> io::Result<()> {
+        self.pushed += 1;
+        if self.printer.config.depth == 0 || self.max_children_reached() {
+            return Ok(());
+        }
+        if let Some(previous) = self.pending.replace(child) {
+            let printer = &self.printer;
+            let (regular, child) = (&printer.characters.regular_prefix, &printer.characters.child_prefix);
+            printer.mark_child(0, Some(self.pushed - 2));
+            printer.print_item(&previous, &mut self.f, &mut String::new(), regular, child, 1)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Prints the last child, finishing the tree
+    ///
+    pub fn finish(mut self) -> io::Result<()> {
+        let printer = &self.printer;
+        let chars = &printer.characters;
+        let shown = match printer.config.max_children {
+            Some(max) if max < self.pushed => max,
+            _ => self.pushed,
+        };
+        let omitted = self.pushed - shown;
+
+        if printer.config.depth == 0 {
+            if self.pushed > 0 && printer.config.depth_marker {
+                printer.print_omitted(&mut self.f, "", None)?;
+            }
+            return Ok(());
+        }
+        if let Some(last) = self.pending.take() {
+            let (regular, child) = if omitted == 0 {
+                (&chars.last_regular_prefix, &chars.last_child_prefix)
+            } else {
+                (&chars.regular_prefix, &chars.child_prefix)
+            };
+            printer.mark_child(0, Some(shown - 1));
+            printer.print_item(&last, &mut self.f, &mut String::new(), regular, child, 1)?;
+        }
+        if omitted > 0 {
+            printer.print_omitted(&mut self.f, "", Some(omitted))?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the child pushed last is beyond `max_children`
+    fn max_children_reached(&self) -> bool {
+        match self.printer.config.max_children {
+            Some(max) => self.pushed > max,
+            None => false,
+        }
+    }
 }
 
-impl Indent {
-    pub fn from_config(config: &PrintConfig) -> Indent {
-        Self::from_characters_and_padding(config.indent, config.padding, &config.characters)
+///
+/// Writer which redraws a changing tree in place
+///
+/// Each call to [`render`] moves the cursor back up to the first line of the previous rendering
+/// and overwrites it, clearing what is left of longer lines and any lines below the new tree.
+/// This way, trees which change over time, such as the progress of a build or a watched directory,
+/// can be displayed without scrolling the terminal.
+///
+/// Cursor movement only works for the lines still visible on the terminal, so the tree should fit on the screen.
+/// Lines wrapped by the terminal are accounted for if [`PrintConfig::line_width`] is set to the terminal width.
+///
+/// For large trees which change only slightly, [`update`] rewrites only the lines which differ from the previous rendering,
+/// which avoids the flicker of redrawing the whole tree.
+///
+/// ```
+/// # use ptree::item::StringItem;
+/// # use ptree::output::LiveWriter;
+/// # use ptree::print_config::{PrintConfig, StyleWhen, UTF_CHARS};
+/// let config = PrintConfig {
+///     characters: UTF_CHARS.into(),
+///     styled: StyleWhen::Never,
+///     ..PrintConfig::default()
+/// };
+/// let mut tree = StringItem::new("jobs".to_string(), vec![]);
+///
+/// let mut writer = LiveWriter::new(Vec::new(), &config);
+/// writer.render(&tree).unwrap();
+/// tree.children.push(StringItem::new("build".to_string(), vec![]));
+/// writer.render(&tree).unwrap();
+///
+/// let out = String::from_utf8(writer.into_inner()).unwrap();
+/// assert_eq!(out, "jobs\x1b[K\n\x1b[J\x1b[1A\rjobs\x1b[K\n└─ build\x1b[K\n\x1b[J");
+/// ```
+///
+/// [`render`]: #method.render
+/// [`update`]: #method.update
+/// [`PrintConfig::line_width`]: ../print_config/struct.PrintConfig.html#structfield.line_width
+pub struct LiveWriter<'a, W> {
+    printer: TreePrinter<'a>,
+    f: W,
+    /// Lines of the previous rendering, with the number of terminal rows taken by each
+    lines: Vec<(String, usize)>,
+}
+
+impl<'a, W: io::Write> LiveWriter<'a, W> {
+    ///
+    /// Creates a writer redrawing trees on `f`, with nothing rendered yet
+    ///
+    pub fn new(f: W, config: &'a PrintConfig) -> Self {
+        LiveWriter {
+            printer: TreePrinter::new(config, config.should_style_output(OutputKind::Unknown)),
+            f,
+            lines: Vec::new(),
+        }
     }
 
-    #[allow(dead_code)]
-    pub fn from_characters(indent_size: usize, characters: &IndentChars) -> Indent {
-        Self::from_characters_and_padding(indent_size, 1, characters)
+    ///
+    /// Replaces the previous rendering with the tree `item`
+    ///
+    pub fn render<T: TreeItem>(&mut self, item: &T) -> io::Result<()> {
+        let lines = self.layout(item)?;
+
+        let mut out = Vec::new();
+        self.write_rewind(&mut out)?;
+        for (line, _) in &lines {
+            writeln!(out, "{}\x1b[K", line)?;
+        }
+        self.lines = lines;
+        // Clears lines of a previous rendering which was taller
+        out.extend_from_slice(b"\x1b[J");
+
+        self.f.write_all(&out)?;
+        self.f.flush()
     }
 
-    pub fn from_characters_and_padding(indent_size: usize, padding: usize, characters: &IndentChars) -> Indent {
-        let m = 1 + padding;
-        let n = if indent_size > m { indent_size - m } else { 0 };
+    ///
+    /// Replaces the previous rendering with the tree `item`, rewriting only the lines which changed
+    ///
+    /// The cursor is moved to each changed line, which is then overwritten.
+    /// Once a line takes a different number of terminal rows than before, all following lines are rewritten,
+    /// as their positions have shifted.
+    /// Nothing is written if the tree looks the same as before.
+    ///
+    /// ```
+    /// # use ptree::item::StringItem;
+    /// # use ptree::output::LiveWriter;
+    /// # use ptree::print_config::{PrintConfig, StyleWhen, UTF_CHARS};
+    /// let config = PrintConfig {
+    ///     characters: UTF_CHARS.into(),
+    ///     styled: StyleWhen::Never,
+    ///     ..PrintConfig::default()
+    /// };
+    /// let leaf = |text: &str| StringItem::new(text.to_string(), vec![]);
+    /// let mut tree = StringItem::new("jobs".to_string(), vec![leaf("build: running"), leaf("test: waiting")]);
+    ///
+    /// let mut writer = LiveWriter::new(Vec::new(), &config);
+    /// writer.render(&tree).unwrap();
+    /// let start = writer.get_ref().len();
+    ///
+    /// tree.children[0].text = "build: done".to_string();
+    /// writer.update(&tree).unwrap();
+    /// let out = String::from_utf8_lossy(&writer.get_ref()[start..]);
+    /// assert_eq!(out, "\x1b[2A\r├─ build: done\x1b[K\n\x1b[1B");
+    /// ```
+    ///
+    pub fn update<T: TreeItem>(&mut self, item: &T) -> io::Result<()> {
+        let lines = self.layout(item)?;
+        let old_rows = self.rows();
 
-        let right_pad = characters.right.repeat(n);
-        let empty_pad = characters.empty.repeat(n);
-        let item_pad = characters.empty.repeat(padding);
+        let mut out = Vec::new();
+        // Rows of the cursor and of the current line, counted from the first line of the rendering
+        let mut cursor = old_rows;
+        let mut top = 0;
+        let mut shifted = false;
+        for (i, (line, rows)) in lines.iter().enumerate() {
+            match self.lines.get(i) {
+                Some((old_line, _)) if !shifted && old_line == line => {}
+                old => {
+                    if cursor > top {
+                        write!(out, "\x1b[{}A\r", cursor - top)?;
+                    } else if cursor < top {
+                        write!(out, "\x1b[{}B\r", top - cursor)?;
+                    }
+                    writeln!(out, "{}\x1b[K", line)?;
+                    cursor = top + rows;
+                    shifted = shifted || old.is_none_or(|&(_, old_rows)| old_rows != *rows);
+                }
+            }
+            top += rows;
+        }
 
-        Indent {
-            regular_prefix: format!("{}{}{}", characters.down_and_right, right_pad, item_pad),
-            child_prefix: format!("{}{}{}", characters.down, empty_pad, item_pad),
-            last_regular_prefix: format!("{}{}{}", characters.turn_right, right_pad, item_pad),
-            last_child_prefix: format!("{}{}{}", characters.empty, empty_pad, item_pad),
+        // The cursor is left below the last line, clearing the lines of a previous rendering which was taller
+        if cursor < top {
+            write!(out, "\x1b[{}B", top - cursor)?;
+        }
+        if old_rows > top {
+            out.extend_from_slice(b"\x1b[J");
         }
+        self.lines = lines;
+
+        self.f.write_all(&out)?;
+        self.f.flush()
     }
-}
 
-fn print_item<T: TreeItem, W: io::Write>(
-    item: &T,
-    f: &mut W,
-    prefix: String,
-    child_prefix: String,
-    config: &PrintConfig,
-    characters: &Indent,
-    branch_style: &Style,
-    leaf_style: &Style,
-    level: u32,
-) -> io::Result<()> {
-    write!(f, "{}", branch_style.paint(prefix))?;
-    item.write_self(f, leaf_style)?;
-    writeln!(f, "")?;
-
-    if level < config.depth {
-        let children = item.children();
-        if let Some((last_child, children)) = children.split_last() {
-            let rp = child_prefix.clone() + &characters.regular_prefix;
-            let cp = child_prefix.clone() + &characters.child_prefix;
-
-            for c in children {
-                print_item(
-                    c,
-                    f,
-                    rp.clone(),
-                    cp.clone(),
-                    config,
-                    characters,
-                    branch_style,
-                    leaf_style,
-                    level + 1,
-                )?;
-            }
-
-            let rp = child_prefix.clone() + &characters.last_regular_prefix;
-            let cp = child_prefix.clone() + &characters.last_child_prefix;
-
-            print_item(
-                last_child,
-                f,
-                rp,
-                cp,
-                config,
-                characters,
-                branch_style,
-                leaf_style,
-                level + 1,
-            )?;
-        }
-    }
-
-    Ok(())
-}
+    ///
+    /// Erases the previous rendering, leaving the cursor where it started
+    ///
+    pub fn clear(&mut self) -> io::Result<()> {
+        let mut out = Vec::new();
+        self.write_rewind(&mut out)?;
+        out.extend_from_slice(b"\x1b[J");
+        self.lines.clear();
+        self.f.write_all(&out)?;
+        self.f.flush()
+    }
 
-/// Print the tree `item` to standard output using default formatting
-pub fn print_tree<T: TreeItem>(item: &T) -> io::Result<()> {
-    print_tree_with(item, &PrintConfig::from_env())
-}
+    ///
+    /// Returns the underlying writer, leaving the last rendering in place
+    ///
+    pub fn into_inner(self) -> W {
+        self.f
+    }
 
-/// Print the tree `item` to standard output using custom formatting
-pub fn print_tree_with<T: TreeItem>(item: &T, config: &PrintConfig) -> io::Result<()> {
-    let (branch_style, leaf_style) = if config.should_style_output(OutputKind::Stdout) {
-        (config.branch.clone(), config.leaf.clone())
-    } else {
-        (Style::default(), Style::default())
-    };
+    ///
+    /// Returns a reference to the underlying writer
+    ///
+    pub fn get_ref(&self) -> &W {
+        &self.f
+    }
 
-    let characters = Indent::from_config(config);
-    let out = io::stdout();
-    let mut handle = out.lock();
-    print_item(
-        item,
-        &mut handle,
-        "".to_string(),
-        "".to_string(),
-        config,
-        &characters,
-        &branch_style,
-        &leaf_style,
-        0,
-    )
-}
+    /// Formats `item` into lines, with the number of terminal rows taken by each
+    fn layout<T: TreeItem>(&self, item: &T) -> io::Result<Vec<(String, usize)>> {
+        let mut buffer = Vec::new();
+        self.printer.print_tree(item, &mut AnsiWriter(&mut buffer))?;
+        String::from_utf8_lossy(&buffer)
+            .lines()
+            .map(|line| Ok((line.to_string(), self.line_rows(line)?)))
+            .collect()
+    }
 
-/// Write the tree `item` to writer `f` using default formatting
-pub fn write_tree<T: TreeItem, W: io::Write>(item: &T, mut f: W) -> io::Result<()> {
-    write_tree_with(item, &mut f, &PrintConfig::from_env())
+    /// Returns the number of terminal rows taken by the previous rendering
+    fn rows(&self) -> usize {
+        self.lines.iter().map(|&(_, rows)| rows).sum()
+    }
+
+    /// Moves the cursor to the start of the previous rendering
+    fn write_rewind(&self, out: &mut Vec<u8>) -> io::Result<()> {
+        let rows = self.rows();
+        if rows > 0 {
+            write!(out, "\x1b[{}A\r", rows)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of terminal rows taken by `line`, considering wrapping
+    fn line_rows(&self, line: &str) -> io::Result<usize> {
+        let width = match self.printer.config.line_width {
+            Some(width) if width > 0 => width,
+            _ => return Ok(1),
+        };
+        Ok(text_width(line).div_ceil(width).max(1))
+    }
 }
 
-/// Write the tree `item` to writer `f` using custom formatting
-pub fn write_tree_with<T: TreeItem, W: io::Write>(item: &T, mut f: W, config: &PrintConfig) -> io::Result<()> {
-    let (branch_style, leaf_style) = if config.should_style_output(OutputKind::Unknown) {
-        (config.branch.clone(), config.leaf.clone())
-    } else {
-        (Style::default(), Style::default())
-    };
+impl<'a> LiveWriter<'a, io::Stdout> {
+    ///
+    /// Creates a writer redrawing trees on standard output, with nothing rendered yet
+    ///
+    /// Like [`print_tree_with`], output is only styled if standard output is a terminal.
+    ///
+    /// [`print_tree_with`]: fn.print_tree_with.html
+    ///
+    pub fn stdout(config: &'a PrintConfig) -> Self {
+        // Cursor movement requires virtual terminal processing on Windows consoles
+        let styled = config.should_style_output(OutputKind::Stdout) && term::enable_virtual_terminal();
+        LiveWriter {
+            printer: TreePrinter::new(config, styled),
+            f: io::stdout(),
+            lines: Vec::new(),
+        }
+    }
+}
 
-    let characters = Indent::from_config(config);
-    print_item(
-        item,
-        &mut f,
-        "".to_string(),
-        "".to_string(),
-        config,
-        &characters,
-        &branch_style,
-        &leaf_style,
-        0,
-    )
+/// Write the tree `item` to a color-aware writer `f` using custom formatting
+///
+/// Styles are applied through the [`WriteColor`] interface rather than with embedded escape codes.
+/// Whether colors are actually written is up to the writer,
+/// unless [`PrintConfig::styled`] is set to [`StyleWhen::Never`].
+///
+/// This function is only available with the `"termcolor"` feature.
+///
+/// [`WriteColor`]: https://docs.rs/termcolor/1/termcolor/trait.WriteColor.html
+/// [`PrintConfig::styled`]: ../print_config/struct.PrintConfig.html#structfield.styled
+/// [`StyleWhen::Never`]: ../print_config/enum.StyleWhen.html#variant.Never
+#[cfg(feature = "termcolor")]
+pub fn write_tree_with_color<T: TreeItem, W: WriteColor>(item: &T, f: W, config: &PrintConfig) -> io::Result<()> {
+    let printer = TreePrinter::new(config, config.styled != StyleWhen::Never);
+    printer.print_tree(item, &mut ColorWriter(f))
 }
 
 #[cfg(test)]
@@ -174,11 +2097,10 @@ mod tests {
 
     #[test]
     fn indent_from_config() {
-        let config = {
-            let mut config = PrintConfig::default();
-            config.indent = 3;
-            config.characters = UTF_CHARS.into();
-            config
+        let config = PrintConfig {
+            indent: 3,
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
         };
         let indent = Indent::from_config(&config);
         assert_eq!(indent.regular_prefix, "├─ ");
@@ -201,4 +2123,906 @@ mod tests {
         assert_eq!(indent.child_prefix, "│   ");
         assert_eq!(indent.last_child_prefix, "    ");
     }
+
+    #[test]
+    fn indent_from_wide_characters() {
+        let characters = IndentChars {
+            down_and_right: "🌿".to_string(),
+            down: "┃".to_string(),
+            turn_right: "🍂".to_string(),
+            right: "＝".to_string(),
+            empty: " ".to_string(),
+        };
+        let indent = Indent::from_characters_and_padding(6, 1, &characters);
+        assert_eq!(indent.regular_prefix, "🌿＝  ");
+        assert_eq!(indent.last_regular_prefix, "🍂＝  ");
+        assert_eq!(indent.child_prefix, "┃     ");
+        assert_eq!(indent.last_child_prefix, "      ");
+
+        // Prefixes are widened to fit the widest character
+        let indent = Indent::from_characters_and_padding(2, 1, &characters);
+        assert_eq!(indent.regular_prefix, "🌿 ");
+        assert_eq!(indent.child_prefix, "┃  ");
+        assert_eq!(indent.last_child_prefix, "   ");
+    }
+
+    #[test]
+    fn strip_ansi() {
+        use std::io::Write;
+
+        let mut out = Vec::new();
+        {
+            let mut w = StripAnsi::new(&mut out);
+            write!(w, "a\u{1b}[1;31mb\u{1b}[0m").unwrap();
+            write!(w, "c\u{1b}").unwrap();
+            write!(w, "[4md\u{1b}]8;;http://x\u{7}e\u{1b}]8;;\u{1b}\\f").unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "abcdef");
+    }
+
+    #[test]
+    fn unstyled_output_strips_item_styles() {
+        use std::borrow::Cow;
+
+        #[derive(Clone)]
+        struct Labeled;
+
+        impl TreeItem for Labeled {
+            type Child = Self;
+            fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+                StyledText::new()
+                    .push_plain("name ")
+                    .push("bold,red".parse().unwrap(), "1.0")
+                    .write_to(f, style)
+            }
+            fn children(&self) -> Cow<'_, [Self::Child]> {
+                Cow::from(vec![])
+            }
+        }
+
+        let config = PrintConfig {
+            styled: StyleWhen::Never,
+            ..PrintConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_tree_with(&Labeled, &mut out, &config).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "name 1.0\n");
+    }
+
+    #[test]
+    fn width_counter() {
+        use std::io::Write;
+
+        let mut counter = WidthCounter::default();
+        counter.write_all("ab漢".as_bytes()).unwrap();
+        let bytes = "字é".as_bytes();
+        counter.write_all(&bytes[..1]).unwrap();
+        counter.write_all(&bytes[1..4]).unwrap();
+        counter.write_all(&bytes[4..]).unwrap();
+        counter.write_all(b"\xffz").unwrap();
+        assert_eq!(counter.width, 9);
+        assert!(counter.pending.is_empty());
+    }
+
+    #[test]
+    #[cfg(any(feature = "ansi", feature = "nu-ansi-term", feature = "crossterm"))]
+    fn fill_background() {
+        use item::StringItem;
+
+        let tree = StringItem::new("root".to_string(), vec![StringItem::new("漢字".to_string(), vec![])]);
+        let leaf = Style {
+            background: Some(Color::Blue),
+            ..Style::default()
+        };
+        let fill = Style {
+            background: Some(Color::Blue),
+            ..Style::default()
+        };
+        let config = PrintConfig {
+            leaf: leaf.clone(),
+            branch: Style::default(),
+            styled: StyleWhen::Always,
+            separator: Style::default(),
+            characters: UTF_CHARS.into(),
+            color_depth: ColorDepth::TrueColor,
+            line_width: Some(10),
+            fill_background: true,
+            ..PrintConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{}{}\n└─ {}{}\n",
+                leaf.paint("root"),
+                fill.paint("      "),
+                leaf.paint("漢字"),
+                fill.paint("   ")
+            )
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "ansi", feature = "nu-ansi-term", feature = "crossterm"))]
+    fn separator_style() {
+        use item::StringItem;
+
+        let tree = StringItem::new("root".to_string(), vec![StringItem::new("leaf".to_string(), vec![])]);
+        let branch = Style {
+            foreground: Some(Color::Red),
+            ..Style::default()
+        };
+        let separator = Style {
+            underline: true,
+            ..Style::default()
+        };
+        let config = PrintConfig {
+            branch: branch.clone(),
+            leaf: Style::default(),
+            separator: separator.clone(),
+            styled: StyleWhen::Always,
+            characters: UTF_CHARS.into(),
+            padding: 2,
+            ..PrintConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("root\n{}{}leaf\n", branch.paint("└"), separator.paint("  "))
+        );
+    }
+
+    #[test]
+    fn omitted_markers() {
+        use builder::TreeBuilder;
+
+        let tree = TreeBuilder::new("root".to_string())
+            .begin_child("a".to_string())
+            .add_empty_child("a1".to_string())
+            .end_child()
+            .add_empty_child("b".to_string())
+            .add_empty_child("c".to_string())
+            .build();
+
+        let config = PrintConfig {
+            max_children: Some(2),
+            depth: 1,
+            depth_marker: true,
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Never,
+            ..PrintConfig::default()
+        };
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "root\n├─ a\n│  └─ \u{2026}\n├─ b\n└─ \u{2026} and 1 more\n"
+        );
+
+        let config = PrintConfig {
+            max_children: Some(3),
+            characters: ASCII_CHARS_PLUS.into(),
+            ..config
+        };
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "root\n+- a\n|  +- ...\n+- b\n+- c\n");
+
+        let config = PrintConfig {
+            depth_marker: false,
+            ..config
+        };
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "root\n+- a\n+- b\n+- c\n");
+    }
+
+    #[test]
+    #[cfg(any(feature = "ansi", feature = "nu-ansi-term", feature = "crossterm"))]
+    fn omitted_style() {
+        use builder::TreeBuilder;
+
+        let tree = TreeBuilder::new("root".to_string())
+            .add_empty_child("a".to_string())
+            .add_empty_child("b".to_string())
+            .build();
+
+        let config = PrintConfig {
+            max_children: Some(1),
+            branch: Style::default(),
+            separator: Style::default(),
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Always,
+            ..PrintConfig::default()
+        };
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("root\n├─ a\n└─ {}\n", config.omitted.paint("\u{2026} and 1 more"))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "termcolor")]
+    fn write_with_color() {
+        use item::StringItem;
+        use termcolor::Buffer;
+
+        let tree = StringItem::new("root".to_string(), vec![StringItem::new("leaf".to_string(), vec![])]);
+        let config = PrintConfig {
+            leaf: Style {
+                foreground: Some(Color::Red),
+                ..Style::default()
+            },
+            branch: Style::default(),
+            separator: Style::default(),
+            styled: StyleWhen::Always,
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut buffer = Buffer::ansi();
+        write_tree_with_color(&tree, &mut buffer, &config).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer.into_inner()).unwrap(),
+            "\u{1b}[0m\u{1b}[31mroot\u{1b}[0m\n└─ \u{1b}[0m\u{1b}[31mleaf\u{1b}[0m\n"
+        );
+
+        let mut buffer = Buffer::no_color();
+        write_tree_with_color(&tree, &mut buffer, &config).unwrap();
+        assert_eq!(String::from_utf8(buffer.into_inner()).unwrap(), "root\n└─ leaf\n");
+    }
+
+    #[test]
+    fn subtree_with_ancestors() {
+        use builder::TreeBuilder;
+
+        let tree = TreeBuilder::new("root".to_string())
+            .begin_child("a".to_string())
+            .begin_child("b".to_string())
+            .begin_child("c".to_string())
+            .add_empty_child("d".to_string())
+            .end_child()
+            .end_child()
+            .end_child()
+            .build();
+
+        let config = PrintConfig {
+            depth: 1,
+            depth_marker: true,
+            characters: ASCII_CHARS_TICK.into(),
+            styled: StyleWhen::Never,
+            ..PrintConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_subtree_with(&tree, |item| item.text == "b", &mut out, &config).unwrap();
+        let expected = "root -> a\n`- b\n   `- c\n      `- ...\n";
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+        let mut out = Vec::new();
+        write_subtree_with(&tree, |item| item.text == "root", &mut out, &config).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "root\n`- a\n   `- ...\n");
+
+        let err = write_subtree_with(&tree, |item| item.text == "x", io::sink(), &config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn live_writer_redraws() {
+        let config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Never,
+            line_width: Some(8),
+            ..PrintConfig::default()
+        };
+        let mut tree = StringItem::new("build".to_string(), vec![StringItem::new("compiling".to_string(), vec![])]);
+
+        let mut writer = LiveWriter::new(Vec::new(), &config);
+        writer.render(&tree).unwrap();
+        tree.children.clear();
+        writer.render(&tree).unwrap();
+        writer.clear().unwrap();
+        writer.clear().unwrap();
+
+        let out = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(
+            out,
+            "build\x1b[K\n└─ compiling\x1b[K\n\x1b[J\
+             \x1b[3A\rbuild\x1b[K\n\x1b[J\
+             \x1b[1A\r\x1b[J\
+             \x1b[J"
+        );
+    }
+
+    #[test]
+    fn live_writer_updates() {
+        let config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Never,
+            line_width: Some(8),
+            ..PrintConfig::default()
+        };
+        let leaf = |text: &str| StringItem::new(text.to_string(), vec![]);
+        let mut tree = StringItem::new("a".to_string(), vec![leaf("b"), leaf("c"), leaf("d")]);
+
+        let mut writer = LiveWriter::new(Vec::new(), &config);
+        writer.render(&tree).unwrap();
+        let start = writer.get_ref().len();
+        writer.update(&tree).unwrap();
+        assert_eq!(writer.get_ref().len(), start);
+
+        // The longer line wraps, which moves the following line down
+        tree.children[1].text = "cccccccc".to_string();
+        writer.update(&tree).unwrap();
+        let start2 = writer.get_ref().len();
+        assert_eq!(
+            str::from_utf8(&writer.get_ref()[start..]).unwrap(),
+            "\x1b[2A\r├─ cccccccc\x1b[K\n└─ d\x1b[K\n"
+        );
+
+        tree.children.truncate(2);
+        tree.children[1].text = "c".to_string();
+        writer.update(&tree).unwrap();
+        assert_eq!(
+            str::from_utf8(&writer.get_ref()[start2..]).unwrap(),
+            "\x1b[3A\r└─ c\x1b[K\n\x1b[J"
+        );
+    }
+
+    #[test]
+    fn measure_matches_output() {
+        let config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            max_children: Some(1),
+            styled: StyleWhen::Always,
+            ..PrintConfig::default()
+        };
+        let tree = StringItem {
+            text: "\x1b[1mroot\x1b[0m".to_string(),
+            children: vec!["漢字漢字", "b"]
+                .into_iter()
+                .map(|text| StringItem::new(text.to_string(), vec![]))
+                .collect(),
+            ..Default::default()
+        };
+
+        // The marker "└─ … and 1 more" is wider than "├─ 漢字漢字", whose characters take two columns each
+        assert_eq!(
+            measure_tree(&tree, &config).unwrap(),
+            Measurement {
+                lines: 3,
+                max_line_width: 15
+            }
+        );
+    }
+
+    #[test]
+    fn tab_modes() {
+        let tree = StringItem::new("root".to_string(), vec![StringItem::new("a\tb".to_string(), vec![])]);
+        let render = |tabs, tab_width| {
+            let config = PrintConfig {
+                characters: UTF_CHARS.into(),
+                styled: StyleWhen::Never,
+                tabs,
+                tab_width,
+                ..PrintConfig::default()
+            };
+            let mut out = Vec::new();
+            write_tree_with(&tree, &mut out, &config).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(render(TabMode::Expand, 2), "root\n└─ a  b\n");
+        assert_eq!(render(TabMode::Escape, 2), "root\n└─ a\\tb\n");
+        assert_eq!(render(TabMode::Keep, 2), "root\n└─ a\tb\n");
+    }
+
+    #[test]
+    fn newline_modes() {
+        let leaf = |text: &str| StringItem::new(text.to_string(), vec![]);
+        let tree = StringItem {
+            text: "root".to_string(),
+            children: vec![
+                StringItem::new("a\nb".to_string(), vec![leaf("c")]),
+                leaf("\x1b[1mx\r\ny\x1b[0m"),
+            ],
+            ..Default::default()
+        };
+        let render = |newline_mode, styled| {
+            let config = PrintConfig {
+                characters: UTF_CHARS.into(),
+                styled,
+                branch: Style::default(),
+                separator: Style::default(),
+                newline_mode,
+                ..PrintConfig::default()
+            };
+            let mut out = Vec::new();
+            write_tree_with(&tree, &mut out, &config).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(
+            render(NewlineMode::Split, StyleWhen::Never),
+            "root\n├─ a\n│  b\n│  └─ c\n└─ x\n   y\n"
+        );
+        assert_eq!(
+            render(NewlineMode::Escape, StyleWhen::Never),
+            "root\n├─ a\\nb\n│  └─ c\n└─ x\\r\\ny\n"
+        );
+        assert_eq!(
+            render(NewlineMode::Truncate, StyleWhen::Never),
+            "root\n├─ a\n│  └─ c\n└─ x\n"
+        );
+        // Styles of truncated items are still reset
+        assert_eq!(
+            render(NewlineMode::Truncate, StyleWhen::Always),
+            "root\n├─ a\n│  └─ c\n└─ \x1b[1mx\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn mirrored_layout() {
+        let leaf = |text: &str| StringItem::new(text.to_string(), vec![]);
+        let tree = StringItem {
+            text: "שורש".to_string(),
+            children: vec![
+                StringItem::new("a".to_string(), vec![leaf("bc"), leaf("d"), leaf("e")]),
+                leaf("f\ng"),
+            ],
+            ..Default::default()
+        };
+        let config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Never,
+            max_children: Some(2),
+            right_to_left: true,
+            ..PrintConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            [
+                "              שורש",
+                "              a ─┤",
+                "          bc ─┤  │",
+                "           d ─┤  │",
+                "… and 1 more ─┘  │",
+                "           f\\ng ─┘",
+                "",
+            ]
+            .join("\n")
+        );
+
+        let mut out = Vec::new();
+        write_subtree_with(&tree, |item| item.text == "d", &mut out, &config).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "a ← שורש\n    d ─┘\n");
+
+        let mirrored = IndentChars::from(ASCII_CHARS_TICK).mirrored();
+        assert_eq!(mirrored.turn_right, "'");
+        assert_eq!(mirrored.down_and_right, "|");
+    }
+
+    #[test]
+    fn styled_characters_width() {
+        let tree = StringItem {
+            text: "root".to_string(),
+            children: vec![StringItem::new("\x1b[1mchild\x1b[0m".to_string(), vec![])],
+            ..Default::default()
+        };
+        let red = |c: &str| format!("\x1b[31m{}\x1b[0m", c);
+        let config = PrintConfig {
+            characters: IndentChars {
+                down_and_right: red("├"),
+                down: red("│"),
+                turn_right: red("└"),
+                right: red("─"),
+                empty: " ".to_string(),
+            },
+            styled: StyleWhen::Never,
+            ..PrintConfig::default()
+        };
+
+        let indent = Indent::from_config(&config);
+        assert_eq!(text_width(&indent.last_regular_prefix), 3);
+        assert_eq!(
+            measure_tree(&tree, &config).unwrap(),
+            Measurement {
+                lines: 2,
+                max_line_width: 8
+            }
+        );
+    }
+
+    /// Check with a status, which is marked by its branch character
+    #[derive(Clone)]
+    struct Check(&'static str, Option<bool>, Vec<Check>);
+
+    impl TreeItem for Check {
+        type Child = Self;
+
+        fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+            style.paint_to(f, format_args!("{}", self.0))
+        }
+
+        fn branch_char(&self) -> Option<&str> {
+            self.1.map(|passed| if passed { "\u{2714}" } else { "\u{2718}" })
+        }
+
+        fn children(&self) -> Cow<'_, [Self]> {
+            Cow::from(&self.2[..])
+        }
+    }
+
+    #[test]
+    fn branch_char_override() {
+        let tree = Check(
+            "suite",
+            Some(true),
+            vec![
+                Check("parse", Some(true), vec![Check("unicode", Some(false), vec![])]),
+                Check("skipped", None, vec![]),
+                Check("render", Some(false), vec![]),
+            ],
+        );
+        let mut config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            indent: 4,
+            styled: StyleWhen::Never,
+            ..PrintConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(
+            str::from_utf8(&out).unwrap(),
+            "\
+             suite\n\
+             ├─\u{2714} parse\n\
+             │   └─\u{2718} unicode\n\
+             ├── skipped\n\
+             └─\u{2718} render\n"
+        );
+
+        // The glyph is written in the branch style
+        config.styled = StyleWhen::Always;
+        config.branch = Style {
+            foreground: Some(Color::Red),
+            ..Style::default()
+        };
+        config.separator = config.branch.clone();
+        config.leaf = Style::default();
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        let expected = format!("{}render", config.branch.paint("└─\u{2718} "));
+        assert!(str::from_utf8(&out).unwrap().contains(&expected));
+    }
+
+    #[test]
+    fn highlighted_path() {
+        use builder::TreeBuilder;
+
+        let tree = TreeBuilder::new("root".to_string())
+            .add_empty_child("a".to_string())
+            .begin_child("b".to_string())
+            .add_empty_child("c".to_string())
+            .add_empty_child("d".to_string())
+            .end_child()
+            .add_empty_child("e".to_string())
+            .build();
+        let mut config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            branch: Style::default(),
+            separator: Style::default(),
+            leaf: Style::default(),
+            highlight: Style {
+                bold: true,
+                ..Style::default()
+            },
+            highlight_path: path_to(&tree, |item| item.text == "d"),
+            styled: StyleWhen::Always,
+            ..PrintConfig::default()
+        };
+        assert_eq!(config.highlight_path, Some(vec![1, 1]));
+
+        let lines: Vec<String> = tree_lines(&tree, &config)
+            .unwrap()
+            .iter()
+            .map(|line| {
+                line.segments()
+                    .iter()
+                    .map(|(style, text)| if style.bold { format!("[{}]", text) } else { text.clone() })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(lines, ["root", "[├]─ a", "[├─] b", "│  [├]─ c", "│  [└─] d", "└─ e"]);
+
+        // The path is not highlighted in unstyled output
+        config.styled = StyleWhen::Never;
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(str::from_utf8(&out).unwrap(), "root\n├─ a\n├─ b\n│  ├─ c\n│  └─ d\n└─ e\n");
+    }
+
+    #[test]
+    fn fold_markers() {
+        use builder::TreeBuilder;
+
+        let tree = TreeBuilder::new("root".to_string())
+            .begin_child("a".to_string())
+            .begin_child("b".to_string())
+            .add_empty_child("c".to_string())
+            .end_child()
+            .add_empty_child("d".to_string())
+            .end_child()
+            .add_empty_child("e".to_string())
+            .build();
+        let mut config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Never,
+            fold_markers: true,
+            depth: 2,
+            depth_marker: true,
+            ..PrintConfig::default()
+        };
+
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(
+            str::from_utf8(&out).unwrap(),
+            "\
+             [-] root\n\
+             ├─ [-] a\n\
+             │  ├─ [+] b\n\
+             │  │  └─ …\n\
+             │  └─ d\n\
+             └─ e\n"
+        );
+
+        config.right_to_left = true;
+        config.depth = 1;
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(
+            str::from_utf8(&out).unwrap(),
+            [
+                "root [-]",
+                "a [+] ─┤",
+                " … ─┘  │",
+                "    e ─┘",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn metadata_modes() {
+        use builder::TreeBuilder;
+
+        let tree = TreeBuilder::new("deps".to_string())
+            .begin_child("serde".to_string())
+            .add_metadata("version".to_string(), "1.0".to_string())
+            .add_metadata("features".to_string(), "derive".to_string())
+            .add_empty_child("serde_derive".to_string())
+            .end_child()
+            .begin_child("rand".to_string())
+            .add_metadata("version".to_string(), "0.8".to_string())
+            .end_child()
+            .build();
+        let mut config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Never,
+            ..PrintConfig::default()
+        };
+        let print = |config: &PrintConfig| {
+            let mut out = Vec::new();
+            write_tree_with(&tree, &mut out, config).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(print(&config), "deps\n├─ serde\n│  └─ serde_derive\n└─ rand\n");
+
+        config.metadata = MetadataMode::Inline;
+        assert_eq!(
+            print(&config),
+            "deps\n├─ serde features=derive version=1.0\n│  └─ serde_derive\n└─ rand version=0.8\n"
+        );
+
+        config.metadata = MetadataMode::Children;
+        assert_eq!(
+            print(&config),
+            "\
+             deps\n\
+             ├─ serde\n\
+             │  ├─ features=derive\n\
+             │  ├─ version=1.0\n\
+             │  └─ serde_derive\n\
+             └─ rand\n   \
+                └─ version=0.8\n"
+        );
+    }
+
+    #[test]
+    fn chunked_output() {
+        let config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Never,
+            ..PrintConfig::default()
+        };
+        let tree = StringItem {
+            text: "root".to_string(),
+            children: vec!["a", "漢字漢字", "b"]
+                .into_iter()
+                .map(|text| StringItem::new(text.to_string(), vec![]))
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut chunks = Vec::new();
+        write_tree_chunked(&tree, &config, 12, |chunk| {
+            chunks.push(chunk.to_string());
+            Ok(())
+        })
+        .unwrap();
+        // Each branch character takes 3 bytes, so the line of "漢字漢字" is split between characters
+        assert_eq!(chunks, ["root\n", "├─ a\n", "├─ 漢", "字漢字\n", "└─ b\n"]);
+
+        // Rendering stops at the first error of the callback
+        let mut calls = 0;
+        let result = write_tree_chunked(&tree, &config, 1, |_| {
+            calls += 1;
+            Err(io::Error::other("cancelled"))
+        });
+        assert_eq!(result.unwrap_err().to_string(), "cancelled");
+        assert_eq!(calls, 1);
+    }
+
+    /// Job with an optional class, such as "failed"
+    #[derive(Clone)]
+    struct Job(&'static str, &'static str, Vec<Job>);
+
+    impl TreeItem for Job {
+        type Child = Self;
+
+        fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+            style.paint_to(f, format_args!("{}", self.0))
+        }
+
+        fn class(&self) -> Option<&str> {
+            Some(self.1).filter(|class| !class.is_empty())
+        }
+
+        fn children(&self) -> Cow<'_, [Self]> {
+            Cow::from(&self.2[..])
+        }
+    }
+
+    #[test]
+    fn legend_of_used_classes() {
+        let red = Style {
+            foreground: Some(Color::Red),
+            ..Style::default()
+        };
+        let dimmed = Style {
+            dimmed: true,
+            ..Style::default()
+        };
+        let mut config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            leaf: Style::default(),
+            branch: Style::default(),
+            separator: Style::default(),
+            styled: StyleWhen::Always,
+            legend: true,
+            ..PrintConfig::default()
+        };
+        config.classes.insert("failed".to_string(), red.clone());
+        config.classes.insert("skipped".to_string(), dimmed.clone());
+        let tree = Job(
+            "build",
+            "",
+            vec![
+                Job("test", "failed", vec![]),
+                Job("docs", "skipped", vec![]),
+                Job("lint", "failed", vec![]),
+            ],
+        );
+
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        let expected = format!(
+            "build\n├─ {}\n├─ {}\n└─ {}\n\nLegend: {} {}\n",
+            red.paint("test"),
+            dimmed.paint("docs"),
+            red.paint("lint"),
+            red.paint("failed"),
+            dimmed.paint("skipped")
+        );
+        assert_eq!(str::from_utf8(&out).unwrap(), expected);
+
+        config.styled = StyleWhen::Never;
+        let mut out = Vec::new();
+        write_tree_with(&tree, &mut out, &config).unwrap();
+        assert_eq!(str::from_utf8(&out).unwrap(), "build\n├─ test\n├─ docs\n└─ lint\n");
+    }
+
+    /// Directory listing in which reading the directory named "locked" fails
+    #[derive(Clone)]
+    struct Listing(&'static str, Vec<Listing>);
+
+    impl TryTreeItem for Listing {
+        type Child = Self;
+        type Error = io::Error;
+
+        fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+            style.paint_to(f, format_args!("{}", self.0))
+        }
+
+        fn try_children(&self) -> Result<Cow<'_, [Self]>, io::Error> {
+            if self.0 == "locked" {
+                return Err(io::ErrorKind::PermissionDenied.into());
+            }
+            Ok(Cow::from(&self.1[..]))
+        }
+    }
+
+    #[test]
+    fn try_write_tree_errors() {
+        let config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Never,
+            ..PrintConfig::default()
+        };
+        let open = Listing(
+            "root",
+            vec![Listing("a", vec![Listing("b", vec![])]), Listing("c", vec![])],
+        );
+        let mut out = Vec::new();
+        try_write_tree_with(&open, &mut out, &config).unwrap();
+        assert_eq!(str::from_utf8(&out).unwrap(), "root\n├─ a\n│  └─ b\n└─ c\n");
+
+        let locked = Listing(
+            "root",
+            vec![Listing("a", vec![Listing("locked", vec![])]), Listing("c", vec![])],
+        );
+        let mut out = Vec::new();
+        match try_write_tree_with(&locked, &mut out, &config) {
+            Err(TryWriteError::Children { path, error }) => {
+                assert_eq!(path, vec![0, 0]);
+                assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+            }
+            _ => panic!("reading the locked directory fails"),
+        }
+        assert!(str::from_utf8(&out).unwrap().starts_with("root\n├─ a\n│  └─ locked\n"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn write_tree_async_output() {
+        use std::task::Waker;
+
+        let config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Never,
+            ..PrintConfig::default()
+        };
+        let tree = StringItem::new("root".to_string(), vec![StringItem::new("child".to_string(), vec![])]);
+
+        // Vec<u8> never returns Poll::Pending, so no runtime is needed
+        let mut out = Vec::new();
+        let mut future = write_tree_with_async(&tree, &mut out, &config);
+        let result = Pin::new(&mut future).poll(&mut Context::from_waker(Waker::noop()));
+        assert!(matches!(result, Poll::Ready(Ok(()))));
+        assert_eq!(str::from_utf8(&out).unwrap(), "root\n└─ child\n");
+    }
 }
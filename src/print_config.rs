@@ -4,16 +4,29 @@
 
 #[cfg(feature = "conf")]
 use config;
-#[cfg(feature = "conf")]
+#[cfg(all(feature = "conf", not(target_arch = "wasm32")))]
 use directories::BaseDirs;
 
-#[cfg(feature = "ansi")]
+#[cfg(all(
+    any(feature = "ansi", feature = "nu-ansi-term", feature = "termcolor"),
+    not(feature = "crossterm"),
+    not(target_arch = "wasm32")
+))]
 use atty::Stream;
+#[cfg(feature = "crossterm")]
+use crossterm::tty::IsTty;
 
-use style::Style;
+use style::{Color, ColorDepth, Style};
+use term;
 
+#[cfg(feature = "conf")]
 use std::env;
+#[cfg(feature = "crossterm")]
+use std::io;
 use std::fmt::{self, Display};
+use std::collections::BTreeMap;
+#[cfg(feature = "conf")]
+use std::collections::BTreeSet;
 use std::marker::PhantomData;
 use std::str::FromStr;
 
@@ -36,6 +49,63 @@ pub enum StyleWhen {
     Tty,
 }
 
+///
+/// Configuration option controlling how tab characters in item text are printed
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TabMode {
+    /// Replace each tab with [`PrintConfig::tab_width`] spaces
+    ///
+    /// [`PrintConfig::tab_width`]: struct.PrintConfig.html#structfield.tab_width
+    Expand,
+    /// Replace each tab with the escape sequence `\t`
+    Escape,
+    /// Print tabs unchanged, leaving their width up to the terminal
+    Keep,
+}
+
+///
+/// Configuration option controlling how newlines in item text are printed
+///
+/// Carriage returns are escaped as `\r` along with newlines, and removed otherwise.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NewlineMode {
+    /// Replace each newline with the escape sequence `\n`, so every item takes a single line
+    Escape,
+    /// Continue the text on the next line, indented like the children of the item
+    ///
+    /// Continuation lines are written in the style of the item text.
+    Split,
+    /// Print only the text before the first newline
+    Truncate,
+}
+
+///
+/// Configuration option controlling how the metadata of items is printed
+///
+/// Metadata is returned by [`TreeItem::metadata`], such as the entries of [`StringItem::metadata`].
+///
+/// [`TreeItem::metadata`]: ../item/trait.TreeItem.html#method.metadata
+/// [`StringItem::metadata`]: ../item/struct.StringItem.html#structfield.metadata
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataMode {
+    /// Do not print metadata
+    Hidden,
+    /// Print each entry as `key=value` after the text of the item, in the same style
+    Inline,
+    /// Print each entry as a `key=value` line above the children of the item
+    ///
+    /// The lines are printed like children, but are not counted by [`PrintConfig::max_children`].
+    ///
+    /// [`PrintConfig::max_children`]: struct.PrintConfig.html#structfield.max_children
+    Children,
+}
+
 ///
 /// Structure controlling the print output formatting
 ///
@@ -46,6 +116,24 @@ pub struct PrintConfig {
     ///
     /// The default is infinity, i.e. there is no recursion limit.
     pub depth: u32,
+    /// Follow items at the maximum [`depth`] which have children by an ellipsis marker
+    ///
+    /// The marker is printed with the [`omitted`] style.
+    /// Finding out whether an item has children requires retrieving them,
+    /// which can be expensive for items which read their children lazily, such as directories.
+    /// The default value is `false`.
+    ///
+    /// [`depth`]: #structfield.depth
+    /// [`omitted`]: #structfield.omitted
+    pub depth_marker: bool,
+    /// Maximum number of children printed for each item
+    ///
+    /// Further children are replaced by a single "… and N more" marker,
+    /// printed with the [`omitted`] style.
+    /// The default value is `None`, i.e. all children are printed.
+    ///
+    /// [`omitted`]: #structfield.omitted
+    pub max_children: Option<usize>,
     /// Indentation size. The default value is 3.
     pub indent: usize,
     /// Padding size. The default value is 1.
@@ -54,20 +142,211 @@ pub struct PrintConfig {
     ///
     /// The default value is [`StyleWhen::Tty`], meaning that ANSI styles are only used for printing to the standard
     /// output, and only when the standard output is a TTY.
+    ///
+    /// When output is not styled, escape sequences written by items themselves are removed as well.
     pub styled: StyleWhen,
+    /// Number of colors supported by the terminal.
+    ///
+    /// RGB colors are converted to the nearest available color when styling output.
+    /// The default value is [`ColorDepth::Auto`], which detects the color depth with [`term::color_depth`].
+    ///
+    /// [`term::color_depth`]: ../term/fn.color_depth.html
+    pub color_depth: ColorDepth,
     /// Characters used to print indentation lines or "branches" of the tree
+    ///
+    /// The default value is [`UTF_CHARS`].
+    /// [`PrintConfig::for_terminal`] uses [`ASCII_CHARS_TICK`] instead if the terminal does not support unicode.
+    ///
+    /// [`PrintConfig::for_terminal`]: #method.for_terminal
     #[serde(deserialize_with = "string_or_struct")]
     pub characters: IndentChars,
     /// ANSI style used for printing the indentation lines ("branches")
     pub branch: Style,
     /// ANSI style used for printing the item text ("leaves")
     pub leaf: Style,
+    /// ANSI style used for printing the padding between the indentation lines and the item text
+    ///
+    /// By default, this is the same as the default branch style.
+    pub separator: Style,
+    /// ANSI style used for printing the markers of omitted items
+    ///
+    /// These are the markers added by [`depth_marker`] and [`max_children`].
+    /// The default style is dimmed and italic.
+    ///
+    /// [`depth_marker`]: #structfield.depth_marker
+    /// [`max_children`]: #structfield.max_children
+    pub omitted: Style,
+    /// ANSI style used for printing edge labels in graph output
+    ///
+    /// This style is used by [`graph::write_graph_with_edges`] unless [`GraphConfig::edge_style`] is set.
+    /// The default style is dimmed, so edge labels stand back from the nodes.
+    ///
+    /// [`graph::write_graph_with_edges`]: ../graph/fn.write_graph_with_edges.html
+    /// [`GraphConfig::edge_style`]: ../graph/struct.GraphConfig.html#structfield.edge_style
+    pub edge: Style,
+    /// ANSI styles of item classes, by class name
+    ///
+    /// Items which report a class through [`TreeItem::class`] are printed with its style,
+    /// merged into the leaf or branch style.
+    /// The default map is empty. [`PrintConfig::default_classes`] returns styles for the scalars of value trees.
+    ///
+    /// [`TreeItem::class`]: ../item/trait.TreeItem.html#method.class
+    /// [`PrintConfig::default_classes`]: #method.default_classes
+    pub classes: BTreeMap<String, Style>,
+    /// Width of the output lines in terminal columns, if known.
+    ///
+    /// The default value is `None`.
+    pub line_width: Option<usize>,
+    /// Extend the background color of the leaf style to the end of the line.
+    ///
+    /// Lines are padded with spaces up to [`line_width`], which has to be set for this to take effect.
+    /// This way, items with a background color are displayed as bars across the output.
+    /// The default value is `false`.
+    ///
+    /// [`line_width`]: #structfield.line_width
+    pub fill_background: bool,
+    /// Print a legend of the item classes after the tree.
+    ///
+    /// The legend names the [`classes`] of the printed items, each written in the style of its items,
+    /// so it always matches the output.
+    /// It is only printed if the output is styled, and if any printed item has a styled class.
+    /// The default value is `false`.
+    ///
+    /// [`classes`]: #structfield.classes
+    pub legend: bool,
+    /// How tab characters in item text are printed.
+    ///
+    /// Terminals move to the next tab stop, which depends on the column of the tab and not on the text before it,
+    /// so raw tabs can shift the text of some items more than that of others.
+    /// The default value is [`TabMode::Expand`].
+    ///
+    /// [`TabMode::Expand`]: enum.TabMode.html#variant.Expand
+    pub tabs: TabMode,
+    /// Number of spaces replacing each tab if [`tabs`] is [`TabMode::Expand`]. The default value is 4.
+    ///
+    /// [`tabs`]: #structfield.tabs
+    /// [`TabMode::Expand`]: enum.TabMode.html#variant.Expand
+    pub tab_width: usize,
+    /// How newlines in item text are printed.
+    ///
+    /// Written unchanged, they would start lines without the branches of the tree.
+    /// The default value is [`NewlineMode::Split`].
+    ///
+    /// [`NewlineMode::Split`]: enum.NewlineMode.html#variant.Split
+    pub newline_mode: NewlineMode,
+    /// Mirror the tree, with the branches on the right and the root at the right edge.
+    ///
+    /// This suits items in right-to-left scripts, such as Arabic and Hebrew.
+    /// Branch characters are replaced by their mirror images, such as `┤` for `├`,
+    /// and lines are aligned to the right, which requires measuring the tree before printing it.
+    /// For this, the printed part of the tree is copied into [`StringItem`]s, which keep the text and metadata
+    /// of the items but not their classes and branch characters.
+    /// Trees written by [`StreamWriter`] are mirrored but not aligned, as their width is not known in advance.
+    /// The text of items is written unchanged, as its direction is up to the terminal.
+    /// Newlines in item text are escaped even if [`newline_mode`] is [`NewlineMode::Split`].
+    /// The default value is `false`.
+    ///
+    /// [`StringItem`]: ../item/struct.StringItem.html
+    /// [`StreamWriter`]: ../output/struct.StreamWriter.html
+    /// [`newline_mode`]: #structfield.newline_mode
+    /// [`NewlineMode::Split`]: enum.NewlineMode.html#variant.Split
+    pub right_to_left: bool,
+    /// Print `[-]` in front of items whose children are shown and `[+]` in front of items whose children are hidden.
+    ///
+    /// Children are hidden at the maximum [`depth`]. Items without children have no marker,
+    /// and neither has the root of a [`StreamWriter`], whose children are not known when it is printed.
+    /// Tools which turn the output into foldable views can find the foldable items by their markers.
+    /// The markers are written in the branch style.
+    /// The default value is `false`.
+    ///
+    /// [`depth`]: #structfield.depth
+    /// [`StreamWriter`]: ../output/struct.StreamWriter.html
+    pub fold_markers: bool,
+    /// Index path of an item whose ancestor branches are highlighted, if any.
+    ///
+    /// The path contains the indices of the item and its ancestors among their siblings, starting below the root,
+    /// which has an empty path. [`item::path_to`] finds the path of the first item matching a predicate.
+    /// The branch characters leading from the root down to the item are printed with the [`highlight`] style,
+    /// like a breadcrumb inside the full tree.
+    /// Highlighting only takes effect if the output is styled.
+    /// The default value is `None`.
+    ///
+    /// [`item::path_to`]: ../item/fn.path_to.html
+    /// [`highlight`]: #structfield.highlight
+    pub highlight_path: Option<Vec<usize>>,
+    /// ANSI style used for printing the branches leading to the item at [`highlight_path`]
+    ///
+    /// The default style is bold and yellow.
+    ///
+    /// [`highlight_path`]: #structfield.highlight_path
+    pub highlight: Style,
+    /// How the metadata of items is printed.
+    ///
+    /// The default value is [`MetadataMode::Hidden`].
+    ///
+    /// [`MetadataMode::Hidden`]: enum.MetadataMode.html#variant.Hidden
+    pub metadata: MetadataMode,
+}
+
+///
+/// Returns the configuration keys containing an underscore,
+/// which must not be split into nested keys when reading environment variables
+///
+/// The keys are taken from the serialized default configuration,
+/// so they include the fields of nested values such as styles and characters.
+///
+#[cfg(feature = "conf")]
+fn multi_word_keys() -> BTreeSet<String> {
+    fn collect(value: config::Value, keys: &mut BTreeSet<String>) {
+        if let Ok(table) = value.into_table() {
+            for (key, value) in table {
+                collect(value, keys);
+                if key.contains('_') {
+                    keys.insert(key);
+                }
+            }
+        }
+    }
+
+    let mut keys = BTreeSet::new();
+    if let Ok(value) = config::Config::try_from(&PrintConfig::default()).and_then(config::Config::try_into) {
+        collect(value, &mut keys);
+    }
+    keys
+}
+
+///
+/// Converts the name of an environment variable, without the `PTREE_` prefix, into a configuration key
+///
+/// Words separated by underscores become nested keys,
+/// except for the words of one of the `multi_word_keys` returned by [`multi_word_keys`].
+/// For example, `LEAF_UNDERLINE_COLOR` becomes `leaf.underline_color`.
+///
+#[cfg(feature = "conf")]
+fn env_key(name: &str, multi_word_keys: &BTreeSet<String>) -> String {
+    let name = name.to_lowercase();
+    let words: Vec<&str> = name.split('_').collect();
+
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let len = (2..=words.len() - i)
+            .rev()
+            .find(|&n| multi_word_keys.contains(&words[i..i + n].join("_")))
+            .unwrap_or(1);
+        parts.push(words[i..i + len].join("_"));
+        i += len;
+    }
+
+    parts.join(".")
 }
 
 impl Default for PrintConfig {
     fn default() -> PrintConfig {
         PrintConfig {
-            depth: u32::max_value(),
+            depth: u32::MAX,
+            depth_marker: false,
+            max_children: None,
             indent: 3,
             padding: 1,
             characters: UTF_CHARS.into(),
@@ -76,7 +355,37 @@ impl Default for PrintConfig {
                 ..Style::default()
             },
             leaf: Style::default(),
+            separator: Style {
+                dimmed: true,
+                ..Style::default()
+            },
+            omitted: Style {
+                dimmed: true,
+                italic: true,
+                ..Style::default()
+            },
+            edge: Style {
+                dimmed: true,
+                ..Style::default()
+            },
             styled: StyleWhen::Tty,
+            color_depth: ColorDepth::Auto,
+            classes: BTreeMap::new(),
+            line_width: None,
+            fill_background: false,
+            legend: false,
+            tabs: TabMode::Expand,
+            tab_width: 4,
+            newline_mode: NewlineMode::Split,
+            right_to_left: false,
+            fold_markers: false,
+            metadata: MetadataMode::Hidden,
+            highlight_path: None,
+            highlight: Style {
+                foreground: Some(Color::Yellow),
+                bold: true,
+                ..Style::default()
+            },
         }
     }
 }
@@ -96,26 +405,84 @@ pub enum OutputKind {
 }
 
 impl PrintConfig {
+    ///
+    /// Returns styles for the classes of scalars in value trees, which color them like `jq` does
+    ///
+    /// `number` is cyan, `string` is green, `boolean` is yellow and `null` is dimmed.
+    /// The map can be assigned to [`classes`] to style the output of [`ValueItem::type_classes`].
+    ///
+    /// [`classes`]: #structfield.classes
+    /// [`ValueItem::type_classes`]: ../value/struct.ValueItem.html#method.type_classes
+    pub fn default_classes() -> BTreeMap<String, Style> {
+        let colored = |color| Style {
+            foreground: Some(color),
+            ..Style::default()
+        };
+        let mut classes = BTreeMap::new();
+        classes.insert("number".to_string(), colored(Color::Cyan));
+        classes.insert("string".to_string(), colored(Color::Green));
+        classes.insert("boolean".to_string(), colored(Color::Yellow));
+        classes.insert(
+            "null".to_string(),
+            Style {
+                dimmed: true,
+                ..Style::default()
+            },
+        );
+        classes
+    }
 
     /// Try to instantiate PrintConfig from environment
     ///
     /// Only available with feature "config"
     #[cfg(feature = "conf")]
     fn try_from_env() -> Option<PrintConfig> {
-        let mut settings = config::Config::default();
+        Self::env_settings()?.try_into().ok()
+    }
+
+    /// Reads the configuration file and environment variables, without converting them into a `PrintConfig`
+    #[cfg(feature = "conf")]
+    fn env_settings() -> Option<config::Config> {
+        let mut file = config::Config::default();
 
         if let Ok(p) = env::var("PTREE_CONFIG") {
-            settings.merge(config::File::with_name(&p)).ok()?;
+            file.merge(config::File::with_name(&p)).ok()?;
         } else {
-            let f = BaseDirs::new()?.config_dir().join("ptree");
-            settings.merge(config::File::with_name(f.to_str()?)).ok()?;
+            // WebAssembly has no configuration directory
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let f = BaseDirs::new()?.config_dir().join("ptree");
+                file.merge(config::File::with_name(f.to_str()?)).ok()?;
+            }
         }
 
-        settings
-            .merge(config::Environment::with_prefix("PTREE").separator("_"))
-            .ok()?;
+        let mut settings = config::Config::default();
+        settings.merge(file.clone()).ok()?;
+
+        // The section for the current platform, such as `[unix]` or `[windows]`, overrides the base values
+        if let Ok(section) = file.get_table(env::consts::FAMILY) {
+            let mut platform = config::Config::default();
+            for (key, value) in section {
+                platform.set(&key, value).ok()?;
+            }
+            settings.merge(platform).ok()?;
+        }
 
-        Some(settings.try_into().ok()?)
+        let multi_word_keys = multi_word_keys();
+        for (name, value) in env::vars_os() {
+            let (name, value) = match (name.into_string(), value.into_string()) {
+                (Ok(name), Ok(value)) => (name, value),
+                _ => continue,
+            };
+            match name.strip_prefix("PTREE_") {
+                Some("CONFIG") | None => {}
+                Some(name) => {
+                    settings.set(&env_key(name, &multi_word_keys), value).ok()?;
+                }
+            }
+        }
+
+        Some(settings)
     }
 
     ///
@@ -128,6 +495,11 @@ impl PrintConfig {
     /// Otherwise, any file with a stem of `ptree` inside the directory returned by [`config_dir`]
     /// is used.
     ///
+    /// The configuration file may contain `windows` and `unix` sections,
+    /// which take the same fields as the configuration itself.
+    /// The section for the current platform is merged over the other values of the file,
+    /// so one file can be shared between machines.
+    ///
     /// Finally, environment variables may be used to override the values from the configuration file.
     /// For every field of the `PrintConfig` structure, the corresponding environment variable name
     /// is PTREE_<FIELD_NAME>, for example `PTREE_INDENT=4` sets the `indent` field to 4.
@@ -135,16 +507,30 @@ impl PrintConfig {
     ///
     /// ### Field values
     ///
-    /// [`indent`] and [`depth`] accept non-negative integers.
+    /// [`indent`], [`depth`], [`max_children`], [`line_width`] and [`tab_width`] accept non-negative integers.
+    ///
+    /// [`fill_background`], [`legend`], [`right_to_left`] and [`fold_markers`] are boolean parameters, with values parsed like the boolean `Style` fields below.
     ///
     /// [`styled`] accepts either `"always"`, `"tty"` or `"never"`
     ///
-    /// [`leaf`] and [`branch`] accept a `Style` structure.
+    /// [`tabs`] accepts either `"expand"`, `"escape"` or `"keep"`.
+    ///
+    /// [`newline_mode`] accepts either `"escape"`, `"split"` or `"truncate"`.
+    ///
+    /// [`metadata`] accepts either `"hidden"`, `"inline"` or `"children"`.
+    ///
+    /// [`color_depth`] accepts either `"auto"`, `"truecolor"`, `"ansi256"` or `"ansi16"`.
+    /// It is set with the `PTREE_COLOR_DEPTH` environment variable.
+    ///
+    /// [`highlight_path`] accepts an array of non-negative integers, which can only be set in a configuration file.
+    ///
+    /// [`leaf`], [`branch`], [`separator`], [`omitted`], [`edge`] and [`highlight`] accept a `Style` structure.
     /// In a configuration file, this takes a form of a map.
     /// Using environment variables, each field has to be set separately.
     ///
     /// Color fields accept either an ANSI named color, a named web color, a hex code like "#33ffbb",
     /// an ANSI integer fixed color, or a [red, green, blue] triple of non-negative integers.
+    /// Named colors are resolved while loading the configuration, and unknown names are treated as errors.
     ///
     /// Other `Style` fields are boolean parameters.
     /// In a configuration file, they are parsed according to the rules of the deserialization format.
@@ -170,6 +556,9 @@ impl PrintConfig {
     /// [leaf]
     /// foreground = "MediumSeaGreen"
     /// background = "#102018"
+    ///
+    /// [windows]
+    /// characters = "ascii"
     /// ```
     ///
     /// ### Errors
@@ -178,21 +567,68 @@ impl PrintConfig {
     /// If anything goes wrong while loading the configuration parameters, a default `PrintConfig` is returned.
     #[cfg(feature = "conf")]
     pub fn from_env() -> PrintConfig {
-        Self::try_from_env().unwrap_or_else(Default::default)
+        Self::try_from_env().unwrap_or_default()
     }
+
+    ///
+    /// Load print configuration from a configuration file or environment variables
+    ///
+    /// Without the `"conf"` feature, this always returns the default configuration.
+    ///
     #[cfg(not(feature = "conf"))]
     pub fn from_env() -> PrintConfig {
         Default::default()
     }
 
+    ///
+    /// Load print configuration like [`from_env`], for printing to the terminal
+    ///
+    /// Unless [`characters`] are configured, they are set to [`ASCII_CHARS_TICK`]
+    /// if [`term::supports_unicode`] finds that the terminal cannot display the default [`UTF_CHARS`].
+    /// This is used by the functions which print to standard output, such as [`print_tree`],
+    /// while output written elsewhere does not depend on the terminal.
+    ///
+    /// [`from_env`]: #method.from_env
+    /// [`characters`]: #structfield.characters
+    /// [`term::supports_unicode`]: ../term/fn.supports_unicode.html
+    /// [`print_tree`]: ../output/fn.print_tree.html
+    pub fn for_terminal() -> PrintConfig {
+        #[cfg(feature = "conf")]
+        let (mut config, configured) = match Self::env_settings() {
+            Some(settings) => {
+                let configured = settings.get::<config::Value>("characters").is_ok();
+                (settings.try_into().unwrap_or_default(), configured)
+            }
+            None => (PrintConfig::default(), false),
+        };
+        #[cfg(not(feature = "conf"))]
+        let (mut config, configured) = (PrintConfig::default(), false);
+
+        if !configured && !term::supports_unicode() {
+            config.characters = ASCII_CHARS_TICK.into();
+        }
+        config
+    }
+
     ///
     /// Checks if output to a writer should be styled
     ///
     pub fn should_style_output(&self, output_kind: OutputKind) -> bool {
-        if cfg!(feature = "ansi") {
+        if cfg!(any(
+            feature = "ansi",
+            feature = "nu-ansi-term",
+            feature = "termcolor",
+            feature = "crossterm"
+        )) {
             match (self.styled, output_kind) {
                 (StyleWhen::Always, _) => true,
-                #[cfg(feature = "ansi")]
+                #[cfg(feature = "crossterm")]
+                (StyleWhen::Tty, OutputKind::Stdout) => io::stdout().is_tty(),
+                #[cfg(all(
+                    any(feature = "ansi", feature = "nu-ansi-term", feature = "termcolor"),
+                    not(feature = "crossterm"),
+                    not(target_arch = "wasm32")
+                ))]
                 (StyleWhen::Tty, OutputKind::Stdout) => atty::is(Stream::Stdout),
                 _ => false,
             }
@@ -229,6 +665,9 @@ fn get_default_empty_string() -> String {
 ///
 /// Set of characters use to draw indentation lines (branches)
 ///
+/// Characters wider than one terminal column, such as emoji or fullwidth forms, are supported.
+/// The indentation is measured in columns, so the text of children stays aligned.
+///
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IndentChars {
     /// Character for pointing down and right (`├`).
@@ -244,6 +683,69 @@ pub struct IndentChars {
     pub empty: String,
 }
 
+impl IndentChars {
+    ///
+    /// Returns the mirror images of these characters, for trees with branches on the right
+    ///
+    /// Box-drawing characters are replaced by their mirrored counterparts, such as `┤` for `├`,
+    /// and the characters of each string are reversed.
+    ///
+    /// ```
+    /// # use ptree::print_config::{IndentChars, UTF_CHARS};
+    /// let mirrored = IndentChars::from(UTF_CHARS).mirrored();
+    /// assert_eq!(mirrored.down_and_right, "┤");
+    /// assert_eq!(mirrored.turn_right, "┘");
+    /// assert_eq!(mirrored.down, "│");
+    /// ```
+    pub fn mirrored(&self) -> IndentChars {
+        let mirror = |s: &str| s.chars().rev().map(mirror_glyph).collect();
+        IndentChars {
+            down_and_right: mirror(&self.down_and_right),
+            down: mirror(&self.down),
+            turn_right: mirror(&self.turn_right),
+            right: mirror(&self.right),
+            empty: mirror(&self.empty),
+        }
+    }
+}
+
+///
+/// Returns the mirror image of a branch character, or the character itself if it is symmetric
+///
+pub(crate) fn mirror_glyph(c: char) -> char {
+    match c {
+        '├' => '┤',
+        '┤' => '├',
+        '└' => '┘',
+        '┘' => '└',
+        '┌' => '┐',
+        '┐' => '┌',
+        '┣' => '┫',
+        '┫' => '┣',
+        '┗' => '┛',
+        '┛' => '┗',
+        '╠' => '╣',
+        '╣' => '╠',
+        '╚' => '╝',
+        '╝' => '╚',
+        '╰' => '╯',
+        '╯' => '╰',
+        '`' => '\'',
+        '\'' => '`',
+        '/' => '\\',
+        '\\' => '/',
+        '<' => '>',
+        '>' => '<',
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        c => c,
+    }
+}
+
 impl From<StaticIndentChars> for IndentChars {
     fn from(s: StaticIndentChars) -> IndentChars {
         IndentChars {
@@ -425,6 +927,19 @@ mod tests {
         static ref ENV_MUTEX: Mutex<()> = Mutex::new(());
     }
 
+    #[test]
+    #[cfg(feature = "conf")]
+    fn env_keys() {
+        let keys = multi_word_keys();
+        assert_eq!(env_key("INDENT", &keys), "indent");
+        assert_eq!(env_key("BRANCH_FOREGROUND", &keys), "branch.foreground");
+        assert_eq!(env_key("COLOR_DEPTH", &keys), "color_depth");
+        assert_eq!(env_key("HIGHLIGHT_PATH", &keys), "highlight_path");
+        assert_eq!(env_key("HIGHLIGHT_EXTRA_CODES", &keys), "highlight.extra_codes");
+        assert_eq!(env_key("LEAF_UNDERLINE_COLOR", &keys), "leaf.underline_color");
+        assert_eq!(env_key("CHARACTERS_DOWN_AND_RIGHT", &keys), "characters.down_and_right");
+    }
+
     #[cfg(feature = "conf")]
     fn load_config_from_path(path: &str) -> PrintConfig {
         env::set_var("PTREE_CONFIG", path);
@@ -446,7 +961,7 @@ mod tests {
 
         let config = load_config_from_path(path);
         assert_eq!(config.indent, 7);
-        assert_eq!(config.branch.foreground, Some(Color::Named("maroon".to_string())));
+        assert_eq!(config.branch.foreground, Some(Color::RGB(128, 0, 0)));
         assert_eq!(config.branch.background, None);
 
         fs::remove_file(path).unwrap();
@@ -468,14 +983,67 @@ mod tests {
 
         let config = load_config_from_path(path);
         assert_eq!(config.indent, 5);
-        assert_eq!(config.leaf.foreground, Some(Color::Named("green".to_string())));
-        assert_eq!(config.leaf.background, Some(Color::Named("steelblue".to_string())));
+        assert_eq!(config.leaf.foreground, Some(Color::Green));
+        assert_eq!(config.leaf.background, Some(Color::RGB(70, 130, 180)));
         assert_eq!(config.branch.foreground, None);
         assert_eq!(config.branch.background, None);
 
         fs::remove_file(path).unwrap();
     }
 
+    #[test]
+    #[cfg(all(feature = "conf", not(windows)))]
+    fn terminal_characters() {
+        let _g = ENV_MUTEX.lock().unwrap();
+        let locale = env::var_os("LC_ALL");
+        env::set_var("LC_ALL", "C");
+
+        assert_eq!(PrintConfig::default().characters, IndentChars::from(UTF_CHARS));
+        assert_eq!(PrintConfig::for_terminal().characters, IndentChars::from(ASCII_CHARS_TICK));
+
+        let path = "ptree-characters.toml";
+        fs::write(path, "characters = \"utf\"\n").unwrap();
+        env::set_var("PTREE_CONFIG", path);
+        assert_eq!(PrintConfig::for_terminal().characters, IndentChars::from(UTF_CHARS));
+        env::remove_var("PTREE_CONFIG");
+        fs::remove_file(path).unwrap();
+
+        match locale {
+            Some(locale) => env::set_var("LC_ALL", locale),
+            None => env::remove_var("LC_ALL"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "conf")]
+    fn load_platform_section() {
+        let _g = ENV_MUTEX.lock().unwrap();
+        let path = "ptree_platform.toml";
+        {
+            let mut f = File::create(path).unwrap();
+            writeln!(
+                f,
+                "indent = 5\n[leaf]\nforeground = \"green\"\nbold = true\n\
+                 [unix]\nindent = 6\n[unix.leaf]\nforeground = \"red\"\n\
+                 [windows]\nindent = 7\ncharacters = \"ascii\"\n"
+            )
+            .unwrap();
+        }
+
+        let config = load_config_from_path(path);
+        if cfg!(windows) {
+            assert_eq!(config.indent, 7);
+            assert_eq!(config.characters, ASCII_CHARS_TICK.into());
+            assert_eq!(config.leaf.foreground, Some(Color::Green));
+        } else {
+            assert_eq!(config.indent, 6);
+            assert_eq!(config.leaf.foreground, Some(Color::Red));
+        }
+        assert!(config.leaf.bold);
+
+        fs::remove_file(path).unwrap();
+    }
+
     #[test]
     #[cfg(feature = "conf")]
     fn load_env() {
@@ -489,19 +1057,43 @@ mod tests {
         env::set_var("PTREE_LEAF_BACKGROUND", "steelblue");
         env::set_var("PTREE_LEAF_BOLD", "true");
         env::set_var("PTREE_DEPTH", "4");
+        env::set_var("PTREE_COLOR_DEPTH", "ansi256");
+        env::set_var("PTREE_LINE_WIDTH", "80");
+        env::set_var("PTREE_FILL_BACKGROUND", "on");
+        env::set_var("PTREE_LEAF_UNDERLINE", "true");
+        env::set_var("PTREE_LEAF_UNDERLINE_COLOR", "blue");
+        env::set_var("PTREE_TABS", "escape");
+        env::set_var("PTREE_TAB_WIDTH", "8");
+        env::set_var("PTREE_NEWLINE_MODE", "truncate");
 
         let config = load_config_from_path(path);
         assert_eq!(config.indent, 5);
         assert_eq!(config.depth, 4);
-        assert_eq!(config.leaf.foreground, Some(Color::Named("green".to_string())));
-        assert_eq!(config.leaf.background, Some(Color::Named("steelblue".to_string())));
-        assert_eq!(config.leaf.bold, true);
+        assert_eq!(config.color_depth, ColorDepth::Ansi256);
+        assert_eq!(config.line_width, Some(80));
+        assert!(config.fill_background);
+        assert_eq!(config.tabs, TabMode::Escape);
+        assert_eq!(config.tab_width, 8);
+        assert_eq!(config.newline_mode, NewlineMode::Truncate);
+        assert!(config.leaf.underline);
+        assert_eq!(config.leaf.underline_color, Some(Color::Blue));
+        assert_eq!(config.leaf.foreground, Some(Color::Green));
+        assert_eq!(config.leaf.background, Some(Color::RGB(70, 130, 180)));
+        assert!(config.leaf.bold);
         assert_eq!(config.branch.foreground, None);
         assert_eq!(config.branch.background, None);
 
         env::remove_var("PTREE_LEAF_BACKGROUND");
         env::remove_var("PTREE_LEAF_BOLD");
         env::remove_var("PTREE_DEPTH");
+        env::remove_var("PTREE_COLOR_DEPTH");
+        env::remove_var("PTREE_LINE_WIDTH");
+        env::remove_var("PTREE_FILL_BACKGROUND");
+        env::remove_var("PTREE_LEAF_UNDERLINE");
+        env::remove_var("PTREE_LEAF_UNDERLINE_COLOR");
+        env::remove_var("PTREE_TABS");
+        env::remove_var("PTREE_TAB_WIDTH");
+        env::remove_var("PTREE_NEWLINE_MODE");
 
         fs::remove_file(path).unwrap();
     }
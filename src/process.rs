@@ -0,0 +1,354 @@
+//!
+//! Printing of process trees
+//!
+//! A [`ProcessItem`] lists the child processes of a process as its children, like `pstree -p`.
+//! Each process is labeled with its name and pid, such as `bash(1234)`,
+//! optionally followed by its CPU time and resident memory, such as `bash(1234)  [cpu 2.5s, mem 4.2M]`.
+//!
+//! On Linux, processes are read from `/proc`. On other systems, they are read with the [`sysinfo`] crate.
+//!
+//! [`ProcessItem`]: struct.ProcessItem.html
+//! [`sysinfo`]: https://docs.rs/sysinfo/0.37/sysinfo/
+//!
+
+use fs::human_size;
+use item::TreeItem;
+use output::{print_tree_with, write_tree_with};
+use print_config::PrintConfig;
+use style::Style;
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io;
+use std::rc::Rc;
+use std::time::Duration;
+
+#[cfg(target_os = "linux")]
+use std::fs;
+
+#[cfg(not(target_os = "linux"))]
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+///
+/// Information about a running process
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Process {
+    /// Id of the process
+    pub pid: u32,
+    /// Id of the parent process, if it has one
+    pub parent: Option<u32>,
+    /// Name of the executable of the process
+    pub name: String,
+    /// CPU time used by the process, in user and kernel mode
+    pub cpu_time: Option<Duration>,
+    /// Resident memory of the process, in bytes
+    pub memory: Option<u64>,
+}
+
+///
+/// Structure controlling how process trees are printed
+///
+#[derive(Clone, Debug, Default)]
+pub struct ProcConfig {
+    /// Whether the CPU time of each process is printed after its name
+    pub show_cpu: bool,
+    /// Whether the resident memory of each process is printed after its name
+    pub show_memory: bool,
+}
+
+/// State shared by all items of one printed process tree
+#[derive(Debug)]
+struct ProcState {
+    config: ProcConfig,
+    processes: BTreeMap<u32, Process>,
+    /// Pids of the children of each process, sorted
+    children: BTreeMap<u32, Vec<u32>>,
+    /// Pids of the processes without a listed parent, sorted
+    roots: Vec<u32>,
+}
+
+///
+/// Item for printing a process tree
+///
+/// The root either is a single process, or lists all processes without a parent, labeled `processes`.
+///
+#[derive(Clone, Debug)]
+pub struct ProcessItem {
+    pid: Option<u32>,
+    state: Rc<ProcState>,
+}
+
+impl ProcessItem {
+    ///
+    /// Reads all running processes into a tree
+    ///
+    pub fn all(config: ProcConfig) -> io::Result<Self> {
+        Ok(ProcessItem::from_processes(read_processes()?, config))
+    }
+
+    ///
+    /// Creates a tree of the given processes
+    ///
+    /// Processes whose parent is not among `processes` are the children of the root.
+    ///
+    pub fn from_processes(processes: Vec<Process>, config: ProcConfig) -> Self {
+        let processes: BTreeMap<_, _> = processes.into_iter().map(|p| (p.pid, p)).collect();
+        let mut children = BTreeMap::<_, Vec<_>>::new();
+        let mut roots = vec![];
+        for process in processes.values() {
+            match process.parent {
+                Some(parent) if parent != process.pid && processes.contains_key(&parent) => {
+                    children.entry(parent).or_default().push(process.pid)
+                }
+                _ => roots.push(process.pid),
+            }
+        }
+
+        ProcessItem {
+            pid: None,
+            state: Rc::new(ProcState {
+                config,
+                processes,
+                children,
+                roots,
+            }),
+        }
+    }
+
+    ///
+    /// Returns the item of the process `pid` in this tree, if it exists
+    ///
+    pub fn find(&self, pid: u32) -> Option<Self> {
+        if !self.state.processes.contains_key(&pid) {
+            return None;
+        }
+        Some(ProcessItem {
+            pid: Some(pid),
+            state: self.state.clone(),
+        })
+    }
+
+    ///
+    /// Returns the process of this item, or `None` for the root listing all processes
+    ///
+    pub fn process(&self) -> Option<&Process> {
+        self.pid.and_then(|pid| self.state.processes.get(&pid))
+    }
+}
+
+impl TreeItem for ProcessItem {
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        let process = match self.process() {
+            Some(process) => process,
+            None => return style.paint_to(f, format_args!("processes")),
+        };
+        style.paint_to(f, format_args!("{}({})", process.name, process.pid))?;
+
+        let config = &self.state.config;
+        let mut annotations = vec![];
+        if let (true, Some(cpu_time)) = (config.show_cpu, process.cpu_time) {
+            annotations.push(format!("cpu {:.1}s", cpu_time.as_secs_f64()));
+        }
+        if let (true, Some(memory)) = (config.show_memory, process.memory) {
+            annotations.push(format!("mem {}", human_size(memory)));
+        }
+        if !annotations.is_empty() {
+            style.paint_to(f, format_args!("  [{}]", annotations.join(", ")))?;
+        }
+        Ok(())
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        let pids = match self.pid {
+            Some(pid) => self.state.children.get(&pid).map_or(&[][..], |pids| &pids[..]),
+            None => &self.state.roots[..],
+        };
+        let children = pids
+            .iter()
+            .map(|&pid| ProcessItem {
+                pid: Some(pid),
+                state: self.state.clone(),
+            })
+            .collect();
+        Cow::Owned(children)
+    }
+}
+
+///
+/// Reads all running processes from `/proc`
+///
+/// Processes which exit while they are read are skipped.
+/// CPU times assume the usual 100 clock ticks per second.
+///
+#[cfg(target_os = "linux")]
+pub fn read_processes() -> io::Result<Vec<Process>> {
+    const TICKS_PER_SECOND: u64 = 100;
+
+    let mut processes = vec![];
+    for entry in fs::read_dir("/proc")? {
+        let entry = entry?;
+        let pid = match entry.file_name().to_str().and_then(|name| name.parse().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+        // The name is in parentheses and may contain spaces and parentheses itself
+        let stat = match fs::read_to_string(entry.path().join("stat")) {
+            Ok(stat) => stat,
+            Err(_) => continue,
+        };
+        let (open, close) = match (stat.find('('), stat.rfind(')')) {
+            (Some(open), Some(close)) if open < close => (open, close),
+            _ => continue,
+        };
+        let fields: Vec<&str> = stat[close + 1..].split_whitespace().collect();
+        let field = |i: usize| fields.get(i).and_then(|field| field.parse::<u64>().ok());
+
+        let cpu_time = match (field(11), field(12)) {
+            (Some(user), Some(system)) => Some(Duration::from_millis((user + system) * 1000 / TICKS_PER_SECOND)),
+            _ => None,
+        };
+        let memory = fs::read_to_string(entry.path().join("status")).ok().and_then(|status| {
+            let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+            let kib: u64 = line["VmRSS:".len()..]
+                .trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse()
+                .ok()?;
+            Some(kib * 1024)
+        });
+
+        processes.push(Process {
+            pid,
+            parent: field(1).map(|ppid| ppid as u32).filter(|&ppid| ppid != 0),
+            name: stat[open + 1..close].to_string(),
+            cpu_time,
+            memory,
+        });
+    }
+    Ok(processes)
+}
+
+///
+/// Reads all running processes with `sysinfo`
+///
+#[cfg(not(target_os = "linux"))]
+pub fn read_processes() -> io::Result<Vec<Process>> {
+    let mut system = System::new();
+    let refresh = ProcessRefreshKind::nothing().with_memory().with_cpu();
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, refresh);
+
+    let processes = system
+        .processes()
+        .values()
+        .map(|process| Process {
+            pid: process.pid().as_u32(),
+            parent: process.parent().map(|parent| parent.as_u32()),
+            name: process.name().to_string_lossy().into_owned(),
+            cpu_time: Some(Duration::from_millis(process.accumulated_cpu_time())),
+            memory: Some(process.memory()),
+        })
+        .collect();
+    Ok(processes)
+}
+
+/// Print the tree of all running processes to standard output using default formatting
+pub fn print_processes() -> io::Result<()> {
+    print_processes_with(&PrintConfig::for_terminal(), &ProcConfig::default())
+}
+
+/// Print the tree of all running processes to standard output using custom formatting
+pub fn print_processes_with(print_config: &PrintConfig, proc_config: &ProcConfig) -> io::Result<()> {
+    print_tree_with(&ProcessItem::all(proc_config.clone())?, print_config)
+}
+
+/// Write the tree of all running processes to writer `f` using custom formatting
+pub fn write_processes_with<W: io::Write>(
+    f: W,
+    print_config: &PrintConfig,
+    proc_config: &ProcConfig,
+) -> io::Result<()> {
+    write_tree_with(&ProcessItem::all(proc_config.clone())?, f, print_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use print_config::UTF_CHARS;
+    use std::process;
+    use std::str::from_utf8;
+
+    fn entry(pid: u32, parent: u32, name: &str) -> Process {
+        Process {
+            pid,
+            parent: Some(parent),
+            name: name.to_string(),
+            cpu_time: Some(Duration::from_millis(2500)),
+            memory: Some(4400 << 10),
+        }
+    }
+
+    #[test]
+    fn process_tree() {
+        let processes = vec![
+            entry(1, 0, "init"),
+            entry(20, 1, "sshd"),
+            entry(300, 20, "bash"),
+            entry(10, 1, "cron"),
+            entry(2, 0, "kthreadd"),
+        ];
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let item = ProcessItem::from_processes(processes.clone(), ProcConfig::default());
+        let mut out = Vec::new();
+        write_tree_with(&item, &mut out, &config).unwrap();
+        assert_eq!(
+            from_utf8(&out).unwrap(),
+            "\
+             processes\n\
+             ├─ init(1)\n\
+             │  ├─ cron(10)\n\
+             │  └─ sshd(20)\n\
+             │     └─ bash(300)\n\
+             └─ kthreadd(2)\n"
+        );
+
+        let proc_config = ProcConfig {
+            show_cpu: true,
+            show_memory: true,
+        };
+        let item = ProcessItem::from_processes(processes, proc_config).find(20).unwrap();
+        let mut out = Vec::new();
+        write_tree_with(&item, &mut out, &config).unwrap();
+        assert_eq!(
+            from_utf8(&out).unwrap(),
+            "\
+             sshd(20)  [cpu 2.5s, mem 4.3M]\n\
+             └─ bash(300)  [cpu 2.5s, mem 4.3M]\n"
+        );
+    }
+
+    #[test]
+    fn running_processes() {
+        let pid = process::id();
+        let item = ProcessItem::all(ProcConfig::default()).unwrap();
+        let current = item.find(pid).unwrap();
+        assert_eq!(current.process().unwrap().pid, pid);
+
+        let parent = current.process().unwrap().parent.unwrap();
+        let parent = item.find(parent).unwrap();
+        assert!(parent
+            .children()
+            .iter()
+            .any(|child| child.process().unwrap().pid == pid));
+    }
+}
@@ -0,0 +1,134 @@
+//!
+//! Searching trees for items, without rendering them
+//!
+//! Items are identified by their index path: the index of each ancestor below the root among its siblings,
+//! followed by the index of the item itself, so the root has the empty path.
+//! These are the same paths as those of [`diff::Edit`] and [`StringItem::graft_at`].
+//!
+//! ```
+//! # use ptree::TreeBuilder;
+//! # use ptree::search::{find_by_label, item_at, search};
+//! let tree = TreeBuilder::new("root".to_string())
+//!     .begin_child("src".to_string())
+//!     .add_empty_child("lib.rs".to_string())
+//!     .add_empty_child("main.rs".to_string())
+//!     .end_child()
+//!     .add_empty_child("build.rs".to_string())
+//!     .build();
+//!
+//! assert_eq!(search(&tree, |item| item.text.ends_with(".rs")), vec![vec![0, 0], vec![0, 1], vec![1]]);
+//! assert_eq!(find_by_label(&tree, "main.rs"), vec![vec![0, 1]]);
+//! assert_eq!(item_at(&tree, &[0, 1]).unwrap().text, "main.rs");
+//! ```
+//!
+//! [`diff::Edit`]: ../diff/enum.Edit.html
+//! [`StringItem::graft_at`]: ../item/struct.StringItem.html#method.graft_at
+//!
+
+use item::TreeItem;
+use output::item_text;
+
+fn search_into<T, P>(item: &T, pred: &mut P, path: &mut Vec<usize>, found: &mut Vec<Vec<usize>>)
+where
+    T: TreeItem<Child = T>,
+    P: FnMut(&T) -> bool,
+{
+    if pred(item) {
+        found.push(path.clone());
+    }
+    for (i, child) in item.children().iter().enumerate() {
+        path.push(i);
+        search_into(child, pred, path, found);
+        path.pop();
+    }
+}
+
+///
+/// Returns the index paths of all items of the tree `item` for which `pred` returns `true`
+///
+/// The tree is searched in depth-first order, calling `pred` for every item,
+/// so the paths are in the order in which the items are printed.
+///
+pub fn search<T, P>(item: &T, mut pred: P) -> Vec<Vec<usize>>
+where
+    T: TreeItem<Child = T>,
+    P: FnMut(&T) -> bool,
+{
+    let mut found = Vec::new();
+    search_into(item, &mut pred, &mut Vec::new(), &mut found);
+    found
+}
+
+///
+/// Returns the index paths of all items of the tree `item` whose text is `label`
+///
+/// The text is compared as it is printed without styles, so escape sequences written by the items are ignored.
+/// Items whose text cannot be written do not match.
+///
+pub fn find_by_label<T>(item: &T, label: &str) -> Vec<Vec<usize>>
+where
+    T: TreeItem<Child = T>,
+{
+    search(item, |item| item_text(item).is_ok_and(|text| text == label))
+}
+
+///
+/// Returns the item of the tree `item` at the index path `path`, or `None` if there is no such item
+///
+pub fn item_at<T>(item: &T, path: &[usize]) -> Option<T>
+where
+    T: TreeItem<Child = T>,
+{
+    let mut current = item.clone();
+    for &index in path {
+        let child = current.children().get(index)?.clone();
+        current = child;
+    }
+    Some(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use builder::TreeBuilder;
+    use item::StringItem;
+
+    fn tree() -> StringItem {
+        TreeBuilder::new("a".to_string())
+            .begin_child("b".to_string())
+            .add_empty_child("a".to_string())
+            .end_child()
+            .add_empty_child("\x1b[1ma\x1b[0m".to_string())
+            .build()
+    }
+
+    #[test]
+    fn search_paths() {
+        let tree = tree();
+        assert_eq!(search(&tree, |_| true), vec![vec![], vec![0], vec![0, 0], vec![1]]);
+        assert_eq!(
+            search(&tree, |item| item.children.is_empty()),
+            vec![vec![0, 0], vec![1]]
+        );
+        assert!(search(&tree, |_| false).is_empty());
+    }
+
+    #[test]
+    fn labels_ignore_escapes() {
+        let tree = tree();
+        assert_eq!(find_by_label(&tree, "a"), vec![vec![], vec![0, 0], vec![1]]);
+        assert!(find_by_label(&tree, "c").is_empty());
+    }
+
+    #[test]
+    fn navigation() {
+        let tree = tree();
+        for path in search(&tree, |_| true) {
+            assert!(item_at(&tree, &path).is_some());
+        }
+        assert_eq!(item_at(&tree, &[0]).unwrap().text, "b");
+        assert!(item_at(&tree, &[0, 1]).is_none());
+        assert!(item_at(&tree, &[2]).is_none());
+    }
+}
@@ -1,11 +1,26 @@
-use std::fmt::Display;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::io;
+use std::iter::FromIterator;
 
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{self, Deserializer, SeqAccess, Unexpected, Visitor},
+    Deserialize, Serialize, Serializer,
+};
 
 #[cfg(feature = "ansi")]
 use ansi_term;
-#[cfg(feature = "ansi")]
+#[cfg(feature = "crossterm")]
+use crossterm;
+#[cfg(feature = "nu-ansi-term")]
+use nu_ansi_term;
+#[cfg(feature = "termcolor")]
+use termcolor;
+use term;
 use tint;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 ///
 /// Terminal output style
@@ -31,6 +46,11 @@ pub struct Style {
     /// Whether this style is underlined.
     pub underline: bool,
 
+    /// The color of the underline, if it differs from the foreground color.
+    ///
+    /// Colored underlines are not supported by all terminals, and are not supported with `termcolor`.
+    pub underline_color: Option<Color>,
+
     /// Whether this style is blinking.
     pub blink: bool,
 
@@ -42,6 +62,22 @@ pub struct Style {
 
     /// Whether this style is struckthrough.
     pub strikethrough: bool,
+
+    /// Additional SGR parameters, written verbatim into the escape sequence.
+    ///
+    /// This allows using attributes which are not modelled by the other fields,
+    /// such as `53` for overline or `4:3` for curly underline.
+    /// Multiple parameters are separated by `;`.
+    /// Parameters are ignored if they contain characters other than digits, `;` and `:`.
+    ///
+    /// Only the ANSI backends use these parameters, they are not supported with `termcolor`.
+    pub extra_codes: String,
+
+    /// Target of a hyperlink wrapping the styled text, if any.
+    ///
+    /// Hyperlinks are written as OSC 8 escape sequences, which are ignored by terminals that do not support them.
+    /// Targets containing control characters are ignored.
+    pub hyperlink: Option<String>,
 }
 
 /// A colour is one specific type of ANSI escape code, and can refer
@@ -49,13 +85,13 @@ pub struct Style {
 ///
 /// These use the standard numeric sequences.
 /// See <http://invisible-island.net/xterm/ctlseqs/ctlseqs.html>
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(untagged, rename_all = "lowercase")]
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
 pub enum Color {
     /// Color #0 (foreground code `30`, background code `40`).
     ///
     /// This is not necessarily the background colour, and using it as one may
     /// render the text hard to read on terminals with dark backgrounds.
+    #[default]
     Black,
 
     /// Color #1 (foreground code `31`, background code `41`).
@@ -103,20 +139,152 @@ pub enum Color {
     /// A 24-bit RGB color, as specified by ISO-8613-3.
     RGB(u8, u8, u8),
 
+    /// The terminal's default foreground or background color (foreground code `39`, background code `49`).
+    ///
+    /// This can be used to explicitly reset a color inherited from another style.
+    Default,
+
     /// A named color, as supported by `tint`.
+    ///
+    /// Parsing or deserializing a color resolves names eagerly,
+    /// so this variant is only produced by constructing it directly.
+    /// Use [`Color::resolve`] to check whether the name is known.
+    ///
+    /// [`Color::resolve`]: #method.resolve
     Named(String),
 }
 
-impl Default for Color {
-    fn default() -> Color {
-        Color::Black
+impl Color {
+    ///
+    /// Resolves a `Named` color into one of the other variants
+    ///
+    /// ANSI color names resolve to the corresponding ANSI variants,
+    /// while web color names and hex codes resolve to `RGB` colors.
+    /// Other variants are returned unchanged.
+    ///
+    /// ### Errors
+    ///
+    /// Returns an error if the name is not a known color.
+    ///
+    pub fn resolve(&self) -> Result<Color, ParseColorError> {
+        match self {
+            Color::Named(n) => n.parse(),
+            c => Ok(c.clone()),
+        }
+    }
+
+    ///
+    /// Converts this color to the closest color representable at the given color depth
+    ///
+    /// With [`ColorDepth::Ansi256`], `RGB` colors are mapped to the nearest color of the
+    /// 256-color palette. With [`ColorDepth::Ansi16`], `RGB` colors and `Fixed` colors above 15
+    /// are mapped to the nearest of the 16 basic colors.
+    /// [`ColorDepth::Auto`] detects the depth supported by the terminal.
+    ///
+    /// Named colors are resolved first. Unknown names are returned unchanged.
+    ///
+    /// [`ColorDepth::Ansi256`]: enum.ColorDepth.html#variant.Ansi256
+    /// [`ColorDepth::Ansi16`]: enum.ColorDepth.html#variant.Ansi16
+    /// [`ColorDepth::Auto`]: enum.ColorDepth.html#variant.Auto
+    pub fn downsample(&self, depth: ColorDepth) -> Color {
+        let color = match self.resolve() {
+            Ok(c) => c,
+            Err(_) => return self.clone(),
+        };
+
+        match (depth.resolve(), color) {
+            (ColorDepth::Ansi256, Color::RGB(r, g, b)) => Color::Fixed(nearest_256(r, g, b)),
+            (ColorDepth::Ansi16, Color::RGB(r, g, b)) => Color::from_index(nearest_16(r, g, b)),
+            (ColorDepth::Ansi16, Color::Fixed(f)) if f >= 16 => {
+                let (r, g, b) = fixed_to_rgb(f);
+                Color::from_index(nearest_16(r, g, b))
+            }
+            (_, c) => c,
+        }
+    }
+
+    ///
+    /// Returns the red, green and blue components of this color
+    ///
+    /// The ANSI and `Fixed` colors are converted using the default xterm palette,
+    /// although terminals may display them differently.
+    ///
+    /// ### Errors
+    ///
+    /// Returns an error if this is a `Named` color with an unknown name,
+    /// or the `Default` color, whose components are not known.
+    ///
+    pub fn to_rgb(&self) -> Result<(u8, u8, u8), ParseColorError> {
+        let rgb = match self.resolve()? {
+            Color::Black => fixed_to_rgb(0),
+            Color::Red => fixed_to_rgb(1),
+            Color::Green => fixed_to_rgb(2),
+            Color::Yellow => fixed_to_rgb(3),
+            Color::Blue => fixed_to_rgb(4),
+            Color::Purple => fixed_to_rgb(5),
+            Color::Cyan => fixed_to_rgb(6),
+            Color::White => fixed_to_rgb(7),
+            Color::Fixed(f) => fixed_to_rgb(f),
+            Color::RGB(r, g, b) => (r, g, b),
+            Color::Default => {
+                return Err(ParseColorError {
+                    input: self.to_string(),
+                })
+            }
+            Color::Named(_) => unreachable!("named colors are resolved"),
+        };
+        Ok(rgb)
+    }
+
+    ///
+    /// Generates `steps` colors evenly interpolated between `from` and `to`
+    ///
+    /// The first color is `from` and the last one is `to`, both converted to `RGB`.
+    /// If `steps` is 1, only `from` is returned.
+    ///
+    /// ```
+    /// # use ptree::Color;
+    /// let colors = Color::gradient(&Color::RGB(0, 0, 0), &Color::RGB(200, 100, 0), 3).unwrap();
+    /// assert_eq!(colors, vec![Color::RGB(0, 0, 0), Color::RGB(100, 50, 0), Color::RGB(200, 100, 0)]);
+    /// ```
+    ///
+    /// ### Errors
+    ///
+    /// Returns an error if either color cannot be converted with [`to_rgb`].
+    ///
+    /// [`to_rgb`]: #method.to_rgb
+    pub fn gradient(from: &Color, to: &Color, steps: usize) -> Result<Vec<Color>, ParseColorError> {
+        let (r1, g1, b1) = from.to_rgb()?;
+        let (r2, g2, b2) = to.to_rgb()?;
+
+        let lerp = |a: u8, b: u8, t: f64| (f64::from(a) + (f64::from(b) - f64::from(a)) * t).round() as u8;
+
+        Ok((0..steps)
+            .map(|i| {
+                let t = if steps > 1 { i as f64 / (steps - 1) as f64 } else { 0.0 };
+                Color::RGB(lerp(r1, r2, t), lerp(g1, g2, t), lerp(b1, b2, t))
+            })
+            .collect())
+    }
+
+    fn from_index(index: u8) -> Color {
+        match index {
+            0 => Color::Black,
+            1 => Color::Red,
+            2 => Color::Green,
+            3 => Color::Yellow,
+            4 => Color::Blue,
+            5 => Color::Purple,
+            6 => Color::Cyan,
+            7 => Color::White,
+            i => Color::Fixed(i),
+        }
     }
-}
 
-impl Color {
     #[cfg(feature = "ansi")]
-    fn to_ansi_color(&self) -> ansi_term::Color {
-        match self {
+    #[cfg_attr(any(feature = "nu-ansi-term", feature = "crossterm"), allow(dead_code))]
+    fn to_ansi_color(&self) -> Option<ansi_term::Color> {
+        let color = match self.resolve().ok()? {
             Color::Black => ansi_term::Color::Black,
             Color::Red => ansi_term::Color::Red,
             Color::Green => ansi_term::Color::Green,
@@ -125,26 +293,360 @@ impl Color {
             Color::Purple => ansi_term::Color::Purple,
             Color::Cyan => ansi_term::Color::Cyan,
             Color::White => ansi_term::Color::White,
-            Color::Fixed(f) => ansi_term::Color::Fixed(*f),
-            Color::RGB(r, g, b) => ansi_term::Color::RGB(*r, *g, *b),
-            Color::Named(n) => match &n[..] {
-                // ANSI color names still take precedence over HTML and CSS colors,
-                // because only ANSI colors can be dimmed.
-                "black" => ansi_term::Color::Black,
-                "red" => ansi_term::Color::Red,
-                "green" => ansi_term::Color::Green,
-                "yellow" => ansi_term::Color::Yellow,
-                "blue" => ansi_term::Color::Blue,
-                "purple" => ansi_term::Color::Purple,
-                "cyan" => ansi_term::Color::Cyan,
-                "white" => ansi_term::Color::White,
-                n => {
-                    let c = tint::Color::from(n);
-                    let (r, g, b) = c.to_rgb255();
-                    ansi_term::Color::RGB(r, g, b)
+            Color::Fixed(f) => ansi_term::Color::Fixed(f),
+            Color::RGB(r, g, b) => ansi_term::Color::RGB(r, g, b),
+            // `ansi_term` has no default color, so the escape code is written separately
+            Color::Default => return None,
+            Color::Named(_) => unreachable!("named colors are resolved"),
+        };
+        Some(color)
+    }
+
+    #[cfg(feature = "nu-ansi-term")]
+    #[cfg_attr(feature = "crossterm", allow(dead_code))]
+    fn to_nu_ansi_color(&self) -> Option<nu_ansi_term::Color> {
+        let color = match self.resolve().ok()? {
+            Color::Black => nu_ansi_term::Color::Black,
+            Color::Red => nu_ansi_term::Color::Red,
+            Color::Green => nu_ansi_term::Color::Green,
+            Color::Yellow => nu_ansi_term::Color::Yellow,
+            Color::Blue => nu_ansi_term::Color::Blue,
+            Color::Purple => nu_ansi_term::Color::Purple,
+            Color::Cyan => nu_ansi_term::Color::Cyan,
+            Color::White => nu_ansi_term::Color::White,
+            Color::Fixed(f) => nu_ansi_term::Color::Fixed(f),
+            Color::RGB(r, g, b) => nu_ansi_term::Color::Rgb(r, g, b),
+            Color::Default => nu_ansi_term::Color::Default,
+            Color::Named(_) => unreachable!("named colors are resolved"),
+        };
+        Some(color)
+    }
+
+    #[cfg(feature = "crossterm")]
+    fn to_crossterm_color(&self) -> Option<crossterm::style::Color> {
+        let color = match self.resolve().ok()? {
+            Color::Black => crossterm::style::Color::Black,
+            Color::Red => crossterm::style::Color::DarkRed,
+            Color::Green => crossterm::style::Color::DarkGreen,
+            Color::Yellow => crossterm::style::Color::DarkYellow,
+            Color::Blue => crossterm::style::Color::DarkBlue,
+            Color::Purple => crossterm::style::Color::DarkMagenta,
+            Color::Cyan => crossterm::style::Color::DarkCyan,
+            Color::White => crossterm::style::Color::Grey,
+            Color::Fixed(f) => crossterm::style::Color::AnsiValue(f),
+            Color::RGB(r, g, b) => crossterm::style::Color::Rgb { r, g, b },
+            Color::Default => crossterm::style::Color::Reset,
+            Color::Named(_) => unreachable!("named colors are resolved"),
+        };
+        Some(color)
+    }
+
+    #[cfg(feature = "termcolor")]
+    fn to_termcolor(&self) -> Option<termcolor::Color> {
+        let color = match self.resolve().ok()? {
+            Color::Black => termcolor::Color::Black,
+            Color::Red => termcolor::Color::Red,
+            Color::Green => termcolor::Color::Green,
+            Color::Yellow => termcolor::Color::Yellow,
+            Color::Blue => termcolor::Color::Blue,
+            Color::Purple => termcolor::Color::Magenta,
+            Color::Cyan => termcolor::Color::Cyan,
+            Color::White => termcolor::Color::White,
+            Color::Fixed(f) => termcolor::Color::Ansi256(f),
+            Color::RGB(r, g, b) => termcolor::Color::Rgb(r, g, b),
+            // A color spec without a color uses the default one
+            Color::Default => return None,
+            Color::Named(_) => unreachable!("named colors are resolved"),
+        };
+        Some(color)
+    }
+}
+
+/// Escape sequence resetting all styles
+#[cfg(any(feature = "ansi", feature = "nu-ansi-term", feature = "crossterm"))]
+const RESET: &str = "\u{1b}[0m";
+
+///
+/// Display adapter which paints its arguments with a style, used by [`Style::paint_to`]
+///
+struct Painted<'a> {
+    style: &'a Style,
+    args: fmt::Arguments<'a>,
+}
+
+impl<'a> Display for Painted<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.style.write_painted(f, self.args)
+    }
+}
+
+///
+/// Number of colors supported by the terminal
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorDepth {
+    /// Detect the color depth from the environment
+    #[default]
+    Auto,
+    /// 24-bit RGB colors
+    TrueColor,
+    /// The 256-color palette
+    Ansi256,
+    /// The 16 basic colors
+    Ansi16,
+}
+
+impl ColorDepth {
+    ///
+    /// Detects the color depth supported by the terminal
+    ///
+    /// See [`term::color_depth`] for details.
+    ///
+    /// [`term::color_depth`]: ../term/fn.color_depth.html
+    pub fn detect() -> ColorDepth {
+        term::color_depth()
+    }
+
+    ///
+    /// Returns the detected color depth for `Auto`, and `self` otherwise
+    ///
+    pub fn resolve(self) -> ColorDepth {
+        match self {
+            ColorDepth::Auto => ColorDepth::detect(),
+            d => d,
+        }
+    }
+}
+
+/// The 16 basic colors, as rendered by xterm
+const ANSI_16_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel intensities of the 6x6x6 color cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+fn fixed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_16_RGB[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    // Only the color cube and the grey ramp are considered,
+    // as the first 16 colors are usually changed by the terminal theme
+    (16..=255)
+        .min_by_key(|&i| distance(fixed_to_rgb(i), (r, g, b)))
+        .unwrap_or(16)
+}
+
+fn nearest_16(r: u8, g: u8, b: u8) -> u8 {
+    (0..16)
+        .min_by_key(|&i| distance(ANSI_16_RGB[i as usize], (r, g, b)))
+        .unwrap_or(0)
+}
+
+///
+/// Error returned when parsing a [`Color`] from a string fails
+///
+/// [`Color`]: enum.Color.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseColorError {
+    input: String,
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid color '{}'", self.input)
+    }
+}
+
+impl Error for ParseColorError {}
+
+///
+/// Parses a color from a string
+///
+/// The following formats are accepted:
+///
+/// - an ANSI color name, such as `red` or `purple`,
+/// - `default` for the terminal's default color,
+/// - a named web color, such as `steelblue`,
+/// - a hex code in the form `#rrggbb`,
+/// - a fixed color index from 0 to 255,
+/// - a triple of red, green and blue components, such as `70,130,180`.
+///
+/// Names are case insensitive.
+/// ANSI color names take precedence over web colors, because only ANSI colors can be dimmed.
+/// Web colors are resolved into `RGB` colors, and unknown names are reported as errors.
+///
+/// ```
+/// # use ptree::Color;
+/// assert_eq!("Red".parse(), Ok(Color::Red));
+/// assert_eq!("SteelBlue".parse(), Ok(Color::RGB(70, 130, 180)));
+/// assert_eq!("#4682b4".parse(), Ok(Color::RGB(70, 130, 180)));
+/// assert_eq!("70, 130, 180".parse(), Ok(Color::RGB(70, 130, 180)));
+/// assert_eq!("110".parse(), Ok(Color::Fixed(110)));
+/// ```
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseColorError { input: s.to_string() };
+        let name = s.trim().to_lowercase();
+
+        let color = match &name[..] {
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "purple" => Color::Purple,
+            "cyan" => Color::Cyan,
+            "white" => Color::White,
+            "default" => Color::Default,
+            n if n.starts_with('#') => {
+                let hex = &n[1..];
+                if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(err());
                 }
-            },
+                let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| err());
+                Color::RGB(component(0)?, component(2)?, component(4)?)
+            }
+            n if n.contains(',') => {
+                let components = n
+                    .split(',')
+                    .map(|c| c.trim().parse::<u8>().map_err(|_| err()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match components[..] {
+                    [r, g, b] => Color::RGB(r, g, b),
+                    _ => return Err(err()),
+                }
+            }
+            n if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => {
+                Color::Fixed(n.parse().map_err(|_| err())?)
+            }
+            n if !n.is_empty() && n.chars().all(|c| c.is_ascii_alphabetic()) => {
+                let (r, g, b) = tint::Color::name(n).ok_or_else(err)?.to_rgb255();
+                Color::RGB(r, g, b)
+            }
+            _ => return Err(err()),
+        };
+
+        Ok(color)
+    }
+}
+
+///
+/// Formats a color in the form accepted by its `FromStr` implementation
+///
+/// ANSI colors and named colors are written by name,
+/// fixed colors as their index, and RGB colors as a `#rrggbb` hex code.
+///
+impl Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Color::Black => f.write_str("black"),
+            Color::Red => f.write_str("red"),
+            Color::Green => f.write_str("green"),
+            Color::Yellow => f.write_str("yellow"),
+            Color::Blue => f.write_str("blue"),
+            Color::Purple => f.write_str("purple"),
+            Color::Cyan => f.write_str("cyan"),
+            Color::White => f.write_str("white"),
+            Color::Fixed(n) => write!(f, "{}", n),
+            Color::RGB(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+            Color::Default => f.write_str("default"),
+            Color::Named(n) => f.write_str(n),
+        }
+    }
+}
+
+// Colors are serialized in the same forms as they are deserialized,
+// so that a serialized configuration can be loaded back.
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Color::Fixed(n) => serializer.serialize_u8(*n),
+            Color::RGB(r, g, b) => (r, g, b).serialize(serializer),
+            c => serializer.collect_str(c),
+        }
+    }
+}
+
+// Colors are deserialized from a string in any of the forms accepted by `FromStr`,
+// from an integer fixed color index, or from a sequence of red, green and blue components.
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ColorVisitor;
+
+        impl<'de> Visitor<'de> for ColorVisitor {
+            type Value = Color;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a color name, a hex code, a fixed color index or a [red, green, blue] triple")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Color, E> {
+                value.parse().map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Color, E> {
+                u8::try_from(value)
+                    .map(Color::Fixed)
+                    .map_err(|_| E::invalid_value(Unexpected::Unsigned(value), &self))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Color, E> {
+                u8::try_from(value)
+                    .map(Color::Fixed)
+                    .map_err(|_| E::invalid_value(Unexpected::Signed(value), &self))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Color, A::Error> {
+                let mut next = |i| seq.next_element::<u8>()?.ok_or_else(|| de::Error::invalid_length(i, &self));
+                let color = Color::RGB(next(0)?, next(1)?, next(2)?);
+
+                if seq.next_element::<u8>()?.is_some() {
+                    return Err(de::Error::invalid_length(4, &self));
+                }
+                Ok(color)
+            }
         }
+
+        deserializer.deserialize_any(ColorVisitor)
     }
 }
 
@@ -152,87 +654,1368 @@ impl Style {
     ///
     /// Paints `input` according to this style.
     ///
-    /// If the `"ansi"` feature is enabled, this function uses
-    /// `ansi_term` to style text.
+    /// If the `"crossterm"` feature is enabled, this function uses
+    /// `crossterm` to style text.
+    /// Otherwise, if the `"nu-ansi-term"` or the `"ansi"` feature is enabled,
+    /// this function uses `nu_ansi_term` or `ansi_term` respectively.
     ///
-    /// If the `"ansi"` feature is disabled, this function
+    /// If none of these features are enabled, this function
     /// always returns the output unchanged.
     ///
     pub fn paint(&self, input: impl Display) -> impl Display {
-        #[cfg(feature = "ansi")]
+        let mut painted = String::new();
+        // Writing to a `String` only fails if formatting `input` fails
+        let _ = self.write_painted(&mut painted, format_args!("{}", input));
+        painted
+    }
+
+    ///
+    /// Writes `args` to `f` according to this style
+    ///
+    /// This produces the same output as [`paint`], but writes it directly to `f`
+    /// instead of converting the input to a `String` first.
+    /// Nothing is allocated, and unstyled text is written without any escape codes.
+    ///
+    /// ```
+    /// # use ptree::{Color, Style};
+    /// # fn main() -> std::io::Result<()> {
+    /// let style = Style {
+    ///     foreground: Some(Color::Red),
+    ///     ..Style::default()
+    /// };
+    ///
+    /// let mut out = Vec::new();
+    /// style.paint_to(&mut out, format_args!("{} items", 3))?;
+    /// assert_eq!(out, style.paint("3 items").to_string().into_bytes());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`paint`]: #method.paint
+    pub fn paint_to<W: io::Write + ?Sized>(&self, f: &mut W, args: fmt::Arguments) -> io::Result<()> {
+        if *self == Style::default() {
+            return f.write_fmt(args);
+        }
+
+        write!(f, "{}", Painted { style: self, args })
+    }
+
+    fn write_painted<W: fmt::Write + ?Sized>(&self, f: &mut W, args: fmt::Arguments) -> fmt::Result {
+        #[cfg(any(feature = "ansi", feature = "nu-ansi-term", feature = "crossterm"))]
+        {
+            if let Some(url) = self.valid_hyperlink() {
+                write!(f, "\u{1b}]8;;{}\u{1b}\\", url)?;
+                self.write_sgr(f, args)?;
+                return f.write_str("\u{1b}]8;;\u{1b}\\");
+            }
+        }
+
+        self.write_sgr(f, args)
+    }
+
+    fn write_sgr<W: fmt::Write + ?Sized>(&self, f: &mut W, args: fmt::Arguments) -> fmt::Result {
+        if *self == Style::default() {
+            return f.write_fmt(args);
+        }
+
+        #[cfg(feature = "crossterm")]
         {
-            let mut ansi_style = ansi_term::Style::new();
+            let content_style = self.to_content_style();
+            let extra_codes = self.valid_extra_codes();
+
+            if let Some(codes) = extra_codes {
+                write!(f, "\u{1b}[{}m", codes)?;
+            }
+            write!(f, "{}", content_style.apply(args))?;
 
-            ansi_style.foreground = self.foreground.as_ref().map(Color::to_ansi_color);
-            ansi_style.background = self.background.as_ref().map(Color::to_ansi_color);
+            // crossterm only resets the style if it set anything itself
+            if extra_codes.is_some() && content_style == crossterm::style::ContentStyle::default() {
+                f.write_str(RESET)?;
+            }
+            Ok(())
+        }
 
-            ansi_style.is_bold = self.bold;
-            ansi_style.is_dimmed = self.dimmed;
-            ansi_style.is_italic = self.italic;
-            ansi_style.is_underline = self.underline;
+        #[cfg(all(feature = "nu-ansi-term", not(feature = "crossterm")))]
+        {
+            let nu_style = self.to_nu_ansi_style();
+            write!(f, "{}", nu_style.prefix())?;
+            let own_codes = self.write_own_codes(f)?;
+            f.write_fmt(args)?;
+            if own_codes {
+                f.write_str(RESET)
+            } else {
+                write!(f, "{}", nu_style.suffix())
+            }
+        }
 
-            ansi_style.paint(input.to_string())
+        #[cfg(all(feature = "ansi", not(any(feature = "nu-ansi-term", feature = "crossterm"))))]
+        {
+            let ansi_style = self.to_ansi_style();
+            write!(f, "{}", ansi_style.prefix())?;
+            let is_default = |c: &Option<Color>| c.as_ref().and_then(|c| c.resolve().ok()) == Some(Color::Default);
+            if is_default(&self.foreground) {
+                f.write_str("\u{1b}[39m")?;
+            }
+            if is_default(&self.background) {
+                f.write_str("\u{1b}[49m")?;
+            }
+            let own_codes = self.write_own_codes(f)?;
+            f.write_fmt(args)?;
+            if own_codes {
+                f.write_str(RESET)
+            } else {
+                write!(f, "{}", ansi_style.suffix())
+            }
         }
 
-        #[cfg(not(feature = "ansi"))]
-        return input;
+        #[cfg(not(any(feature = "ansi", feature = "nu-ansi-term", feature = "crossterm")))]
+        return f.write_fmt(args);
     }
-}
 
-#[cfg(test)]
-mod tests {
+    /// Writes the escape sequences which are not supported by the ANSI libraries
+    ///
+    /// These are the underline color and `extra_codes`.
+    /// Returns whether anything was written, in which case the style has to be reset afterwards.
+    #[cfg(any(feature = "ansi", feature = "nu-ansi-term"))]
+    #[cfg_attr(feature = "crossterm", allow(dead_code))]
+    fn write_own_codes<W: fmt::Write + ?Sized>(&self, f: &mut W) -> Result<bool, fmt::Error> {
+        let underline_color = self.underline_color.as_ref().and_then(|c| c.resolve().ok());
+        match underline_color {
+            Some(Color::Default) => f.write_str("\u{1b}[59m")?,
+            Some(Color::RGB(r, g, b)) => write!(f, "\u{1b}[58;2;{};{};{}m", r, g, b)?,
+            Some(Color::Fixed(n)) => write!(f, "\u{1b}[58;5;{}m", n)?,
+            Some(ref c) => {
+                let index = (0..8).find(|&i| Color::from_index(i) == *c).unwrap_or(0);
+                write!(f, "\u{1b}[58;5;{}m", index)?
+            }
+            None => {}
+        }
 
-    use super::*;
-    use serde_any;
+        let extra_codes = self.valid_extra_codes();
+        if let Some(codes) = extra_codes {
+            write!(f, "\u{1b}[{}m", codes)?;
+        }
 
-    #[cfg(feature = "ansi")]
-    use ansi_term;
+        Ok(underline_color.is_some() || extra_codes.is_some())
+    }
 
-    #[derive(Deserialize)]
-    pub struct Wrapper {
-        color: Color,
+    #[cfg_attr(
+        not(any(feature = "ansi", feature = "nu-ansi-term", feature = "crossterm", feature = "termcolor")),
+        allow(dead_code)
+    )]
+    fn valid_hyperlink(&self) -> Option<&str> {
+        self.hyperlink
+            .as_ref()
+            .map(|url| &url[..])
+            .filter(|url| !url.chars().any(char::is_control))
     }
 
-    #[cfg(feature = "ansi")]
-    fn toml_to_ansi(s: &str) -> ansi_term::Color {
-        serde_any::from_str::<Wrapper>(&format!("color = {}", s), serde_any::Format::Toml)
-            .unwrap()
-            .color
-            .to_ansi_color()
+    #[cfg_attr(not(any(feature = "ansi", feature = "nu-ansi-term", feature = "crossterm")), allow(dead_code))]
+    fn valid_extra_codes(&self) -> Option<&str> {
+        let codes = &self.extra_codes[..];
+        let valid = !codes.is_empty() && codes.chars().all(|c| c.is_ascii_digit() || c == ';' || c == ':');
+        if valid {
+            Some(codes)
+        } else {
+            None
+        }
     }
 
-    #[cfg(feature = "ansi")]
-    fn yaml_to_ansi(s: &str) -> ansi_term::Color {
-        serde_any::from_str::<Wrapper>(&format!("color: {}", s), serde_any::Format::Yaml)
-            .unwrap()
-            .color
-            .to_ansi_color()
+    #[cfg(feature = "nu-ansi-term")]
+    #[cfg_attr(feature = "crossterm", allow(dead_code))]
+    fn to_nu_ansi_style(&self) -> nu_ansi_term::Style {
+        let mut nu_style = nu_ansi_term::Style::new();
+
+        nu_style.foreground = self.foreground.as_ref().and_then(Color::to_nu_ansi_color);
+        nu_style.background = self.background.as_ref().and_then(Color::to_nu_ansi_color);
+
+        nu_style.is_bold = self.bold;
+        nu_style.is_dimmed = self.dimmed;
+        nu_style.is_italic = self.italic;
+        nu_style.is_underline = self.underline;
+        nu_style.is_blink = self.blink;
+        nu_style.is_reverse = self.reverse;
+        nu_style.is_hidden = self.hidden;
+        nu_style.is_strikethrough = self.strikethrough;
+
+        nu_style
     }
 
-    #[test]
     #[cfg(feature = "ansi")]
-    fn color_from_toml() {
-        assert_eq!(toml_to_ansi("\"red\""), ansi_term::Color::Red);
-        assert_eq!(toml_to_ansi("\"green\""), ansi_term::Color::Green);
-        assert_eq!(toml_to_ansi("10"), ansi_term::Color::Fixed(10));
-        assert_eq!(toml_to_ansi("110"), ansi_term::Color::Fixed(110));
-        assert_eq!(toml_to_ansi("[10, 20, 30]"), ansi_term::Color::RGB(10, 20, 30));
-        assert_eq!(toml_to_ansi("\"maroon\""), ansi_term::Color::RGB(128, 0, 0));
-        assert_eq!(toml_to_ansi("\"steelblue\""), ansi_term::Color::RGB(70, 130, 180));
-        assert_eq!(toml_to_ansi("\"#4682B4\""), ansi_term::Color::RGB(70, 130, 180));
+    #[cfg_attr(any(feature = "nu-ansi-term", feature = "crossterm"), allow(dead_code))]
+    fn to_ansi_style(&self) -> ansi_term::Style {
+        let mut ansi_style = ansi_term::Style::new();
+
+        ansi_style.foreground = self.foreground.as_ref().and_then(Color::to_ansi_color);
+        ansi_style.background = self.background.as_ref().and_then(Color::to_ansi_color);
+
+        ansi_style.is_bold = self.bold;
+        ansi_style.is_dimmed = self.dimmed;
+        ansi_style.is_italic = self.italic;
+        ansi_style.is_underline = self.underline;
+        ansi_style.is_blink = self.blink;
+        ansi_style.is_reverse = self.reverse;
+        ansi_style.is_hidden = self.hidden;
+        ansi_style.is_strikethrough = self.strikethrough;
+
+        ansi_style
     }
 
-    #[test]
-    #[cfg(feature = "ansi")]
-    fn color_from_yaml() {
-        assert_eq!(yaml_to_ansi("\"red\""), ansi_term::Color::Red);
-        assert_eq!(yaml_to_ansi("\"green\""), ansi_term::Color::Green);
-        assert_eq!(yaml_to_ansi("10"), ansi_term::Color::Fixed(10));
-        assert_eq!(yaml_to_ansi("110"), ansi_term::Color::Fixed(110));
-        assert_eq!(yaml_to_ansi("[10, 20, 30]"), ansi_term::Color::RGB(10, 20, 30));
-        assert_eq!(yaml_to_ansi("\"maroon\""), ansi_term::Color::RGB(128, 0, 0));
-        assert_eq!(yaml_to_ansi("\"steelblue\""), ansi_term::Color::RGB(70, 130, 180));
-        assert_eq!(yaml_to_ansi("\"#4682B4\""), ansi_term::Color::RGB(70, 130, 180));
+    ///
+    /// Resolves the named colors of this style
+    ///
+    /// See [`Color::resolve`] for details.
+    /// Styles loaded from configuration files have their colors resolved already.
+    ///
+    /// [`Color::resolve`]: enum.Color.html#method.resolve
+    pub fn resolve(&self) -> Result<Style, ParseColorError> {
+        Ok(Style {
+            foreground: self.foreground.as_ref().map(Color::resolve).transpose()?,
+            background: self.background.as_ref().map(Color::resolve).transpose()?,
+            underline_color: self.underline_color.as_ref().map(Color::resolve).transpose()?,
+            ..self.clone()
+        })
+    }
+
+    ///
+    /// Formats this style using the compact syntax accepted by [`Style::from_str`]
+    ///
+    /// The foreground color comes first, followed by the background color and the enabled attributes.
+    /// The default style is formatted as an empty string.
+    /// Hyperlinks are not part of the compact syntax, so they are omitted.
+    ///
+    /// ```
+    /// # use ptree::{Color, Style};
+    /// let style = Style {
+    ///     foreground: Some(Color::RGB(255, 128, 0)),
+    ///     background: Some(Color::Blue),
+    ///     bold: true,
+    ///     ..Style::default()
+    /// };
+    /// assert_eq!(style.to_spec_string(), "#ff8000,on_blue,bold");
+    /// assert_eq!(style.to_spec_string().parse::<Style>().unwrap(), style);
+    /// ```
+    ///
+    /// [`Style::from_str`]: #impl-FromStr-for-Style
+    pub fn to_spec_string(&self) -> String {
+        let colors = self
+            .foreground
+            .iter()
+            .map(|c| c.to_string())
+            .chain(self.background.iter().map(|c| format!("on_{}", c)));
+
+        let flags = [
+            (self.bold, "bold"),
+            (self.dimmed, "dimmed"),
+            (self.italic, "italic"),
+            (self.underline, "underline"),
+            (self.blink, "blink"),
+            (self.reverse, "reverse"),
+            (self.hidden, "hidden"),
+            (self.strikethrough, "strikethrough"),
+        ];
+
+        colors
+            .chain(flags.iter().filter(|(set, _)| *set).map(|(_, name)| name.to_string()))
+            .chain(Some(&self.extra_codes).filter(|c| !c.is_empty()).map(|c| format!("sgr:{}", c)))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    ///
+    /// Converts this style into CSS declarations for the `style` attribute of an HTML element
+    ///
+    /// Colors are written as `#rrggbb`, using the standard xterm palette for the basic and fixed colors.
+    /// The `Default` color, unknown named colors, blinking and extra SGR parameters are left out.
+    /// The hyperlink is not part of the CSS, and has to be written as a separate link element.
+    ///
+    /// ```
+    /// # use ptree::{Color, Style};
+    /// let style = Style {
+    ///     foreground: Some(Color::RGB(255, 128, 0)),
+    ///     bold: true,
+    ///     underline: true,
+    ///     ..Style::default()
+    /// };
+    /// assert_eq!(style.to_css(), "color:#ff8000;font-weight:bold;text-decoration:underline");
+    /// ```
+    pub fn to_css(&self) -> String {
+        let (foreground, background) = if self.reverse {
+            (&self.background, &self.foreground)
+        } else {
+            (&self.foreground, &self.background)
+        };
+        let hex = |color: &Option<Color>| {
+            color
+                .as_ref()
+                .and_then(|c| c.to_rgb().ok())
+                .map(|(r, g, b)| format!("#{:02x}{:02x}{:02x}", r, g, b))
+        };
+
+        let mut declarations = vec![];
+        if let Some(color) = hex(foreground) {
+            declarations.push(format!("color:{}", color));
+        }
+        if let Some(color) = hex(background) {
+            declarations.push(format!("background-color:{}", color));
+        }
+        if self.bold {
+            declarations.push("font-weight:bold".to_string());
+        }
+        if self.dimmed {
+            declarations.push("opacity:0.5".to_string());
+        }
+        if self.italic {
+            declarations.push("font-style:italic".to_string());
+        }
+
+        let lines = [(self.underline, "underline"), (self.strikethrough, "line-through")];
+        let lines: Vec<&str> = lines.iter().filter(|(set, _)| *set).map(|(_, line)| *line).collect();
+        if !lines.is_empty() {
+            declarations.push(format!("text-decoration:{}", lines.join(" ")));
+        }
+        if let (true, Some(color)) = (self.underline, hex(&self.underline_color)) {
+            declarations.push(format!("text-decoration-color:{}", color));
+        }
+        if self.hidden {
+            declarations.push("visibility:hidden".to_string());
+        }
+        declarations.join(";")
+    }
+
+    ///
+    /// Layers `other` on top of this style
+    ///
+    /// Colors set in `other` replace the colors of this style, while colors that `other` does not set
+    /// are kept. Boolean attributes are enabled if they are enabled in either style.
+    /// Extra SGR parameters and the hyperlink set in `other` replace those of this style.
+    ///
+    /// This makes it possible to apply a per-item style over the styles from a `PrintConfig`.
+    ///
+    /// ```
+    /// # use ptree::{Color, Style};
+    /// let base = Style {
+    ///     foreground: Some(Color::Red),
+    ///     background: Some(Color::Black),
+    ///     ..Style::default()
+    /// };
+    /// let item = Style {
+    ///     foreground: Some(Color::Green),
+    ///     bold: true,
+    ///     ..Style::default()
+    /// };
+    ///
+    /// let merged = base.merge(&item);
+    /// assert_eq!(merged.foreground, Some(Color::Green));
+    /// assert_eq!(merged.background, Some(Color::Black));
+    /// assert!(merged.bold);
+    /// ```
+    pub fn merge(&self, other: &Style) -> Style {
+        Style {
+            foreground: other.foreground.clone().or_else(|| self.foreground.clone()),
+            background: other.background.clone().or_else(|| self.background.clone()),
+            bold: self.bold || other.bold,
+            dimmed: self.dimmed || other.dimmed,
+            italic: self.italic || other.italic,
+            underline: self.underline || other.underline,
+            underline_color: other.underline_color.clone().or_else(|| self.underline_color.clone()),
+            blink: self.blink || other.blink,
+            reverse: self.reverse || other.reverse,
+            hidden: self.hidden || other.hidden,
+            strikethrough: self.strikethrough || other.strikethrough,
+            extra_codes: if other.extra_codes.is_empty() {
+                self.extra_codes.clone()
+            } else {
+                other.extra_codes.clone()
+            },
+            hyperlink: other.hyperlink.clone().or_else(|| self.hyperlink.clone()),
+        }
+    }
+
+    ///
+    /// Generates `steps` copies of this style with foreground colors interpolated between `from` and `to`
+    ///
+    /// This is useful for styling each level of a deep tree differently, see [`Color::gradient`].
+    ///
+    /// ### Errors
+    ///
+    /// Returns an error if either color is a `Named` color with an unknown name.
+    ///
+    /// [`Color::gradient`]: enum.Color.html#method.gradient
+    pub fn gradient(&self, from: &Color, to: &Color, steps: usize) -> Result<Vec<Style>, ParseColorError> {
+        Ok(Color::gradient(from, to, steps)?
+            .into_iter()
+            .map(|c| Style {
+                foreground: Some(c),
+                ..self.clone()
+            })
+            .collect())
+    }
+
+    ///
+    /// Converts the colors of this style to the given color depth
+    ///
+    /// See [`Color::downsample`] for details.
+    ///
+    /// [`Color::downsample`]: enum.Color.html#method.downsample
+    pub fn downsample(&self, depth: ColorDepth) -> Style {
+        let depth = depth.resolve();
+        Style {
+            foreground: self.foreground.as_ref().map(|c| c.downsample(depth)),
+            background: self.background.as_ref().map(|c| c.downsample(depth)),
+            underline_color: self.underline_color.as_ref().map(|c| c.downsample(depth)),
+            ..self.clone()
+        }
+    }
+
+    ///
+    /// Converts this style into a `crossterm` content style
+    ///
+    /// This function is only available with the `"crossterm"` feature.
+    ///
+    #[cfg(feature = "crossterm")]
+    pub fn to_content_style(&self) -> crossterm::style::ContentStyle {
+        use crossterm::style::{Attribute, ContentStyle};
+
+        let mut style = ContentStyle::new();
+
+        style.foreground_color = self.foreground.as_ref().and_then(Color::to_crossterm_color);
+        style.background_color = self.background.as_ref().and_then(Color::to_crossterm_color);
+        style.underline_color = self.underline_color.as_ref().and_then(Color::to_crossterm_color);
+
+        let flags = [
+            (self.bold, Attribute::Bold),
+            (self.dimmed, Attribute::Dim),
+            (self.italic, Attribute::Italic),
+            (self.underline, Attribute::Underlined),
+            (self.blink, Attribute::SlowBlink),
+            (self.reverse, Attribute::Reverse),
+            (self.hidden, Attribute::Hidden),
+            (self.strikethrough, Attribute::CrossedOut),
+        ];
+        for (enabled, attribute) in flags.iter() {
+            if *enabled {
+                style.attributes.set(*attribute);
+            }
+        }
+
+        style
+    }
+
+    ///
+    /// Converts this style into a `termcolor` color specification
+    ///
+    /// `termcolor` has no notion of blinking, reversed or hidden text,
+    /// so these flags are ignored.
+    ///
+    /// This function is only available with the `"termcolor"` feature.
+    ///
+    #[cfg(feature = "termcolor")]
+    pub fn to_color_spec(&self) -> termcolor::ColorSpec {
+        let mut spec = termcolor::ColorSpec::new();
+
+        spec.set_fg(self.foreground.as_ref().and_then(Color::to_termcolor))
+            .set_bg(self.background.as_ref().and_then(Color::to_termcolor))
+            .set_bold(self.bold)
+            .set_dimmed(self.dimmed)
+            .set_italic(self.italic)
+            .set_underline(self.underline)
+            .set_strikethrough(self.strikethrough);
+
+        spec
+    }
+
+    ///
+    /// Writes `input` to `f` according to this style
+    ///
+    /// Unlike [`paint`], which embeds ANSI escape codes into the output text,
+    /// this function sets the style through the [`WriteColor`] interface.
+    /// This allows styling to work on legacy Windows consoles,
+    /// which do not process escape codes.
+    ///
+    /// This function is only available with the `"termcolor"` feature.
+    ///
+    /// [`paint`]: #method.paint
+    /// [`WriteColor`]: https://docs.rs/termcolor/1/termcolor/trait.WriteColor.html
+    #[cfg(feature = "termcolor")]
+    pub fn write_to<W: termcolor::WriteColor>(&self, f: &mut W, input: impl Display) -> io::Result<()> {
+        if *self == Style::default() {
+            return write!(f, "{}", input);
+        }
+
+        let link = self.valid_hyperlink().filter(|_| f.supports_hyperlinks());
+        if let Some(url) = link {
+            f.set_hyperlink(&termcolor::HyperlinkSpec::open(url.as_bytes()))?;
+        }
+
+        f.set_color(&self.to_color_spec())?;
+        write!(f, "{}", input)?;
+        f.reset()?;
+
+        if link.is_some() {
+            f.set_hyperlink(&termcolor::HyperlinkSpec::close())?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Error returned when parsing a [`Style`] from a string fails
+///
+/// [`Style`]: struct.Style.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseStyleError {
+    token: String,
+}
+
+impl ParseStyleError {
+    ///
+    /// The part of the input that could not be parsed
+    ///
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+impl fmt::Display for ParseStyleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid style specifier '{}'", self.token)
+    }
+}
+
+impl Error for ParseStyleError {}
+
+///
+/// Parses a style from a compact, comma-separated list of specifiers
+///
+/// Each specifier is one of:
+///
+/// - a text attribute: `bold`, `dimmed`, `italic`, `underline`, `blink`, `reverse`, `hidden` or `strikethrough`,
+/// - a foreground color: an ANSI color name such as `red`, a named web color such as `steelblue`,
+///   a hex code such as `#4682b4`, or a fixed color index from 0 to 255,
+/// - a background color: any of the foreground colors prefixed with `on_`, such as `on_yellow`,
+/// - extra SGR parameters prefixed with `sgr:`, such as `sgr:53` or `sgr:4:3;53`, see [`Style::extra_codes`].
+///
+/// Specifiers are case insensitive, and whitespace around them is ignored.
+///
+/// [`Style::extra_codes`]: struct.Style.html#structfield.extra_codes
+///
+/// ```
+/// # use ptree::{Color, Style};
+/// let style: Style = "red,bold,on_yellow,underline".parse().unwrap();
+/// assert_eq!(style.foreground, Some(Color::Red));
+/// assert_eq!(style.background, Some(Color::Yellow));
+/// assert!(style.bold && style.underline);
+/// ```
+impl FromStr for Style {
+    type Err = ParseStyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = Style::default();
+
+        for token in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let lower = token.to_lowercase();
+            match &lower[..] {
+                "bold" => style.bold = true,
+                "dimmed" => style.dimmed = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                "blink" => style.blink = true,
+                "reverse" => style.reverse = true,
+                "hidden" => style.hidden = true,
+                "strikethrough" => style.strikethrough = true,
+                t if t.starts_with("sgr:") => {
+                    if !style.extra_codes.is_empty() {
+                        style.extra_codes.push(';');
+                    }
+                    style.extra_codes.push_str(&t["sgr:".len()..]);
+                }
+                t => {
+                    let (background, name) = match t.strip_prefix("on_") {
+                        Some(name) => (true, name),
+                        None => (false, t),
+                    };
+                    let color = name.parse::<Color>().map_err(|_| ParseStyleError {
+                        token: token.to_string(),
+                    })?;
+                    if background {
+                        style.background = Some(color);
+                    } else {
+                        style.foreground = Some(color);
+                    }
+                }
+            }
+        }
+
+        Ok(style)
+    }
+}
+
+///
+/// Text consisting of several segments, each with its own style
+///
+/// This is useful for items whose label mixes styles, such as a name followed by a highlighted version.
+/// When written with [`write_to`], each segment's style is [merged] over the item's style.
+///
+/// ```
+/// # use std::{io, borrow::Cow};
+/// # use ptree::{Style, StyledText, TreeItem};
+/// #[derive(Clone)]
+/// struct Package {
+///     name: String,
+///     version: String,
+/// }
+///
+/// impl TreeItem for Package {
+///     type Child = Self;
+///     fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+///         StyledText::new()
+///             .push_plain(&self.name[..])
+///             .push_plain(" ")
+///             .push("bold".parse().unwrap(), &self.version[..])
+///             .write_to(f, style)
+///     }
+///     fn children(&self) -> Cow<'_, [Self::Child]> {
+///         Cow::from(vec![])
+///     }
+/// }
+/// ```
+///
+/// [`write_to`]: #method.write_to
+/// [merged]: struct.Style.html#method.merge
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct StyledText {
+    segments: Vec<(Style, String)>,
+}
+
+impl StyledText {
+    ///
+    /// Creates empty styled text
+    ///
+    pub fn new() -> StyledText {
+        StyledText::default()
+    }
+
+    ///
+    /// Appends a segment of `text` with the given `style`
+    ///
+    pub fn push(&mut self, style: Style, text: impl Into<String>) -> &mut StyledText {
+        self.segments.push((style, text.into()));
+        self
+    }
+
+    ///
+    /// Appends a segment of `text` without its own style
+    ///
+    pub fn push_plain(&mut self, text: impl Into<String>) -> &mut StyledText {
+        self.push(Style::default(), text)
+    }
+
+    ///
+    /// Returns the segments of this text
+    ///
+    pub fn segments(&self) -> &[(Style, String)] {
+        &self.segments
+    }
+
+    ///
+    /// Returns the text without any styling
+    ///
+    pub fn plain_text(&self) -> String {
+        self.segments.iter().map(|(_, t)| &t[..]).collect()
+    }
+
+    ///
+    /// Returns the number of terminal columns needed to display this text
+    ///
+    /// Wide characters, such as most CJK characters, count as two columns,
+    /// and combining characters do not take up any space.
+    ///
+    pub fn width(&self) -> usize {
+        self.segments.iter().map(|(_, t)| t.width()).sum()
+    }
+
+    ///
+    /// Shortens this text so that its display width does not exceed `max_width` columns
+    ///
+    /// Segments past the limit are removed, and the segment crossing it is cut at a character boundary.
+    ///
+    pub fn truncate(&mut self, max_width: usize) {
+        let mut remaining = max_width;
+        let mut keep = 0;
+
+        for (_, text) in &mut self.segments {
+            if remaining == 0 {
+                break;
+            }
+            keep += 1;
+
+            let mut end = 0;
+            for (i, c) in text.char_indices() {
+                let w = c.width().unwrap_or(0);
+                if w > remaining {
+                    break;
+                }
+                remaining -= w;
+                end = i + c.len_utf8();
+            }
+            if end < text.len() {
+                text.truncate(end);
+                remaining = 0;
+            }
+        }
+
+        self.segments.truncate(keep);
+    }
+
+    ///
+    /// Writes this text to `f`, with each segment's style merged over `style`
+    ///
+    pub fn write_to<W: io::Write + ?Sized>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        for (segment_style, text) in &self.segments {
+            style.merge(segment_style).paint_to(f, format_args!("{}", text))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for StyledText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (style, text) in &self.segments {
+            write!(f, "{}", style.paint(text))?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: Into<String>> From<S> for StyledText {
+    fn from(text: S) -> StyledText {
+        let mut styled = StyledText::new();
+        styled.push_plain(text);
+        styled
+    }
+}
+
+impl<S: Into<String>> FromIterator<(Style, S)> for StyledText {
+    fn from_iter<I: IntoIterator<Item = (Style, S)>>(iter: I) -> StyledText {
+        StyledText {
+            segments: iter.into_iter().map(|(style, text)| (style, text.into())).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use serde_any;
+
+    #[cfg(feature = "ansi")]
+    use ansi_term;
+
+    #[derive(Deserialize)]
+    pub struct Wrapper {
+        color: Color,
+    }
+
+    #[cfg(feature = "ansi")]
+    fn toml_to_ansi(s: &str) -> ansi_term::Color {
+        serde_any::from_str::<Wrapper>(&format!("color = {}", s), serde_any::Format::Toml)
+            .unwrap()
+            .color
+            .to_ansi_color()
+            .unwrap()
+    }
+
+    #[cfg(feature = "ansi")]
+    fn yaml_to_ansi(s: &str) -> ansi_term::Color {
+        serde_any::from_str::<Wrapper>(&format!("color: {}", s), serde_any::Format::Yaml)
+            .unwrap()
+            .color
+            .to_ansi_color()
+            .unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn color_from_toml() {
+        assert_eq!(toml_to_ansi("\"red\""), ansi_term::Color::Red);
+        assert_eq!(toml_to_ansi("\"green\""), ansi_term::Color::Green);
+        assert_eq!(toml_to_ansi("10"), ansi_term::Color::Fixed(10));
+        assert_eq!(toml_to_ansi("110"), ansi_term::Color::Fixed(110));
+        assert_eq!(toml_to_ansi("[10, 20, 30]"), ansi_term::Color::RGB(10, 20, 30));
+        assert_eq!(toml_to_ansi("\"maroon\""), ansi_term::Color::RGB(128, 0, 0));
+        assert_eq!(toml_to_ansi("\"steelblue\""), ansi_term::Color::RGB(70, 130, 180));
+        assert_eq!(toml_to_ansi("\"#4682B4\""), ansi_term::Color::RGB(70, 130, 180));
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn color_from_yaml() {
+        assert_eq!(yaml_to_ansi("\"red\""), ansi_term::Color::Red);
+        assert_eq!(yaml_to_ansi("\"green\""), ansi_term::Color::Green);
+        assert_eq!(yaml_to_ansi("10"), ansi_term::Color::Fixed(10));
+        assert_eq!(yaml_to_ansi("110"), ansi_term::Color::Fixed(110));
+        assert_eq!(yaml_to_ansi("[10, 20, 30]"), ansi_term::Color::RGB(10, 20, 30));
+        assert_eq!(yaml_to_ansi("\"maroon\""), ansi_term::Color::RGB(128, 0, 0));
+        assert_eq!(yaml_to_ansi("\"steelblue\""), ansi_term::Color::RGB(70, 130, 180));
+        assert_eq!(yaml_to_ansi("\"#4682B4\""), ansi_term::Color::RGB(70, 130, 180));
+    }
+
+    #[test]
+    #[cfg(all(any(feature = "ansi", feature = "nu-ansi-term"), not(feature = "crossterm")))]
+    fn paint_all_flags() {
+        let style = Style {
+            blink: true,
+            strikethrough: true,
+            ..Style::default()
+        };
+        assert_eq!(style.paint("text").to_string(), "\u{1b}[5;9mtext\u{1b}[0m");
+
+        let style = Style {
+            reverse: true,
+            hidden: true,
+            ..Style::default()
+        };
+        assert_eq!(style.paint("text").to_string(), "\u{1b}[7;8mtext\u{1b}[0m");
+    }
+
+    #[test]
+    #[cfg(all(any(feature = "ansi", feature = "nu-ansi-term"), not(feature = "crossterm")))]
+    fn paint_extra_codes() {
+        let style = Style {
+            extra_codes: "53".to_string(),
+            ..Style::default()
+        };
+        assert_eq!(style.paint("text").to_string(), "\u{1b}[53mtext\u{1b}[0m");
+
+        let style = Style {
+            bold: true,
+            extra_codes: "4:3".to_string(),
+            ..Style::default()
+        };
+        assert_eq!(style.paint("text").to_string(), "\u{1b}[1m\u{1b}[4:3mtext\u{1b}[0m");
+
+        let style = Style {
+            extra_codes: "53m\u{1b}[0".to_string(),
+            ..Style::default()
+        };
+        assert_eq!(style.paint("text").to_string(), "text");
+    }
+
+    #[test]
+    #[cfg(all(any(feature = "ansi", feature = "nu-ansi-term"), not(feature = "crossterm")))]
+    fn paint_underline_color() {
+        let style = Style {
+            underline: true,
+            underline_color: Some(Color::Fixed(5)),
+            ..Style::default()
+        };
+        let painted = style.paint("text").to_string();
+        assert!(painted.contains("\u{1b}[58;5;5mtext"));
+        assert!(painted.ends_with("\u{1b}[0m"));
+
+        let style = Style {
+            underline_color: Some(Color::RGB(1, 2, 3)),
+            ..Style::default()
+        };
+        assert_eq!(style.paint("text").to_string(), "\u{1b}[58;2;1;2;3mtext\u{1b}[0m");
+
+        let style = Style {
+            underline_color: Some(Color::Named("red".to_string())),
+            ..Style::default()
+        };
+        assert_eq!(style.paint("text").to_string(), "\u{1b}[58;5;1mtext\u{1b}[0m");
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn paint_underline_color_crossterm() {
+        let style = Style {
+            underline_color: Some(Color::RGB(1, 2, 3)),
+            ..Style::default()
+        };
+        assert!(style.paint("text").to_string().contains("58;2;1;2;3m"));
+    }
+
+    #[test]
+    #[cfg(any(feature = "ansi", feature = "nu-ansi-term", feature = "crossterm"))]
+    fn paint_default_color() {
+        let style = Style {
+            foreground: Some(Color::Default),
+            ..Style::default()
+        };
+        assert!(style.paint("text").to_string().starts_with("\u{1b}[39mtext"));
+
+        let style = Style {
+            background: Some(Color::Named("Default".to_string())),
+            ..Style::default()
+        };
+        assert!(style.paint("text").to_string().starts_with("\u{1b}[49mtext"));
+    }
+
+    #[test]
+    #[cfg(any(feature = "ansi", feature = "nu-ansi-term", feature = "crossterm"))]
+    fn paint_hyperlink() {
+        let style = Style {
+            hyperlink: Some("https://example.com".to_string()),
+            ..Style::default()
+        };
+        assert_eq!(
+            style.paint("text").to_string(),
+            "\u{1b}]8;;https://example.com\u{1b}\\text\u{1b}]8;;\u{1b}\\"
+        );
+
+        let bold = Style {
+            bold: true,
+            ..Style::default()
+        };
+        let style = Style {
+            hyperlink: Some("https://example.com".to_string()),
+            ..bold.clone()
+        };
+        assert_eq!(
+            style.paint("text").to_string(),
+            format!(
+                "\u{1b}]8;;https://example.com\u{1b}\\{}\u{1b}]8;;\u{1b}\\",
+                bold.paint("text")
+            )
+        );
+
+        let style = Style {
+            hyperlink: Some("https://example.com\u{7}".to_string()),
+            ..Style::default()
+        };
+        assert_eq!(style.paint("text").to_string(), "text");
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn paint_extra_codes_crossterm() {
+        let style = Style {
+            extra_codes: "53".to_string(),
+            ..Style::default()
+        };
+        assert_eq!(style.paint("text").to_string(), "\u{1b}[53mtext\u{1b}[0m");
+    }
+
+    #[test]
+    fn paint_to_matches_paint() {
+        let styles = [
+            Style::default(),
+            Style {
+                foreground: Some(Color::RGB(1, 2, 3)),
+                background: Some(Color::Fixed(200)),
+                bold: true,
+                underline: true,
+                ..Style::default()
+            },
+        ];
+
+        for style in &styles {
+            let mut out = Vec::new();
+            style.paint_to(&mut out, format_args!("{}-{}", "text", 42)).unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), style.paint("text-42").to_string());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "nu-ansi-term")]
+    fn color_to_nu_ansi() {
+        assert_eq!(Color::Purple.to_nu_ansi_color(), Some(nu_ansi_term::Color::Purple));
+        assert_eq!(Color::Fixed(110).to_nu_ansi_color(), Some(nu_ansi_term::Color::Fixed(110)));
+        assert_eq!(
+            Color::Named("steelblue".to_string()).to_nu_ansi_color(),
+            Some(nu_ansi_term::Color::Rgb(70, 130, 180))
+        );
+        assert_eq!(
+            Color::Named("red".to_string()).to_nu_ansi_color(),
+            Some(nu_ansi_term::Color::Red)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn style_to_content_style() {
+        use crossterm::style::Attribute;
+
+        let style = Style {
+            foreground: Some(Color::Red),
+            background: Some(Color::Named("maroon".to_string())),
+            italic: true,
+            reverse: true,
+            ..Style::default()
+        };
+        let content_style = style.to_content_style();
+
+        assert_eq!(content_style.foreground_color, Some(crossterm::style::Color::DarkRed));
+        assert_eq!(
+            content_style.background_color,
+            Some(crossterm::style::Color::Rgb { r: 128, g: 0, b: 0 })
+        );
+        assert!(content_style.attributes.has(Attribute::Italic));
+        assert!(content_style.attributes.has(Attribute::Reverse));
+        assert!(!content_style.attributes.has(Attribute::Bold));
+    }
+
+    #[test]
+    #[cfg(feature = "termcolor")]
+    fn style_to_color_spec() {
+        let style = Style {
+            foreground: Some(Color::Named("steelblue".to_string())),
+            background: Some(Color::Purple),
+            bold: true,
+            strikethrough: true,
+            ..Style::default()
+        };
+        let spec = style.to_color_spec();
+
+        assert_eq!(spec.fg(), Some(&termcolor::Color::Rgb(70, 130, 180)));
+        assert_eq!(spec.bg(), Some(&termcolor::Color::Magenta));
+        assert!(spec.bold());
+        assert!(spec.strikethrough());
+        assert!(!spec.italic());
+    }
+
+    #[test]
+    #[cfg(feature = "termcolor")]
+    fn write_to_hyperlink() {
+        let style = Style {
+            hyperlink: Some("https://example.com".to_string()),
+            underline: true,
+            ..Style::default()
+        };
+
+        let mut buffer = termcolor::Buffer::ansi();
+        style.write_to(&mut buffer, "text").unwrap();
+        assert_eq!(
+            String::from_utf8(buffer.into_inner()).unwrap(),
+            "\u{1b}]8;;https://example.com\u{1b}\\\u{1b}[0m\u{1b}[4mtext\u{1b}[0m\u{1b}]8;;\u{1b}\\"
+        );
+
+        let mut buffer = termcolor::Buffer::no_color();
+        style.write_to(&mut buffer, "text").unwrap();
+        assert_eq!(String::from_utf8(buffer.into_inner()).unwrap(), "text");
+    }
+
+    #[test]
+    fn color_from_str() {
+        assert_eq!("green".parse(), Ok(Color::Green));
+        assert_eq!(" CYAN ".parse(), Ok(Color::Cyan));
+        assert_eq!("MediumSeaGreen".parse(), Ok(Color::RGB(60, 179, 113)));
+        assert_eq!("#FF8000".parse(), Ok(Color::RGB(255, 128, 0)));
+        assert_eq!("255".parse(), Ok(Color::Fixed(255)));
+        assert_eq!("10,20,30".parse(), Ok(Color::RGB(10, 20, 30)));
+
+        assert!("256".parse::<Color>().is_err());
+        assert!("#12345".parse::<Color>().is_err());
+        assert!("1,2".parse::<Color>().is_err());
+        assert!("light blue".parse::<Color>().is_err());
+        assert!("notacolor".parse::<Color>().is_err());
+        assert!("".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn color_default() {
+        assert_eq!("Default".parse(), Ok(Color::Default));
+        assert_eq!(Color::Default.to_string(), "default");
+        assert_eq!("on_default".parse::<Style>().unwrap().background, Some(Color::Default));
+        assert_eq!(Color::Default.downsample(ColorDepth::Ansi16), Color::Default);
+        assert!(Color::Default.to_rgb().is_err());
+
+        let base = Style {
+            foreground: Some(Color::Red),
+            ..Style::default()
+        };
+        let reset = Style {
+            foreground: Some(Color::Default),
+            ..Style::default()
+        };
+        assert_eq!(base.merge(&reset).foreground, Some(Color::Default));
+    }
+
+    #[test]
+    fn color_display_round_trip() {
+        let colors = vec![
+            Color::Black,
+            Color::Purple,
+            Color::White,
+            Color::Fixed(42),
+            Color::RGB(70, 130, 180),
+        ];
+        for c in colors {
+            assert_eq!(c.to_string().parse(), Ok(c));
+        }
+        assert_eq!(Color::Named("steelblue".to_string()).to_string(), "steelblue");
+        assert_eq!(Color::RGB(1, 2, 255).to_string(), "#0102ff");
+    }
+
+    #[test]
+    fn color_toml_round_trip() {
+        #[derive(Serialize, Deserialize)]
+        struct Colors {
+            named: Color,
+            fixed: Color,
+            rgb: Color,
+        }
+
+        let colors = Colors {
+            named: Color::Red,
+            fixed: Color::Fixed(10),
+            rgb: Color::RGB(10, 20, 30),
+        };
+        let toml = serde_any::to_string(&colors, serde_any::Format::Toml).unwrap();
+        assert_eq!(toml, "named = \"red\"\nfixed = 10\nrgb = [10, 20, 30]\n");
+
+        let colors = serde_any::from_str::<Colors>(&toml, serde_any::Format::Toml).unwrap();
+        assert_eq!(colors.named, Color::Red);
+        assert_eq!(colors.fixed, Color::Fixed(10));
+        assert_eq!(colors.rgb, Color::RGB(10, 20, 30));
+    }
+
+    #[test]
+    fn color_resolve() {
+        assert_eq!(Color::Named("Blue".to_string()).resolve(), Ok(Color::Blue));
+        assert_eq!(Color::Named("maroon".to_string()).resolve(), Ok(Color::RGB(128, 0, 0)));
+        assert_eq!(Color::Named("#4682B4".to_string()).resolve(), Ok(Color::RGB(70, 130, 180)));
+        assert_eq!(Color::Fixed(3).resolve(), Ok(Color::Fixed(3)));
+        assert!(Color::Named("notacolor".to_string()).resolve().is_err());
+
+        let style = Style {
+            foreground: Some(Color::Named("bogus".to_string())),
+            ..Style::default()
+        };
+        assert!(style.resolve().is_err());
+    }
+
+    #[test]
+    fn color_deserialize_errors() {
+        let from_toml = |s: &str| serde_any::from_str::<Wrapper>(&format!("color = {}", s), serde_any::Format::Toml);
+        assert!(from_toml("\"notacolor\"").is_err());
+        assert!(from_toml("256").is_err());
+        assert!(from_toml("-1").is_err());
+        assert!(from_toml("[1, 2]").is_err());
+        assert!(from_toml("[1, 2, 3, 4]").is_err());
+        assert_eq!(from_toml("\"Maroon\"").unwrap().color, Color::RGB(128, 0, 0));
+    }
+
+    #[test]
+    fn style_merge() {
+        let base = Style {
+            foreground: Some(Color::Red),
+            background: Some(Color::Blue),
+            dimmed: true,
+            ..Style::default()
+        };
+        let other = Style {
+            background: Some(Color::RGB(1, 2, 3)),
+            italic: true,
+            underline_color: Some(Color::Green),
+            ..Style::default()
+        };
+
+        assert_eq!(
+            base.merge(&other),
+            Style {
+                foreground: Some(Color::Red),
+                background: Some(Color::RGB(1, 2, 3)),
+                dimmed: true,
+                italic: true,
+                underline_color: Some(Color::Green),
+                ..Style::default()
+            }
+        );
+        assert_eq!(base.merge(&Style::default()), base);
+        assert_eq!(Style::default().merge(&base), base);
+    }
+
+    #[test]
+    fn styled_text_width() {
+        let bold = Style {
+            bold: true,
+            ..Style::default()
+        };
+
+        let mut text = StyledText::new();
+        text.push_plain("ab").push(bold.clone(), "漢字").push_plain("e\u{301}");
+        assert_eq!(text.width(), 7);
+        assert_eq!(text.plain_text(), "ab漢字e\u{301}");
+
+        let mut truncated = text.clone();
+        truncated.truncate(5);
+        assert_eq!(
+            truncated.segments(),
+            &[(Style::default(), "ab".to_string()), (bold.clone(), "漢".to_string())]
+        );
+
+        let mut truncated = text.clone();
+        truncated.truncate(2);
+        assert_eq!(truncated.segments(), &[(Style::default(), "ab".to_string())]);
+
+        let mut truncated = text.clone();
+        truncated.truncate(10);
+        assert_eq!(truncated, text);
+    }
+
+    #[test]
+    fn styled_text_write_to() {
+        let text: StyledText = vec![
+            (Style::default(), "name "),
+            (
+                Style {
+                    bold: true,
+                    ..Style::default()
+                },
+                "1.0",
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let base = Style {
+            foreground: Some(Color::Red),
+            ..Style::default()
+        };
+        let bold_red = Style {
+            bold: true,
+            ..base.clone()
+        };
+
+        let mut out = Vec::new();
+        text.write_to(&mut out, &base).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{}{}", base.paint("name "), bold_red.paint("1.0"))
+        );
+    }
+
+    #[test]
+    fn style_to_spec_string() {
+        assert_eq!(Style::default().to_spec_string(), "");
+
+        let styles = [
+            Style {
+                foreground: Some(Color::Fixed(208)),
+                ..Style::default()
+            },
+            Style {
+                background: Some(Color::Cyan),
+                italic: true,
+                strikethrough: true,
+                ..Style::default()
+            },
+            Style {
+                foreground: Some(Color::White),
+                background: Some(Color::RGB(1, 2, 3)),
+                bold: true,
+                dimmed: true,
+                underline: true,
+                blink: true,
+                reverse: true,
+                hidden: true,
+                ..Style::default()
+            },
+        ];
+
+        for style in &styles {
+            assert_eq!(&style.to_spec_string().parse::<Style>().unwrap(), style);
+        }
+        assert_eq!(styles[1].to_spec_string(), "on_cyan,italic,strikethrough");
+
+        let style = Style {
+            underline: true,
+            extra_codes: "4:3;53".to_string(),
+            ..Style::default()
+        };
+        assert_eq!(style.to_spec_string(), "underline,sgr:4:3;53");
+        assert_eq!("underline,sgr:4:3,sgr:53".parse::<Style>().unwrap(), style);
+    }
+
+    #[test]
+    fn color_gradient() {
+        assert_eq!(Color::Red.to_rgb(), Ok((205, 0, 0)));
+        assert_eq!(Color::Fixed(244).to_rgb(), Ok((128, 128, 128)));
+        assert_eq!(Color::Named("maroon".to_string()).to_rgb(), Ok((128, 0, 0)));
+
+        let colors = Color::gradient(&Color::RGB(10, 20, 30), &Color::Named("white".to_string()), 4).unwrap();
+        assert_eq!(
+            colors,
+            vec![
+                Color::RGB(10, 20, 30),
+                Color::RGB(83, 90, 96),
+                Color::RGB(156, 159, 163),
+                Color::RGB(229, 229, 229),
+            ]
+        );
+        assert_eq!(
+            Color::gradient(&Color::Blue, &Color::Red, 1),
+            Ok(vec![Color::RGB(0, 0, 238)])
+        );
+        assert_eq!(Color::gradient(&Color::Blue, &Color::Red, 0), Ok(vec![]));
+        assert!(Color::gradient(&Color::Named("bogus".to_string()), &Color::Red, 2).is_err());
+
+        let base = Style {
+            bold: true,
+            ..Style::default()
+        };
+        let styles = base.gradient(&Color::RGB(0, 0, 0), &Color::RGB(0, 0, 100), 3).unwrap();
+        assert_eq!(styles.len(), 3);
+        assert!(styles.iter().all(|s| s.bold));
+        assert_eq!(styles[1].foreground, Some(Color::RGB(0, 0, 50)));
+    }
+
+    #[test]
+    fn color_downsample() {
+        assert_eq!(Color::RGB(255, 0, 0).downsample(ColorDepth::TrueColor), Color::RGB(255, 0, 0));
+        assert_eq!(Color::RGB(255, 0, 0).downsample(ColorDepth::Ansi256), Color::Fixed(196));
+        assert_eq!(Color::RGB(128, 128, 128).downsample(ColorDepth::Ansi256), Color::Fixed(244));
+        assert_eq!(Color::RGB(250, 5, 5).downsample(ColorDepth::Ansi16), Color::Fixed(9));
+        assert_eq!(Color::Fixed(196).downsample(ColorDepth::Ansi16), Color::Fixed(9));
+        assert_eq!(Color::Fixed(3).downsample(ColorDepth::Ansi16), Color::Fixed(3));
+        assert_eq!(Color::Named("maroon".to_string()).downsample(ColorDepth::Ansi16), Color::Red);
+        assert_eq!(Color::Cyan.downsample(ColorDepth::Ansi16), Color::Cyan);
+
+        let style = Style {
+            foreground: Some(Color::RGB(0, 0, 240)),
+            background: Some(Color::RGB(255, 255, 255)),
+            bold: true,
+            ..Style::default()
+        };
+        assert_eq!(
+            style.downsample(ColorDepth::Ansi16),
+            Style {
+                foreground: Some(Color::Blue),
+                background: Some(Color::Fixed(15)),
+                bold: true,
+                ..Style::default()
+            }
+        );
+    }
+
+    #[test]
+    fn style_from_str() {
+        let style: Style = "red, bold,on_Yellow,underline".parse().unwrap();
+        let expected = Style {
+            foreground: Some(Color::Red),
+            background: Some(Color::Yellow),
+            bold: true,
+            underline: true,
+            ..Style::default()
+        };
+        assert_eq!(style, expected);
+
+        let style: Style = "SteelBlue,on_#102030,strikethrough".parse().unwrap();
+        let expected = Style {
+            foreground: Some(Color::RGB(70, 130, 180)),
+            background: Some(Color::RGB(16, 32, 48)),
+            strikethrough: true,
+            ..Style::default()
+        };
+        assert_eq!(style, expected);
+
+        assert_eq!("on_110".parse::<Style>().unwrap().background, Some(Color::Fixed(110)));
+        assert_eq!("".parse::<Style>().unwrap(), Style::default());
+        assert_eq!("bold,#12345".parse::<Style>().unwrap_err().token(), "#12345");
+        assert_eq!("on_".parse::<Style>().unwrap_err().token(), "on_");
     }
 
     #[test]
@@ -242,7 +2025,7 @@ mod tests {
         let expected = Style {
             dimmed: true,
             bold: true,
-            foreground: Some(Color::Named("#102030".to_string())),
+            foreground: Some(Color::RGB(16, 32, 48)),
             background: Some(Color::Fixed(3)),
             ..Style::default()
         };
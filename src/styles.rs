@@ -0,0 +1,66 @@
+//!
+//! Semantic style presets
+//!
+//! These styles use the basic ANSI colors, whose exact appearance is defined by the terminal theme.
+//! This keeps them readable on both light and dark backgrounds,
+//! and they are left unchanged when styles are downsampled to a lower color depth.
+//!
+//! ```
+//! # use ptree::{styles, Style};
+//! let error = styles::ERROR.paint("failed");
+//! let muted = Style {
+//!     italic: true,
+//!     ..styles::MUTED
+//! };
+//! ```
+//!
+
+use style::{Color, Style};
+
+/// Builds a preset, as `Style::default()` cannot be used in constants
+const fn preset(foreground: Option<Color>, bold: bool, dimmed: bool) -> Style {
+    Style {
+        foreground,
+        background: None,
+        bold,
+        dimmed,
+        italic: false,
+        underline: false,
+        underline_color: None,
+        blink: false,
+        reverse: false,
+        hidden: false,
+        strikethrough: false,
+        extra_codes: String::new(),
+        hyperlink: None,
+    }
+}
+
+/// Style for failures and invalid data, in bold red
+pub const ERROR: Style = preset(Some(Color::Red), true, false);
+
+/// Style for items which need attention, in yellow
+pub const WARNING: Style = preset(Some(Color::Yellow), false, false);
+
+/// Style for successful or healthy items, in green
+pub const SUCCESS: Style = preset(Some(Color::Green), false, false);
+
+/// Style for secondary information, dimmed in the default color
+pub const MUTED: Style = preset(None, false, true);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use style::ColorDepth;
+
+    #[test]
+    fn presets_adapt_to_theme() {
+        assert_eq!(preset(None, false, false), Style::default());
+
+        for style in &[ERROR, WARNING, SUCCESS, MUTED] {
+            assert_ne!(*style, Style::default());
+            assert_eq!(style.downsample(ColorDepth::Ansi16), *style);
+        }
+    }
+}
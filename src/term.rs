@@ -0,0 +1,173 @@
+//!
+//! Detection of terminal capabilities
+//!
+//! The functions in this module inspect the environment to find out which colors and characters
+//! the terminal is able to display.
+//! They are used to resolve [`ColorDepth::Auto`] and by [`PrintConfig::for_terminal`].
+//!
+//! [`ColorDepth::Auto`]: ../style/enum.ColorDepth.html#variant.Auto
+//! [`PrintConfig::for_terminal`]: ../print_config/struct.PrintConfig.html#method.for_terminal
+//!
+
+use style::ColorDepth;
+
+use std::env;
+
+///
+/// Detects the color depth supported by the terminal
+///
+/// Truecolor support is assumed if `COLORTERM` is set to `truecolor` or `24bit`.
+/// Otherwise, a `TERM` value containing `256color` selects the 256-color palette,
+/// and any other value selects the 16 basic colors.
+///
+/// If `TERM` is not set, the result depends on the platform.
+/// On Windows, the console mode is queried, and truecolor is assumed if the console processes
+/// virtual terminal sequences. Other platforms assume truecolor support.
+/// The console is only queried with the `"ansi"` or `"nu-ansi-term"` feature,
+/// without them Windows is treated like the other platforms.
+///
+pub fn color_depth() -> ColorDepth {
+    let (colorterm, term) = (env::var("COLORTERM").ok(), env::var("TERM").ok());
+    terminal_color_depth(colorterm.as_deref(), term.as_deref()).unwrap_or_else(console_color_depth)
+}
+
+/// Detects the color depth from the values of `COLORTERM` and `TERM`, or returns `None` if `TERM` is not set
+fn terminal_color_depth(colorterm: Option<&str>, term: Option<&str>) -> Option<ColorDepth> {
+    if colorterm == Some("truecolor") || colorterm == Some("24bit") {
+        return Some(ColorDepth::TrueColor);
+    }
+
+    match term {
+        Some(term) if term.contains("256color") => Some(ColorDepth::Ansi256),
+        Some(_) => Some(ColorDepth::Ansi16),
+        None => None,
+    }
+}
+
+#[cfg(all(windows, any(feature = "ansi", feature = "nu-ansi-term")))]
+fn console_color_depth() -> ColorDepth {
+    use winapi::um::consoleapi::GetConsoleMode;
+    use winapi::um::processenv::GetStdHandle;
+    use winapi::um::winbase::STD_OUTPUT_HANDLE;
+    use winapi::um::wincon::ENABLE_VIRTUAL_TERMINAL_PROCESSING;
+
+    if env::var_os("WT_SESSION").is_some() {
+        return ColorDepth::TrueColor;
+    }
+
+    let mut mode = 0;
+    // SAFETY: the handle is only passed to `GetConsoleMode`, which fails for non-console handles
+    let is_console = unsafe { GetConsoleMode(GetStdHandle(STD_OUTPUT_HANDLE), &mut mode) != 0 };
+
+    if !is_console || mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0 {
+        ColorDepth::TrueColor
+    } else {
+        ColorDepth::Ansi16
+    }
+}
+
+#[cfg(not(all(windows, any(feature = "ansi", feature = "nu-ansi-term"))))]
+fn console_color_depth() -> ColorDepth {
+    ColorDepth::TrueColor
+}
+
+///
+/// Enables processing of ANSI escape sequences by the Windows console
+///
+/// Windows 10 and later consoles understand ANSI escape codes,
+/// but only after virtual terminal processing is enabled for the output handle.
+/// This function enables it for the standard output, and returns whether escape codes can be used.
+/// It returns `false` on older Windows versions, which do not support virtual terminal processing.
+///
+/// If the standard output is not a console, or on platforms other than Windows,
+/// this function does nothing and returns `true`.
+/// It also does nothing without the `"ansi"` or `"nu-ansi-term"` feature.
+///
+pub fn enable_virtual_terminal() -> bool {
+    console_enable_virtual_terminal()
+}
+
+#[cfg(all(windows, any(feature = "ansi", feature = "nu-ansi-term")))]
+fn console_enable_virtual_terminal() -> bool {
+    use winapi::um::consoleapi::{GetConsoleMode, SetConsoleMode};
+    use winapi::um::processenv::GetStdHandle;
+    use winapi::um::winbase::STD_OUTPUT_HANDLE;
+    use winapi::um::wincon::ENABLE_VIRTUAL_TERMINAL_PROCESSING;
+
+    // SAFETY: the handle is only passed to console functions, which fail for non-console handles
+    unsafe {
+        let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+        let mut mode = 0;
+
+        if GetConsoleMode(handle, &mut mode) == 0 {
+            return true;
+        }
+
+        mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+            || SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+    }
+}
+
+#[cfg(not(all(windows, any(feature = "ansi", feature = "nu-ansi-term"))))]
+fn console_enable_virtual_terminal() -> bool {
+    true
+}
+
+///
+/// Detects whether the terminal can display unicode box-drawing characters
+///
+/// Dumb terminals never support unicode.
+/// On Windows, the console is assumed to support unicode.
+/// On other platforms, the locale set by the `LC_ALL`, `LC_CTYPE` or `LANG` environment variables
+/// has to use the UTF-8 encoding.
+///
+pub fn supports_unicode() -> bool {
+    let locales: Vec<_> = ["LC_ALL", "LC_CTYPE", "LANG"].iter().map(|v| env::var(v).ok()).collect();
+    terminal_supports_unicode(env::var("TERM").ok().as_deref(), &locales)
+}
+
+/// Detects unicode support from the value of `TERM` and the values of the locale variables, in order of precedence
+fn terminal_supports_unicode(term: Option<&str>, locales: &[Option<String>]) -> bool {
+    if term == Some("dumb") {
+        return false;
+    }
+
+    cfg!(windows) || locale_is_utf8(locales)
+}
+
+fn locale_is_utf8(locales: &[Option<String>]) -> bool {
+    locales
+        .iter()
+        .flatten()
+        .find(|l| !l.is_empty())
+        .map(|l| {
+            let l = l.to_lowercase();
+            l.contains("utf-8") || l.contains("utf8")
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_color_depth() {
+        assert_eq!(terminal_color_depth(Some("truecolor"), Some("xterm")), Some(ColorDepth::TrueColor));
+        assert_eq!(terminal_color_depth(None, Some("xterm-256color")), Some(ColorDepth::Ansi256));
+        assert_eq!(terminal_color_depth(None, Some("linux")), Some(ColorDepth::Ansi16));
+        assert_eq!(terminal_color_depth(None, None), None);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn detect_unicode() {
+        let locale = |lang: Option<&str>| [None, None, lang.map(String::from)];
+
+        assert!(terminal_supports_unicode(Some("xterm"), &locale(Some("en_US.UTF-8"))));
+        assert!(terminal_supports_unicode(Some("xterm"), &locale(Some("de_DE.utf8"))));
+        assert!(!terminal_supports_unicode(Some("xterm"), &locale(Some("C"))));
+        assert!(!terminal_supports_unicode(Some("xterm"), &locale(None)));
+        assert!(!terminal_supports_unicode(Some("dumb"), &[Some("en_US.UTF-8".to_string()), None, None]));
+    }
+}
@@ -0,0 +1,214 @@
+//!
+//! Helpers for comparing rendered trees in tests
+//!
+//! [`render_plain`] renders a tree with a fixed configuration which does not depend on the terminal or the environment:
+//! no styles, UTF-8 branch characters, an indentation of 3 and LF line endings.
+//! The [`assert_tree_eq!`] macro compares a rendered tree with the expected text,
+//! and panics with a line-by-line diff if they differ.
+//!
+//! ```
+//! # #[macro_use] extern crate ptree;
+//! # use ptree::TreeBuilder;
+//! # fn main() {
+//! let tree = TreeBuilder::new("root".to_string())
+//!     .begin_child("a".to_string())
+//!     .add_empty_child("b".to_string())
+//!     .end_child()
+//!     .build();
+//!
+//! assert_tree_eq!(tree, "root\n└─ a\n   └─ b\n");
+//! # }
+//! ```
+//!
+//! [`render_plain`]: fn.render_plain.html
+//! [`assert_tree_eq!`]: ../macro.assert_tree_eq.html
+//!
+
+use item::TreeItem;
+use output::write_tree_with;
+use print_config::{IndentChars, PrintConfig, StyleWhen, ASCII_CHARS_TICK, UTF_CHARS};
+
+use std::fmt::Write;
+
+///
+/// Returns the configuration used by [`render_plain`], with the branch characters `characters`
+///
+/// [`render_plain`]: fn.render_plain.html
+///
+pub fn plain_config(characters: IndentChars) -> PrintConfig {
+    PrintConfig {
+        depth: u32::MAX,
+        max_children: None,
+        indent: 3,
+        padding: 1,
+        characters,
+        styled: StyleWhen::Never,
+        line_width: None,
+        ..PrintConfig::default()
+    }
+}
+
+///
+/// Renders the tree `item` using the configuration `config`, with escape sequences removed and LF line endings
+///
+/// # Panics
+///
+/// Panics if the tree cannot be written.
+///
+pub fn render_with<T: TreeItem>(item: &T, config: &PrintConfig) -> String {
+    let config = PrintConfig {
+        styled: StyleWhen::Never,
+        ..config.clone()
+    };
+    let mut out = Vec::new();
+    write_tree_with(item, &mut out, &config).expect("the tree can be written");
+    String::from_utf8_lossy(&out).replace("\r\n", "\n")
+}
+
+///
+/// Renders the tree `item` without styles, using UTF-8 branch characters
+///
+pub fn render_plain<T: TreeItem>(item: &T) -> String {
+    render_with(item, &plain_config(UTF_CHARS.into()))
+}
+
+///
+/// Renders the tree `item` without styles, using ASCII branch characters
+///
+pub fn render_ascii<T: TreeItem>(item: &T) -> String {
+    render_with(item, &plain_config(ASCII_CHARS_TICK.into()))
+}
+
+///
+/// Returns a line-by-line diff between `expected` and `actual`, or `None` if they are equal
+///
+/// Lines only in `expected` are prefixed with `-`, and lines only in `actual` with `+`.
+/// Line endings are shown as `⏎`, so differences in trailing newlines are visible.
+///
+pub fn line_diff(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let old = expected.split_inclusive('\n').collect::<Vec<_>>();
+    let new = actual.split_inclusive('\n').collect::<Vec<_>>();
+
+    // Lengths of the longest common subsequences of the suffixes of both texts
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::from("--- expected\n+++ actual\n");
+    let mut line = |prefix: char, text: &str| {
+        let text = match text.strip_suffix('\n') {
+            Some(text) => format!("{}\u{23ce}", text),
+            None => text.to_string(),
+        };
+        let _ = writeln!(diff, "{} {}", prefix, text);
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            line(' ', old[i]);
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            line('-', old[i]);
+            i += 1;
+        } else {
+            line('+', new[j]);
+            j += 1;
+        }
+    }
+    Some(diff)
+}
+
+///
+/// Asserts that a tree renders to the expected text
+///
+/// The tree is rendered with [`render_plain`], or with [`render_with`] if a configuration is given as the third argument.
+/// On a mismatch, the panic message contains a diff of the expected and the rendered lines.
+///
+/// ```should_panic
+/// # #[macro_use] extern crate ptree;
+/// # use ptree::TreeBuilder;
+/// # use ptree::testing::plain_config;
+/// # use ptree::print_config::ASCII_CHARS_PLUS;
+/// # fn main() {
+/// let tree = TreeBuilder::new("root".to_string())
+///     .add_empty_child("a".to_string())
+///     .build();
+///
+/// assert_tree_eq!(tree, "root\n+- a\n", &plain_config(ASCII_CHARS_PLUS.into()));
+/// assert_tree_eq!(tree, "root\n└─ b\n");
+/// # }
+/// ```
+///
+/// [`render_plain`]: testing/fn.render_plain.html
+/// [`render_with`]: testing/fn.render_with.html
+///
+#[macro_export]
+macro_rules! assert_tree_eq {
+    ($item:expr, $expected:expr) => {
+        if let Some(diff) = $crate::testing::line_diff($expected, &$crate::testing::render_plain(&$item)) {
+            panic!("rendered tree does not match the expected tree\n{}", diff);
+        }
+    };
+    ($item:expr, $expected:expr, $config:expr) => {
+        if let Some(diff) = $crate::testing::line_diff($expected, &$crate::testing::render_with(&$item, $config)) {
+            panic!("rendered tree does not match the expected tree\n{}", diff);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use builder::TreeBuilder;
+    use item::StringItem;
+    use print_config::ASCII_CHARS_PLUS;
+
+    fn tree() -> StringItem {
+        TreeBuilder::new("root".to_string())
+            .begin_child("\x1b[1ma\x1b[0m".to_string())
+            .add_empty_child("b".to_string())
+            .end_child()
+            .add_empty_child("c".to_string())
+            .build()
+    }
+
+    #[test]
+    fn plain_rendering() {
+        assert_eq!(render_plain(&tree()), "root\n├─ a\n│  └─ b\n└─ c\n");
+        assert_eq!(render_ascii(&tree()), "root\n|- a\n|  `- b\n`- c\n");
+        assert_tree_eq!(tree(), "root\n├─ a\n│  └─ b\n└─ c\n");
+        assert_tree_eq!(
+            tree(),
+            "root\n+- a\n|  +- b\n+- c\n",
+            &plain_config(ASCII_CHARS_PLUS.into())
+        );
+    }
+
+    #[test]
+    fn diffs() {
+        assert_eq!(line_diff("a\nb\n", "a\nb\n"), None);
+        assert_eq!(
+            line_diff("a\nb\nc\n", "a\nx\nc").unwrap(),
+            "--- expected\n+++ actual\n  a\u{23ce}\n- b\u{23ce}\n- c\u{23ce}\n+ x\u{23ce}\n+ c\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "- └─ d")]
+    fn mismatch() {
+        assert_tree_eq!(tree(), "root\n├─ a\n│  └─ b\n└─ d\n");
+    }
+}
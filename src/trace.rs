@@ -0,0 +1,437 @@
+//!
+//! Rendering of `tracing` spans as trees
+//!
+//! A [`SpanTreeLayer`] is a [`Layer`] which collects the hierarchy of spans recorded with the [`tracing`] crate.
+//! When a span without a parent closes, the tree of it and all spans entered below it is complete,
+//! and is either written right away, or kept until it is taken through a [`SpanTrees`] handle.
+//!
+//! Each span is labeled with its name and fields, followed by the time it was open,
+//! such as `request{id=7}  [12.3ms]`.
+//!
+//! ```
+//! # extern crate ptree;
+//! # extern crate tracing;
+//! # extern crate tracing_subscriber;
+//! # use ptree::trace::SpanTreeLayer;
+//! # use tracing_subscriber::prelude::*;
+//! # fn main() {
+//! let layer = SpanTreeLayer::new().durations(false);
+//! let trees = layer.trees();
+//! let subscriber = tracing_subscriber::registry().with(layer);
+//!
+//! tracing::subscriber::with_default(subscriber, || {
+//!     let _build = tracing::info_span!("build", target = "release").entered();
+//!     tracing::info_span!("compile").in_scope(|| {});
+//! });
+//!
+//! let trees = trees.take();
+//! assert_eq!(trees[0].text, "build{target=\"release\"}");
+//! assert_eq!(trees[0].children[0].text, "compile");
+//! # }
+//! ```
+//!
+//! [`SpanTreeLayer`]: struct.SpanTreeLayer.html
+//! [`SpanTrees`]: struct.SpanTrees.html
+//! [`Layer`]: https://docs.rs/tracing-subscriber/0.3/tracing_subscriber/layer/trait.Layer.html
+//! [`tracing`]: https://docs.rs/tracing/0.1/tracing/
+//!
+
+use item::StringItem;
+use output::write_tree_with;
+use print_config::PrintConfig;
+
+use std::fmt::{self, Write as FmtWrite};
+use std::io;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Destination of trees which are written when their root span closes
+struct Output {
+    writer: Mutex<Box<dyn io::Write + Send>>,
+    config: PrintConfig,
+}
+
+///
+/// Layer collecting the hierarchy of spans into trees
+///
+/// By default, completed trees are kept until they are taken with [`SpanTrees::take`].
+/// They can be written when their root span closes instead, with [`print_on_close`] or [`write_on_close`].
+///
+/// The layer stores its data in the extensions of spans, so it must be used with a subscriber
+/// implementing [`LookupSpan`], such as the [`Registry`].
+///
+/// [`SpanTrees::take`]: struct.SpanTrees.html#method.take
+/// [`print_on_close`]: struct.SpanTreeLayer.html#method.print_on_close
+/// [`write_on_close`]: struct.SpanTreeLayer.html#method.write_on_close
+/// [`LookupSpan`]: https://docs.rs/tracing-subscriber/0.3/tracing_subscriber/registry/trait.LookupSpan.html
+/// [`Registry`]: https://docs.rs/tracing-subscriber/0.3/tracing_subscriber/registry/struct.Registry.html
+///
+pub struct SpanTreeLayer {
+    trees: Arc<Mutex<Vec<StringItem>>>,
+    output: Option<Output>,
+    durations: bool,
+    events: bool,
+}
+
+///
+/// Handle to the trees completed by a [`SpanTreeLayer`]
+///
+/// [`SpanTreeLayer`]: struct.SpanTreeLayer.html
+///
+#[derive(Clone, Debug)]
+pub struct SpanTrees {
+    trees: Arc<Mutex<Vec<StringItem>>>,
+}
+
+/// Data of an open span, stored in its extensions
+struct SpanNode {
+    label: String,
+    start: Instant,
+    /// Completed child spans and events, with the times they started
+    children: Vec<(Instant, StringItem)>,
+}
+
+/// Appends the fields of spans and events to a label, such as `{id=7, user="jane"}`
+struct FieldVisitor<'a> {
+    label: &'a mut String,
+    /// Whether the label already contains fields, in which case the closing brace is replaced
+    has_fields: bool,
+    /// Message of an event, which is printed before its other fields
+    message: Option<String>,
+}
+
+impl<'a> FieldVisitor<'a> {
+    fn new(label: &'a mut String) -> Self {
+        let has_fields = label.ends_with('}');
+        FieldVisitor {
+            label,
+            has_fields,
+            message: None,
+        }
+    }
+}
+
+impl<'a> Visit for FieldVisitor<'a> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{:?}", value));
+            return;
+        }
+        if self.has_fields {
+            self.label.pop();
+            self.label.push_str(", ");
+        } else {
+            self.label.push('{');
+            self.has_fields = true;
+        }
+        let _ = write!(self.label, "{}={:?}}}", field.name(), value);
+    }
+}
+
+impl SpanTreeLayer {
+    ///
+    /// Creates a layer which keeps completed trees until they are taken
+    ///
+    pub fn new() -> Self {
+        SpanTreeLayer {
+            trees: Arc::new(Mutex::new(vec![])),
+            output: None,
+            durations: true,
+            events: false,
+        }
+    }
+
+    ///
+    /// Returns a handle for taking the trees completed by this layer
+    ///
+    pub fn trees(&self) -> SpanTrees {
+        SpanTrees {
+            trees: self.trees.clone(),
+        }
+    }
+
+    ///
+    /// Prints each tree to standard error when its root span closes, instead of keeping it
+    ///
+    pub fn print_on_close(self, config: PrintConfig) -> Self {
+        self.write_on_close(io::stderr(), config)
+    }
+
+    ///
+    /// Writes each tree to `writer` when its root span closes, instead of keeping it
+    ///
+    pub fn write_on_close<W: io::Write + Send + 'static>(mut self, writer: W, config: PrintConfig) -> Self {
+        self.output = Some(Output {
+            writer: Mutex::new(Box::new(writer)),
+            config,
+        });
+        self
+    }
+
+    ///
+    /// Sets whether the time each span was open is printed after its label
+    ///
+    /// The default value is `true`.
+    ///
+    pub fn durations(mut self, enabled: bool) -> Self {
+        self.durations = enabled;
+        self
+    }
+
+    ///
+    /// Sets whether events are listed as children of the spans they occur in
+    ///
+    /// Events are labeled with their level and message, followed by their other fields.
+    /// Events outside of any span are ignored. The default value is `false`.
+    ///
+    pub fn events(mut self, enabled: bool) -> Self {
+        self.events = enabled;
+        self
+    }
+
+    /// Writes or keeps a completed tree
+    fn complete(&self, tree: StringItem) {
+        match self.output {
+            Some(ref output) => {
+                let mut writer = output.writer.lock().unwrap_or_else(|e| e.into_inner());
+                // Errors can't be reported from within a layer, so the tree is dropped
+                let _ = write_tree_with(&tree, &mut *writer, &output.config);
+            }
+            None => self.trees.lock().unwrap_or_else(|e| e.into_inner()).push(tree),
+        }
+    }
+}
+
+impl Default for SpanTreeLayer {
+    fn default() -> Self {
+        SpanTreeLayer::new()
+    }
+}
+
+impl<S> Layer<S> for SpanTreeLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+        let mut label = span.name().to_string();
+        attrs.record(&mut FieldVisitor::new(&mut label));
+        span.extensions_mut().insert(SpanNode {
+            label,
+            start: Instant::now(),
+            children: vec![],
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(node) = span.extensions_mut().get_mut::<SpanNode>() {
+                values.record(&mut FieldVisitor::new(&mut node.label));
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if !self.events {
+            return;
+        }
+        let span = match ctx.event_span(event) {
+            Some(span) => span,
+            None => return,
+        };
+
+        let mut fields = String::new();
+        let mut visitor = FieldVisitor::new(&mut fields);
+        event.record(&mut visitor);
+        let message = visitor.message.take();
+        let text = match message {
+            Some(message) => format!("{} {}{}", event.metadata().level(), message, fields),
+            None => format!("{} {}{}", event.metadata().level(), event.metadata().name(), fields),
+        };
+
+        let mut extensions = span.extensions_mut();
+        if let Some(node) = extensions.get_mut::<SpanNode>() {
+            let item = StringItem::new(text, vec![]);
+            node.children.push((Instant::now(), item));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(&id) {
+            Some(span) => span,
+            None => return,
+        };
+        let node = match span.extensions_mut().remove::<SpanNode>() {
+            Some(node) => node,
+            None => return,
+        };
+
+        let mut text = node.label;
+        if self.durations {
+            text.push_str(&format!("  [{}]", format_duration(node.start.elapsed())));
+        }
+        let mut children = node.children;
+        children.sort_by_key(|&(start, _)| start);
+        let item = StringItem::new(text, children.into_iter().map(|(_, child)| child).collect());
+
+        match span.parent() {
+            Some(parent) => match parent.extensions_mut().get_mut::<SpanNode>() {
+                Some(parent) => parent.children.push((node.start, item)),
+                None => self.complete(item),
+            },
+            None => self.complete(item),
+        }
+    }
+}
+
+impl SpanTrees {
+    ///
+    /// Removes and returns all trees completed since they were last taken
+    ///
+    pub fn take(&self) -> Vec<StringItem> {
+        mem::take(&mut *self.trees.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    ///
+    /// Removes all completed trees and writes them to writer `f` using custom formatting
+    ///
+    pub fn write_with<W: io::Write>(&self, mut f: W, config: &PrintConfig) -> io::Result<()> {
+        for tree in self.take() {
+            write_tree_with(&tree, &mut f, config)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a duration with three significant digits, such as `12.3ms`
+fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos() as f64;
+    let (value, unit) = if nanos < 1e3 {
+        return format!("{}ns", nanos);
+    } else if nanos < 1e6 {
+        (nanos / 1e3, "µs")
+    } else if nanos < 1e9 {
+        (nanos / 1e6, "ms")
+    } else {
+        (nanos / 1e9, "s")
+    };
+    if value < 10.0 {
+        format!("{:.2}{}", value, unit)
+    } else if value < 100.0 {
+        format!("{:.1}{}", value, unit)
+    } else {
+        format!("{:.0}{}", value, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use print_config::UTF_CHARS;
+    use std::str::from_utf8;
+    use style::Style;
+
+    use tracing;
+    use tracing_subscriber::prelude::*;
+
+    /// Writer which can be read after it was moved into a layer
+    #[derive(Clone)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn config() -> PrintConfig {
+        PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        }
+    }
+
+    #[test]
+    fn formatted_durations() {
+        assert_eq!(format_duration(Duration::from_nanos(850)), "850ns");
+        assert_eq!(format_duration(Duration::from_nanos(12_345)), "12.3µs");
+        assert_eq!(format_duration(Duration::from_micros(4_567)), "4.57ms");
+        assert_eq!(format_duration(Duration::from_millis(123_456)), "123s");
+    }
+
+    #[test]
+    fn span_trees_on_close() {
+        let buffer = SharedBuffer(Arc::new(Mutex::new(vec![])));
+        let layer = SpanTreeLayer::new()
+            .durations(false)
+            .events(true)
+            .write_on_close(buffer.clone(), config());
+        let trees = layer.trees();
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let request = tracing::info_span!("request", id = 7, user = tracing::field::Empty).entered();
+            request.record("user", "jane");
+            tracing::info_span!("load").in_scope(|| {
+                tracing::info_span!("query", table = "users").in_scope(|| {});
+                tracing::warn!(rows = 0, "no rows");
+            });
+            tracing::info_span!("render").in_scope(|| {});
+            drop(request);
+
+            tracing::info_span!("idle").in_scope(|| {});
+        });
+
+        assert!(trees.take().is_empty());
+        let out = buffer.0.lock().unwrap();
+        assert_eq!(
+            from_utf8(&out).unwrap(),
+            "\
+             request{id=7, user=\"jane\"}\n\
+             ├─ load\n\
+             │  ├─ query{table=\"users\"}\n\
+             │  └─ WARN no rows{rows=0}\n\
+             └─ render\n\
+             idle\n"
+        );
+    }
+
+    #[test]
+    fn span_trees_on_demand() {
+        let layer = SpanTreeLayer::new();
+        let trees = layer.trees();
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info_span!("outer").in_scope(|| {
+                tracing::info_span!("inner").in_scope(|| {});
+            });
+        });
+
+        let mut out = Vec::new();
+        trees.write_with(&mut out, &config()).unwrap();
+        let out = from_utf8(&out).unwrap();
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("outer  [") && lines[0].ends_with(']'));
+        assert!(lines[1].starts_with("└─ inner  ["));
+        assert!(trees.take().is_empty());
+    }
+}
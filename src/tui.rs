@@ -0,0 +1,547 @@
+//!
+//! Interactive viewer for trees in the terminal
+//!
+//! [`view`] shows any [`TreeItem`] in the alternate screen of the terminal, where it can be browsed with the keyboard:
+//!
+//! | Key                  | Action                                            |
+//! |----------------------|---------------------------------------------------|
+//! | `↑` `↓` or `k` `j`   | Select the previous or next item                  |
+//! | `→` or `l`           | Expand the selected item                          |
+//! | `←` or `h`           | Collapse the selected item, or select its parent  |
+//! | `Enter` or `Space`   | Expand or collapse the selected item              |
+//! | `/`                  | Search for items containing the typed text        |
+//! | `n`                  | Select the next item matching the search          |
+//! | `q` or `Esc`         | Quit                                              |
+//!
+//! Children are only loaded when their parent is expanded, so large or expensive trees can be browsed quickly.
+//! Lines are indented with the same branch characters as printed trees, as configured in a [`PrintConfig`].
+//!
+//! The state of the viewer is kept in a [`TreeView`], which can also be driven by other user interfaces.
+//!
+//! [`view`]: fn.view.html
+//! [`TreeItem`]: ../item/trait.TreeItem.html
+//! [`PrintConfig`]: ../print_config/struct.PrintConfig.html
+//! [`TreeView`]: struct.TreeView.html
+//!
+
+use item::TreeItem;
+use output::{item_text, Indent};
+use print_config::PrintConfig;
+
+use std::io::{self, Write};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{ExecutableCommand, QueueableCommand};
+use unicode_width::UnicodeWidthChar;
+
+/// Tree item with its type erased, so items with different child types can be stored together
+trait ViewItem {
+    fn view_text(&self) -> String;
+    fn view_children(&self) -> Vec<Box<dyn ViewItem>>;
+}
+
+impl<T: TreeItem + 'static> ViewItem for T {
+    fn view_text(&self) -> String {
+        item_text(self).unwrap_or_default()
+    }
+
+    fn view_children(&self) -> Vec<Box<dyn ViewItem>> {
+        self.children()
+            .iter()
+            .map(|child| Box::new(child.clone()) as Box<dyn ViewItem>)
+            .collect()
+    }
+}
+
+/// Item in a `TreeView`, with its children once they are loaded
+struct Node {
+    item: Box<dyn ViewItem>,
+    text: String,
+    children: Option<Vec<Node>>,
+    expanded: bool,
+}
+
+impl Node {
+    fn new(item: Box<dyn ViewItem>) -> Node {
+        Node {
+            text: item.view_text(),
+            item,
+            children: None,
+            expanded: false,
+        }
+    }
+
+    fn expand(&mut self) {
+        if self.children.is_none() {
+            self.children = Some(self.item.view_children().into_iter().map(Node::new).collect());
+        }
+        self.expanded = true;
+    }
+
+    /// Returns whether this node has no children, which is only known once they are loaded
+    fn is_leaf(&self) -> bool {
+        self.children.as_ref().is_some_and(Vec::is_empty)
+    }
+
+    fn child(&self, index: usize) -> &Node {
+        &self.children.as_ref().expect("children are loaded")[index]
+    }
+
+    fn child_mut(&mut self, index: usize) -> &mut Node {
+        &mut self.children.as_mut().expect("children are loaded")[index]
+    }
+}
+
+///
+/// Line of a `TreeView`, for one visible item
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Row {
+    /// Indices of the item and its ancestors among their siblings, from the root, which has an empty path
+    pub path: Vec<usize>,
+    /// Full text of the line, with branch characters, an expansion marker and the text of the item
+    pub line: String,
+}
+
+///
+/// State of an interactive tree viewer: the loaded items, which of them are expanded, and the selected item
+///
+pub struct TreeView {
+    root: Node,
+    indent: Indent,
+    markers: (&'static str, &'static str, &'static str),
+    selected: Vec<usize>,
+    query: String,
+}
+
+impl TreeView {
+    ///
+    /// Creates a viewer for `item`, which is expanded and selected
+    ///
+    pub fn new<T: TreeItem + 'static>(item: &T, config: &PrintConfig) -> Self {
+        let mut root = Node::new(Box::new(item.clone()));
+        root.expand();
+        let markers = if config.characters.down.is_ascii() {
+            ("+ ", "- ", "  ")
+        } else {
+            ("\u{25b8} ", "\u{25be} ", "  ")
+        };
+        TreeView {
+            root,
+            indent: Indent::from_config(config),
+            markers,
+            selected: vec![],
+            query: String::new(),
+        }
+    }
+
+    ///
+    /// Returns the lines of all visible items, i.e. the root and the items whose ancestors are all expanded
+    ///
+    pub fn rows(&self) -> Vec<Row> {
+        let mut rows = vec![];
+        self.add_rows(&self.root, &mut vec![], &mut String::new(), "", &mut rows);
+        rows
+    }
+
+    fn add_rows(&self, node: &Node, path: &mut Vec<usize>, prefix: &mut String, own: &str, rows: &mut Vec<Row>) {
+        let (collapsed, expanded, leaf) = self.markers;
+        let marker = match (node.is_leaf(), node.expanded) {
+            (true, _) => leaf,
+            (false, true) => expanded,
+            (false, false) => collapsed,
+        };
+        rows.push(Row {
+            path: path.clone(),
+            line: format!("{}{}{}{}", prefix, own, marker, node.text),
+        });
+        if !node.expanded {
+            return;
+        }
+
+        let children = node.children.as_ref().expect("children are loaded");
+        let len = prefix.len();
+        // The root has no branch characters, so its children are not indented
+        if !path.is_empty() {
+            prefix.push_str(if own == self.indent.last_regular_prefix {
+                &self.indent.last_child_prefix
+            } else {
+                &self.indent.child_prefix
+            });
+        }
+        for (i, child) in children.iter().enumerate() {
+            let own = if i + 1 == children.len() {
+                &self.indent.last_regular_prefix
+            } else {
+                &self.indent.regular_prefix
+            };
+            path.push(i);
+            self.add_rows(child, path, prefix, own, rows);
+            path.pop();
+        }
+        prefix.truncate(len);
+    }
+
+    ///
+    /// Returns the index of the selected row
+    ///
+    pub fn selected(&self) -> usize {
+        self.rows()
+            .iter()
+            .position(|row| row.path == self.selected)
+            .unwrap_or(0)
+    }
+
+    ///
+    /// Returns the path of the selected item, in the format of [`Row::path`]
+    ///
+    /// [`Row::path`]: struct.Row.html#structfield.path
+    ///
+    pub fn selected_path(&self) -> &[usize] {
+        &self.selected
+    }
+
+    ///
+    /// Selects the row below the selected one, if there is one
+    ///
+    pub fn select_next(&mut self) {
+        let rows = self.rows();
+        let next = self.selected() + 1;
+        if next < rows.len() {
+            self.selected = rows[next].path.clone();
+        }
+    }
+
+    ///
+    /// Selects the row above the selected one, if there is one
+    ///
+    pub fn select_previous(&mut self) {
+        let selected = self.selected();
+        if selected > 0 {
+            self.selected = self.rows()[selected - 1].path.clone();
+        }
+    }
+
+    fn node_mut(&mut self, path: &[usize]) -> &mut Node {
+        path.iter().fold(&mut self.root, |node, &i| node.child_mut(i))
+    }
+
+    ///
+    /// Expands the selected item, loading its children if needed
+    ///
+    pub fn expand(&mut self) {
+        let path = self.selected.clone();
+        self.node_mut(&path).expand();
+    }
+
+    ///
+    /// Collapses the selected item, or selects its parent if it is already collapsed
+    ///
+    pub fn collapse(&mut self) {
+        let path = self.selected.clone();
+        let node = self.node_mut(&path);
+        if node.expanded && !node.is_leaf() {
+            node.expanded = false;
+        } else {
+            self.selected.pop();
+        }
+    }
+
+    ///
+    /// Expands the selected item if it is collapsed, and collapses it otherwise
+    ///
+    pub fn toggle(&mut self) {
+        let path = self.selected.clone();
+        let node = self.node_mut(&path);
+        if node.expanded {
+            node.expanded = false;
+        } else {
+            node.expand();
+        }
+    }
+
+    ///
+    /// Returns the text of the last search
+    ///
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    ///
+    /// Selects the next item after the selected one whose text contains `query`, ignoring case
+    ///
+    /// All loaded items are searched, including those in collapsed subtrees, whose ancestors are then expanded.
+    /// The search wraps around at the end of the tree. Returns whether a matching item was found.
+    ///
+    pub fn search(&mut self, query: &str) -> bool {
+        self.query = query.to_string();
+        let query = query.to_lowercase();
+
+        let mut paths = vec![];
+        collect_paths(&self.root, &mut vec![], &mut paths);
+        let start = paths.iter().position(|path| *path == self.selected).unwrap_or(0);
+        let found = paths
+            .iter()
+            .cycle()
+            .skip(start + 1)
+            .take(paths.len())
+            .find(|path| {
+                let node = path.iter().fold(&self.root, |node, &i| node.child(i));
+                node.text.to_lowercase().contains(&query)
+            })
+            .cloned();
+
+        match found {
+            Some(path) => {
+                for depth in 0..path.len() {
+                    self.node_mut(&path[..depth]).expanded = true;
+                }
+                self.selected = path;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Collects the paths of `node` and all loaded nodes below it, in the order in which they are shown
+fn collect_paths(node: &Node, path: &mut Vec<usize>, paths: &mut Vec<Vec<usize>>) {
+    paths.push(path.clone());
+    if let Some(ref children) = node.children {
+        for (i, child) in children.iter().enumerate() {
+            path.push(i);
+            collect_paths(child, path, paths);
+            path.pop();
+        }
+    }
+}
+
+/// Truncates `line` to at most `width` columns
+fn truncated(line: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in line.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return &line[..i];
+        }
+    }
+    line
+}
+
+/// Draws the visible part of `view` and a status line, scrolling so the selected row is shown
+fn draw<W: Write>(view: &TreeView, f: &mut W, offset: &mut usize, status: &str) -> io::Result<()> {
+    let (width, height) = terminal::size()?;
+    let (width, height) = (width as usize, (height as usize).saturating_sub(1).max(1));
+    let rows = view.rows();
+    let selected = view.selected();
+    if selected < *offset {
+        *offset = selected;
+    } else if selected >= *offset + height {
+        *offset = selected + 1 - height;
+    }
+
+    for y in 0..height {
+        f.queue(MoveTo(0, y as u16))?.queue(Clear(ClearType::CurrentLine))?;
+        if let Some(row) = rows.get(*offset + y) {
+            if *offset + y == selected {
+                f.queue(SetAttribute(Attribute::Reverse))?;
+            }
+            f.queue(Print(truncated(&row.line, width)))?
+                .queue(SetAttribute(Attribute::Reset))?;
+        }
+    }
+    f.queue(MoveTo(0, height as u16))?
+        .queue(Clear(ClearType::CurrentLine))?
+        .queue(Print(truncated(status, width)))?;
+    f.flush()
+}
+
+/// Handles key presses until the viewer is closed
+fn run<W: Write>(view: &mut TreeView, f: &mut W) -> io::Result<()> {
+    const HELP: &str = "\u{2191}\u{2193} select  \u{2190}\u{2192} collapse/expand  / search  n next  q quit";
+
+    let mut offset = 0;
+    let mut input: Option<String> = None;
+    let mut message = String::new();
+    loop {
+        let status = match input {
+            Some(ref text) => format!("/{}", text),
+            None if !message.is_empty() => message.clone(),
+            None => HELP.to_string(),
+        };
+        draw(view, f, &mut offset, &status)?;
+
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        message.clear();
+
+        if let Some(mut text) = input.take() {
+            match key.code {
+                KeyCode::Enter => {
+                    if !view.search(&text) {
+                        message = format!("not found: {}", text);
+                    }
+                }
+                KeyCode::Esc => {}
+                KeyCode::Backspace => {
+                    text.pop();
+                    input = Some(text);
+                }
+                KeyCode::Char(c) => {
+                    text.push(c);
+                    input = Some(text);
+                }
+                _ => input = Some(text),
+            }
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Down | KeyCode::Char('j') => view.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => view.select_previous(),
+            KeyCode::Right | KeyCode::Char('l') => view.expand(),
+            KeyCode::Left | KeyCode::Char('h') => view.collapse(),
+            KeyCode::Enter | KeyCode::Char(' ') => view.toggle(),
+            KeyCode::Char('/') => input = Some(String::new()),
+            KeyCode::Char('n') if !view.query().is_empty() => {
+                let query = view.query().to_string();
+                if !view.search(&query) {
+                    message = format!("not found: {}", query);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+///
+/// Guard restoring the cursor, the main screen and the normal mode of the terminal when it is dropped
+///
+/// This way, the terminal is restored however the viewer is closed, including by errors and panics.
+///
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        // Errors cannot be reported here, and every step is attempted regardless
+        let mut stdout = io::stdout();
+        let _ = stdout.execute(Show);
+        let _ = stdout.execute(LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+///
+/// Shows `item` in an interactive viewer on the terminal, until the user quits it
+///
+/// The viewer uses the alternate screen and raw mode of the terminal, which are restored when it is closed,
+/// even if it fails or panics.
+/// Branch characters, indentation and padding are taken from `config`, while styles are not used.
+///
+pub fn view<T: TreeItem + 'static>(item: &T, config: &PrintConfig) -> io::Result<()> {
+    let mut view = TreeView::new(item, config);
+    let mut stdout = io::stdout();
+
+    terminal::enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    stdout.execute(EnterAlternateScreen)?.execute(Hide)?;
+    run(&mut view, &mut stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use builder::TreeBuilder;
+    use print_config::UTF_CHARS;
+
+    fn lines(view: &TreeView) -> Vec<String> {
+        view.rows().into_iter().map(|row| row.line).collect()
+    }
+
+    fn tree_view() -> TreeView {
+        let tree = TreeBuilder::new("root".to_string())
+            .begin_child("src".to_string())
+            .add_empty_child("main.rs".to_string())
+            .begin_child("bin".to_string())
+            .add_empty_child("cli.rs".to_string())
+            .end_child()
+            .end_child()
+            .add_empty_child("README.md".to_string())
+            .build();
+        let config = PrintConfig {
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        TreeView::new(&tree, &config)
+    }
+
+    #[test]
+    fn expand_and_collapse() {
+        let mut view = tree_view();
+        assert_eq!(lines(&view), vec!["▾ root", "├─ ▸ src", "└─ ▸ README.md"]);
+
+        view.select_next();
+        view.expand();
+        view.select_next();
+        view.select_next();
+        view.toggle();
+        assert_eq!(
+            lines(&view),
+            vec![
+                "▾ root",
+                "├─ ▾ src",
+                "│  ├─ ▸ main.rs",
+                "│  └─ ▾ bin",
+                "│     └─ ▸ cli.rs",
+                "└─ ▸ README.md",
+            ]
+        );
+        assert_eq!(view.selected(), 3);
+        assert_eq!(view.selected_path(), &[0, 1]);
+
+        view.select_next();
+        view.select_next();
+        view.expand();
+        assert_eq!(lines(&view)[5], "└─   README.md");
+        view.select_next();
+        assert_eq!(view.selected(), 5);
+
+        view.collapse();
+        assert_eq!(view.selected(), 0);
+        view.collapse();
+        assert_eq!(lines(&view), vec!["▸ root"]);
+    }
+
+    #[test]
+    fn search() {
+        let mut view = tree_view();
+        assert!(!view.search("cli"));
+
+        view.select_next();
+        view.expand();
+        view.select_next();
+        view.select_next();
+        view.expand();
+        view.collapse();
+        view.collapse();
+        view.collapse();
+        assert_eq!(lines(&view), vec!["▾ root", "├─ ▸ src", "└─ ▸ README.md"]);
+
+        assert!(view.search("CLI"));
+        assert_eq!(view.selected_path(), &[0, 1, 0]);
+        assert_eq!(lines(&view)[4], "│     └─ ▸ cli.rs");
+
+        assert!(view.search("r"));
+        assert_eq!(view.selected_path(), &[1]);
+        assert!(view.search("r"));
+        assert_eq!(view.selected_path(), &[] as &[usize]);
+        assert_eq!(view.query(), "r");
+    }
+}
@@ -1,11 +1,26 @@
-use item::TreeItem;
-use style::Style;
+use item::{StringItem, TreeItem};
+#[cfg(feature = "json")]
+use output::StreamWriter;
+use output::{print_tree, print_tree_with, write_tree_with};
+use print_config::PrintConfig;
+use style::{Color, Style};
 
+use std::any;
+use std::fmt;
 use std::io;
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::rc::Rc;
 
-use serde_value::Value;
+#[cfg(feature = "json")]
+use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::Serialize;
+#[cfg(feature = "json")]
+use serde_json;
+#[cfg(feature = "value")]
+use serde_value::{self, Value};
 
+#[cfg(feature = "value")]
 fn value_to_string(v: &Value) -> String {
     match v {
         Value::Bool(b) => b.to_string(),
@@ -21,12 +36,13 @@ fn value_to_string(v: &Value) -> String {
         Value::F64(f) => f.to_string(),
         Value::Char(c) => c.to_string(),
         Value::String(s) => s.clone(),
-        Value::Option(Some(b)) => value_to_string(&*b),
-        Value::Newtype(b) => value_to_string(&*b),
+        Value::Option(Some(b)) => value_to_string(b),
+        Value::Newtype(b) => value_to_string(b),
         _ => "".to_string(),
     }
 }
 
+#[cfg(feature = "value")]
 impl TreeItem for Value {
     type Child = (String, Value);
 
@@ -34,7 +50,7 @@ impl TreeItem for Value {
         write!(f, "{}", style.paint(value_to_string(self)))
     }
 
-    fn children(&self) -> Cow<[Self::Child]> {
+    fn children(&self) -> Cow<'_, [Self::Child]> {
         match self {
             Value::Seq(v) => Cow::from(
                 v.iter()
@@ -59,6 +75,7 @@ impl TreeItem for Value {
     }
 }
 
+#[cfg(feature = "value")]
 impl TreeItem for (String, Value) {
     type Child = Self;
 
@@ -66,11 +83,11 @@ impl TreeItem for (String, Value) {
         if self.0.is_empty() {
             write!(f, "{}", style.paint(value_to_string(&self.1)))
         } else {
-            write!(f, "{}", style.paint(&self.0))
+            style.paint_to(f, format_args!("{}", self.0))
         }
     }
 
-    fn children(&self) -> Cow<[Self::Child]> {
+    fn children(&self) -> Cow<'_, [Self::Child]> {
         match &self.1 {
             Value::Seq(v) => Cow::from(
                 v.iter()
@@ -95,54 +112,2236 @@ impl TreeItem for (String, Value) {
     }
 }
 
+#[cfg(feature = "json")]
+fn json_to_string(v: &serde_json::Value) -> String {
+    match v {
+        serde_json::Value::Null => "null".to_string(),
+        serde_json::Value::Bool(b) => b.to_string(),
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => s.clone(),
+        _ => "".to_string(),
+    }
+}
+
+/// Lists the children of a JSON value, with the scalar members of objects written as `key = value`
+#[cfg(feature = "json")]
+fn json_children(v: &serde_json::Value) -> Vec<(String, serde_json::Value)> {
+    match v {
+        serde_json::Value::Array(a) => a.iter().map(|v| ("".to_string(), v.clone())).collect(),
+        serde_json::Value::Object(m) => m
+            .iter()
+            .map(|(k, v)| match v {
+                serde_json::Value::Array(_) | serde_json::Value::Object(_) => (k.clone(), v.clone()),
+                _ => (
+                    "".to_string(),
+                    serde_json::Value::String(format!("{} = {}", k, json_to_string(v))),
+                ),
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+#[cfg(feature = "json")]
+impl TreeItem for serde_json::Value {
+    type Child = (String, serde_json::Value);
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        style.paint_to(f, format_args!("{}", json_to_string(self)))
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        Cow::from(json_children(self))
+    }
+}
+
+#[cfg(feature = "json")]
+impl TreeItem for (String, serde_json::Value) {
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        if self.0.is_empty() {
+            style.paint_to(f, format_args!("{}", json_to_string(&self.1)))
+        } else {
+            style.paint_to(f, format_args!("{}", self.0))
+        }
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        Cow::from(json_children(&self.1))
+    }
+}
+
+///
+/// Contents of a structured value, as seen by a [`ValueItem`]
+///
+/// [`ValueItem`]: struct.ValueItem.html
+#[derive(Debug)]
+pub enum ValueKind<'a, V: 'a> {
+    /// A scalar value, with its text and the name of its type
+    Scalar(String, &'static str),
+    /// A missing value, such as a JSON `null`, `None` or `()`, with the name of its type
+    Null(&'static str),
+    /// A byte array
+    Bytes(&'a [u8]),
+    /// A sequence of values
+    Seq(Vec<&'a V>),
+    /// A map, with its keys converted to text
+    Map(Vec<(String, &'a V)>),
+}
+
+///
+/// Structured value which can be printed as a tree by a [`ValueItem`]
+///
+/// This trait is implemented for [`serde_value::Value`] with the `"value"` feature,
+/// and for [`serde_json::Value`] with the `"json"` feature.
+///
+/// [`ValueItem`]: struct.ValueItem.html
+/// [`serde_value::Value`]: https://docs.rs/serde-value/0.7/serde_value/enum.Value.html
+/// [`serde_json::Value`]: https://docs.rs/serde_json/1/serde_json/enum.Value.html
+pub trait TreeValue: Clone {
+    ///
+    /// Returns the contents of this value
+    ///
+    fn kind(&self) -> ValueKind<'_, Self>;
+}
+
+#[cfg(feature = "value")]
+fn value_type(v: &Value) -> &'static str {
+    match v {
+        Value::Bool(_) => "bool",
+        Value::U8(_) => "u8",
+        Value::U16(_) => "u16",
+        Value::U32(_) => "u32",
+        Value::U64(_) => "u64",
+        Value::I8(_) => "i8",
+        Value::I16(_) => "i16",
+        Value::I32(_) => "i32",
+        Value::I64(_) => "i64",
+        Value::F32(_) => "f32",
+        Value::F64(_) => "f64",
+        Value::Char(_) => "char",
+        Value::String(_) => "string",
+        Value::Unit => "unit",
+        Value::Option(_) => "option",
+        Value::Newtype(_) => "newtype",
+        Value::Seq(_) => "seq",
+        Value::Map(_) => "map",
+        Value::Bytes(_) => "bytes",
+    }
+}
+
+#[cfg(feature = "value")]
+impl TreeValue for Value {
+    fn kind(&self) -> ValueKind<'_, Self> {
+        match self {
+            Value::Seq(v) => ValueKind::Seq(v.iter().collect()),
+            Value::Map(m) => ValueKind::Map(m.iter().map(|(k, v)| (value_to_string(k), v)).collect()),
+            Value::Option(Some(v)) | Value::Newtype(v) => v.kind(),
+            Value::Option(None) | Value::Unit => ValueKind::Null(value_type(self)),
+            Value::Bytes(b) => ValueKind::Bytes(b),
+            _ => ValueKind::Scalar(value_to_string(self), value_type(self)),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl TreeValue for serde_json::Value {
+    fn kind(&self) -> ValueKind<'_, Self> {
+        use serde_json::Value::*;
+
+        match self {
+            Array(a) => ValueKind::Seq(a.iter().collect()),
+            Object(m) => ValueKind::Map(m.iter().map(|(k, v)| (k.clone(), v)).collect()),
+            Null => ValueKind::Null("null"),
+            Bool(_) => ValueKind::Scalar(json_to_string(self), "bool"),
+            Number(n) if n.is_u64() => ValueKind::Scalar(json_to_string(self), "u64"),
+            Number(n) if n.is_i64() => ValueKind::Scalar(json_to_string(self), "i64"),
+            Number(_) => ValueKind::Scalar(json_to_string(self), "f64"),
+            String(_) => ValueKind::Scalar(json_to_string(self), "string"),
+        }
+    }
+}
+
+///
+/// Labels of the elements of sequences
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum IndexLabels {
+    /// Elements are not labeled, and nested maps and sequences are printed as empty lines
+    #[default]
+    Bare,
+    /// Elements are labeled with their index in brackets, such as `[0]`
+    Brackets,
+    /// Elements are labeled with a dash, like YAML list items
+    Dash,
+}
+
+///
+/// Order of the entries of maps
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum KeyOrder {
+    /// Entries are printed in the order stored in the value
+    ///
+    /// `serde_value::Value` always stores maps sorted by key.
+    /// `serde_json::Value` keeps the order of the source document if the `"preserve_order"` feature is enabled,
+    /// and sorts the keys otherwise.
+    #[default]
+    Source,
+    /// Entries are sorted by the text of their keys
+    Sorted,
+}
+
+///
+/// Labels of items with the path from the root of the value
+///
+/// Paths are printed as [JSON Pointers], such as `/servers/0/port`,
+/// which can be used with `jq` or JSON Patch documents.
+///
+/// [JSON Pointers]: https://tools.ietf.org/html/rfc6901
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PathLabels {
+    /// Items are labeled with their key or index only
+    #[default]
+    Off,
+    /// Items are labeled with their path instead of their key
+    Replace,
+    /// Items are labeled with their key, followed by their path in parentheses
+    Append,
+}
+
+/// Escapes `key` for use as a JSON Pointer reference token
+fn pointer_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+///
+/// Pattern selecting the parts of a value to print
+///
+/// Patterns are keys and sequence indices separated by dots, such as `servers.0.port`.
+/// A `*` segment matches any single key or index, and a `**` segment matches any number of them,
+/// so `servers.*.port` selects the port of every server, and `**.port` selects all ports.
+/// Keys containing dots cannot be matched, except by wildcards.
+///
+/// Selected values are printed with all of their contents, and with their ancestors.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathQuery {
+    segments: Vec<QuerySegment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum QuerySegment {
+    Key(String),
+    Any,
+    AnyDepth,
+}
+
+impl PathQuery {
+    ///
+    /// Parses `pattern` into a query
+    ///
+    pub fn new(pattern: &str) -> Self {
+        let segments = pattern
+            .split('.')
+            .filter(|s| !s.is_empty())
+            .map(|s| match s {
+                "*" => QuerySegment::Any,
+                "**" => QuerySegment::AnyDepth,
+                _ => QuerySegment::Key(s.to_string()),
+            })
+            .collect();
+        PathQuery { segments }
+    }
+
+    ///
+    /// Returns whether `path` is selected by this query, and whether its descendants may be
+    ///
+    fn matches(&self, path: &[String]) -> (bool, bool) {
+        fn walk(query: &[QuerySegment], path: &[String]) -> (bool, bool) {
+            match (query.first(), path.first()) {
+                (None, None) => (true, false),
+                (Some(_), None) => (query.iter().all(|q| *q == QuerySegment::AnyDepth), true),
+                (None, Some(_)) => (false, false),
+                (Some(QuerySegment::Key(k)), Some(p)) if k != p => (false, false),
+                (Some(QuerySegment::AnyDepth), Some(_)) => {
+                    let (skip_selected, skip_partial) = walk(&query[1..], path);
+                    let (more_selected, more_partial) = walk(query, &path[1..]);
+                    (skip_selected || more_selected, skip_partial || more_partial)
+                }
+                (Some(_), Some(_)) => walk(&query[1..], &path[1..]),
+            }
+        }
+        walk(&self.segments, path)
+    }
+}
+
+/// Splits a JSON Pointer into its unescaped reference tokens
+fn pointer_segments(path: &str) -> Vec<String> {
+    path.split('/')
+        .skip(1)
+        .map(|s| s.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+/// Formats a hex preview of the first `preview` bytes, followed by the total size
+fn bytes_to_string(bytes: &[u8], preview: usize) -> String {
+    let mut s = String::new();
+    for (i, b) in bytes.iter().take(preview).enumerate() {
+        s.push_str(if i == 0 { "0x" } else { " " });
+        s.push_str(&format!("{:02x}", b));
+    }
+    if bytes.len() > preview {
+        s.push('\u{2026}');
+    }
+
+    let len = bytes.len() as f64;
+    let size = if len < 1024.0 {
+        format!("{} B", bytes.len())
+    } else if len < 1024.0 * 1024.0 {
+        format!("{:.1} KiB", len / 1024.0)
+    } else {
+        format!("{:.1} MiB", len / (1024.0 * 1024.0))
+    };
+    if s.is_empty() {
+        format!("({})", size)
+    } else {
+        format!("{} ({})", s, size)
+    }
+}
+
+/// Formats `n` with commas as thousands separators, such as `1,000`
+fn grouped(n: usize) -> String {
+    let digits = n.to_string();
+    let mut s = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            s.push(',');
+        }
+        s.push(c);
+    }
+    s
+}
+
+///
+/// Scalar value passed to a [`ScalarFormatter`]
+///
+/// [`ScalarFormatter`]: struct.ScalarFormatter.html
+#[derive(Clone, Copy, Debug)]
+pub struct Scalar<'a> {
+    /// The key of the value in its parent map, which is empty for sequence elements
+    pub key: &'a str,
+    /// The JSON Pointer of the value, such as `/servers/0/port`
+    pub path: &'a str,
+    /// The default text of the value
+    pub text: &'a str,
+    /// The name of the type of the value, such as `u16` or `string`
+    pub type_name: &'static str,
+}
+
+///
+/// Function customizing the text of scalar values
+///
+/// The function returns the new text of a value, or `None` to keep its default text.
+///
+/// ```
+/// # extern crate ptree;
+/// # use ptree::value::ScalarFormatter;
+/// # fn main() {
+/// let redact = ScalarFormatter::new(|scalar| match scalar.key {
+///     "password" | "token" => Some("<redacted>".to_string()),
+///     _ => None,
+/// });
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ScalarFormatter(Rc<FormatFn>);
+
+/// Function returning the new text of a scalar
+type FormatFn = dyn Fn(&Scalar) -> Option<String>;
+
+impl ScalarFormatter {
+    ///
+    /// Creates a formatter calling `f` for each scalar
+    ///
+    pub fn new<F: Fn(&Scalar) -> Option<String> + 'static>(f: F) -> Self {
+        ScalarFormatter(Rc::new(f))
+    }
+}
+
+impl fmt::Debug for ScalarFormatter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ScalarFormatter")
+    }
+}
+
+///
+/// Structure controlling how values are printed by a [`ValueItem`]
+///
+/// These options complement the [`PrintConfig`], which controls the layout of the tree.
+///
+/// [`ValueItem`]: struct.ValueItem.html
+/// [`PrintConfig`]: ../print_config/struct.PrintConfig.html
+#[derive(Clone, Debug)]
+pub struct ValueConfig {
+    /// Label of the root item
+    ///
+    /// The default label is `.`, like the root of a `jq` filter.
+    pub root_label: String,
+    /// Text between the key and the value of scalar map entries
+    ///
+    /// The default separator is ` = `, as in TOML. Use `: ` for output resembling YAML or JSON.
+    pub separator: String,
+    /// Whether the type of each scalar is appended to its text, such as `port = 8080 (u16)`
+    pub type_hints: bool,
+    /// Labels of the elements of sequences
+    pub index_labels: IndexLabels,
+    /// Order of the entries of maps
+    pub key_order: KeyOrder,
+    /// Text printed for missing values, such as `None` and JSON `null`
+    ///
+    /// The default text is `null`.
+    pub null_text: String,
+    /// Maximum number of characters of string values, after which they are shortened with an ellipsis
+    ///
+    /// The default value is `None`, i.e. strings are printed in full.
+    pub max_value_length: Option<usize>,
+    /// Whether the total length is appended to shortened string values, such as `Lorem ipsum… (1024 chars)`
+    pub show_value_length: bool,
+    /// Number of leading bytes shown in the hex preview of byte arrays
+    ///
+    /// The default value is 8.
+    pub bytes_preview: usize,
+    /// Whether chains of maps with a single entry are joined into one dotted key, such as `server.http.port`
+    pub collapse_chains: bool,
+    /// Maximum number of entries of sequences and maps, above which they are summarized
+    ///
+    /// The default value is `None`, i.e. all entries are printed.
+    pub max_entries: Option<usize>,
+    /// Number of entries printed for summarized sequences and maps
+    pub shown_entries: usize,
+    /// Labels of items with their path from the root
+    pub path_labels: PathLabels,
+    /// Function customizing the text of scalar values
+    ///
+    /// The default value is `None`, i.e. scalars are printed with their default text.
+    pub formatter: Option<ScalarFormatter>,
+    /// Query selecting the parts of the value to print
+    ///
+    /// The default value is `None`, i.e. the whole value is printed.
+    pub query: Option<PathQuery>,
+    /// Whether scalars are styled by their type, with the styles of [`PrintConfig::classes`]
+    ///
+    /// The classes of scalars are `number`, `string`, `boolean`, `null` and `bytes`.
+    /// [`PrintConfig::classes`] is empty by default, [`PrintConfig::default_classes`] returns styles for these classes.
+    ///
+    /// [`PrintConfig::classes`]: ../print_config/struct.PrintConfig.html#structfield.classes
+    /// [`PrintConfig::default_classes`]: ../print_config/struct.PrintConfig.html#method.default_classes
+    pub type_classes: bool,
+}
+
+impl Default for ValueConfig {
+    fn default() -> ValueConfig {
+        ValueConfig {
+            root_label: ".".to_string(),
+            separator: " = ".to_string(),
+            type_hints: false,
+            index_labels: IndexLabels::default(),
+            key_order: KeyOrder::default(),
+            null_text: "null".to_string(),
+            max_value_length: None,
+            show_value_length: false,
+            bytes_preview: 8,
+            collapse_chains: false,
+            max_entries: None,
+            shown_entries: 0,
+            path_labels: PathLabels::default(),
+            formatter: None,
+            query: None,
+            type_classes: false,
+        }
+    }
+}
+
+///
+/// Item for printing a [`TreeValue`] with additional options
+///
+/// Maps and sequences are printed with their entries as children.
+/// Scalar entries of maps are printed on a single line, as `key = value`.
+/// The options are stored in a [`ValueConfig`], and can also be set with the builder methods of this type.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate serde;
+/// # extern crate serde_value;
+/// # extern crate ptree;
+/// # use ptree::print_config::{PrintConfig, StyleWhen, UTF_CHARS};
+/// # use ptree::output::write_tree_with;
+/// # use ptree::value::ValueItem;
+/// # fn main() {
+/// #[derive(Serialize)]
+/// struct Server {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let server = Server {
+///     host: "localhost".to_string(),
+///     port: 8080,
+/// };
+/// let item = ValueItem::new("server", serde_value::to_value(server).unwrap()).type_hints(true);
+///
+/// let config = PrintConfig {
+///     characters: UTF_CHARS.into(),
+///     styled: StyleWhen::Never,
+///     ..PrintConfig::default()
+/// };
+/// let mut out = Vec::new();
+/// write_tree_with(&item, &mut out, &config).unwrap();
+/// let expected = "server\n├─ host = localhost (string)\n└─ port = 8080 (u16)\n";
+/// assert_eq!(String::from_utf8(out).unwrap(), expected);
+/// # }
+/// ```
+///
+/// [`TreeValue`]: trait.TreeValue.html
+/// [`ValueConfig`]: struct.ValueConfig.html
+#[derive(Clone, Debug)]
+pub struct ValueItem<V> {
+    /// The key of this value in its parent map, or the label of the root
+    key: String,
+    /// The position of this value in its parent sequence
+    index: Option<usize>,
+    /// The JSON Pointer of this value, which is empty for the root
+    path: String,
+    /// Whether one of the ancestors of this value is selected by the query
+    selected: bool,
+    value: V,
+    config: Rc<ValueConfig>,
+}
+
+impl<V: TreeValue> ValueItem<V> {
+    ///
+    /// Creates the root item for printing `value`, labeled with `label`
+    ///
+    pub fn new<S: Into<String>>(label: S, value: V) -> Self {
+        ValueItem::with_config(
+            value,
+            ValueConfig {
+                root_label: label.into(),
+                ..ValueConfig::default()
+            },
+        )
+    }
+
+    ///
+    /// Creates the root item for printing `value` with the options in `config`
+    ///
+    pub fn with_config(value: V, config: ValueConfig) -> Self {
+        ValueItem {
+            key: config.root_label.clone(),
+            index: None,
+            path: String::new(),
+            selected: false,
+            value,
+            config: Rc::new(config),
+        }
+    }
+
+    ///
+    /// Returns the options of this item
+    ///
+    pub fn config(&self) -> &ValueConfig {
+        &self.config
+    }
+
+    ///
+    /// Sets the text between the key and the value of scalar map entries
+    ///
+    /// The default separator is ` = `, as in TOML. Use `: ` for output resembling YAML or JSON.
+    ///
+    pub fn separator<S: Into<String>>(mut self, separator: S) -> Self {
+        Rc::make_mut(&mut self.config).separator = separator.into();
+        self
+    }
+
+    ///
+    /// Appends the type of each scalar to its text, such as `port = 8080 (u16)`
+    ///
+    /// The type names follow the serde data model, which is useful for debugging deserialization.
+    ///
+    pub fn type_hints(mut self, enabled: bool) -> Self {
+        Rc::make_mut(&mut self.config).type_hints = enabled;
+        self
+    }
+
+    ///
+    /// Labels the elements of sequences, which are printed bare by default
+    ///
+    /// Labels make long sequences navigable, and allow referencing single elements.
+    ///
+    pub fn index_labels(mut self, labels: IndexLabels) -> Self {
+        Rc::make_mut(&mut self.config).index_labels = labels;
+        self
+    }
+
+    ///
+    /// Selects the order of the entries of maps
+    ///
+    pub fn key_order(mut self, order: KeyOrder) -> Self {
+        Rc::make_mut(&mut self.config).key_order = order;
+        self
+    }
+
+    ///
+    /// Sets the text printed for missing values, such as `None` and JSON `null`
+    ///
+    /// The default text is `null`. Other common choices are `~` and `()`.
+    ///
+    pub fn null_text<S: Into<String>>(mut self, text: S) -> Self {
+        Rc::make_mut(&mut self.config).null_text = text.into();
+        self
+    }
+
+    ///
+    /// Shortens string values longer than `max` characters, ending them with an ellipsis
+    ///
+    /// This keeps long embedded texts from wrecking the layout of the tree.
+    /// The default value is `None`, i.e. strings are printed in full.
+    ///
+    pub fn max_value_length(mut self, max: Option<usize>) -> Self {
+        Rc::make_mut(&mut self.config).max_value_length = max;
+        self
+    }
+
+    ///
+    /// Appends the total length to shortened string values, such as `text = Lorem ipsum… (1024 chars)`
+    ///
+    pub fn show_value_length(mut self, enabled: bool) -> Self {
+        Rc::make_mut(&mut self.config).show_value_length = enabled;
+        self
+    }
+
+    ///
+    /// Sets the number of leading bytes shown for byte arrays
+    ///
+    /// Byte arrays are printed as a hex preview followed by their size, such as `0x48 65 6c 6c… (1.2 KiB)`.
+    /// The default value is 8.
+    ///
+    pub fn bytes_preview(mut self, len: usize) -> Self {
+        Rc::make_mut(&mut self.config).bytes_preview = len;
+        self
+    }
+
+    ///
+    /// Joins chains of maps with a single entry into one dotted key, such as `server.http.port = 8080`
+    ///
+    /// This shortens deeply nested documents, like many configuration files, considerably.
+    ///
+    pub fn collapse_chains(mut self, enabled: bool) -> Self {
+        Rc::make_mut(&mut self.config).collapse_chains = enabled;
+        self
+    }
+
+    ///
+    /// Summarizes sequences and maps with more than `max` entries, such as `values [1,000 items]`
+    ///
+    /// Only the first `shown` entries of summarized values are printed, which keeps huge documents printable.
+    /// The default value is `None`, i.e. all entries are printed.
+    ///
+    pub fn max_entries(mut self, max: Option<usize>, shown: usize) -> Self {
+        let config = Rc::make_mut(&mut self.config);
+        config.max_entries = max;
+        config.shown_entries = shown;
+        self
+    }
+
+    /// Returns the number of entries of this value if it is summarized
+    fn summarized_len(&self, kind: &ValueKind<'_, V>) -> Option<usize> {
+        let len = match *kind {
+            ValueKind::Seq(ref values) => values.len(),
+            ValueKind::Map(ref entries) => entries.len(),
+            _ => return None,
+        };
+        match self.config.max_entries {
+            Some(max) if len > max => Some(len),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Labels items with their path from the root, such as `/servers/0/port`
+    ///
+    pub fn path_labels(mut self, labels: PathLabels) -> Self {
+        Rc::make_mut(&mut self.config).path_labels = labels;
+        self
+    }
+
+    ///
+    /// Customizes the text of scalar values with `f`, which returns `None` to keep the default text
+    ///
+    /// This allows rendering timestamps in a readable format, or redacting secrets by key, for example.
+    ///
+    pub fn format_scalars<F: Fn(&Scalar) -> Option<String> + 'static>(mut self, f: F) -> Self {
+        Rc::make_mut(&mut self.config).formatter = Some(ScalarFormatter::new(f));
+        self
+    }
+
+    ///
+    /// Prints only the parts of the value selected by `query`, such as `servers.*.port`
+    ///
+    /// See [`PathQuery`] for the syntax of queries.
+    ///
+    /// [`PathQuery`]: struct.PathQuery.html
+    pub fn query(mut self, query: &str) -> Self {
+        Rc::make_mut(&mut self.config).query = Some(PathQuery::new(query));
+        self
+    }
+
+    ///
+    /// Styles scalars by their type, such as numbers in cyan and strings in green
+    ///
+    /// The styles are configured in [`PrintConfig::classes`], see [`ValueConfig::type_classes`].
+    ///
+    /// [`PrintConfig::classes`]: ../print_config/struct.PrintConfig.html#structfield.classes
+    /// [`ValueConfig::type_classes`]: struct.ValueConfig.html#structfield.type_classes
+    pub fn type_classes(mut self, enabled: bool) -> Self {
+        Rc::make_mut(&mut self.config).type_classes = enabled;
+        self
+    }
+
+    ///
+    /// Converts this item and its printed descendants into a tree of [`StringItem`]s
+    ///
+    /// Each item gets the text it would be printed with, so the tree can be edited, merged with other trees
+    /// or annotated before printing.
+    ///
+    /// [`StringItem`]: ../item/struct.StringItem.html
+    pub fn to_string_item(&self) -> StringItem {
+        let mut text = Vec::new();
+        self.write_self(&mut text, &Style::default())
+            .expect("writing to a vector does not fail");
+        StringItem {
+            text: String::from_utf8_lossy(&text).into_owned(),
+            children: self.children().iter().map(ValueItem::to_string_item).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns whether this value is printed with all of its contents
+    fn is_selected(&self) -> bool {
+        let matches = |query: &PathQuery| query.matches(&pointer_segments(&self.path)).0;
+        self.selected || self.config.query.as_ref().is_none_or(matches)
+    }
+
+    /// Returns whether `child` is printed, when this value is not selected
+    fn keeps(&self, child: &Self) -> bool {
+        let query = match self.config.query {
+            Some(ref query) => query,
+            None => return true,
+        };
+        match query.matches(&pointer_segments(&child.path)) {
+            (true, _) => true,
+            (false, true) => !child.children().is_empty(),
+            (false, false) => false,
+        }
+    }
+
+    fn child(&self, key: String, index: Option<usize>, path: String, value: V) -> Self {
+        ValueItem {
+            key,
+            index,
+            selected: self.is_selected(),
+            path,
+            value,
+            config: self.config.clone(),
+        }
+    }
+
+    /// Creates the item of element `i` of this sequence
+    fn element(&self, i: usize, value: V) -> Self {
+        self.child(String::new(), Some(i), format!("{}/{}", self.path, i), value)
+    }
+
+    /// Creates the item of the entry `key` of this map, collapsing chains of single entries if enabled
+    fn entry(&self, mut key: String, mut value: &V) -> Self {
+        let mut path = format!("{}/{}", self.path, pointer_token(&key));
+        while let ValueKind::Map(inner) = value.kind() {
+            if !self.config.collapse_chains || inner.len() != 1 {
+                break;
+            }
+            key = format!("{}.{}", key, inner[0].0);
+            path = format!("{}/{}", path, pointer_token(&inner[0].0));
+            value = inner[0].1;
+        }
+        self.child(key, None, path, value.clone())
+    }
+}
+
+impl<V: TreeValue> TreeItem for ValueItem<V> {
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        let replace_path = self.config.path_labels == PathLabels::Replace && !self.path.is_empty();
+        let index_label = match (self.index, self.config.index_labels) {
+            _ if replace_path => None,
+            (None, _) | (Some(_), IndexLabels::Bare) => None,
+            (Some(i), IndexLabels::Brackets) => Some(format!("[{}]", i)),
+            (Some(_), IndexLabels::Dash) => Some("-".to_string()),
+        };
+        let key = if replace_path { &self.path } else { &self.key };
+        let path_suffix = match self.config.path_labels {
+            PathLabels::Append if !self.path.is_empty() => Some(&self.path),
+            _ => None,
+        };
+
+        let kind = self.value.kind();
+        let (text, type_name) = match kind {
+            ValueKind::Scalar(text, type_name) => (Cow::from(text), type_name),
+            ValueKind::Null(type_name) => (Cow::from(&self.config.null_text[..]), type_name),
+            ValueKind::Bytes(bytes) => (Cow::from(bytes_to_string(bytes, self.config.bytes_preview)), "bytes"),
+            _ => {
+                let label = index_label.as_ref().unwrap_or(key);
+                match self.summarized_len(&kind) {
+                    Some(len) if label.is_empty() => style.paint_to(f, format_args!("[{} items]", grouped(len)))?,
+                    Some(len) => style.paint_to(f, format_args!("{} [{} items]", label, grouped(len)))?,
+                    None => style.paint_to(f, format_args!("{}", label))?,
+                }
+                if let Some(path) = path_suffix {
+                    style.paint_to(f, format_args!(" ({})", path))?;
+                }
+                return Ok(());
+            }
+        };
+        let formatted = self.config.formatter.as_ref().and_then(|formatter| {
+            (formatter.0)(&Scalar {
+                key: &self.key,
+                path: &self.path,
+                text: &text,
+                type_name,
+            })
+        });
+        let text = formatted.map(Cow::from).unwrap_or(text);
+
+        if let Some(label) = index_label {
+            style.paint_to(f, format_args!("{} ", label))?;
+        } else if !key.is_empty() {
+            style.paint_to(f, format_args!("{}{}", key, self.config.separator))?;
+        }
+        // Byte offset of the first character past the maximum length
+        let end = match self.config.max_value_length {
+            Some(max) if type_name == "string" => text.char_indices().nth(max).map(|(i, _)| i),
+            _ => None,
+        };
+        match end {
+            Some(end) => {
+                style.paint_to(f, format_args!("{}\u{2026}", &text[..end]))?;
+                if self.config.show_value_length {
+                    style.paint_to(f, format_args!(" ({} chars)", text.chars().count()))?;
+                }
+            }
+            _ => style.paint_to(f, format_args!("{}", text))?,
+        }
+        if self.config.type_hints {
+            style.paint_to(f, format_args!(" ({})", type_name))?;
+        }
+        if let Some(path) = path_suffix {
+            style.paint_to(f, format_args!(" ({})", path))?;
+        }
+        Ok(())
+    }
+
+    fn class(&self) -> Option<&str> {
+        if !self.config.type_classes {
+            return None;
+        }
+        match self.value.kind() {
+            ValueKind::Scalar(_, "bool") => Some("boolean"),
+            ValueKind::Scalar(_, "string") | ValueKind::Scalar(_, "char") => Some("string"),
+            ValueKind::Scalar(..) => Some("number"),
+            ValueKind::Null(_) => Some("null"),
+            ValueKind::Bytes(_) => Some("bytes"),
+            ValueKind::Seq(_) | ValueKind::Map(_) => None,
+        }
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        let kind = self.value.kind();
+        let shown = match self.summarized_len(&kind) {
+            Some(_) => self.config.shown_entries,
+            None => usize::MAX,
+        };
+        let selected = self.is_selected();
+        let v: Vec<_> = match kind {
+            ValueKind::Scalar(..) | ValueKind::Null(_) | ValueKind::Bytes(_) => vec![],
+            ValueKind::Seq(values) => values
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| self.element(i, v.clone()))
+                .filter(|child| selected || self.keeps(child))
+                .take(shown)
+                .collect(),
+            ValueKind::Map(mut entries) => {
+                if self.config.key_order == KeyOrder::Sorted {
+                    entries.sort_by(|a, b| a.0.cmp(&b.0));
+                }
+                entries
+                    .into_iter()
+                    .map(|(k, v)| self.entry(k, v))
+                    .filter(|child| selected || self.keeps(child))
+                    .take(shown)
+                    .collect()
+            }
+        };
+        Cow::from(v)
+    }
+}
+
+///
+/// Returns the name of type `T` without its module path and generic parameters
+///
+/// For example, `alloc::vec::Vec<u8>` becomes `Vec`.
+///
+fn type_label<T: ?Sized>() -> String {
+    let name = any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name).to_string()
+}
+
+///
+/// Converts `value` into a tree labeled with the name of its type
+///
+#[cfg(feature = "value")]
+fn serializable_tree<T: Serialize + ?Sized>(value: &T) -> io::Result<ValueItem<Value>> {
+    let value = serde_value::to_value(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(ValueItem::new(type_label::<T>(), value))
+}
+
+///
+/// Converts `value` into a tree labeled with the name of its type, going through JSON
+///
+#[cfg(all(feature = "json", not(feature = "value")))]
+fn serializable_tree<T: Serialize + ?Sized>(value: &T) -> io::Result<ValueItem<serde_json::Value>> {
+    let value = serde_json::to_value(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(ValueItem::new(type_label::<T>(), value))
+}
+
+///
+/// Print any serializable value to standard output using default formatting
+///
+/// The value is converted into a [`serde_value::Value`] and printed as a tree,
+/// with the name of its type as the root.
+/// With only the `"json"` feature, it is converted into a [`serde_json::Value`] instead,
+/// which cannot represent some values, such as maps with non-string keys.
+/// Errors reported by the `Serialize` implementation are returned as [`InvalidData`] errors.
+///
+/// [`serde_value::Value`]: https://docs.rs/serde-value/0.7/serde_value/enum.Value.html
+/// [`serde_json::Value`]: https://docs.rs/serde_json/1/serde_json/enum.Value.html
+/// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+pub fn print_serializable<T: Serialize + ?Sized>(value: &T) -> io::Result<()> {
+    print_tree(&serializable_tree(value)?)
+}
+
+///
+/// Write any serializable value to writer `f` using custom formatting
+///
+/// See [`print_serializable`] for details.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate serde;
+/// # extern crate ptree;
+/// # use ptree::PrintConfig;
+/// # use ptree::print_config::{StyleWhen, UTF_CHARS};
+/// # use ptree::value::write_serializable_with;
+/// # fn main() {
+/// #[derive(Serialize)]
+/// struct Package {
+///     name: &'static str,
+///     authors: Vec<&'static str>,
+/// }
+///
+/// let package = Package {
+///     name: "ptree",
+///     authors: vec!["alice"],
+/// };
+/// let config = PrintConfig {
+///     characters: UTF_CHARS.into(),
+///     styled: StyleWhen::Never,
+///     ..PrintConfig::default()
+/// };
+///
+/// let mut out = Vec::new();
+/// write_serializable_with(&package, &mut out, &config).unwrap();
+/// let expected = "Package\n├─ authors\n│  └─ alice\n└─ name = ptree\n";
+/// assert_eq!(String::from_utf8(out).unwrap(), expected);
+/// # }
+/// ```
+///
+/// [`print_serializable`]: fn.print_serializable.html
+pub fn write_serializable_with<T, W>(value: &T, f: W, config: &PrintConfig) -> io::Result<()>
+where
+    T: Serialize + ?Sized,
+    W: io::Write,
+{
+    write_tree_with(&serializable_tree(value)?, f, config)
+}
+
+///
+/// Print `value` to standard output using custom formatting
+///
+/// The layout of the tree is controlled by `print_config`, and the printing of the value by `value_config`.
+///
+pub fn print_value_tree_with<V: TreeValue>(
+    value: &V,
+    print_config: &PrintConfig,
+    value_config: &ValueConfig,
+) -> io::Result<()> {
+    let item = ValueItem::with_config(value.clone(), value_config.clone());
+    print_tree_with(&item, print_config)
+}
+
+///
+/// Write `value` to writer `f` using custom formatting
+///
+/// The layout of the tree is controlled by `print_config`, and the printing of the value by `value_config`.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate serde;
+/// # extern crate serde_value;
+/// # extern crate ptree;
+/// # use ptree::print_config::{PrintConfig, StyleWhen, UTF_CHARS};
+/// # use ptree::value::{write_value_tree_with, ValueConfig};
+/// # fn main() {
+/// #[derive(Serialize)]
+/// struct Server {
+///     host: &'static str,
+///     port: u16,
+/// }
+///
+/// let value = serde_value::to_value(Server { host: "localhost", port: 8080 }).unwrap();
+///
+/// let print_config = PrintConfig {
+///     characters: UTF_CHARS.into(),
+///     styled: StyleWhen::Never,
+///     ..PrintConfig::default()
+/// };
+/// let value_config = ValueConfig {
+///     separator: ": ".to_string(),
+///     ..ValueConfig::default()
+/// };
+/// let mut out = Vec::new();
+/// write_value_tree_with(&value, &mut out, &print_config, &value_config).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), ".\n├─ host: localhost\n└─ port: 8080\n");
+/// # }
+/// ```
+pub fn write_value_tree_with<V, W>(
+    value: &V,
+    f: W,
+    print_config: &PrintConfig,
+    value_config: &ValueConfig,
+) -> io::Result<()>
+where
+    V: TreeValue,
+    W: io::Write,
+{
+    let item = ValueItem::with_config(value.clone(), value_config.clone());
+    write_tree_with(&item, f, print_config)
+}
+
+///
+/// Converts `value` into a tree of [`StringItem`]s, as printed with the default [`ValueConfig`]
+///
+/// The root is labeled `.`. Use [`ValueItem::to_string_item`] to convert a value with other options.
+///
+/// ```
+/// # extern crate serde_value;
+/// # extern crate ptree;
+/// # use std::collections::BTreeMap;
+/// # use ptree::item::StringItem;
+/// # use ptree::value::to_string_item;
+/// # fn main() {
+/// let mut package = BTreeMap::new();
+/// package.insert("name", "ptree");
+/// let value = serde_value::to_value(&package).unwrap();
+///
+/// let mut item = to_string_item(&value);
+/// item.children.push(StringItem::new("note = added later".to_string(), vec![]));
+/// assert_eq!(item.children[0].text, "name = ptree");
+/// # }
+/// ```
+///
+/// [`StringItem`]: ../item/struct.StringItem.html
+/// [`ValueConfig`]: struct.ValueConfig.html
+/// [`ValueItem::to_string_item`]: struct.ValueItem.html#method.to_string_item
+pub fn to_string_item<V: TreeValue>(value: &V) -> StringItem {
+    ValueItem::with_config(value.clone(), ValueConfig::default()).to_string_item()
+}
+
+///
+/// Visitor printing a JSON document while it is parsed
+///
+/// The entries of a top-level array or object are parsed and printed one at a time.
+///
+#[cfg(feature = "json")]
+struct JsonStream<'a, W> {
+    f: W,
+    print_config: &'a PrintConfig,
+    value_config: &'a ValueConfig,
+    /// Error writing the output, which is passed through the deserializer as a custom error
+    error: &'a mut Option<io::Error>,
+}
+
+/// Converts an output error into a deserialization error, keeping the original in `slot`
+#[cfg(feature = "json")]
+fn stash_error<T, E: de::Error>(slot: &mut Option<io::Error>, result: io::Result<T>) -> Result<T, E> {
+    result.map_err(|e| {
+        let error = E::custom(&e);
+        *slot = Some(e);
+        error
+    })
+}
+
+#[cfg(feature = "json")]
+impl<'a, W: io::Write> JsonStream<'a, W> {
+    fn scalar<E: de::Error>(self, value: serde_json::Value) -> Result<(), E> {
+        let item = ValueItem::with_config(value, self.value_config.clone());
+        stash_error(self.error, write_tree_with(&item, self.f, self.print_config))
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'de, 'a, W: io::Write> Visitor<'de> for JsonStream<'a, W> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<(), E> {
+        self.scalar(v.into())
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<(), E> {
+        self.scalar(v.into())
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<(), E> {
+        self.scalar(v.into())
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<(), E> {
+        self.scalar(v.into())
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<(), E> {
+        self.scalar(v.into())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+        self.scalar(serde_json::Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let root = ValueItem::with_config(serde_json::Value::Array(vec![]), self.value_config.clone());
+        let selected = root.is_selected();
+        let mut writer = stash_error(self.error, StreamWriter::new(&root, self.f, self.print_config))?;
+
+        let mut i = 0;
+        while let Some(value) = seq.next_element()? {
+            let child = root.element(i, value);
+            if selected || root.keeps(&child) {
+                stash_error(self.error, writer.push(child))?;
+            }
+            i += 1;
+        }
+        stash_error(self.error, writer.finish())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let root = ValueItem::with_config(serde_json::Value::Object(Default::default()), self.value_config.clone());
+        let selected = root.is_selected();
+        let mut writer = stash_error(self.error, StreamWriter::new(&root, self.f, self.print_config))?;
+
+        while let Some((key, value)) = map.next_entry::<String, serde_json::Value>()? {
+            let child = root.entry(key, &value);
+            if selected || root.keeps(&child) {
+                stash_error(self.error, writer.push(child))?;
+            }
+        }
+        stash_error(self.error, writer.finish())
+    }
+}
+
+///
+/// Write the JSON document read from `reader` to writer `f` while it is parsed
+///
+/// The entries of a top-level array or object are parsed and printed one at a time,
+/// so only a single entry is held in memory, rather than the whole document.
+/// This allows printing documents of hundreds of megabytes, which usually consist of many small records.
+/// The entries of the top level are printed in the order of the document,
+/// regardless of [`ValueConfig::key_order`] and [`ValueConfig::max_entries`].
+///
+/// The reader is read byte by byte, so it should be buffered, such as with a [`BufReader`].
+/// Syntax errors in the document are returned as [`InvalidData`] errors, and truncated documents as
+/// [`UnexpectedEof`] errors, after the part of the tree before the error has been written.
+///
+/// This function is only available with the `"json"` feature.
+///
+/// [`ValueConfig::key_order`]: struct.ValueConfig.html#structfield.key_order
+/// [`ValueConfig::max_entries`]: struct.ValueConfig.html#structfield.max_entries
+/// [`BufReader`]: https://doc.rust-lang.org/std/io/struct.BufReader.html
+/// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+/// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+#[cfg(feature = "json")]
+pub fn write_json_stream_with<R, W>(
+    reader: R,
+    f: W,
+    print_config: &PrintConfig,
+    value_config: &ValueConfig,
+) -> io::Result<()>
+where
+    R: io::Read,
+    W: io::Write,
+{
+    let mut error = None;
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let visitor = JsonStream {
+        f,
+        print_config,
+        value_config,
+        error: &mut error,
+    };
+    let result = deserializer.deserialize_any(visitor).and_then(|()| deserializer.end());
+    match (result, error) {
+        (_, Some(e)) => Err(e),
+        (Err(e), None) => Err(e.into()),
+        (Ok(()), None) => Ok(()),
+    }
+}
+
+///
+/// Kind of change of an entry in a [`DiffItem`]
+///
+/// [`DiffItem`]: struct.DiffItem.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// The entry is equal in both values
+    Unchanged,
+    /// The entry only exists in the new value
+    Added,
+    /// The entry only exists in the old value
+    Removed,
+    /// The entry exists in both values, but its contents differ
+    Changed,
+}
+
+impl Change {
+    ///
+    /// Returns the marker printed before entries with this change, such as `+ ` for added entries
+    ///
+    pub fn marker(self) -> &'static str {
+        match self {
+            Change::Unchanged => "",
+            Change::Added => "+ ",
+            Change::Removed => "- ",
+            Change::Changed => "~ ",
+        }
+    }
+
+    ///
+    /// Returns the style of entries with this change
+    ///
+    /// Added entries are green, removed entries are red and changed entries are yellow.
+    ///
+    pub fn style(self) -> Style {
+        let color = match self {
+            Change::Unchanged => return Style::default(),
+            Change::Added => Color::Green,
+            Change::Removed => Color::Red,
+            Change::Changed => Color::Yellow,
+        };
+        Style {
+            foreground: Some(color),
+            ..Style::default()
+        }
+    }
+}
+
+///
+/// Item for printing the structural differences between two values
+///
+/// Each entry is marked and styled according to its [`Change`].
+/// Changed scalars are printed with their old and new text, as `key = old → new`.
+/// Entries of maps are printed in the order of the old value, followed by the entries only in the new value.
+///
+/// ```
+/// # extern crate serde_value;
+/// # extern crate ptree;
+/// # use std::collections::BTreeMap;
+/// # use ptree::print_config::{PrintConfig, StyleWhen, UTF_CHARS};
+/// # use ptree::output::write_tree_with;
+/// # use ptree::value::diff;
+/// # fn main() {
+/// let mut package = BTreeMap::new();
+/// package.insert("name", "ptree");
+/// package.insert("version", "0.4.0");
+/// let old = serde_value::to_value(&package).unwrap();
+///
+/// package.insert("version", "0.5.0");
+/// package.insert("edition", "2015");
+/// let new = serde_value::to_value(&package).unwrap();
+///
+/// let config = PrintConfig {
+///     characters: UTF_CHARS.into(),
+///     styled: StyleWhen::Never,
+///     ..PrintConfig::default()
+/// };
+/// let mut out = Vec::new();
+/// write_tree_with(&diff(&old, &new), &mut out, &config).unwrap();
+/// let expected = "~ .\n├─ name = ptree\n├─ ~ version = 0.4.0 → 0.5.0\n└─ + edition = 2015\n";
+/// assert_eq!(String::from_utf8(out).unwrap(), expected);
+/// # }
+/// ```
+///
+/// [`Change`]: enum.Change.html
+#[derive(Clone, Debug)]
+pub struct DiffItem {
+    label: String,
+    change: Change,
+    children: Vec<DiffItem>,
+}
+
+impl DiffItem {
+    ///
+    /// Returns the change of this entry
+    ///
+    pub fn change(&self) -> Change {
+        self.change
+    }
+
+    /// Creates the item of a value which only exists on one side
+    fn one_sided<V: TreeValue>(label: String, value: &V, change: Change) -> Self {
+        let (label, children) = match value.kind() {
+            ValueKind::Seq(values) => {
+                let children = values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, v)| DiffItem::one_sided(format!("[{}]", i), v, change))
+                    .collect();
+                (label, children)
+            }
+            ValueKind::Map(entries) => {
+                let children = entries
+                    .into_iter()
+                    .map(|(k, v)| DiffItem::one_sided(k, v, change))
+                    .collect();
+                (label, children)
+            }
+            ref kind => (scalar_label(&label, &diff_text(kind).unwrap_or_default()), vec![]),
+        };
+        DiffItem {
+            label,
+            change,
+            children,
+        }
+    }
+
+    /// Creates the items of a value which exists on both sides
+    fn both<V: TreeValue>(label: String, old: &V, new: &V) -> Vec<Self> {
+        let children = match (old.kind(), new.kind()) {
+            (ValueKind::Seq(old_values), ValueKind::Seq(new_values)) => {
+                let len = old_values.len().max(new_values.len());
+                (0..len)
+                    .flat_map(|i| {
+                        let label = format!("[{}]", i);
+                        match (old_values.get(i), new_values.get(i)) {
+                            (Some(o), Some(n)) => DiffItem::both(label, *o, *n),
+                            (Some(o), None) => vec![DiffItem::one_sided(label, *o, Change::Removed)],
+                            (None, Some(n)) => vec![DiffItem::one_sided(label, *n, Change::Added)],
+                            (None, None) => vec![],
+                        }
+                    })
+                    .collect()
+            }
+            (ValueKind::Map(old_entries), ValueKind::Map(new_entries)) => {
+                let new_values: HashMap<_, _> = new_entries.iter().map(|(k, v)| (&k[..], *v)).collect();
+                let mut children: Vec<_> = old_entries
+                    .iter()
+                    .flat_map(|(k, o)| match new_values.get(&k[..]) {
+                        Some(n) => DiffItem::both(k.clone(), *o, *n),
+                        None => vec![DiffItem::one_sided(k.clone(), *o, Change::Removed)],
+                    })
+                    .collect();
+                for (k, n) in &new_entries {
+                    if !old_entries.iter().any(|(old_key, _)| old_key == k) {
+                        children.push(DiffItem::one_sided(k.clone(), *n, Change::Added));
+                    }
+                }
+                children
+            }
+            (ref old_kind, ref new_kind) => {
+                return match (diff_text(old_kind), diff_text(new_kind)) {
+                    (Some(o), Some(n)) if o == n => vec![DiffItem {
+                        label: scalar_label(&label, &o),
+                        change: Change::Unchanged,
+                        children: vec![],
+                    }],
+                    (Some(o), Some(n)) => vec![DiffItem {
+                        label: scalar_label(&label, &format!("{} \u{2192} {}", o, n)),
+                        change: Change::Changed,
+                        children: vec![],
+                    }],
+                    _ => vec![
+                        DiffItem::one_sided(label.clone(), old, Change::Removed),
+                        DiffItem::one_sided(label, new, Change::Added),
+                    ],
+                };
+            }
+        };
+
+        let change = if children.iter().all(|c: &DiffItem| c.change == Change::Unchanged) {
+            Change::Unchanged
+        } else {
+            Change::Changed
+        };
+        vec![DiffItem {
+            label,
+            change,
+            children,
+        }]
+    }
+}
+
+/// Returns the text of a scalar value in a diff, or `None` for sequences and maps
+fn diff_text<V>(kind: &ValueKind<'_, V>) -> Option<String> {
+    match *kind {
+        ValueKind::Scalar(ref text, _) => Some(text.clone()),
+        ValueKind::Null(_) => Some("null".to_string()),
+        ValueKind::Bytes(bytes) => Some(bytes_to_string(bytes, 8)),
+        ValueKind::Seq(_) | ValueKind::Map(_) => None,
+    }
+}
+
+/// Formats the label of a scalar entry, where sequence elements are labeled as `[0]`
+fn scalar_label(label: &str, text: &str) -> String {
+    if label.starts_with('[') {
+        format!("{} {}", label, text)
+    } else {
+        format!("{} = {}", label, text)
+    }
+}
+
+impl TreeItem for DiffItem {
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        let style = style.merge(&self.change.style());
+        style.paint_to(f, format_args!("{}{}", self.change.marker(), self.label))
+    }
+
+    fn children(&self) -> Cow<'_, [Self::Child]> {
+        Cow::from(&self.children[..])
+    }
+}
+
+///
+/// Compares two values, returning a tree of their differences
+///
+/// The root of the tree is labeled `.`, and its entries are marked as added, removed or changed.
+/// See [`DiffItem`] for an example.
+///
+/// [`DiffItem`]: struct.DiffItem.html
+pub fn diff<V: TreeValue>(old: &V, new: &V) -> DiffItem {
+    let mut items = DiffItem::both(".".to_string(), old, new);
+    if items.len() == 1 {
+        items.remove(0)
+    } else {
+        DiffItem {
+            label: ".".to_string(),
+            change: Change::Changed,
+            children: items,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
     use std::str::from_utf8;
     use super::*;
 
-    use output::write_tree_with;
-    use print_config::PrintConfig;
+    use output::write_tree_with;
+    use print_config::{PrintConfig, UTF_CHARS};
+
+    #[cfg(feature = "value")]
+    use serde_any;
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn toml_value_output() {
+        let toml = "\
+                    configuration = [\"toml\", \"yaml\", \"json\", \"environment\"]\n\
+                    charactersets = [\"utf\", \"ascii\"]\n\
+                    \n\
+                    default_depth = 3\n\
+                    \n\
+                    ";
+
+        let value: Value = serde_any::from_str(toml, serde_any::Format::Toml).unwrap();
+        let tree = ("toml".to_string(), value);
+
+        let config = PrintConfig {
+            indent: 4,
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+
+        write_tree_with(&tree, &mut cursor, &config).unwrap();
+
+        let data = cursor.into_inner();
+        let expected = "\
+                        toml\n\
+                        ├── charactersets\n\
+                        │   ├── utf\n\
+                        │   └── ascii\n\
+                        ├── configuration\n\
+                        │   ├── toml\n\
+                        │   ├── yaml\n\
+                        │   ├── json\n\
+                        │   └── environment\n\
+                        └── default_depth = 3\n\
+                        ";
+        assert_eq!(from_utf8(&data).unwrap(), expected);
+    }
+
+    #[test]
+    fn serializable_output() {
+        use std::collections::BTreeMap;
+
+        let mut deps = BTreeMap::new();
+        deps.insert("petgraph", vec!["fixedbitset"]);
+        deps.insert("serde", vec![]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_serializable_with(&deps, &mut cursor, &config).unwrap();
+
+        let expected = "BTreeMap\n├─ petgraph\n│  └─ fixedbitset\n└─ serde\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn json_value_output() {
+        let json = r#"{"docs": null, "features": {"json": true}, "name": "ptree", "version": [0, 5]}"#;
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&("crate".to_string(), value), &mut cursor, &config).unwrap();
+
+        let expected = "\
+                        crate\n\
+                        ├─ docs = null\n\
+                        ├─ features\n\
+                        │  └─ json = true\n\
+                        ├─ name = ptree\n\
+                        └─ version\n   \
+                           ├─ 0\n   \
+                           └─ 5\n\
+                        ";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn type_hints_output() {
+        #[derive(Serialize)]
+        struct Server {
+            host: &'static str,
+            port: u16,
+            tls: Option<bool>,
+            ratio: f32,
+        }
+
+        let server = Server {
+            host: "localhost",
+            port: 8080,
+            tls: Some(true),
+            ratio: 0.5,
+        };
+        let item = ValueItem::new("server", serde_value::to_value(server).unwrap()).type_hints(true);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+
+        let expected = "\
+                        server\n\
+                        ├─ host = localhost (string)\n\
+                        ├─ port = 8080 (u16)\n\
+                        ├─ ratio = 0.5 (f32)\n\
+                        └─ tls = true (bool)\n\
+                        ";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn index_labels_output() {
+        use std::collections::BTreeMap;
 
-    use serde_any;
+        #[derive(Serialize)]
+        struct Config {
+            servers: Vec<BTreeMap<&'static str, u16>>,
+            tags: Vec<&'static str>,
+        }
+
+        let config = Config {
+            servers: vec![Some(("port", 80)).into_iter().collect()],
+            tags: vec!["web", "prod"],
+        };
+        let value = serde_value::to_value(config).unwrap();
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = ValueItem::new("config", value.clone()).index_labels(IndexLabels::Brackets);
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+
+        let expected = "\
+                        config\n\
+                        ├─ servers\n\
+                        │  └─ [0]\n\
+                        │     └─ port = 80\n\
+                        └─ tags\n   \
+                           ├─ [0] web\n   \
+                           └─ [1] prod\n\
+                        ";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = ValueItem::new("config", value).index_labels(IndexLabels::Dash);
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+
+        let expected = "\
+                        config\n\
+                        ├─ servers\n\
+                        │  └─ -\n\
+                        │     └─ port = 80\n\
+                        └─ tags\n   \
+                           ├─ - web\n   \
+                           └─ - prod\n\
+                        ";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
 
     #[test]
-    fn toml_value_output() {
-        let toml = "\
-                    configuration = [\"toml\", \"yaml\", \"json\", \"environment\"]\n\
-                    charactersets = [\"utf\", \"ascii\"]\n\
-                    \n\
-                    default_depth = 3\n\
-                    \n\
-                    ";
+    #[cfg(feature = "value")]
+    fn key_order_output() {
+        use std::collections::BTreeMap;
 
-        let value: Value = serde_any::from_str(toml, serde_any::Format::Toml).unwrap();
-        let tree = ("toml".to_string(), value);
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        // Keys of different types are ordered by type first in `serde_value::Value`
+        let mut map = BTreeMap::new();
+        map.insert(Value::String("b".to_string()), Value::Bool(true));
+        map.insert(Value::U8(10), Value::Bool(false));
+        map.insert(Value::U8(9), Value::Bool(true));
+        let value = Value::Map(map);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&ValueItem::new("map", value.clone()), &mut cursor, &config).unwrap();
+        let expected = "map\n├─ 9 = true\n├─ 10 = false\n└─ b = true\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = ValueItem::new("map", value).key_order(KeyOrder::Sorted);
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+        let expected = "map\n├─ 10 = false\n├─ 9 = true\n└─ b = true\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "preserve_order")]
+    fn json_source_order() {
+        let value: serde_json::Value = serde_json::from_str(r#"{"name": "ptree", "edition": 2015}"#).unwrap();
 
         let config = PrintConfig {
-            indent: 4,
             leaf: Style::default(),
             branch: Style::default(),
+            characters: UTF_CHARS.into(),
             ..PrintConfig::default()
         };
 
         let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&ValueItem::new("package", value), &mut cursor, &config).unwrap();
+        let expected = "package\n├─ name = ptree\n└─ edition = 2015\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
 
-        write_tree_with(&tree, &mut cursor, &config).unwrap();
+    #[test]
+    #[cfg(feature = "value")]
+    fn null_text_output() {
+        #[derive(Serialize)]
+        struct Sparse {
+            name: Option<&'static str>,
+            unit: (),
+            values: Vec<Option<u8>>,
+        }
 
-        let data = cursor.into_inner();
+        let sparse = Sparse {
+            name: None,
+            unit: (),
+            values: vec![Some(1), None],
+        };
+        let value = serde_value::to_value(sparse).unwrap();
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&ValueItem::new("sparse", value.clone()), &mut cursor, &config).unwrap();
+        let expected = "sparse\n├─ name = null\n├─ unit = null\n└─ values\n   ├─ 1\n   └─ null\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = ValueItem::new("sparse", value).null_text("~").type_hints(true);
+        write_tree_with(&item, &mut cursor, &config).unwrap();
         let expected = "\
-                        toml\n\
-                        ├── charactersets\n\
-                        │   ├── utf\n\
-                        │   └── ascii\n\
-                        ├── configuration\n\
-                        │   ├── toml\n\
-                        │   ├── yaml\n\
-                        │   ├── json\n\
-                        │   └── environment\n\
-                        └── default_depth = 3\n\
+                        sparse\n\
+                        ├─ name = ~ (option)\n\
+                        ├─ unit = ~ (unit)\n\
+                        └─ values\n   \
+                           ├─ 1 (u8)\n   \
+                           └─ ~ (option)\n\
                         ";
-        assert_eq!(from_utf8(&data).unwrap(), expected);
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn truncated_values_output() {
+        let value = Value::Seq(vec![
+            Value::String("short".to_string()),
+            Value::String("Grüße aus Köln".to_string()),
+            Value::U64(123_456_789),
+        ]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = ValueItem::new("texts", value.clone()).max_value_length(Some(5));
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+        let expected = "texts\n├─ short\n├─ Grüße…\n└─ 123456789\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = item.show_value_length(true);
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+        let expected = "texts\n├─ short\n├─ Grüße… (14 chars)\n└─ 123456789\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn bytes_preview() {
+        assert_eq!(bytes_to_string(b"Hello", 8), "0x48 65 6c 6c 6f (5 B)");
+        assert_eq!(bytes_to_string(b"Hello", 3), "0x48 65 6c\u{2026} (5 B)");
+        assert_eq!(bytes_to_string(&[0; 1229], 1), "0x00\u{2026} (1.2 KiB)");
+        assert_eq!(bytes_to_string(&[0; 3 << 20], 0), "\u{2026} (3.0 MiB)");
+        assert_eq!(bytes_to_string(&[], 8), "(0 B)");
+    }
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn collapsed_chains_output() {
+        let map = |entries: Vec<(&str, Value)>| {
+            Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (Value::String(k.to_string()), v))
+                    .collect(),
+            )
+        };
+        let value = map(vec![
+            ("server", map(vec![("http", map(vec![("port", Value::U16(8080))]))])),
+            (
+                "tls",
+                map(vec![(
+                    "files",
+                    map(vec![
+                        ("cert", Value::String("cert.pem".to_string())),
+                        ("key", Value::String("key.pem".to_string())),
+                    ]),
+                )]),
+            ),
+        ]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = ValueItem::new("config", value).collapse_chains(true);
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+        let expected =
+            "config\n├─ server.http.port = 8080\n└─ tls.files\n   ├─ cert = cert.pem\n   └─ key = key.pem\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn custom_separator() {
+        let map = |entries: Vec<(&str, Value)>| {
+            Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (Value::String(k.to_string()), v))
+                    .collect(),
+            )
+        };
+        let value = map(vec![
+            ("name", Value::String("ptree".to_string())),
+            ("dependencies", map(vec![("serde", Value::String("1.0".to_string()))])),
+            ("features", map(vec![])),
+        ]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = ValueItem::new("package", value).separator(": ");
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+        // Keys of maps and sequences have no value, so they are printed without the separator
+        let expected = "package\n├─ dependencies\n│  └─ serde: 1.0\n├─ features\n└─ name: ptree\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn summarized_output() {
+        let values = Value::Seq((0..1000).map(Value::U32).collect());
+        let value = Value::Map(
+            vec![(Value::String("values".to_string()), values)]
+                .into_iter()
+                .collect(),
+        );
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = ValueItem::new("data", value).max_entries(Some(100), 2);
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+        let expected = "data\n└─ values [1,000 items]\n   ├─ 0\n   └─ 1\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        assert_eq!(grouped(0), "0");
+        assert_eq!(grouped(999), "999");
+        assert_eq!(grouped(1234567), "1,234,567");
+    }
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn path_labels_output() {
+        use std::collections::BTreeMap;
+
+        #[derive(Serialize)]
+        struct Server {
+            port: u16,
+        }
+
+        let mut servers = BTreeMap::new();
+        servers.insert("servers", vec![Server { port: 80 }, Server { port: 443 }]);
+        servers.insert("a/b", vec![]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let value = serde_value::to_value(servers).unwrap();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = ValueItem::new("config", value).path_labels(PathLabels::Replace);
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+        let expected = "\
+                        config\n\
+                        ├─ /a~1b\n\
+                        └─ /servers\n   \
+                           ├─ /servers/0\n   \
+                           │  └─ /servers/0/port = 80\n   \
+                           └─ /servers/1\n      \
+                              └─ /servers/1/port = 443\n\
+                        ";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = item.path_labels(PathLabels::Append).index_labels(IndexLabels::Brackets);
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+        let expected = "\
+                        config\n\
+                        ├─ a/b (/a~1b)\n\
+                        └─ servers (/servers)\n   \
+                           ├─ [0] (/servers/0)\n   \
+                           │  └─ port = 80 (/servers/0/port)\n   \
+                           └─ [1] (/servers/1)\n      \
+                              └─ port = 443 (/servers/1/port)\n\
+                        ";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn formatted_scalars_output() {
+        #[derive(Serialize)]
+        struct Account {
+            user: &'static str,
+            password: &'static str,
+            quota: u64,
+        }
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let account = Account {
+            user: "alice",
+            password: "hunter2",
+            quota: 10_000_000,
+        };
+        let value = serde_value::to_value(account).unwrap();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = ValueItem::new("account", value).format_scalars(|scalar| match (scalar.key, scalar.type_name) {
+            ("password", _) => Some("<redacted>".to_string()),
+            (_, "u64") => scalar.text.parse().ok().map(grouped),
+            _ => None,
+        });
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+        let expected = "account\n├─ password = <redacted>\n├─ quota = 10,000,000\n└─ user = alice\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn query_output() {
+        #[derive(Serialize)]
+        struct Server {
+            host: &'static str,
+            port: u16,
+        }
+
+        #[derive(Serialize)]
+        struct Config {
+            name: &'static str,
+            servers: Vec<Server>,
+        }
+
+        let config = Config {
+            name: "test",
+            servers: vec![
+                Server {
+                    host: "a.example.com",
+                    port: 80,
+                },
+                Server {
+                    host: "b.example.com",
+                    port: 443,
+                },
+            ],
+        };
+        let value = serde_value::to_value(config).unwrap();
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = ValueItem::new("config", value).index_labels(IndexLabels::Brackets);
+        write_tree_with(&item.clone().query("servers.*.port"), &mut cursor, &config).unwrap();
+        let expected = "\
+                        config\n\
+                        └─ servers\n   \
+                           ├─ [0]\n   \
+                           │  └─ port = 80\n   \
+                           └─ [1]\n      \
+                              └─ port = 443\n\
+                        ";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&item.clone().query("servers.1"), &mut cursor, &config).unwrap();
+        let expected = "\
+                        config\n\
+                        └─ servers\n   \
+                           └─ [1]\n      \
+                              ├─ host = b.example.com\n      \
+                              └─ port = 443\n\
+                        ";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&item.clone().query("**.host"), &mut cursor, &config).unwrap();
+        let expected = "\
+                        config\n\
+                        └─ servers\n   \
+                           ├─ [0]\n   \
+                           │  └─ host = a.example.com\n   \
+                           └─ [1]\n      \
+                              └─ host = b.example.com\n\
+                        ";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&item.query("missing"), &mut cursor, &config).unwrap();
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), "config\n");
+    }
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn diff_output() {
+        use print_config::StyleWhen;
+
+        #[derive(Serialize)]
+        struct Config {
+            name: &'static str,
+            ports: Vec<u16>,
+            tls: Option<bool>,
+        }
+
+        let old = serde_value::to_value(Config {
+            name: "test",
+            ports: vec![80, 443],
+            tls: Some(true),
+        })
+        .unwrap();
+        let new = serde_value::to_value(Config {
+            name: "test",
+            ports: vec![8080],
+            tls: None,
+        })
+        .unwrap();
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&diff(&old, &new), &mut cursor, &config).unwrap();
+        let expected = "\
+                        ~ .\n\
+                        ├─ name = test\n\
+                        ├─ ~ ports\n\
+                        │  ├─ ~ [0] 80 → 8080\n\
+                        │  └─ - [1] 443\n\
+                        └─ ~ tls = true → null\n\
+                        ";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        assert_eq!(diff(&old, &old).change(), Change::Unchanged);
+
+        let list = Value::Seq(vec![Value::U8(1)]);
+        let nested = Value::Seq(vec![Value::Seq(vec![Value::U8(1)])]);
+        let styled = PrintConfig {
+            separator: Style::default(),
+            styled: StyleWhen::Always,
+            ..config
+        };
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&diff(&list, &nested), &mut cursor, &styled).unwrap();
+        let expected = format!(
+            "{}\n├─ {}\n└─ {}\n   └─ {}\n",
+            Change::Changed.style().paint("~ ."),
+            Change::Removed.style().paint("- [0] 1"),
+            Change::Added.style().paint("+ [0]"),
+            Change::Added.style().paint("+ [0] 1"),
+        );
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn type_classes_output() {
+        use print_config::StyleWhen;
+
+        let value = Value::Seq(vec![
+            Value::U8(1),
+            Value::String("text".to_string()),
+            Value::Bool(true),
+            Value::Unit,
+        ]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            separator: Style::default(),
+            characters: UTF_CHARS.into(),
+            styled: StyleWhen::Always,
+            classes: PrintConfig::default_classes(),
+            ..PrintConfig::default()
+        };
+        let class = |name: &str| config.classes[name].clone();
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let item = ValueItem::new("values", value).type_classes(true);
+        write_tree_with(&item, &mut cursor, &config).unwrap();
+        let expected = format!(
+            "values\n├─ {}\n├─ {}\n├─ {}\n└─ {}\n",
+            class("number").paint("1"),
+            class("string").paint("text"),
+            class("boolean").paint("true"),
+            class("null").paint("null"),
+        );
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn json_stream_output() {
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+        let value_config = ValueConfig {
+            root_label: "servers".to_string(),
+            ..ValueConfig::default()
+        };
+        let document = r#"[{"host": "a", "port": 80}, {"host": "b", "port": 443}, {"host": "c", "port": 8080}]"#;
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_json_stream_with(document.as_bytes(), &mut cursor, &config, &value_config).unwrap();
+        let expected = "\
+                        servers\n\
+                        ├─ \n\
+                        │  ├─ host = a\n\
+                        │  └─ port = 80\n\
+                        ├─ \n\
+                        │  ├─ host = b\n\
+                        │  └─ port = 443\n\
+                        └─ \n   \
+                           ├─ host = c\n   \
+                           └─ port = 8080\n\
+                        ";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let limited = PrintConfig {
+            max_children: Some(1),
+            ..config.clone()
+        };
+        let query = ValueConfig {
+            query: Some(PathQuery::new("*.port")),
+            index_labels: IndexLabels::Brackets,
+            ..value_config.clone()
+        };
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_json_stream_with(document.as_bytes(), &mut cursor, &limited, &query).unwrap();
+        let expected = "servers\n├─ [0]\n│  └─ port = 80\n└─ … and 2 more\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let document = r#"{"a": {"b": true}}"#;
+        write_json_stream_with(document.as_bytes(), &mut cursor, &config, &value_config).unwrap();
+        let expected = "servers\n└─ a\n   └─ b = true\n";
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), expected);
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_json_stream_with(&b"42"[..], &mut cursor, &config, &value_config).unwrap();
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), "servers = 42\n");
+
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let error = write_json_stream_with(&b"[1, 2,"[..], &mut cursor, &config, &value_config).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(from_utf8(&cursor.into_inner()).unwrap(), "servers\n├─ 1\n");
+    }
+
+    #[test]
+    #[cfg(feature = "value")]
+    fn string_item_output() {
+        let value = Value::Seq(vec![Value::U8(1), Value::Seq(vec![Value::Bool(true)])]);
+
+        let config = PrintConfig {
+            leaf: Style::default(),
+            branch: Style::default(),
+            characters: UTF_CHARS.into(),
+            ..PrintConfig::default()
+        };
+
+        let item = ValueItem::new("values", value.clone()).index_labels(IndexLabels::Brackets);
+        let mut expected: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&item, &mut expected, &config).unwrap();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        write_tree_with(&item.to_string_item(), &mut cursor, &config).unwrap();
+        assert_eq!(cursor.into_inner(), expected.into_inner());
+
+        let item = to_string_item(&value);
+        assert_eq!(item.text, ".");
+        assert_eq!(item.children[0].text, "1");
+        assert_eq!(item.children[1].children[0].text, "true");
     }
 }